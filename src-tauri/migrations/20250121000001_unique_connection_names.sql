@@ -0,0 +1,25 @@
+-- Enforce unique connection names per connection type
+
+-- Names inserted more than once keep the most recent row under the original name;
+-- older rows are renamed "<name> (2)", "<name> (3)", ... so no connection or secret is lost
+CREATE TEMP TABLE connection_renames AS
+SELECT id, name || ' (' || copy || ')' AS new_name
+FROM (
+    SELECT id, name,
+           ROW_NUMBER() OVER (PARTITION BY connection_type, name ORDER BY id DESC) AS copy
+    FROM connections
+)
+WHERE copy > 1;
+
+UPDATE connections
+SET name = (SELECT new_name FROM connection_renames r WHERE r.id = connections.id),
+    config_json = CASE
+        WHEN json_valid(config_json)
+            THEN json_set(config_json, '$.name', (SELECT new_name FROM connection_renames r WHERE r.id = connections.id))
+        ELSE config_json
+    END
+WHERE id IN (SELECT id FROM connection_renames);
+
+DROP TABLE connection_renames;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_connection_type_name ON connections(connection_type, name);
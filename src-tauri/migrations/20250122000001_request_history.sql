@@ -0,0 +1,31 @@
+-- Extend the requests table into a full DICOMweb request history.
+-- SQLite cannot alter the request_type CHECK constraint, so the table is rebuilt.
+
+CREATE TABLE requests_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    request_type TEXT NOT NULL,
+    connection_name TEXT,
+    base_url TEXT NOT NULL DEFAULT '',
+    endpoint TEXT NOT NULL,
+    method TEXT NOT NULL,
+    headers_json TEXT NOT NULL,
+    body TEXT,
+    response_status INTEGER,
+    response_headers_json TEXT,
+    response_body TEXT,
+    duration_ms INTEGER,
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO requests_new (id, request_type, endpoint, method, headers_json, body,
+                          response_status, response_body, created_at)
+SELECT id, request_type, endpoint, method, headers_json, body,
+       response_status, response_body, created_at
+FROM requests;
+
+DROP TABLE requests;
+ALTER TABLE requests_new RENAME TO requests;
+
+CREATE INDEX idx_requests_created_at ON requests(created_at);
+CREATE INDEX idx_requests_type ON requests(request_type);
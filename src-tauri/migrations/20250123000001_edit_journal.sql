@@ -0,0 +1,17 @@
+-- Per-file journal of tag edits, replayed by undo/redo.
+-- old_json/new_json hold the attribute (or sequence item) as DICOM JSON; NULL means absent.
+
+CREATE TABLE edit_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_path TEXT NOT NULL,
+    tag_path TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    old_json TEXT,
+    new_json TEXT,
+    undone INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_edit_journal_file ON edit_journal(file_path, id);
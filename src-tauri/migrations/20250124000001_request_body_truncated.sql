@@ -0,0 +1,6 @@
+-- Flag request bodies that were cut when recorded, so they are never replayed
+
+ALTER TABLE requests ADD COLUMN body_truncated INTEGER NOT NULL DEFAULT 0;
+
+UPDATE requests SET body_truncated = 1
+WHERE body LIKE '%... [truncated, % bytes total]';
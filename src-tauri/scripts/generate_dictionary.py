@@ -0,0 +1,97 @@
+#!/usr/bin/env python3
+"""Generate src/dicom/dictionary_table.rs from the DICOM standard, PS3.6.
+
+Reads the DocBook source of PS3.6, downloaded from NEMA unless a local copy is
+given, and writes the name, value multiplicity and retired flag of every data
+element in the registry (table 6-1) and of the file meta elements (table 7-1).
+Keywords and VRs are not written; they come from dicom-dictionary-std.
+
+    python3 scripts/generate_dictionary.py [part06.xml]
+"""
+
+import sys
+import urllib.request
+import xml.etree.ElementTree as ET
+from pathlib import Path
+
+URL = "https://dicom.nema.org/medical/dicom/current/source/docbook/part06/part06.xml"
+DOCBOOK = "{http://docbook.org/ns/docbook}"
+XML_ID = "{http://www.w3.org/XML/1998/namespace}id"
+TABLES = ("table_6-1", "table_7-1")
+OUTPUT = Path(__file__).resolve().parent.parent / "src" / "dicom" / "dictionary_table.rs"
+# PS3.6 lists over 4000 data elements; fewer means the tables weren't found whole
+MIN_ENTRIES = 4000
+
+HEADER = """\
+// Attribute names, value multiplicities and retired flags from DICOM PS3.6.
+// Regenerate with `python3 scripts/generate_dictionary.py` instead of editing.
+
+/// (tag, name, VM, retired), sorted by tag. Tags are `group << 16 | element`;
+/// repeating groups (50xx, 60xx) are listed under their base group.
+pub(super) const ATTRIBUTES: &[(u32, &str, &str, bool)] = &[
+"""
+
+
+def cell_text(cell):
+    # PS3.6 uses zero width spaces to allow line breaks in long names
+    return "".join(cell.itertext()).replace("\u200b", "").strip()
+
+
+def parse_tag(text):
+    """Tag as an integer, or None for ranges other than the 50xx/60xx groups"""
+    group, element = text.strip("()").split(",")
+    if group in ("50xx", "60xx"):
+        group = group.replace("xx", "00")
+    if "x" in group.lower() or "x" in element.lower():
+        return None
+    return int(group, 16) << 16 | int(element, 16)
+
+
+def parse(xml):
+    root = ET.fromstring(xml)
+    entries = {}
+    for table in root.iter(DOCBOOK + "table"):
+        if table.get(XML_ID) not in TABLES:
+            continue
+        for row in table.iter(DOCBOOK + "tr"):
+            cells = [cell_text(c) for c in row.findall(DOCBOOK + "td")]
+            if len(cells) < 5 or not cells[0].startswith("("):
+                continue
+            tag = parse_tag(cells[0])
+            name, vm = cells[1], cells[4]
+            if tag is None or not name or not vm:
+                continue
+            retired = len(cells) > 5 and cells[5].startswith("RET")
+            entries[tag] = (name, vm, retired)
+    return entries
+
+
+def rust_str(value):
+    return '"' + value.replace("\\", "\\\\").replace('"', '\\"') + '"'
+
+
+def render(entries):
+    lines = [HEADER]
+    for tag in sorted(entries):
+        name, vm, retired = entries[tag]
+        lines.append(f"    (0x{tag:08X}, {rust_str(name)}, {rust_str(vm)}, {str(retired).lower()}),\n")
+    lines.append("];\n")
+    return "".join(lines)
+
+
+def main():
+    if len(sys.argv) > 1:
+        xml = Path(sys.argv[1]).read_bytes()
+    else:
+        with urllib.request.urlopen(URL) as response:
+            xml = response.read()
+
+    entries = parse(xml)
+    if len(entries) < MIN_ENTRIES:
+        sys.exit(f"Only {len(entries)} data elements found; is this the PS3.6 DocBook source?")
+    OUTPUT.write_text(render(entries), encoding="utf-8")
+    print(f"Wrote {len(entries)} attributes to {OUTPUT}")
+
+
+if __name__ == "__main__":
+    main()
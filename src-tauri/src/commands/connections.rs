@@ -0,0 +1,207 @@
+// Saved connection and credential storage commands
+
+use crate::database::connections::{self, ConnectionConfig, SavedConnection};
+use crate::database::DbPool;
+use crate::utils::secrets::{SecretVault, VaultStatus};
+use tauri::State;
+
+/// List saved connections ("dimse", "dicomweb" or all when omitted); secrets are redacted
+#[tauri::command]
+pub async fn list_connections(
+    db: State<'_, DbPool>,
+    connection_type: Option<String>,
+) -> Result<Vec<SavedConnection>, String> {
+    connections::list(&db, connection_type.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    config: ConnectionConfig,
+) -> Result<i64, String> {
+    let id = connections::create(&db, &vault, config)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("Created connection {}", id);
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn update_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    id: i64,
+    config: ConnectionConfig,
+) -> Result<(), String> {
+    connections::update(&db, &vault, id, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    id: i64,
+    new_name: String,
+) -> Result<(), String> {
+    connections::rename(&db, &vault, id, &new_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    id: i64,
+) -> Result<(), String> {
+    connections::delete(&db, &vault, id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn duplicate_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    id: i64,
+    new_name: Option<String>,
+) -> Result<i64, String> {
+    connections::duplicate(&db, &vault, id, new_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Check a saved connection: C-ECHO for PACS, a one-result QIDO-RS study search for DICOMweb
+#[tauri::command]
+pub async fn test_connection(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    id: i64,
+) -> Result<ConnectionTestResult, String> {
+    use std::time::Instant;
+
+    let connection = connections::get(&db, &vault, id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let outcome: anyhow::Result<String> = match connection.config {
+        ConnectionConfig::Dimse(endpoint) => crate::dimse::scu::c_echo(&endpoint)
+            .await
+            .map(|_| "C-ECHO succeeded".to_string()),
+        ConnectionConfig::Dicomweb(endpoint) => {
+            use crate::dicomweb::client::DicomWebClient;
+            use crate::dicomweb::qido::{self, QidoQuery, QueryLevel};
+
+            let mut query = QidoQuery::new(QueryLevel::Studies);
+            query.limit = Some(1);
+            // Invalid proxy or TLS settings are reported as a failed test
+            match DicomWebClient::new(endpoint) {
+                Ok(client) => qido::query(&client.with_history(db.inner().clone()), query)
+                    .await
+                    .map(|response| format!("QIDO-RS responded with {} result(s)", response.results.len())),
+                Err(e) => Err(e),
+            }
+        }
+    };
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    Ok(match outcome {
+        Ok(message) => ConnectionTestResult { success: true, message, elapsed_ms },
+        Err(e) => ConnectionTestResult { success: false, message: e.to_string(), elapsed_ms },
+    })
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub message: String,
+    pub elapsed_ms: u64,
+}
+
+#[tauri::command]
+pub async fn get_secrets_status(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+) -> Result<VaultStatus, String> {
+    vault.status(&db).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unlock_secrets(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    passphrase: String,
+) -> Result<(), String> {
+    vault.unlock(&db, &passphrase).await.map_err(|e| e.to_string())
+}
+
+/// Protect stored secrets with a passphrase instead of the keyfile
+#[tauri::command]
+pub async fn set_secrets_passphrase(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    passphrase: String,
+) -> Result<(), String> {
+    vault.use_passphrase(&db, &passphrase).await.map_err(|e| e.to_string())?;
+    tracing::info!("Connection secrets are now protected by a passphrase");
+    Ok(())
+}
+
+/// Protect stored secrets with a keyfile (the default location when `path` is omitted)
+#[tauri::command]
+pub async fn use_secrets_keyfile(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    path: Option<String>,
+) -> Result<(), String> {
+    vault.use_keyfile(&db, path).await.map_err(|e| e.to_string())?;
+    tracing::info!("Connection secrets are now protected by a keyfile");
+    Ok(())
+}
+
+/// Export all connections to a file; secrets are redacted unless a passphrase is given
+#[tauri::command]
+pub async fn export_connection_profiles(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    output_path: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    use std::fs;
+
+    let profiles = crate::dicomweb::config::export_profiles(&db, &vault, passphrase.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&profiles).map_err(|e| e.to_string())?;
+
+    fs::write(&output_path, json).map_err(|e| e.to_string())?;
+
+    tracing::info!("Exported connection profiles to {}", output_path);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn import_connection_profiles(
+    db: State<'_, DbPool>,
+    vault: State<'_, SecretVault>,
+    input_path: String,
+    passphrase: Option<String>,
+) -> Result<crate::dicomweb::config::ProfileImport, String> {
+    use std::fs;
+
+    let json = fs::read_to_string(&input_path).map_err(|e| e.to_string())?;
+    let profiles = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+
+    crate::dicomweb::config::import_profiles(&db, &vault, profiles, passphrase.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,24 +1,75 @@
 // DICOMweb commands
 
-use crate::dicomweb::{DicomWebEndpoint, qido::QidoQuery};
+use crate::database::models::Request;
+use crate::database::DbPool;
+use crate::dicomweb::capabilities::{self, EndpointCapabilities, InspectOptions};
+use crate::dicomweb::history::{self, HistoryFilter};
+use crate::dicomweb::server::{DicomWebServer, ServerConfig, ServerStatus};
+use crate::dicomweb::wado_uri::WadoUriParams;
+use crate::dicomweb::{DicomWebEndpoint, qido::{QidoQuery, QidoResponse}};
+use tauri::State;
 
 #[tauri::command]
 pub async fn qido_rs(
+    db: State<'_, DbPool>,
     endpoint: DicomWebEndpoint,
     query: QidoQuery,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<QidoResponse, String> {
     use crate::dicomweb::client::DicomWebClient;
 
-    let client = DicomWebClient::new(endpoint);
-    let results = crate::dicomweb::qido::query(&client, query)
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let response = crate::dicomweb::qido::query(&client, query)
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(results)
+    Ok(response)
+}
+
+/// Follow limit/offset paging until the server runs out of matches (or `max_results` is reached)
+#[tauri::command]
+pub async fn qido_rs_all(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    query: QidoQuery,
+    max_results: Option<usize>,
+) -> Result<QidoResponse, String> {
+    use crate::dicomweb::client::DicomWebClient;
+    use crate::dicomweb::qido::QidoPager;
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    QidoPager::new(&client, query)
+        .collect_all(max_results)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// QIDO-RS search returning typed study/series/instance records
+#[tauri::command]
+pub async fn qido_rs_records(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    query: QidoQuery,
+) -> Result<Vec<crate::dicomweb::qido::QidoRecord>, String> {
+    use crate::dicomweb::client::DicomWebClient;
+
+    let level = query.level.clone();
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let response = crate::dicomweb::qido::query(&client, query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    crate::dicomweb::qido::to_records(&level, &response.results).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn wado_rs(
+    db: State<'_, DbPool>,
     endpoint: DicomWebEndpoint,
     study_uid: String,
     series_uid: String,
@@ -27,7 +78,9 @@ pub async fn wado_rs(
     use crate::dicomweb::client::DicomWebClient;
     use base64::{Engine as _, engine::general_purpose};
 
-    let client = DicomWebClient::new(endpoint);
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
     let data = crate::dicomweb::wado::retrieve_instance(
         &client,
         &study_uid,
@@ -42,15 +95,129 @@ pub async fn wado_rs(
     Ok(base64_data)
 }
 
+/// Retrieve a whole study, or one series of it, save it to a folder and add it to the local index
+#[tauri::command]
+pub async fn wado_rs_retrieve(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    study_uid: String,
+    series_uid: Option<String>,
+    output_dir: String,
+    transfer_syntax: Option<String>,
+) -> Result<WadoRetrieveResult, String> {
+    use crate::dicomweb::client::DicomWebClient;
+    use crate::dicomweb::wado;
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+
+    let instances = match &series_uid {
+        Some(series) => {
+            wado::retrieve_series(&client, &study_uid, series, transfer_syntax.as_deref()).await
+        }
+        None => wado::retrieve_study(&client, &study_uid, transfer_syntax.as_deref()).await,
+    }
+    .map_err(|e| e.to_string())?;
+
+    let dir = output_dir.clone();
+    let saved = tokio::task::spawn_blocking(move || wado::save_instances(&instances, dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let indexed_count = crate::database::index::index_files(&db, &saved.paths).await;
+
+    tracing::info!(
+        "Retrieved {} instances of study {} into {} ({} failed)",
+        saved.paths.len(),
+        study_uid,
+        output_dir,
+        saved.failures.len()
+    );
+
+    Ok(WadoRetrieveResult {
+        file_paths: saved
+            .paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        indexed_count,
+        failures: saved.failures,
+    })
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct WadoRetrieveResult {
+    pub file_paths: Vec<String>,
+    pub indexed_count: usize,
+    pub failures: Vec<crate::dicomweb::wado::SaveFailure>,
+}
+
+/// Retrieve an object through WADO-URI. The data is returned as base64; with
+/// `output_path` it is also written to disk, and DICOM objects are indexed.
+#[tauri::command]
+pub async fn wado_uri_retrieve(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    params: WadoUriParams,
+    output_path: Option<String>,
+) -> Result<WadoUriResult, String> {
+    use crate::dicomweb::client::DicomWebClient;
+    use base64::{Engine as _, engine::general_purpose};
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let object = crate::dicomweb::wado_uri::retrieve(&client, &params)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let content_type = object.content_type;
+    let bytes = object.data;
+    let target = output_path.clone();
+    let data = tokio::task::spawn_blocking(move || {
+        if let Some(path) = &target {
+            std::fs::write(path, &bytes)?;
+        }
+        Ok::<_, std::io::Error>(general_purpose::STANDARD.encode(&bytes))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if let Some(path) = &output_path {
+        if content_type.starts_with("application/dicom") {
+            crate::database::index::index_files(&db, &[std::path::PathBuf::from(path)]).await;
+        }
+    }
+
+    Ok(WadoUriResult {
+        content_type,
+        data,
+        file_path: output_path,
+    })
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct WadoUriResult {
+    pub content_type: String,
+    /// Base64-encoded object
+    pub data: String,
+    pub file_path: Option<String>,
+}
+
 #[tauri::command]
 pub async fn stow_rs(
+    db: State<'_, DbPool>,
     endpoint: DicomWebEndpoint,
     file_paths: Vec<String>,
 ) -> Result<StowResult, String> {
     use crate::dicomweb::client::DicomWebClient;
     use std::fs;
 
-    let client = DicomWebClient::new(endpoint);
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
 
     // Read all DICOM files
     let mut instances = Vec::new();
@@ -75,3 +242,110 @@ pub struct StowResult {
     pub success_count: usize,
     pub failed_count: usize,
 }
+
+/// Probe which DICOMweb features an endpoint supports. With `connection_id` the
+/// report is saved on that connection.
+#[tauri::command]
+pub async fn inspect_endpoint(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    connection_id: Option<i64>,
+    options: Option<InspectOptions>,
+) -> Result<EndpointCapabilities, String> {
+    use crate::dicomweb::client::DicomWebClient;
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let report = capabilities::inspect(&client, &options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(id) = connection_id {
+        crate::database::connections::save_capabilities(&db, id, report.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+/// List recorded DICOMweb requests, newest first
+#[tauri::command]
+pub async fn list_request_history(
+    db: State<'_, DbPool>,
+    filter: Option<HistoryFilter>,
+) -> Result<Vec<Request>, String> {
+    history::list(&db, &filter.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_request_history_entry(db: State<'_, DbPool>, id: i64) -> Result<Request, String> {
+    history::get(&db, id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_request_history(db: State<'_, DbPool>) -> Result<u64, String> {
+    history::clear(&db).await.map_err(|e| e.to_string())
+}
+
+/// Re-run a recorded request against `endpoint`, which supplies the credentials
+/// that were masked in the history entry
+#[tauri::command]
+pub async fn replay_request(
+    db: State<'_, DbPool>,
+    id: i64,
+    endpoint: DicomWebEndpoint,
+) -> Result<crate::dicomweb::DicomWebResponse, String> {
+    use crate::dicomweb::client::DicomWebClient;
+
+    let entry = history::get(&db, id).await.map_err(|e| e.to_string())?;
+    let request = history::to_request(&entry).map_err(|e| e.to_string())?;
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    client.execute(request).await.map_err(|e| e.to_string())
+}
+
+/// Export a history entry as a curl command ("curl") or an HTTP request file ("http")
+#[tauri::command]
+pub async fn export_request(
+    db: State<'_, DbPool>,
+    id: i64,
+    format: String,
+) -> Result<String, String> {
+    let entry = history::get(&db, id).await.map_err(|e| e.to_string())?;
+
+    match format.as_str() {
+        "curl" => history::to_curl(&entry),
+        "http" => history::to_http_file(&entry),
+        other => return Err(format!("Unknown export format: {}", other)),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Start serving the local store over DICOMweb on localhost
+#[tauri::command]
+pub async fn start_dicomweb_server(
+    db: State<'_, DbPool>,
+    server: State<'_, DicomWebServer>,
+    config: ServerConfig,
+) -> Result<ServerStatus, String> {
+    server
+        .start(db.inner().clone(), config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn stop_dicomweb_server(server: State<'_, DicomWebServer>) -> Result<(), String> {
+    server.stop().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_dicomweb_server_status(server: State<'_, DicomWebServer>) -> Result<ServerStatus, String> {
+    Ok(server.status().await)
+}
@@ -7,8 +7,27 @@ pub async fn export_tags_json(file_path: String, output_path: String) -> Result<
     // Load DICOM file
     let obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
 
-    // Export to JSON
-    let json = crate::dicom::tags::tags_to_json(&obj).map_err(|e| e.to_string())?;
+    // Export to JSON, with vendor blobs decoded
+    let tags = crate::dicom::tags::extract_tags_with_vendor_data(&obj);
+    let json = serde_json::to_string_pretty(&tags).map_err(|e| e.to_string())?;
+
+    // Write to file
+    fs::write(&output_path, json).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Export a file as a PS3.18 DICOM JSON object
+#[tauri::command]
+pub async fn export_dicom_json(file_path: String, output_path: String) -> Result<(), String> {
+    use std::fs;
+
+    // Load DICOM file
+    let obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
+
+    // Convert to the DICOM JSON model
+    let json = crate::dicom::json::to_json(&obj).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
 
     // Write to file
     fs::write(&output_path, json).map_err(|e| e.to_string())?;
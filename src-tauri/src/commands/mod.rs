@@ -5,4 +5,8 @@ pub mod viewer;
 pub mod tags;
 pub mod dimse;
 pub mod dicomweb;
+pub mod ups;
 pub mod export;
+pub mod connections;
+pub mod transfer;
+pub mod validation;
@@ -1,77 +1,163 @@
 // Tag manipulation commands
 
+use crate::database::journal;
+use crate::dicom::path::TagPath;
+use crate::dicom::private::PrivateDictEntry;
 use crate::dicom::tags::DicomTag;
-use dicom_core::Tag;
 
 #[tauri::command]
 pub async fn get_all_tags(file_path: String) -> Result<Vec<DicomTag>, String> {
     let obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
-    let tags = crate::dicom::tags::extract_all_tags(&obj);
+    let tags = crate::dicom::tags::extract_tags_with_vendor_data(&obj);
     Ok(tags)
 }
 
+/// Decoded vendor headers and a summary of the values most often needed from them
+#[tauri::command]
+pub async fn get_vendor_info(file_path: String) -> Result<crate::dicom::vendor::VendorReport, String> {
+    let obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
+    Ok(crate::dicom::vendor::decode(&obj))
+}
+
+/// Get one attribute by path, e.g. "(0010,0010)" or "(0040,0275)[0].(0032,1060)"
+#[tauri::command]
+pub async fn get_tag(file_path: String, tag: String) -> Result<DicomTag, String> {
+    let path = TagPath::parse(&tag).map_err(|e| e.to_string())?;
+    let obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
+    crate::dicom::tags::get_tag_at(&obj, &path).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn update_tag(
+    db: tauri::State<'_, crate::database::DbPool>,
     file_path: String,
     tag: String,
     value: String,
+    vr: Option<String>,
 ) -> Result<(), String> {
-    use dicom_core::Tag;
+    // Parse tag path (e.g., "(0010,0010)", "00100010" or "(0040,0275)[0].(0032,1060)")
+    let path = TagPath::parse(&tag).map_err(|e| e.to_string())?;
 
-    // Parse tag string (e.g., "(0010,0010)" or "00100010")
-    let tag_parsed = parse_tag_string(&tag).map_err(|e| e.to_string())?;
+    // Explicit VR, needed for tags the dictionaries don't know
+    let vr = vr
+        .map(|vr| vr.parse::<dicom_core::VR>().map_err(|_| format!("Unknown VR: {}", vr)))
+        .transpose()?;
 
     // Load DICOM file
     let mut obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
 
-    // Update tag
-    crate::dicom::tags::update_tag(&mut obj, tag_parsed, value).map_err(|e| e.to_string())?;
+    // Update tag; invalid values are rejected before anything is written
+    let before = journal::capture(&obj, &path).map_err(|e| e.to_string())?;
+    crate::dicom::tags::update_tag_at(&mut obj, &path, value, vr).map_err(|e| e.to_string())?;
+    let after = journal::capture(&obj, &path).map_err(|e| e.to_string())?;
 
-    // Save modified file
-    crate::dicom::save_dicom_file(&obj, &file_path).map_err(|e| e.to_string())?;
+    // Journal the edit and save the file (backed up and written atomically)
+    let edit = journal::Edit { path, operation: "update", before, after };
+    journal::save_edits(&db, &obj, &file_path, vec![edit]).await.map_err(|e| e.to_string())?;
 
     tracing::info!("Tag updated and saved for {}", file_path);
 
     Ok(())
 }
 
-fn parse_tag_string(tag: &str) -> Result<Tag, String> {
-    // Remove parentheses and comma if present: "(0010,0010)" -> "00100010"
-    let cleaned = tag.replace("(", "").replace(")", "").replace(",", "");
+#[tauri::command]
+pub async fn delete_tag(
+    db: tauri::State<'_, crate::database::DbPool>,
+    file_path: String,
+    tag: String,
+) -> Result<(), String> {
+    // Parse tag path; a trailing item index deletes that sequence item
+    let path = TagPath::parse(&tag).map_err(|e| e.to_string())?;
+
+    // Load DICOM file
+    let mut obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
 
-    if cleaned.len() != 8 {
-        return Err(format!("Invalid tag format: {}", tag));
+    // Delete tag; deleting something absent changes nothing and isn't journaled
+    let before = journal::capture(&obj, &path).map_err(|e| e.to_string())?;
+    if before.json.is_none() {
+        return Ok(());
     }
+    crate::dicom::tags::delete_tag_at(&mut obj, &path).map_err(|e| e.to_string())?;
+
+    // Journal the edit and save the file (backed up and written atomically)
+    let edit = journal::Edit { path, operation: "delete", before, after: journal::Snapshot::default() };
+    journal::save_edits(&db, &obj, &file_path, vec![edit]).await.map_err(|e| e.to_string())?;
 
-    let group = u16::from_str_radix(&cleaned[0..4], 16)
-        .map_err(|_| format!("Invalid group number: {}", &cleaned[0..4]))?;
-    let element = u16::from_str_radix(&cleaned[4..8], 16)
-        .map_err(|_| format!("Invalid element number: {}", &cleaned[4..8]))?;
+    tracing::info!("Tag deleted and saved for {}", file_path);
 
-    Ok(Tag(group, element))
+    Ok(())
 }
 
+/// Undo the latest tag edit of a file; `None` when there is nothing to undo
 #[tauri::command]
-pub async fn delete_tag(file_path: String, tag: String) -> Result<(), String> {
-    // Parse tag string
-    let tag_parsed = parse_tag_string(&tag).map_err(|e| e.to_string())?;
+pub async fn undo_tag_edit(
+    db: tauri::State<'_, crate::database::DbPool>,
+    file_path: String,
+) -> Result<Option<crate::database::models::EditJournalEntry>, String> {
+    journal::undo(&db, &file_path).await.map_err(|e| e.to_string())
+}
 
-    // Load DICOM file
-    let mut obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
+/// Redo the last undone tag edit of a file; `None` when there is nothing to redo
+#[tauri::command]
+pub async fn redo_tag_edit(
+    db: tauri::State<'_, crate::database::DbPool>,
+    file_path: String,
+) -> Result<Option<crate::database::models::EditJournalEntry>, String> {
+    journal::redo(&db, &file_path).await.map_err(|e| e.to_string())
+}
+
+/// Edit journal of a file, oldest first
+#[tauri::command]
+pub async fn get_edit_journal(
+    db: tauri::State<'_, crate::database::DbPool>,
+    file_path: String,
+) -> Result<Vec<crate::database::models::EditJournalEntry>, String> {
+    journal::list(&db, &file_path).await.map_err(|e| e.to_string())
+}
 
-    // Delete tag
-    crate::dicom::tags::delete_tag(&mut obj, tag_parsed).map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn get_backup_mode(
+    db: tauri::State<'_, crate::database::DbPool>,
+) -> Result<crate::utils::file_helpers::BackupMode, String> {
+    journal::get_backup_mode(&db).await.map_err(|e| e.to_string())
+}
 
-    // Save modified file
-    crate::dicom::save_dicom_file(&obj, &file_path).map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn set_backup_mode(
+    db: tauri::State<'_, crate::database::DbPool>,
+    mode: crate::utils::file_helpers::BackupMode,
+) -> Result<(), String> {
+    journal::set_backup_mode(&db, mode).await.map_err(|e| e.to_string())
+}
 
-    tracing::info!("Tag deleted and saved for {}", file_path);
+#[tauri::command]
+pub async fn list_file_versions(file_path: String) -> Result<Vec<crate::utils::file_helpers::FileVersion>, String> {
+    Ok(crate::utils::file_helpers::list_versions(&file_path))
+}
 
+/// Put a saved version back in place of the file. The current file is backed up
+/// first, and its edit journal, which no longer applies, is cleared.
+#[tauri::command]
+pub async fn restore_file_version(
+    db: tauri::State<'_, crate::database::DbPool>,
+    file_path: String,
+    version_path: String,
+) -> Result<(), String> {
+    let mode = journal::get_backup_mode(&db).await.map_err(|e| e.to_string())?;
+    journal::replace_file(&db, &file_path, || {
+        crate::utils::file_helpers::restore_version(&file_path, &version_path, mode)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    tracing::info!("Restored {} from {}", file_path, version_path);
     Ok(())
 }
 
+/// Write an anonymized copy of each file next to it. An earlier copy is backed
+/// up before it is replaced, and its edit journal cleared.
 #[tauri::command]
 pub async fn anonymize_study(
+    db: tauri::State<'_, crate::database::DbPool>,
     file_paths: Vec<String>,
     template_name: String,
 ) -> Result<Vec<String>, String> {
@@ -86,11 +172,13 @@ pub async fn anonymize_study(
         .find(|t| t.name == template_name)
         .ok_or_else(|| format!("Template not found: {}", template_name))?;
 
+    let backup_mode = journal::get_backup_mode(&db).await.map_err(|e| e.to_string())?;
     let mut anonymized_paths = Vec::new();
 
     for file_path in file_paths {
-        // Load DICOM file
+        // Load DICOM file, remembering its transfer syntax for the copy
         let mut obj = crate::dicom::load_dicom_file(&file_path).map_err(|e| e.to_string())?;
+        let transfer_syntax = crate::dicom::read_transfer_syntax(&file_path);
 
         // Apply anonymization
         anonymizer::anonymize(&mut obj, template).map_err(|e| e.to_string())?;
@@ -104,7 +192,12 @@ pub async fn anonymize_study(
         };
 
         // Save anonymized file
-        crate::dicom::save_dicom_file(&obj, &anon_path).map_err(|e| e.to_string())?;
+        journal::replace_file(&db, &anon_path, || {
+            crate::utils::file_helpers::backup_file(&anon_path, backup_mode)?;
+            crate::dicom::save_dicom_file_as(&obj, &anon_path, transfer_syntax.as_deref())
+        })
+        .await
+        .map_err(|e| e.to_string())?;
 
         tracing::info!("Anonymized file saved to {}", anon_path);
 
@@ -118,3 +211,103 @@ pub async fn anonymize_study(
 pub async fn get_anonymization_templates() -> Result<Vec<crate::dicom::anonymizer::AnonymizationTemplate>, String> {
     Ok(crate::dicom::anonymizer::get_builtin_templates())
 }
+
+/// Bundled and user-defined private dictionary entries
+#[tauri::command]
+pub async fn get_private_dictionary() -> Result<PrivateDictionary, String> {
+    use crate::dicom::private;
+
+    Ok(PrivateDictionary {
+        builtin: private::builtin_entries(),
+        user: private::user_entries(),
+    })
+}
+
+/// Replace the user-defined private dictionary entries
+#[tauri::command]
+pub async fn save_private_dictionary(entries: Vec<PrivateDictEntry>) -> Result<(), String> {
+    crate::dicom::private::save_user_entries(entries).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct PrivateDictionary {
+    pub builtin: Vec<PrivateDictEntry>,
+    pub user: Vec<PrivateDictEntry>,
+}
+
+/// Files a batch edit applies to
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BatchSelection {
+    Files { paths: Vec<String> },
+    /// Indexed files of a series
+    Series { series_instance_uid: String },
+    /// Every file below a directory; non-DICOM files are reported as failed
+    Directory { path: String },
+}
+
+/// Apply set/remove/regex-replace/copy operations to many files in parallel,
+/// emitting `batch-edit-progress` events. With `dry_run` nothing is written and
+/// the report shows the changes each file would get. Without `output_dir` the
+/// files are overwritten in place, backed up and journaled like single edits.
+#[tauri::command]
+pub async fn batch_edit_tags(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::database::DbPool>,
+    selection: BatchSelection,
+    operations: Vec<crate::dicom::batch::BatchOperation>,
+    output_dir: Option<String>,
+    dry_run: bool,
+) -> Result<crate::dicom::batch::BatchReport, String> {
+    use std::path::PathBuf;
+    use tauri::Emitter;
+
+    let (files, base_dir): (Vec<PathBuf>, Option<PathBuf>) = match selection {
+        BatchSelection::Files { paths } => (paths.into_iter().map(PathBuf::from).collect(), None),
+        BatchSelection::Series { series_instance_uid } => {
+            let paths: Vec<String> =
+                sqlx::query_scalar("SELECT file_path FROM instances WHERE series_instance_uid = ?")
+                    .bind(&series_instance_uid)
+                    .fetch_all(db.inner())
+                    .await
+                    .map_err(|e| e.to_string())?;
+            if paths.is_empty() {
+                return Err(format!("No indexed files for series {}", series_instance_uid));
+            }
+            (paths.into_iter().map(PathBuf::from).collect(), None)
+        }
+        BatchSelection::Directory { path } => {
+            let files = walkdir::WalkDir::new(&path)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.path().to_path_buf())
+                .collect();
+            (files, Some(PathBuf::from(path)))
+        }
+    };
+
+    let pool = db.inner().clone();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        crate::dicom::batch::run(
+            &files,
+            &operations,
+            output_dir.as_deref().map(std::path::Path::new),
+            base_dir.as_deref(),
+            dry_run,
+            |progress| {
+                if let Err(e) = app.emit("batch-edit-progress", progress) {
+                    tracing::warn!("Failed to emit batch progress: {}", e);
+                }
+            },
+            |file, obj, edits| {
+                runtime.block_on(journal::save_edits(&pool, obj, &file.to_string_lossy(), edits))
+            },
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
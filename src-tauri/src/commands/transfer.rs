@@ -0,0 +1,54 @@
+// Cross-protocol transfer and archive reconciliation commands
+
+use crate::database::DbPool;
+use crate::reconcile::{self, InstanceRef, ReconcileReport, ReconcileRequest, SyncResult};
+use crate::transfer::{self, TransferEndpoint, TransferReport, TransferRequest};
+use tauri::State;
+
+/// Copy a study, series or instance between any two of: local folder, DIMSE PACS
+/// and DICOMweb endpoint, optionally anonymizing it, then verify the destination
+#[tauri::command]
+pub async fn transfer_study(
+    db: State<'_, DbPool>,
+    request: TransferRequest,
+) -> Result<TransferReport, String> {
+    transfer::transfer(&db, &request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Compare local instances (index or scanned folder) with a DIMSE or DICOMweb archive
+#[tauri::command]
+pub async fn reconcile_archive(
+    db: State<'_, DbPool>,
+    request: ReconcileRequest,
+) -> Result<ReconcileReport, String> {
+    reconcile::reconcile(&db, &request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Send instances reported as local-only to the remote archive
+#[tauri::command]
+pub async fn send_reconcile_differences(
+    db: State<'_, DbPool>,
+    remote: TransferEndpoint,
+    instances: Vec<InstanceRef>,
+) -> Result<SyncResult, String> {
+    reconcile::send_differences(&db, &remote, &instances)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Retrieve instances reported as remote-only into `output_dir` and index them
+#[tauri::command]
+pub async fn retrieve_reconcile_differences(
+    db: State<'_, DbPool>,
+    remote: TransferEndpoint,
+    instances: Vec<InstanceRef>,
+    output_dir: String,
+) -> Result<SyncResult, String> {
+    reconcile::retrieve_differences(&db, &remote, &instances, &output_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
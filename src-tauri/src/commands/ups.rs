@@ -0,0 +1,199 @@
+// UPS-RS worklist commands
+
+use crate::database::DbPool;
+use crate::dicomweb::client::DicomWebClient;
+use crate::dicomweb::qido::{QidoQuery, QidoResponse};
+use crate::dicomweb::ups::{self, EventMode, SubscriptionTarget, UpsEvent, UpsWatchers};
+use crate::dicomweb::DicomWebEndpoint;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Payload of the `ups-event` frontend event
+#[derive(Debug, Clone, Serialize)]
+pub struct UpsEventPayload {
+    pub watch_id: String,
+    pub event: UpsEvent,
+}
+
+#[tauri::command]
+pub async fn ups_search(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    query: QidoQuery,
+) -> Result<QidoResponse, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::search(&client, &query).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_retrieve(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+) -> Result<serde_json::Value, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::retrieve(&client, &uid).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_create(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    workitem: serde_json::Value,
+    uid: Option<String>,
+) -> Result<String, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::create(&client, workitem, uid.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_update(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+    transaction_uid: Option<String>,
+    changes: serde_json::Value,
+) -> Result<(), String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::update(&client, &uid, transaction_uid.as_deref(), changes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Claim a workitem, returning its Transaction UID
+#[tauri::command]
+pub async fn ups_claim(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+) -> Result<String, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::claim(&client, &uid).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_complete(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+    transaction_uid: String,
+) -> Result<(), String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::complete(&client, &uid, &transaction_uid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_cancel(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+    transaction_uid: String,
+) -> Result<(), String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::cancel(&client, &uid, &transaction_uid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_request_cancellation(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    uid: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::request_cancellation(&client, &uid, reason.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_subscribe(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    target: SubscriptionTarget,
+    aet: String,
+    deletion_lock: bool,
+) -> Result<Option<String>, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::subscribe(&client, &target, &aet, deletion_lock)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ups_unsubscribe(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    target: SubscriptionTarget,
+    aet: String,
+) -> Result<(), String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    ups::unsubscribe(&client, &target, &aet)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Start emitting `ups-event` events for the endpoint, returning the watch id.
+/// Polling requests are not recorded in the request history.
+#[tauri::command]
+pub async fn watch_ups_events(
+    app: AppHandle,
+    watchers: State<'_, UpsWatchers>,
+    endpoint: DicomWebEndpoint,
+    mode: EventMode,
+) -> Result<String, String> {
+    use tauri::Emitter;
+
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    let event_id = watch_id.clone();
+
+    let client = DicomWebClient::new(endpoint).map_err(|e| e.to_string())?;
+    watchers
+        .start(watch_id.clone(), client, mode, move |event| {
+            let payload = UpsEventPayload {
+                watch_id: event_id.clone(),
+                event,
+            };
+            if let Err(e) = app.emit("ups-event", payload) {
+                tracing::warn!("Failed to emit UPS event: {}", e);
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(watch_id)
+}
+
+#[tauri::command]
+pub async fn unwatch_ups_events(
+    watchers: State<'_, UpsWatchers>,
+    watch_id: String,
+) -> Result<bool, String> {
+    watchers.stop(&watch_id).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,97 @@
+// IOD conformance validation and study QA commands
+
+use crate::database::DbPool;
+use crate::dicom::validator::{FileValidation, ValidationReport};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::State;
+
+/// What to validate for a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValidationTarget {
+    File { path: String },
+    /// All indexed instances of a study
+    Study { study_instance_uid: String },
+}
+
+async fn study_files(pool: &DbPool, study_instance_uid: &str) -> Result<Vec<PathBuf>, String> {
+    let paths: Vec<String> = sqlx::query_scalar(
+        "SELECT i.file_path FROM instances i
+         JOIN studies s ON s.id = i.study_id
+         WHERE s.study_instance_uid = ?
+         ORDER BY i.series_instance_uid, i.instance_number"
+    )
+    .bind(study_instance_uid)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if paths.is_empty() {
+        return Err(format!("No indexed files for study {}", study_instance_uid));
+    }
+    Ok(paths.into_iter().map(PathBuf::from).collect())
+}
+
+async fn run_validation(pool: &DbPool, target: ValidationTarget) -> Result<ValidationReport, String> {
+    let files = match target {
+        ValidationTarget::File { path } => vec![PathBuf::from(path)],
+        ValidationTarget::Study { study_instance_uid } => study_files(pool, &study_instance_uid).await?,
+    };
+
+    tokio::task::spawn_blocking(move || crate::dicom::validator::validate_files(&files))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Validate one file against its IOD and its File Meta
+#[tauri::command]
+pub async fn validate_file(file_path: String) -> Result<FileValidation, String> {
+    tokio::task::spawn_blocking(move || crate::dicom::validator::validate_file(&file_path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Validate every indexed instance of a study, with findings aggregated across files
+#[tauri::command]
+pub async fn validate_study(
+    db: State<'_, DbPool>,
+    study_instance_uid: String,
+) -> Result<ValidationReport, String> {
+    run_validation(&db, ValidationTarget::Study { study_instance_uid }).await
+}
+
+/// Validate a file or study and write the report as JSON
+#[tauri::command]
+pub async fn export_validation_report(
+    db: State<'_, DbPool>,
+    target: ValidationTarget,
+    output_path: String,
+) -> Result<ValidationReport, String> {
+    let report = run_validation(&db, target).await?;
+
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(&output_path, json).map_err(|e| e.to_string())?;
+
+    tracing::info!("Validation report written to {}", output_path);
+
+    Ok(report)
+}
+
+/// Study QA report for a directory, scanned the same way as `organize_directory`:
+/// duplicate or conflicting instances, attributes that disagree across a study
+/// or series, Instance Number gaps and irregular slice geometry
+#[tauri::command]
+pub async fn check_study_consistency(path: String) -> Result<crate::dicom::consistency::ConsistencyReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let files = crate::dicom::parser::scan_directory_fast(&path)?;
+        if files.is_empty() {
+            return Err(anyhow::anyhow!("No DICOM files found in directory"));
+        }
+        Ok(crate::dicom::consistency::check(&files))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
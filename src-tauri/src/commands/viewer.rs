@@ -1,6 +1,11 @@
 // Viewer commands
 
+use crate::database::DbPool;
 use crate::dicom;
+use crate::dicomweb::client::DicomWebClient;
+use crate::dicomweb::wado::{self, RenderedParams};
+use crate::dicomweb::DicomWebEndpoint;
+use tauri::State;
 
 #[tauri::command]
 pub async fn get_image_data(
@@ -78,3 +83,172 @@ pub async fn apply_windowing(
 
     Ok(base64_png)
 }
+
+/// Render a remote study, series, instance or frame server-side via WADO-RS `/rendered`
+#[tauri::command]
+pub async fn get_remote_rendered(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    study_uid: String,
+    series_uid: Option<String>,
+    instance_uid: Option<String>,
+    frame: Option<u32>,
+    params: RenderedParams,
+) -> Result<RemoteImage, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let image = wado::retrieve_rendered(
+        &client,
+        &study_uid,
+        series_uid.as_deref(),
+        instance_uid.as_deref(),
+        frame,
+        &params,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to retrieve rendered image: {}", e);
+        e.to_string()
+    })?;
+
+    Ok(RemoteImage::from(image))
+}
+
+/// Fetch a thumbnail for a remote study, series or instance via WADO-RS `/thumbnail`
+#[tauri::command]
+pub async fn get_remote_thumbnail(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    study_uid: String,
+    series_uid: Option<String>,
+    instance_uid: Option<String>,
+    params: RenderedParams,
+) -> Result<RemoteImage, String> {
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+    let image = wado::retrieve_thumbnail(
+        &client,
+        &study_uid,
+        series_uid.as_deref(),
+        instance_uid.as_deref(),
+        &params,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(RemoteImage::from(image))
+}
+
+/// Fetch one frame (1-based) of a remote instance via WADO-RS `/frames` and render it.
+/// Uncompressed frames are rendered locally as PNG with the instance metadata and the
+/// given windowing; frames the server sends as JPEG or PNG are passed through.
+#[tauri::command]
+pub async fn get_remote_frame(
+    db: State<'_, DbPool>,
+    endpoint: DicomWebEndpoint,
+    study_uid: String,
+    series_uid: String,
+    instance_uid: String,
+    frame: u32,
+    window_center: Option<f32>,
+    window_width: Option<f32>,
+) -> Result<RemoteFrame, String> {
+    use base64::{Engine as _, engine::general_purpose};
+    use dicom_dictionary_std::tags;
+
+    let client = DicomWebClient::new(endpoint)
+        .map_err(|e| e.to_string())?
+        .with_history(db.inner().clone());
+
+    let metadata = wado::retrieve_metadata(&client, &study_uid, Some(&series_uid), Some(&instance_uid))
+        .await
+        .and_then(|json| dicom::json::from_json_array(&json))
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No metadata returned for instance {}", instance_uid))?;
+    let number_of_frames = metadata
+        .element(tags::NUMBER_OF_FRAMES)
+        .ok()
+        .and_then(|e| e.to_int::<u32>().ok())
+        .unwrap_or(1);
+
+    let frame_data = wado::retrieve_frames(
+        &client,
+        &study_uid,
+        &series_uid,
+        &instance_uid,
+        &[frame],
+        None,
+        Some("1.2.840.10008.1.2.1"),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to retrieve frame {}: {}", frame, e);
+        e.to_string()
+    })?
+    .pop()
+    .ok_or_else(|| format!("Server returned no data for frame {}", frame))?;
+
+    let image = if frame_data.content_type.starts_with("image/jpeg")
+        || frame_data.content_type.starts_with("image/png")
+    {
+        RemoteImage {
+            content_type: frame_data.content_type,
+            data: general_purpose::STANDARD.encode(&frame_data.data),
+        }
+    } else {
+        let options = dicom::pixeldata::RenderOptions {
+            window: window_center.zip(window_width),
+            format: image::ImageFormat::Png,
+            ..Default::default()
+        };
+        let png = tokio::task::spawn_blocking(move || {
+            let obj = dicom::pixeldata::single_frame_object(metadata, frame_data.data)?;
+            dicom::pixeldata::render_object(&obj, &options)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+        RemoteImage {
+            content_type: "image/png".to_string(),
+            data: general_purpose::STANDARD.encode(&png),
+        }
+    };
+
+    Ok(RemoteFrame {
+        frame_number: frame,
+        number_of_frames,
+        image,
+    })
+}
+
+/// A rendered frame of a remote instance
+#[derive(Debug, serde::Serialize)]
+pub struct RemoteFrame {
+    pub frame_number: u32,
+    pub number_of_frames: u32,
+    #[serde(flatten)]
+    pub image: RemoteImage,
+}
+
+/// Image returned by a DICOMweb server, base64 encoded for the frontend
+#[derive(Debug, serde::Serialize)]
+pub struct RemoteImage {
+    pub content_type: String,
+    pub data: String,
+}
+
+impl From<wado::RenderedImage> for RemoteImage {
+    fn from(image: wado::RenderedImage) -> Self {
+        use base64::{Engine as _, engine::general_purpose};
+
+        Self {
+            content_type: image.content_type,
+            data: general_purpose::STANDARD.encode(&image.data),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+// Saved PACS (DIMSE) and DICOMweb connections
+
+use super::DbPool;
+use crate::dicomweb::capabilities::EndpointCapabilities;
+use crate::dicomweb::{config as dicomweb_config, DicomWebEndpoint};
+use crate::dimse::PacsEndpoint;
+use crate::utils::secrets::SecretVault;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of a saved connection, tagged by its type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "connection_type", content = "config", rename_all = "lowercase")]
+pub enum ConnectionConfig {
+    Dimse(PacsEndpoint),
+    Dicomweb(DicomWebEndpoint),
+}
+
+impl ConnectionConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            ConnectionConfig::Dimse(endpoint) => &endpoint.name,
+            ConnectionConfig::Dicomweb(endpoint) => &endpoint.name,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        match self {
+            ConnectionConfig::Dimse(endpoint) => endpoint.name = name,
+            ConnectionConfig::Dicomweb(endpoint) => endpoint.name = name,
+        }
+    }
+
+    /// Value of the `connection_type` column
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            ConnectionConfig::Dimse(_) => "dimse",
+            ConnectionConfig::Dicomweb(_) => "dicomweb",
+        }
+    }
+}
+
+/// A row of the `connections` table with its parsed config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedConnection {
+    pub id: i64,
+    #[serde(flatten)]
+    pub config: ConnectionConfig,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// List saved connections, optionally filtered by type. DICOMweb secrets are redacted.
+pub async fn list(pool: &DbPool, connection_type: Option<&str>) -> Result<Vec<SavedConnection>> {
+    let rows = sqlx::query_as::<_, super::models::Connection>(
+        "SELECT * FROM connections
+         WHERE (? IS NULL OR connection_type = ?)
+         ORDER BY connection_type, name"
+    )
+    .bind(connection_type)
+    .bind(connection_type)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let mut connection = from_row(row)?;
+            if let ConnectionConfig::Dicomweb(endpoint) = connection.config {
+                connection.config = ConnectionConfig::Dicomweb(dicomweb_config::redact_endpoint(endpoint));
+            }
+            Ok(connection)
+        })
+        .collect()
+}
+
+/// Load a connection with its secrets decrypted
+pub async fn get(pool: &DbPool, vault: &SecretVault, id: i64) -> Result<SavedConnection> {
+    let mut connection = get_sealed(pool, id).await?;
+    if let ConnectionConfig::Dicomweb(endpoint) = connection.config {
+        connection.config = ConnectionConfig::Dicomweb(
+            dicomweb_config::unseal_endpoint(pool, vault, endpoint).await?,
+        );
+    }
+    Ok(connection)
+}
+
+/// Create a connection; names must be unique per connection type
+pub async fn create(pool: &DbPool, vault: &SecretVault, mut config: ConnectionConfig) -> Result<i64> {
+    normalize_name(&mut config)?;
+    ensure_name_available(pool, config.type_str(), config.name(), None).await?;
+
+    let config = seal(pool, vault, config).await?;
+    let json_str = config_json(&config)?;
+
+    let result = sqlx::query(
+        "INSERT INTO connections (name, connection_type, config_json, created_at, updated_at)
+         VALUES (?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"
+    )
+    .bind(config.name())
+    .bind(config.type_str())
+    .bind(&json_str)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Replace the config of an existing connection. Blank DICOMweb secret fields keep
+/// their stored values.
+pub async fn update(pool: &DbPool, vault: &SecretVault, id: i64, mut config: ConnectionConfig) -> Result<()> {
+    normalize_name(&mut config)?;
+    let existing = get_sealed(pool, id).await?;
+    if existing.config.type_str() != config.type_str() {
+        return Err(anyhow::anyhow!(
+            "Cannot change a {} connection into a {} connection",
+            existing.config.type_str(),
+            config.type_str()
+        ));
+    }
+    ensure_name_available(pool, config.type_str(), config.name(), Some(id)).await?;
+
+    let config = match (config, &existing.config) {
+        (ConnectionConfig::Dicomweb(mut edited), ConnectionConfig::Dicomweb(stored)) => {
+            // Editors don't round-trip the inspection report; keep the last one
+            if edited.capabilities.is_none() {
+                edited.capabilities = stored.capabilities.clone();
+            }
+            ConnectionConfig::Dicomweb(dicomweb_config::merge_secrets(edited, stored))
+        }
+        (config, _) => config,
+    };
+    let config = seal(pool, vault, config).await?;
+
+    sqlx::query(
+        "UPDATE connections SET name = ?, config_json = ?, updated_at = CURRENT_TIMESTAMP
+         WHERE id = ?"
+    )
+    .bind(config.name())
+    .bind(config_json(&config)?)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    // Drop secrets that were replaced by the update
+    if let (ConnectionConfig::Dicomweb(old), ConnectionConfig::Dicomweb(new)) = (&existing.config, &config) {
+        let current = dicomweb_config::references(new);
+        for reference in dicomweb_config::references(old) {
+            if !current.contains(&reference) {
+                vault.delete(pool, &reference).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Store the result of an endpoint inspection on a saved DICOMweb connection
+pub async fn save_capabilities(pool: &DbPool, id: i64, capabilities: EndpointCapabilities) -> Result<()> {
+    let mut connection = get_sealed(pool, id).await?;
+    let ConnectionConfig::Dicomweb(endpoint) = &mut connection.config else {
+        return Err(anyhow::anyhow!("Connection {} is not a DICOMweb connection", id));
+    };
+    endpoint.capabilities = Some(capabilities);
+
+    // The stored config is already sealed, so it can be written back as is
+    sqlx::query("UPDATE connections SET config_json = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(config_json(&connection.config)?)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Rename a connection, keeping its config and secrets
+pub async fn rename(pool: &DbPool, vault: &SecretVault, id: i64, new_name: &str) -> Result<()> {
+    let mut connection = get_sealed(pool, id).await?;
+    connection.config.set_name(new_name.to_string());
+    update(pool, vault, id, connection.config).await
+}
+
+/// Delete a connection and its stored secrets
+pub async fn delete(pool: &DbPool, vault: &SecretVault, id: i64) -> Result<()> {
+    let connection = get_sealed(pool, id).await?;
+
+    sqlx::query("DELETE FROM connections WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if let ConnectionConfig::Dicomweb(endpoint) = &connection.config {
+        for reference in dicomweb_config::references(endpoint) {
+            vault.delete(pool, &reference).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy a connection under a new name ("<name> (copy)" by default), with its own copy of any secrets
+pub async fn duplicate(pool: &DbPool, vault: &SecretVault, id: i64, new_name: Option<String>) -> Result<i64> {
+    let mut connection = get(pool, vault, id).await?;
+    let type_str = connection.config.type_str();
+
+    let name = match new_name {
+        Some(name) => name,
+        None => {
+            let base = format!("{} (copy)", connection.config.name());
+            let mut candidate = base.clone();
+            let mut n = 2;
+            while name_exists(pool, type_str, &candidate, None).await? {
+                candidate = format!("{} {}", base, n);
+                n += 1;
+            }
+            candidate
+        }
+    };
+
+    connection.config.set_name(name);
+    create(pool, vault, connection.config).await
+}
+
+async fn get_sealed(pool: &DbPool, id: i64) -> Result<SavedConnection> {
+    let row = sqlx::query_as::<_, super::models::Connection>("SELECT * FROM connections WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Connection {} not found", id))?;
+
+    from_row(row)
+}
+
+fn from_row(row: super::models::Connection) -> Result<SavedConnection> {
+    let config = match row.connection_type.as_str() {
+        "dimse" => ConnectionConfig::Dimse(serde_json::from_str(&row.config_json)?),
+        "dicomweb" => ConnectionConfig::Dicomweb(serde_json::from_str(&row.config_json)?),
+        other => return Err(anyhow::anyhow!("Unknown connection type: {}", other)),
+    };
+
+    Ok(SavedConnection {
+        id: row.id,
+        config,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
+
+fn config_json(config: &ConnectionConfig) -> Result<String> {
+    Ok(match config {
+        ConnectionConfig::Dimse(endpoint) => serde_json::to_string(endpoint)?,
+        ConnectionConfig::Dicomweb(endpoint) => serde_json::to_string(endpoint)?,
+    })
+}
+
+async fn seal(pool: &DbPool, vault: &SecretVault, config: ConnectionConfig) -> Result<ConnectionConfig> {
+    Ok(match config {
+        ConnectionConfig::Dicomweb(endpoint) => {
+            ConnectionConfig::Dicomweb(dicomweb_config::seal_endpoint(pool, vault, endpoint).await?)
+        }
+        other => other,
+    })
+}
+
+/// Names are compared as entered, so surrounding whitespace is dropped everywhere
+fn normalize_name(config: &mut ConnectionConfig) -> Result<()> {
+    let name = config.name().trim().to_string();
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("Connection name cannot be empty"));
+    }
+    config.set_name(name);
+    Ok(())
+}
+
+async fn name_exists(pool: &DbPool, connection_type: &str, name: &str, exclude_id: Option<i64>) -> Result<bool> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM connections
+         WHERE connection_type = ? AND name = ? AND (? IS NULL OR id != ?)"
+    )
+    .bind(connection_type)
+    .bind(name)
+    .bind(exclude_id)
+    .bind(exclude_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count > 0)
+}
+
+async fn ensure_name_available(
+    pool: &DbPool,
+    connection_type: &str,
+    name: &str,
+    exclude_id: Option<i64>,
+) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(anyhow::anyhow!("Connection name cannot be empty"));
+    }
+    if name_exists(pool, connection_type, name, exclude_id).await? {
+        return Err(anyhow::anyhow!(
+            "A {} connection named '{}' already exists",
+            connection_type,
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_config_serde() {
+        let config = ConnectionConfig::Dimse(PacsEndpoint {
+            name: "Main PACS".to_string(),
+            ae_title: "PACS".to_string(),
+            host: "10.0.0.5".to_string(),
+            port: 104,
+            our_ae_title: "DICOMFLOW".to_string(),
+        });
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["connection_type"], "dimse");
+        assert_eq!(json["config"]["name"], "Main PACS");
+
+        let parsed: ConnectionConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.type_str(), "dimse");
+        assert_eq!(parsed.name(), "Main PACS");
+    }
+
+    #[tokio::test]
+    async fn test_unique_name_migration_renames_duplicates() {
+        use sqlx::Executor;
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let (before, after): (Vec<_>, Vec<_>) = migrator
+            .iter()
+            .partition(|m| m.version < 20250121000001);
+        for migration in before {
+            pool.execute(&*migration.sql).await.unwrap();
+        }
+
+        for host in ["10.0.0.1", "10.0.0.2"] {
+            let config = serde_json::json!({ "name": "PACS", "ae_title": "PACS", "host": host, "port": 104, "our_ae_title": "US" });
+            sqlx::query(
+                "INSERT INTO connections (name, connection_type, config_json, created_at, updated_at)
+                 VALUES ('PACS', 'dimse', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"
+            )
+            .bind(config.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        pool.execute(&*after[0].sql).await.unwrap();
+
+        let rows = list(&pool, None).await.unwrap();
+        assert_eq!(rows.len(), 2);
+        let ConnectionConfig::Dimse(renamed) = &rows[1].config else { unreachable!() };
+        assert_eq!(renamed.name, "PACS (2)");
+        assert_eq!(renamed.host, "10.0.0.1");
+        assert_eq!(rows[0].config.name(), "PACS");
+    }
+}
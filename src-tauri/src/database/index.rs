@@ -0,0 +1,94 @@
+// Local study index - keeps the studies/instances tables in sync with files on disk
+
+use super::DbPool;
+use anyhow::Result;
+use std::path::Path;
+
+/// Add a DICOM file to the local index, updating existing rows for the same UIDs
+pub async fn index_file<P: AsRef<Path>>(pool: &DbPool, path: P) -> Result<()> {
+    use dicom_dictionary_std::tags;
+
+    let path = path.as_ref();
+    let obj = crate::dicom::load_dicom_file(path)?;
+    let metadata = crate::dicom::extract_metadata(&obj)?;
+    let tags_json = crate::dicom::tags::tags_to_json(&obj)?;
+
+    let study_description = obj
+        .element(tags::STUDY_DESCRIPTION)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|s| s.to_string());
+
+    let instance_number = obj
+        .element(tags::INSTANCE_NUMBER)
+        .ok()
+        .and_then(|e| e.to_int::<i32>().ok());
+
+    let file_path = path.to_string_lossy().to_string();
+    let study_dir = path
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+
+    sqlx::query(
+        "INSERT INTO studies (study_instance_uid, patient_id, patient_name, study_date,
+                              study_description, modality, file_path)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(study_instance_uid) DO UPDATE SET
+             patient_id = excluded.patient_id,
+             patient_name = excluded.patient_name,
+             study_date = excluded.study_date,
+             study_description = excluded.study_description,
+             modality = excluded.modality"
+    )
+    .bind(&metadata.study_instance_uid)
+    .bind(&metadata.patient_id)
+    .bind(&metadata.patient_name)
+    .bind(&metadata.study_date)
+    .bind(&study_description)
+    .bind(&metadata.modality)
+    .bind(&study_dir)
+    .execute(pool)
+    .await?;
+
+    let study_id = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM studies WHERE study_instance_uid = ?"
+    )
+    .bind(&metadata.study_instance_uid)
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO instances (study_id, sop_instance_uid, series_instance_uid,
+                                instance_number, tags_json, file_path)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(sop_instance_uid) DO UPDATE SET
+             study_id = excluded.study_id,
+             series_instance_uid = excluded.series_instance_uid,
+             instance_number = excluded.instance_number,
+             tags_json = excluded.tags_json,
+             file_path = excluded.file_path"
+    )
+    .bind(study_id)
+    .bind(&metadata.sop_instance_uid)
+    .bind(&metadata.series_instance_uid)
+    .bind(instance_number)
+    .bind(&tags_json)
+    .bind(&file_path)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Index a batch of files, returning how many were added successfully
+pub async fn index_files<P: AsRef<Path>>(pool: &DbPool, paths: &[P]) -> usize {
+    let mut indexed = 0;
+    for path in paths {
+        match index_file(pool, path).await {
+            Ok(()) => indexed += 1,
+            Err(e) => tracing::warn!("Failed to index {:?}: {}", path.as_ref(), e),
+        }
+    }
+    indexed
+}
@@ -0,0 +1,313 @@
+// Edit journal - records tag edits per file so they can be undone and redone
+
+use super::models::EditJournalEntry;
+use super::DbPool;
+use crate::dicom::path::TagPath;
+use crate::dicom::tags;
+use crate::utils::file_helpers::{self, BackupMode};
+use anyhow::Result;
+use dicom_object::InMemDicomObject;
+
+const SETTING_BACKUP_MODE: &str = "edit_backup_mode";
+
+/// State of one attribute or sequence item, captured before or after an edit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// Display value, for listing the journal
+    pub value: Option<String>,
+    /// The attribute (in a one-element dataset) or the item as DICOM JSON;
+    /// `None` when nothing is at the path
+    pub json: Option<String>,
+}
+
+/// Capture what is currently at `path`
+pub fn capture(obj: &InMemDicomObject, path: &TagPath) -> Result<Snapshot> {
+    if path.is_item() {
+        return Ok(match tags::sequence_item_at(obj, path) {
+            Some(item) => Snapshot {
+                value: path.segments.last().and_then(|s| s.item).map(|i| format!("item {}", i)),
+                json: Some(crate::dicom::json::to_json(item)?.to_string()),
+            },
+            None => Snapshot::default(),
+        });
+    }
+
+    Ok(match tags::element_at(obj, path) {
+        Some(elem) => Snapshot {
+            value: Some(match elem.items() {
+                Some(items) => format!("{} items", items.len()),
+                None => elem.to_str().map(|s| s.to_string()).unwrap_or_else(|_| "<binary>".to_string()),
+            }),
+            json: Some(
+                crate::dicom::json::to_json(&InMemDicomObject::from_element_iter([elem.clone()]))?.to_string(),
+            ),
+        },
+        None => Snapshot::default(),
+    })
+}
+
+/// Put a captured state back at `path`, deleting whatever is there if it was absent
+pub fn restore(obj: &mut InMemDicomObject, path: &TagPath, json: Option<&str>) -> Result<()> {
+    let Some(json) = json else {
+        return tags::delete_tag_at(obj, path);
+    };
+    let dataset = crate::dicom::json::from_json(&serde_json::from_str(json)?)?;
+
+    if path.is_item() {
+        // Replace the item if it is still there, else put it back at its index
+        if tags::sequence_item_at(obj, path).is_some() {
+            tags::delete_tag_at(obj, path)?;
+        }
+        return tags::insert_item_at(obj, path, dataset);
+    }
+
+    let elem = dataset
+        .element(path.tag())
+        .map_err(|_| anyhow::anyhow!("Journal entry for {} holds no value", path))?
+        .clone();
+    tags::put_element_at(obj, path, elem)
+}
+
+/// An edit of one attribute, to journal
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub path: TagPath,
+    pub operation: &'static str,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+/// Save an edited file and journal its edits together. A new edit discards the
+/// file's redo history. The entries are inserted in a transaction that commits
+/// only once the file is backed up and written, so a failed write leaves no
+/// entry and a failed insert no write.
+pub async fn save_edits(pool: &DbPool, obj: &InMemDicomObject, file_path: &str, edits: Vec<Edit>) -> Result<()> {
+    let mode = get_backup_mode(pool).await?;
+    let mut tx = pool.begin().await?;
+
+    discard_redo(&mut tx, file_path).await?;
+    for edit in edits {
+        insert(&mut tx, file_path, &edit.path, edit.operation, edit.before, edit.after).await?;
+    }
+    file_helpers::backup_file(file_path, mode)?;
+    crate::dicom::save_dicom_file(obj, file_path)?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Replace the whole of `file_path` outside tag editing, e.g. by restoring a
+/// saved version. Its journal is cleared along with it, since the entries
+/// describe content that is gone; the clearing commits only once `write` succeeds.
+pub async fn replace_file(pool: &DbPool, file_path: &str, write: impl FnOnce() -> Result<()>) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM edit_journal WHERE file_path = ?")
+        .bind(file_path)
+        .execute(&mut *tx)
+        .await?;
+    write()?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn discard_redo(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, file_path: &str) -> Result<()> {
+    sqlx::query("DELETE FROM edit_journal WHERE file_path = ? AND undone = 1")
+        .bind(file_path)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn insert(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    file_path: &str,
+    path: &TagPath,
+    operation: &str,
+    before: Snapshot,
+    after: Snapshot,
+) -> Result<i64> {
+    Ok(sqlx::query(
+        "INSERT INTO edit_journal (file_path, tag_path, operation, old_value, new_value, old_json, new_json)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(file_path)
+    .bind(path.to_string())
+    .bind(operation)
+    .bind(before.value)
+    .bind(after.value)
+    .bind(before.json)
+    .bind(after.json)
+    .execute(&mut **tx)
+    .await?
+    .last_insert_rowid())
+}
+
+/// Journal of `file_path`, oldest first; undone entries are the redo stack
+pub async fn list(pool: &DbPool, file_path: &str) -> Result<Vec<EditJournalEntry>> {
+    Ok(sqlx::query_as::<_, EditJournalEntry>(
+        "SELECT * FROM edit_journal WHERE file_path = ? ORDER BY id"
+    )
+    .bind(file_path)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Undo the latest edit of `file_path`; `None` when there is nothing to undo.
+/// Like `save_edits`, the entry is marked in a transaction that commits only
+/// once the file is written.
+pub async fn undo(pool: &DbPool, file_path: &str) -> Result<Option<EditJournalEntry>> {
+    let mode = get_backup_mode(pool).await?;
+    let mut tx = pool.begin().await?;
+
+    let entry = sqlx::query_as::<_, EditJournalEntry>(
+        "SELECT * FROM edit_journal WHERE file_path = ? AND undone = 0 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(file_path)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+
+    set_undone(&mut tx, entry.id, true).await?;
+    replay(&entry, entry.old_json.as_deref(), mode)?;
+    tx.commit().await?;
+
+    tracing::info!("Undid edit of {} in {}", entry.tag_path, file_path);
+    Ok(Some(EditJournalEntry { undone: true, ..entry }))
+}
+
+/// Redo the oldest undone edit of `file_path`; `None` when there is nothing to redo
+pub async fn redo(pool: &DbPool, file_path: &str) -> Result<Option<EditJournalEntry>> {
+    let mode = get_backup_mode(pool).await?;
+    let mut tx = pool.begin().await?;
+
+    let entry = sqlx::query_as::<_, EditJournalEntry>(
+        "SELECT * FROM edit_journal WHERE file_path = ? AND undone = 1 ORDER BY id LIMIT 1"
+    )
+    .bind(file_path)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+
+    set_undone(&mut tx, entry.id, false).await?;
+    replay(&entry, entry.new_json.as_deref(), mode)?;
+    tx.commit().await?;
+
+    tracing::info!("Redid edit of {} in {}", entry.tag_path, file_path);
+    Ok(Some(EditJournalEntry { undone: false, ..entry }))
+}
+
+/// Restore one side of a journal entry into its file, backing the file up first
+fn replay(entry: &EditJournalEntry, json: Option<&str>, mode: BackupMode) -> Result<()> {
+    let path = TagPath::parse(&entry.tag_path)?;
+    let mut obj = crate::dicom::load_dicom_file(&entry.file_path)?;
+    restore(&mut obj, &path, json)?;
+    file_helpers::backup_file(&entry.file_path, mode)?;
+    crate::dicom::save_dicom_file(&obj, &entry.file_path)
+}
+
+async fn set_undone(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, id: i64, undone: bool) -> Result<()> {
+    sqlx::query("UPDATE edit_journal SET undone = ? WHERE id = ?")
+        .bind(undone)
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_backup_mode(pool: &DbPool) -> Result<BackupMode> {
+    let value = sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(SETTING_BACKUP_MODE)
+        .fetch_optional(pool)
+        .await?;
+    Ok(value
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
+pub async fn set_backup_mode(pool: &DbPool, mode: BackupMode) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO settings (key, value, updated_at)
+         VALUES (?, ?, CURRENT_TIMESTAMP)"
+    )
+    .bind(SETTING_BACKUP_MODE)
+    .bind(serde_json::to_string(&mode)?)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{Tag, VR};
+    use dicom_object::mem::InMemElement;
+
+    async fn test_pool() -> DbPool {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_capture_and_restore() {
+        let mut obj = InMemDicomObject::from_element_iter([InMemElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::from("Doe^John"),
+        )]);
+        let path = TagPath::parse("(0010,0010)").unwrap();
+
+        let before = capture(&obj, &path).unwrap();
+        assert_eq!(before.value.as_deref(), Some("Doe^John"));
+
+        tags::delete_tag_at(&mut obj, &path).unwrap();
+        assert_eq!(capture(&obj, &path).unwrap(), Snapshot::default());
+
+        restore(&mut obj, &path, before.json.as_deref()).unwrap();
+        let elem = obj.element(Tag(0x0010, 0x0010)).unwrap();
+        assert_eq!(elem.vr(), VR::PN);
+        assert_eq!(elem.to_str().unwrap(), "Doe^John");
+
+        restore(&mut obj, &path, None).unwrap();
+        assert!(obj.element(Tag(0x0010, 0x0010)).is_err());
+    }
+
+    /// Journal an edit without saving a file, as `save_edits` does
+    async fn record(pool: &DbPool, file_path: &str, path: &TagPath, operation: &str, before: Snapshot, after: Snapshot) {
+        let mut tx = pool.begin().await.unwrap();
+        discard_redo(&mut tx, file_path).await.unwrap();
+        insert(&mut tx, file_path, path, operation, before, after).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_redo_stack() {
+        let pool = test_pool().await;
+        let path = TagPath::parse("(0010,0010)").unwrap();
+        let snapshot = |value: &str| Snapshot { value: Some(value.to_string()), json: None };
+
+        record(&pool, "a.dcm", &path, "update", snapshot("A"), snapshot("B")).await;
+        record(&pool, "a.dcm", &path, "update", snapshot("B"), snapshot("C")).await;
+        set_undone(&pool, 2, true).await.unwrap();
+
+        // A new edit drops the undone entry from the redo stack
+        record(&pool, "a.dcm", &path, "update", snapshot("B"), snapshot("D")).await;
+        let entries = list(&pool, "a.dcm").await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].new_value.as_deref(), Some("D"));
+        assert!(entries.iter().all(|e| !e.undone));
+
+        assert_eq!(get_backup_mode(&pool).await.unwrap(), BackupMode::None);
+        set_backup_mode(&pool, BackupMode::Versions { keep: 5 }).await.unwrap();
+        assert_eq!(get_backup_mode(&pool).await.unwrap(), BackupMode::Versions { keep: 5 });
+    }
+}
@@ -1,3 +1,6 @@
+pub mod connections;
+pub mod index;
+pub mod journal;
 pub mod models;
 pub mod schema;
 
@@ -39,13 +39,19 @@ pub struct Connection {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Request {
     pub id: i64,
-    pub request_type: String, // "qido", "wado", "stow"
-    pub endpoint: String,
+    pub request_type: String, // "qido", "wado", "stow", "ups", "other"
+    pub connection_name: Option<String>,
+    pub base_url: String,
+    pub endpoint: String,     // Path relative to base_url, including query string
     pub method: String,
-    pub headers_json: String,
+    pub headers_json: String, // Request headers with secrets masked
     pub body: Option<String>,
+    pub body_truncated: bool, // Body was cut when recorded and can't be replayed
     pub response_status: Option<i32>,
+    pub response_headers_json: Option<String>,
     pub response_body: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub error: Option<String>,
     pub created_at: String,
 }
 
@@ -55,3 +61,19 @@ pub struct Setting {
     pub value: String,
     pub updated_at: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EditJournalEntry {
+    pub id: i64,
+    pub file_path: String,
+    pub tag_path: String,
+    pub operation: String, // "update" or "delete"
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    #[serde(skip)]
+    pub old_json: Option<String>, // DICOM JSON of the attribute or item before the edit
+    #[serde(skip)]
+    pub new_json: Option<String>,
+    pub undone: bool,
+    pub created_at: String,
+}
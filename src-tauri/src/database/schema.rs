@@ -43,13 +43,19 @@ pub const CREATE_CONNECTIONS_TABLE: &str = r#"
 pub const CREATE_REQUESTS_TABLE: &str = r#"
     CREATE TABLE IF NOT EXISTS requests (
         id INTEGER PRIMARY KEY AUTOINCREMENT,
-        request_type TEXT NOT NULL CHECK(request_type IN ('qido', 'wado', 'stow')),
+        request_type TEXT NOT NULL,
+        connection_name TEXT,
+        base_url TEXT NOT NULL DEFAULT '',
         endpoint TEXT NOT NULL,
         method TEXT NOT NULL,
         headers_json TEXT NOT NULL,
         body TEXT,
+        body_truncated INTEGER NOT NULL DEFAULT 0,
         response_status INTEGER,
+        response_headers_json TEXT,
         response_body TEXT,
+        duration_ms INTEGER,
+        error TEXT,
         created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
     )
 "#;
@@ -61,3 +67,26 @@ pub const CREATE_SETTINGS_TABLE: &str = r#"
         updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
     )
 "#;
+
+pub const CREATE_SECRETS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS secrets (
+        id TEXT PRIMARY KEY,
+        ciphertext TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    )
+"#;
+
+pub const CREATE_EDIT_JOURNAL_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS edit_journal (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        file_path TEXT NOT NULL,
+        tag_path TEXT NOT NULL,
+        operation TEXT NOT NULL,
+        old_value TEXT,
+        new_value TEXT,
+        old_json TEXT,
+        new_json TEXT,
+        undone INTEGER NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    )
+"#;
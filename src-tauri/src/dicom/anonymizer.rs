@@ -15,6 +15,32 @@ pub struct AnonymizationTemplate {
     pub name: String,
     pub description: String,
     pub rules: Vec<AnonymizationRule>,
+    #[serde(default)]
+    pub private_tags: PrivateTagPolicy,
+}
+
+/// What to do with private tags after the rules have run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivateTagPolicy {
+    /// Remove private tags, except those listed in `keep`
+    pub remove: bool,
+    pub keep: Vec<PrivateTagRef>,
+}
+
+/// A private attribute identified by its creator rather than its block number,
+/// so (0019,100C) and (0019,110C) match alike when "SIEMENS MR HEADER" owns the block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivateTagRef {
+    pub creator: String,
+    pub group: u16,
+    /// Low byte of the element number
+    pub element: u8,
+}
+
+impl PrivateTagRef {
+    fn new(creator: &str, group: u16, element: u8) -> Self {
+        PrivateTagRef { creator: creator.to_string(), group, element }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,14 +61,32 @@ pub enum AnonymizationAction {
 
 /// Anonymize a DICOM object using a template
 pub fn anonymize(obj: &mut InMemDicomObject, template: &AnonymizationTemplate) -> Result<()> {
+    anonymize_with_uid_map(obj, template, &mut HashMap::new())
+}
+
+/// Anonymize an object that belongs to a larger set, such as a study. Generated UIDs
+/// are recorded in `uid_map` (original -> new) so every object of the set gets the
+/// same replacement for the same original UID.
+pub fn anonymize_with_uid_map(
+    obj: &mut InMemDicomObject,
+    template: &AnonymizationTemplate,
+    uid_map: &mut HashMap<String, String>,
+) -> Result<()> {
     for rule in &template.rules {
-        apply_rule(obj, rule)?;
+        apply_rule(obj, rule, uid_map)?;
+    }
+    if template.private_tags.remove {
+        delete_private_tags_except(obj, &template.private_tags.keep)?;
     }
     Ok(())
 }
 
 /// Apply a single anonymization rule
-fn apply_rule(obj: &mut InMemDicomObject, rule: &AnonymizationRule) -> Result<()> {
+fn apply_rule(
+    obj: &mut InMemDicomObject,
+    rule: &AnonymizationRule,
+    uid_map: &mut HashMap<String, String>,
+) -> Result<()> {
     use dicom_object::mem::InMemElement;
     use dicom_core::value::{PrimitiveValue, Value};
     use dicom_core::VR;
@@ -95,7 +139,18 @@ fn apply_rule(obj: &mut InMemDicomObject, rule: &AnonymizationRule) -> Result<()
             }
         }
         AnonymizationAction::GenerateUID => {
-            let new_uid = format!("2.25.{}", Uuid::new_v4().as_u128());
+            let original = obj
+                .element(tag)
+                .ok()
+                .and_then(|e| e.to_str().ok())
+                .map(|s| s.trim_end_matches('\0').to_string());
+            let new_uid = match original {
+                Some(original) if !original.is_empty() => uid_map
+                    .entry(original)
+                    .or_insert_with(|| format!("2.25.{}", Uuid::new_v4().as_u128()))
+                    .clone(),
+                _ => format!("2.25.{}", Uuid::new_v4().as_u128()),
+            };
             let new_elem = InMemElement::new(
                 tag,
                 VR::UI,
@@ -164,6 +219,7 @@ fn basic_template() -> AnonymizationTemplate {
                 action: AnonymizationAction::Blank,
             },
         ],
+        private_tags: PrivateTagPolicy::default(),
     }
 }
 
@@ -321,6 +377,7 @@ fn full_template() -> AnonymizationTemplate {
                 action: AnonymizationAction::Remove,
             },
         ],
+        private_tags: PrivateTagPolicy::default(),
     }
 }
 
@@ -423,15 +480,55 @@ fn research_template() -> AnonymizationTemplate {
                 action: AnonymizationAction::Remove,
             },
         ],
+        // Private tags stay unless removal is turned on; these attributes, needed
+        // to analyse diffusion and scaled MR data, survive it
+        private_tags: PrivateTagPolicy {
+            remove: false,
+            keep: vec![
+                PrivateTagRef::new("SIEMENS MR HEADER", 0x0019, 0x0C), // B value
+                PrivateTagRef::new("SIEMENS MR HEADER", 0x0019, 0x0E), // Diffusion gradient direction
+                PrivateTagRef::new("GEMS_PARM_01", 0x0043, 0x39),      // Slop int 6-9 (b value)
+                PrivateTagRef::new("Philips Imaging DD 001", 0x2001, 0x03), // Diffusion B-factor
+                PrivateTagRef::new("Philips MR Imaging DD 001", 0x2005, 0x0D), // Scale intercept
+                PrivateTagRef::new("Philips MR Imaging DD 001", 0x2005, 0x0E), // Scale slope
+            ],
+        },
     }
 }
 
 /// Delete all private tags
 pub fn delete_private_tags(obj: &mut InMemDicomObject) -> Result<usize> {
+    delete_private_tags_except(obj, &[])
+}
+
+/// Delete private tags other than those in `keep`. Elements are matched through
+/// their private creator; creators of blocks with kept elements are kept too.
+pub fn delete_private_tags_except(obj: &mut InMemDicomObject, keep: &[PrivateTagRef]) -> Result<usize> {
+    use crate::dicom::private;
+
+    let creator_of = |tag: Tag| -> Option<String> {
+        if private::is_creator_element(tag) {
+            obj.element(tag).ok()?.to_str().ok().map(|c| c.trim_end_matches(['\0', ' ']).to_string())
+        } else {
+            private::private_creator(obj, tag)
+        }
+    };
+    let kept = |tag: Tag| -> bool {
+        let Some(creator) = creator_of(tag) else {
+            return false;
+        };
+        keep.iter().any(|k| {
+            k.group == tag.group()
+                && k.creator.trim() == creator.trim()
+                && (private::is_creator_element(tag) || u16::from(k.element) == tag.element() & 0xFF)
+        })
+    };
+
     let private_tags: Vec<Tag> = obj
         .iter()
         .filter(|e| e.tag().group() % 2 == 1) // Private tags have odd group numbers
         .map(|e| e.tag())
+        .filter(|tag| !kept(*tag))
         .collect();
 
     let count = private_tags.len();
@@ -451,4 +548,62 @@ mod tests {
         let templates = get_builtin_templates();
         assert_eq!(templates.len(), 3);
     }
+
+    #[test]
+    fn test_delete_private_tags_matches_creator() {
+        use dicom_core::value::PrimitiveValue;
+        use dicom_core::{DataElement, VR};
+
+        // The b value block sits at 0x11 here, not the usual 0x10
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(Tag(0x0019, 0x0010), VR::LO, PrimitiveValue::from("GEMS_ACQU_01")),
+            DataElement::new(Tag(0x0019, 0x0011), VR::LO, PrimitiveValue::from("SIEMENS MR HEADER")),
+            DataElement::new(Tag(0x0019, 0x100C), VR::DS, PrimitiveValue::from("1")),
+            DataElement::new(Tag(0x0019, 0x110C), VR::IS, PrimitiveValue::from("1000")),
+            DataElement::new(Tag(0x0019, 0x1110), VR::LO, PrimitiveValue::from("x")),
+        ]);
+        let keep = [PrivateTagRef::new("SIEMENS MR HEADER", 0x0019, 0x0C)];
+
+        let removed = delete_private_tags_except(&mut obj, &keep).unwrap();
+
+        assert_eq!(removed, 3);
+        assert!(obj.element(Tag(0x0019, 0x110C)).is_ok());
+        assert!(obj.element(Tag(0x0019, 0x0011)).is_ok());
+        assert!(obj.element(Tag(0x0019, 0x100C)).is_err());
+    }
+
+    #[test]
+    fn test_generated_uids_consistent_across_objects() {
+        use dicom_core::value::PrimitiveValue;
+        use dicom_core::{DataElement, VR};
+
+        let template = AnonymizationTemplate {
+            name: "uids".to_string(),
+            description: String::new(),
+            rules: vec![AnonymizationRule {
+                tag: "(0020,000D)".to_string(),
+                action: AnonymizationAction::GenerateUID,
+            }],
+            private_tags: PrivateTagPolicy::default(),
+        };
+        let object = || {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::STUDY_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("1.2.3"),
+            )])
+        };
+
+        let mut uid_map = HashMap::new();
+        let mut first = object();
+        let mut second = object();
+        anonymize_with_uid_map(&mut first, &template, &mut uid_map).unwrap();
+        anonymize_with_uid_map(&mut second, &template, &mut uid_map).unwrap();
+
+        let first_uid = first.element(tags::STUDY_INSTANCE_UID).unwrap().to_str().unwrap().to_string();
+        let second_uid = second.element(tags::STUDY_INSTANCE_UID).unwrap().to_str().unwrap().to_string();
+        assert_ne!(first_uid, "1.2.3");
+        assert_eq!(first_uid, second_uid);
+        assert_eq!(uid_map["1.2.3"], first_uid);
+    }
 }
@@ -0,0 +1,429 @@
+// Batch tag editing: one list of operations applied to many files
+
+use super::path::TagPath;
+use super::tags;
+use crate::database::journal;
+use anyhow::Result;
+use dicom_core::VR;
+use dicom_object::InMemDicomObject;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// An edit applied to every file of a batch. Tags are paths, so nested
+/// attributes such as "(0040,0275)[0].(0032,1060)" can be edited too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    /// Set a value, parsed according to the VR (given or from the dictionary)
+    Set {
+        tag: String,
+        value: String,
+        vr: Option<String>,
+    },
+    Remove {
+        tag: String,
+    },
+    /// Regex replacement in the value's text; `replacement` may use $1 etc.
+    RegexReplace {
+        tag: String,
+        pattern: String,
+        replacement: String,
+    },
+    /// Copy the value of one attribute to another
+    Copy {
+        from: String,
+        to: String,
+    },
+}
+
+/// Operation with its paths parsed and its regex compiled
+enum PreparedOperation {
+    Set { path: TagPath, value: String, vr: Option<VR> },
+    Remove { path: TagPath },
+    RegexReplace { path: TagPath, regex: regex::Regex, replacement: String },
+    Copy { from: TagPath, to: TagPath },
+}
+
+impl PreparedOperation {
+    fn target(&self) -> &TagPath {
+        match self {
+            PreparedOperation::Set { path, .. }
+            | PreparedOperation::Remove { path }
+            | PreparedOperation::RegexReplace { path, .. } => path,
+            PreparedOperation::Copy { to, .. } => to,
+        }
+    }
+}
+
+/// Check all operations up front so a typo fails the batch before any file is touched
+fn prepare(operations: &[BatchOperation]) -> Result<Vec<PreparedOperation>> {
+    operations
+        .iter()
+        .map(|op| {
+            Ok(match op {
+                BatchOperation::Set { tag, value, vr } => PreparedOperation::Set {
+                    path: TagPath::parse(tag)?,
+                    value: value.clone(),
+                    vr: vr
+                        .as_deref()
+                        .map(|vr| vr.parse::<VR>().map_err(|_| anyhow::anyhow!("Unknown VR: {}", vr)))
+                        .transpose()?,
+                },
+                BatchOperation::Remove { tag } => PreparedOperation::Remove { path: TagPath::parse(tag)? },
+                BatchOperation::RegexReplace { tag, pattern, replacement } => {
+                    PreparedOperation::RegexReplace {
+                        path: TagPath::parse(tag)?,
+                        regex: regex::Regex::new(pattern)
+                            .map_err(|e| anyhow::anyhow!("Invalid pattern {:?}: {}", pattern, e))?,
+                        replacement: replacement.clone(),
+                    }
+                }
+                BatchOperation::Copy { from, to } => PreparedOperation::Copy {
+                    from: TagPath::parse(from)?,
+                    to: TagPath::parse(to)?,
+                },
+            })
+        })
+        .collect()
+}
+
+/// One changed attribute; `None` means absent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagChange {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChanges {
+    pub path: String,
+    /// Where the edited file was (or, in a dry run, would be) written
+    pub output_path: Option<String>,
+    pub changes: Vec<TagChange>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub dry_run: bool,
+    pub files: Vec<FileChanges>,
+    pub changed_files: usize,
+    pub failed_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub path: String,
+}
+
+/// Where the edited copy of `file` goes: the same path when overwriting, else
+/// `output_dir` keeping the layout below `base_dir` (or just the file name)
+fn output_path(file: &Path, output_dir: Option<&Path>, base_dir: Option<&Path>) -> PathBuf {
+    let Some(output_dir) = output_dir else {
+        return file.to_path_buf();
+    };
+    let relative = base_dir
+        .and_then(|base| file.strip_prefix(base).ok())
+        .map(Path::to_path_buf)
+        .or_else(|| file.file_name().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("edited.dcm"));
+    output_dir.join(relative)
+}
+
+/// Deepest directory containing all of `files`, so that same-named files from
+/// different folders keep apart below the output directory
+fn common_dir(files: &[PathBuf]) -> Option<PathBuf> {
+    let mut common = files.first()?.parent()?.to_path_buf();
+    for file in &files[1..] {
+        while !file.starts_with(&common) {
+            if !common.pop() {
+                return None;
+            }
+        }
+    }
+    Some(common)
+}
+
+/// Output path of each file, failing when two files would be written to the
+/// same path or one would overwrite another file of the batch
+fn output_paths(files: &[PathBuf], output_dir: Option<&Path>, base_dir: Option<&Path>) -> Result<Vec<PathBuf>> {
+    use std::collections::HashMap;
+
+    let common = base_dir.is_none().then(|| common_dir(files)).flatten();
+    let base_dir = base_dir.or(common.as_deref());
+    let outputs: Vec<PathBuf> = files.iter().map(|file| output_path(file, output_dir, base_dir)).collect();
+
+    let mut targets: HashMap<&Path, &Path> = HashMap::new();
+    for (file, output) in files.iter().zip(&outputs) {
+        if let Some(other) = targets.insert(output.as_path(), file.as_path()) {
+            return Err(anyhow::anyhow!("{:?} and {:?} would both be written to {:?}", other, file, output));
+        }
+    }
+    if output_dir.is_some() {
+        let inputs: std::collections::HashSet<&Path> = files.iter().map(PathBuf::as_path).collect();
+        for (file, output) in files.iter().zip(&outputs) {
+            if output != file && inputs.contains(output.as_path()) {
+                return Err(anyhow::anyhow!("The edited copy of {:?} would overwrite {:?}", file, output));
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+/// Current value at `path`; sequence items show as "item N"
+fn value_at(obj: &InMemDicomObject, path: &TagPath) -> Option<String> {
+    if path.is_item() {
+        let mut sequence = path.clone();
+        let last = sequence.segments.last_mut()?;
+        let index = last.item.take()?;
+        let tag = tags::get_tag_at(obj, &sequence).ok()?;
+        return (index < tag.items.len()).then(|| format!("item {}", index));
+    }
+    tags::get_tag_at(obj, path).ok().map(|tag| tag.value)
+}
+
+fn apply(obj: &mut InMemDicomObject, op: &PreparedOperation) -> Result<()> {
+    match op {
+        PreparedOperation::Set { path, value, vr } => tags::update_tag_at(obj, path, value.clone(), *vr),
+        PreparedOperation::Remove { path } => tags::delete_tag_at(obj, path),
+        PreparedOperation::RegexReplace { path, regex, replacement } => {
+            let Some(current) = value_at(obj, path) else {
+                return Ok(());
+            };
+            let replaced = regex.replace_all(&current, replacement.as_str());
+            if replaced != current {
+                tags::update_tag_at(obj, path, replaced.into_owned(), None)?;
+            }
+            Ok(())
+        }
+        PreparedOperation::Copy { from, to } => {
+            let Ok(source) = tags::get_tag_at(obj, from) else {
+                return Ok(());
+            };
+            // An existing or standard target keeps its own VR; others take the source's
+            let vr = if tags::get_tag_at(obj, to).is_ok() || super::dictionary::lookup(to.tag()).is_some() {
+                None
+            } else {
+                source.vr.parse::<VR>().ok()
+            };
+            tags::update_tag_at(obj, to, source.value, vr)
+        }
+    }
+}
+
+/// Apply `operations` to one file and save it unless this is a dry run. An
+/// in-place edit is handed to `save_in_place` with the journal of its changes.
+fn edit_file(
+    file: &Path,
+    operations: &[PreparedOperation],
+    output: &Path,
+    dry_run: bool,
+    save_in_place: &(dyn Fn(&Path, &InMemDicomObject, Vec<journal::Edit>) -> Result<()> + Sync),
+) -> Result<Vec<TagChange>> {
+    let mut obj = super::load_dicom_file(file)?;
+    let journaled = !dry_run && output == file;
+
+    // Remember each touched attribute's value before the first operation on it
+    let mut before: Vec<(TagPath, Option<String>, journal::Snapshot)> = Vec::new();
+    for op in operations {
+        let target = op.target();
+        if !before.iter().any(|(path, ..)| path == target) {
+            let snapshot = if journaled { journal::capture(&obj, target)? } else { Default::default() };
+            before.push((target.clone(), value_at(&obj, target), snapshot));
+        }
+        apply(&mut obj, op)?;
+    }
+
+    let mut changes = Vec::new();
+    let mut edits = Vec::new();
+    for (path, before, snapshot) in before {
+        let after = value_at(&obj, &path);
+        if before == after {
+            continue;
+        }
+        if journaled {
+            edits.push(journal::Edit {
+                operation: if after.is_some() { "update" } else { "delete" },
+                after: journal::capture(&obj, &path)?,
+                before: snapshot,
+                path: path.clone(),
+            });
+        }
+        changes.push(TagChange { path: path.to_string(), before, after });
+    }
+
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
+    if journaled {
+        save_in_place(file, &obj, edits)?;
+    } else {
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        super::save_dicom_file_as(&obj, output, super::read_transfer_syntax(file).as_deref())?;
+    }
+
+    Ok(changes)
+}
+
+/// Apply `operations` to `files` in parallel. With `dry_run` nothing is written
+/// and the report previews the changes. Copies written to `output_dir` keep the
+/// directory layout below `base_dir`, or below the files' common directory.
+/// Files edited in place are saved through `save_in_place`, which backs them up
+/// and journals their changes.
+pub fn run(
+    files: &[PathBuf],
+    operations: &[BatchOperation],
+    output_dir: Option<&Path>,
+    base_dir: Option<&Path>,
+    dry_run: bool,
+    on_progress: impl Fn(BatchProgress) + Sync,
+    save_in_place: impl Fn(&Path, &InMemDicomObject, Vec<journal::Edit>) -> Result<()> + Sync,
+) -> Result<BatchReport> {
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    if operations.is_empty() {
+        return Err(anyhow::anyhow!("No operations given"));
+    }
+    let operations = prepare(operations)?;
+
+    // A file listed twice would be edited twice, concurrently
+    let mut files = files.to_vec();
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    let files = &files[..];
+    let outputs = output_paths(files, output_dir, base_dir)?;
+    let processed = AtomicUsize::new(0);
+
+    let results: Vec<FileChanges> = files
+        .par_iter()
+        .zip(&outputs)
+        .map(|(file, output)| {
+            let result = edit_file(file, &operations, output, dry_run, &save_in_place);
+
+            on_progress(BatchProgress {
+                processed: processed.fetch_add(1, Ordering::SeqCst) + 1,
+                total: files.len(),
+                path: file.to_string_lossy().to_string(),
+            });
+
+            match result {
+                Ok(changes) => FileChanges {
+                    path: file.to_string_lossy().to_string(),
+                    output_path: (!changes.is_empty()).then(|| output.to_string_lossy().to_string()),
+                    changes,
+                    error: None,
+                },
+                Err(e) => FileChanges {
+                    path: file.to_string_lossy().to_string(),
+                    output_path: None,
+                    changes: Vec::new(),
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect();
+
+    let report = BatchReport {
+        dry_run,
+        changed_files: results.iter().filter(|f| !f.changes.is_empty()).count(),
+        failed_files: results.iter().filter(|f| f.error.is_some()).count(),
+        files: results,
+    };
+
+    tracing::info!(
+        "Batch edit{}: {} of {} files changed, {} failed",
+        if dry_run { " (dry run)" } else { "" },
+        report.changed_files,
+        files.len(),
+        report.failed_files
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::Tag;
+    use dicom_object::mem::InMemElement;
+
+    fn object() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            InMemElement::new(Tag(0x0008, 0x0080), VR::LO, PrimitiveValue::from("General Hospital")),
+            InMemElement::new(Tag(0x0010, 0x0010), VR::PN, PrimitiveValue::from("Doe^John")),
+            InMemElement::new(Tag(0x0010, 0x0020), VR::LO, PrimitiveValue::from("PID-0042")),
+        ])
+    }
+
+    #[test]
+    fn test_operations() {
+        let operations = prepare(&[
+            BatchOperation::RegexReplace {
+                tag: "(0010,0020)".to_string(),
+                pattern: r"^PID-(\d+)$".to_string(),
+                replacement: "ANON-$1".to_string(),
+            },
+            BatchOperation::Copy { from: "(0010,0020)".to_string(), to: "(0010,1000)".to_string() },
+            BatchOperation::Remove { tag: "(0008,0080)".to_string() },
+            BatchOperation::Set { tag: "(0010,0010)".to_string(), value: "Anonymous".to_string(), vr: None },
+        ])
+        .unwrap();
+
+        let mut obj = object();
+        for op in &operations {
+            apply(&mut obj, op).unwrap();
+        }
+
+        let value = |tag: &str| value_at(&obj, &TagPath::parse(tag).unwrap());
+        assert_eq!(value("(0010,0020)").as_deref(), Some("ANON-0042"));
+        assert_eq!(value("(0010,1000)").as_deref(), Some("ANON-0042"));
+        assert_eq!(value("(0008,0080)"), None);
+        assert_eq!(value("(0010,0010)").as_deref(), Some("Anonymous"));
+    }
+
+    #[test]
+    fn test_prepare_rejects_bad_input() {
+        let bad_regex = BatchOperation::RegexReplace {
+            tag: "(0010,0020)".to_string(),
+            pattern: "(".to_string(),
+            replacement: String::new(),
+        };
+        assert!(prepare(&[bad_regex]).is_err());
+        assert!(prepare(&[BatchOperation::Remove { tag: "(0010)".to_string() }]).is_err());
+    }
+
+    #[test]
+    fn test_output_path_keeps_layout() {
+        let file = Path::new("/data/study/series1/img1.dcm");
+        assert_eq!(output_path(file, None, None), file);
+        assert_eq!(
+            output_path(file, Some(Path::new("/out")), Some(Path::new("/data/study"))),
+            Path::new("/out/series1/img1.dcm")
+        );
+        assert_eq!(output_path(file, Some(Path::new("/out")), None), Path::new("/out/img1.dcm"));
+    }
+
+    #[test]
+    fn test_output_paths_keep_same_names_apart() {
+        let files = [PathBuf::from("/data/a/img1.dcm"), PathBuf::from("/data/b/img1.dcm")];
+        let out = Some(Path::new("/out"));
+
+        assert_eq!(
+            output_paths(&files, out, None).unwrap(),
+            [PathBuf::from("/out/a/img1.dcm"), PathBuf::from("/out/b/img1.dcm")]
+        );
+        // Outside the base directory both fall back to the file name
+        assert!(output_paths(&files, out, Some(Path::new("/elsewhere"))).is_err());
+        // A copy must not land on another file of the batch
+        let nested = [PathBuf::from("/data/img1.dcm"), PathBuf::from("/data/out/img1.dcm")];
+        assert!(output_paths(&nested, Some(Path::new("/data/out")), Some(Path::new("/data"))).is_err());
+        assert_eq!(output_paths(&files, None, None).unwrap(), files);
+    }
+}
@@ -0,0 +1,470 @@
+// Study-level consistency checks across the files of a scanned directory
+
+use super::parser::MinimalFileInfo;
+use super::validator::Severity;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Identical copies of an instance
+    DuplicateInstance,
+    /// Files sharing a SOP Instance UID with different content
+    ConflictingInstance,
+    /// Files of one study or series disagree on an attribute
+    InconsistentAttribute,
+    InstanceNumberGap,
+    DuplicateInstanceNumber,
+    MixedOrientation,
+    DuplicatePosition,
+    InconsistentSpacing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyIssue {
+    pub kind: IssueKind,
+    pub severity: Severity,
+    pub study_instance_uid: String,
+    pub series_instance_uid: Option<String>,
+    pub message: String,
+    /// Offending files
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub file_count: usize,
+    pub study_count: usize,
+    pub series_count: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<ConsistencyIssue>,
+}
+
+/// Positions closer than this (mm) along the slice normal are the same slice
+const POSITION_TOLERANCE: f64 = 1e-3;
+
+/// Check the files of a scan (see `parser::scan_directory_fast`) for problems
+/// that only show across instances. Files with duplicate SOP Instance UIDs are
+/// read again to compare their content.
+pub fn check(files: &[MinimalFileInfo]) -> ConsistencyReport {
+    let mut issues = Vec::new();
+
+    check_duplicates(files, &mut issues);
+
+    let studies = group_by(files, |f| f.study_instance_uid.clone());
+    let mut series_count = 0;
+    for (study_uid, study_files) in &studies {
+        let study_attributes: [(&str, Severity, fn(&MinimalFileInfo) -> Option<String>); 3] = [
+            ("Patient ID", Severity::Error, |f| f.patient_id.clone()),
+            ("Patient Name", Severity::Warning, |f| f.patient_name.clone()),
+            ("Study Date", Severity::Error, |f| f.study_date.clone()),
+        ];
+        for (name, severity, value) in study_attributes {
+            check_attribute(study_uid, None, study_files, name, severity, value, &mut issues);
+        }
+
+        let series = group_by(study_files.iter().copied(), |f| f.series_instance_uid.clone());
+        series_count += series.len();
+        for (series_uid, series_files) in &series {
+            check_attribute(
+                study_uid,
+                Some(series_uid.as_str()),
+                series_files,
+                "Modality",
+                Severity::Error,
+                |f| f.modality.clone(),
+                &mut issues,
+            );
+            check_instance_numbers(study_uid, series_uid, series_files, &mut issues);
+            check_geometry(study_uid, series_uid, series_files, &mut issues);
+        }
+    }
+
+    let report = ConsistencyReport {
+        file_count: files.len(),
+        study_count: studies.len(),
+        series_count,
+        errors: issues.iter().filter(|i| i.severity == Severity::Error).count(),
+        warnings: issues.iter().filter(|i| i.severity == Severity::Warning).count(),
+        issues,
+    };
+
+    tracing::info!(
+        "Consistency check of {} files in {} studies: {} errors, {} warnings",
+        report.file_count,
+        report.study_count,
+        report.errors,
+        report.warnings
+    );
+
+    report
+}
+
+fn group_by<'a, F>(files: impl IntoIterator<Item = &'a MinimalFileInfo>, key: F) -> BTreeMap<String, Vec<&'a MinimalFileInfo>>
+where
+    F: Fn(&MinimalFileInfo) -> String,
+{
+    let mut groups: BTreeMap<String, Vec<&MinimalFileInfo>> = BTreeMap::new();
+    for file in files {
+        groups.entry(key(file)).or_default().push(file);
+    }
+    groups
+}
+
+fn paths(files: &[&MinimalFileInfo]) -> Vec<String> {
+    files.iter().map(|f| f.path.clone()).collect()
+}
+
+/// Hash of the dataset (not the file, so differing File Meta doesn't count)
+fn content_hash(path: &str) -> Option<String> {
+    use dicom_core::value::Value;
+    use dicom_dictionary_std::tags;
+    use sha2::{Digest, Sha256};
+
+    let mut obj = super::load_dicom_file(path).ok()?;
+    let mut hasher = Sha256::new();
+    // Encapsulated pixel data has no JSON form, so it is hashed fragment by fragment
+    if let Ok(pixel_data) = obj.take_element(tags::PIXEL_DATA) {
+        match pixel_data.value() {
+            Value::PixelSequence(sequence) => {
+                sequence.fragments().iter().for_each(|f| hasher.update(f));
+            }
+            _ => hasher.update(pixel_data.to_bytes().ok()?),
+        }
+    }
+    hasher.update(super::json::to_json(&obj).ok()?.to_string().as_bytes());
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Unreadable files can't be shown to be copies, so they count as different
+fn is_identical(hashes: &[Option<String>]) -> bool {
+    hashes.iter().all(|h| h.is_some() && *h == hashes[0])
+}
+
+fn check_duplicates(files: &[MinimalFileInfo], issues: &mut Vec<ConsistencyIssue>) {
+    for (sop_uid, copies) in group_by(files, |f| f.sop_instance_uid.clone()) {
+        if copies.len() < 2 {
+            continue;
+        }
+        let hashes: Vec<Option<String>> = copies.iter().map(|f| content_hash(&f.path)).collect();
+        let (kind, severity, message) = if is_identical(&hashes) {
+            (
+                IssueKind::DuplicateInstance,
+                Severity::Warning,
+                format!("{} identical copies of instance {}", copies.len(), sop_uid),
+            )
+        } else {
+            (
+                IssueKind::ConflictingInstance,
+                Severity::Error,
+                format!("{} files share SOP Instance UID {} but differ in content", copies.len(), sop_uid),
+            )
+        };
+        issues.push(ConsistencyIssue {
+            kind,
+            severity,
+            study_instance_uid: copies[0].study_instance_uid.clone(),
+            series_instance_uid: Some(copies[0].series_instance_uid.clone()),
+            message,
+            files: paths(&copies),
+        });
+    }
+}
+
+/// Report an attribute with more than one value; the files outside the most
+/// common value are the offending ones
+fn check_attribute(
+    study_uid: &str,
+    series_uid: Option<&str>,
+    files: &[&MinimalFileInfo],
+    name: &str,
+    severity: Severity,
+    value: impl Fn(&MinimalFileInfo) -> Option<String>,
+    issues: &mut Vec<ConsistencyIssue>,
+) {
+    let groups = group_by(files.iter().copied(), |f| {
+        value(f).map(|v| v.trim().to_string()).unwrap_or_default()
+    });
+    if groups.len() < 2 {
+        return;
+    }
+
+    let mut groups: Vec<(String, Vec<&MinimalFileInfo>)> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, files)| std::cmp::Reverse(files.len()));
+    let values: Vec<String> = groups
+        .iter()
+        .map(|(value, files)| {
+            let value = if value.is_empty() { "(empty)".to_string() } else { format!("{:?}", value) };
+            format!("{} in {} files", value, files.len())
+        })
+        .collect();
+
+    issues.push(ConsistencyIssue {
+        kind: IssueKind::InconsistentAttribute,
+        severity,
+        study_instance_uid: study_uid.to_string(),
+        series_instance_uid: series_uid.map(str::to_string),
+        message: format!(
+            "{} differs across the {}: {}",
+            name,
+            if series_uid.is_some() { "series" } else { "study" },
+            values.join(", ")
+        ),
+        files: groups[1..].iter().flat_map(|(_, files)| paths(files)).collect(),
+    });
+}
+
+/// Ranges of numbers missing from a sorted, deduplicated list, e.g. "4-6, 9"
+fn missing_ranges(numbers: &[i32]) -> Vec<(i32, i32)> {
+    numbers
+        .windows(2)
+        // Widened: numbers far apart, e.g. negative and positive, overflow i32
+        .filter(|pair| i64::from(pair[1]) - i64::from(pair[0]) > 1)
+        .map(|pair| (pair[0] + 1, pair[1] - 1))
+        .collect()
+}
+
+fn check_instance_numbers(
+    study_uid: &str,
+    series_uid: &str,
+    files: &[&MinimalFileInfo],
+    issues: &mut Vec<ConsistencyIssue>,
+) {
+    let mut issue = |kind, message, files| {
+        issues.push(ConsistencyIssue {
+            kind,
+            severity: Severity::Warning,
+            study_instance_uid: study_uid.to_string(),
+            series_instance_uid: Some(series_uid.to_string()),
+            message,
+            files,
+        })
+    };
+
+    let mut numbered: BTreeMap<i32, Vec<&MinimalFileInfo>> = BTreeMap::new();
+    for file in files {
+        if let Some(number) = file.instance_number {
+            numbered.entry(number).or_default().push(file);
+        }
+    }
+
+    for (number, files) in numbered.iter().filter(|(_, files)| files.len() > 1) {
+        issue(
+            IssueKind::DuplicateInstanceNumber,
+            format!("Instance Number {} is used by {} instances", number, files.len()),
+            paths(files),
+        );
+    }
+
+    let numbers: Vec<i32> = numbered.keys().copied().collect();
+    let gaps = missing_ranges(&numbers);
+    if !gaps.is_empty() {
+        let ranges: Vec<String> = gaps
+            .iter()
+            .map(|&(from, to)| if from == to { from.to_string() } else { format!("{}-{}", from, to) })
+            .collect();
+        // The instances on either side of each gap
+        let mut neighbours: Vec<String> = gaps
+            .iter()
+            .flat_map(|&(from, to)| [from - 1, to + 1])
+            .filter_map(|n| numbered.get(&n))
+            .flat_map(|files| paths(files))
+            .collect();
+        neighbours.dedup();
+        issue(
+            IssueKind::InstanceNumberGap,
+            format!("Instance Numbers missing: {}", ranges.join(", ")),
+            neighbours,
+        );
+    }
+}
+
+fn cross(a: &[f64], b: &[f64]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn check_geometry(
+    study_uid: &str,
+    series_uid: &str,
+    files: &[&MinimalFileInfo],
+    issues: &mut Vec<ConsistencyIssue>,
+) {
+    let mut issue = |kind, message, files| {
+        issues.push(ConsistencyIssue {
+            kind,
+            severity: Severity::Warning,
+            study_instance_uid: study_uid.to_string(),
+            series_instance_uid: Some(series_uid.to_string()),
+            message,
+            files,
+        })
+    };
+
+    let planes: Vec<(&MinimalFileInfo, &[f64], &[f64])> = files
+        .iter()
+        .filter_map(|f| match (&f.image_position, &f.image_orientation) {
+            (Some(position), Some(orientation)) if position.len() == 3 && orientation.len() == 6 => {
+                Some((*f, position.as_slice(), orientation.as_slice()))
+            }
+            _ => None,
+        })
+        .collect();
+    if planes.len() < 2 {
+        return;
+    }
+
+    // Orientations equal to three decimals count as the same
+    let orientations = group_by(planes.iter().map(|(f, _, _)| *f), |f| {
+        let o = f.image_orientation.as_deref().unwrap_or_default();
+        o.iter().map(|v| format!("{:.3}", v + 0.0)).collect::<Vec<_>>().join("\\")
+    });
+    if orientations.len() > 1 {
+        let mut groups: Vec<Vec<&MinimalFileInfo>> = orientations.into_values().collect();
+        groups.sort_by_key(|files| std::cmp::Reverse(files.len()));
+        issue(
+            IssueKind::MixedOrientation,
+            format!("Series mixes {} image orientations", groups.len()),
+            groups[1..].iter().flat_map(|files| paths(files)).collect(),
+        );
+        return;
+    }
+
+    // Distance of each slice along the normal of the (shared) image plane
+    let normal = cross(&planes[0].2[0..3], &planes[0].2[3..6]);
+    let mut slices: Vec<(f64, &MinimalFileInfo)> = planes
+        .iter()
+        .map(|(f, position, _)| (position.iter().zip(normal).map(|(p, n)| p * n).sum(), *f))
+        .collect();
+    slices.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let gaps: Vec<(f64, &MinimalFileInfo, &MinimalFileInfo)> = slices
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0, pair[0].1, pair[1].1))
+        .collect();
+
+    let same_position: Vec<String> = gaps
+        .iter()
+        .filter(|(gap, _, _)| *gap < POSITION_TOLERANCE)
+        .flat_map(|(_, a, b)| [a.path.clone(), b.path.clone()])
+        .collect();
+    if !same_position.is_empty() {
+        let mut files = same_position;
+        files.dedup();
+        issue(
+            IssueKind::DuplicatePosition,
+            format!("{} instances share a slice position", files.len()),
+            files,
+        );
+    }
+
+    let mut spacings: Vec<f64> = gaps
+        .iter()
+        .map(|(gap, _, _)| *gap)
+        .filter(|gap| *gap >= POSITION_TOLERANCE)
+        .collect();
+    if spacings.len() < 2 {
+        return;
+    }
+    spacings.sort_by(f64::total_cmp);
+    let median = spacings[spacings.len() / 2];
+    let tolerance = (median * 0.01).max(0.01);
+
+    let irregular: Vec<&(f64, &MinimalFileInfo, &MinimalFileInfo)> = gaps
+        .iter()
+        .filter(|(gap, _, _)| *gap >= POSITION_TOLERANCE && (gap - median).abs() > tolerance)
+        .collect();
+    if !irregular.is_empty() {
+        let found: Vec<String> = irregular.iter().map(|(gap, _, _)| format!("{:.2}", gap)).collect();
+        let mut files: Vec<String> = irregular
+            .iter()
+            .flat_map(|(_, a, b)| [a.path.clone(), b.path.clone()])
+            .collect();
+        files.dedup();
+        issue(
+            IssueKind::InconsistentSpacing,
+            format!("Slice spacing is {:.2} mm but varies: {} mm", median, found.join(", ")),
+            files,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, series: &str, number: i32, z: f64) -> MinimalFileInfo {
+        MinimalFileInfo {
+            path: path.to_string(),
+            study_instance_uid: "2.25.1".to_string(),
+            series_instance_uid: series.to_string(),
+            sop_instance_uid: format!("2.25.1.{}", path),
+            patient_name: Some("Doe^John".to_string()),
+            patient_id: Some("PID-1".to_string()),
+            study_date: Some("20240102".to_string()),
+            modality: Some("CT".to_string()),
+            instance_number: Some(number),
+            image_position: Some(vec![0.0, 0.0, z]),
+            image_orientation: Some(vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0]),
+        }
+    }
+
+    fn kinds(report: &ConsistencyReport) -> Vec<IssueKind> {
+        report.issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_consistent_series() {
+        let files: Vec<_> = (1..=5).map(|i| file(&format!("f{}", i), "s1", i, i as f64 * 2.5)).collect();
+        let report = check(&files);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert_eq!((report.study_count, report.series_count), (1, 1));
+    }
+
+    #[test]
+    fn test_series_issues() {
+        let mut files: Vec<_> = [1, 2, 3, 6].iter().map(|&i| file(&format!("f{}", i), "s1", i, i as f64)).collect();
+        files[3].patient_id = Some("PID-2".to_string());
+        files.push(file("f6b", "s1", 6, 7.0));
+
+        let report = check(&files);
+        let found = kinds(&report);
+        assert!(found.contains(&IssueKind::InstanceNumberGap));
+        assert!(found.contains(&IssueKind::DuplicateInstanceNumber));
+        assert!(found.contains(&IssueKind::InconsistentSpacing));
+
+        let patient = report
+            .issues
+            .iter()
+            .find(|i| i.kind == IssueKind::InconsistentAttribute)
+            .unwrap();
+        assert_eq!(patient.severity, Severity::Error);
+        assert_eq!(patient.files, vec!["f6".to_string()]);
+
+        let gap = report.issues.iter().find(|i| i.kind == IssueKind::InstanceNumberGap).unwrap();
+        assert_eq!(gap.message, "Instance Numbers missing: 4-5");
+    }
+
+    #[test]
+    fn test_mixed_orientation() {
+        let mut files: Vec<_> = (1..=3).map(|i| file(&format!("f{}", i), "s1", i, i as f64)).collect();
+        files[2].image_orientation = Some(vec![1.0, 0.0, 0.0, 0.0, 0.0, -1.0]);
+        let report = check(&files);
+        assert_eq!(kinds(&report), vec![IssueKind::MixedOrientation]);
+        assert_eq!(report.issues[0].files, vec!["f3".to_string()]);
+    }
+
+    #[test]
+    fn test_duplicates_and_gaps() {
+        assert!(is_identical(&[Some("a".to_string()), Some("a".to_string())]));
+        assert!(!is_identical(&[Some("a".to_string()), Some("b".to_string())]));
+        assert!(!is_identical(&[None, None]));
+        assert_eq!(missing_ranges(&[1, 2, 5, 7]), vec![(3, 4), (6, 6)]);
+        assert_eq!(missing_ranges(&[i32::MIN, i32::MAX]), vec![(i32::MIN + 1, i32::MAX - 1)]);
+    }
+}
@@ -0,0 +1,185 @@
+// Data dictionary lookups for tag display and validation
+//
+// Keywords and VRs come from `dicom-dictionary-std`. That dictionary carries no
+// names, value multiplicities or retired flags, so those come from the PS3.6
+// table in `dictionary_table.rs`, generated by `scripts/generate_dictionary.py`.
+
+use dicom_core::dictionary::{DataDictionary, VirtualVr};
+use dicom_core::{Tag, VR};
+use dicom_dictionary_std::StandardDataDictionary;
+
+/// Dictionary information about an attribute
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictionaryEntry {
+    pub keyword: String,
+    pub name: String,
+    /// VRs the attribute may be encoded with (US/SS and OB/OW alternatives)
+    pub vrs: Vec<VR>,
+    /// `None` when the PS3.6 table has no entry for the attribute
+    pub vm: Option<Multiplicity>,
+    pub retired: bool,
+}
+
+impl DictionaryEntry {
+    /// Expected VR as shown to the user, e.g. "PN" or "US or SS"
+    pub fn vr_label(&self) -> String {
+        let names: Vec<String> = self.vrs.iter().map(|vr| format!("{:?}", vr)).collect();
+        match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            _ => names.join(""),
+        }
+    }
+}
+
+/// Value multiplicity as written in PS3.6: "1", "2", "1-n", "1-2", "2-2n", "3-3n"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Multiplicity {
+    pub min: u32,
+    /// `None` when unbounded ("n")
+    pub max: Option<u32>,
+    /// Values must come in multiples of this ("2-2n" has step 2)
+    pub step: u32,
+}
+
+impl Multiplicity {
+    pub fn parse(vm: &str) -> Option<Multiplicity> {
+        let (low, high) = match vm.split_once('-') {
+            Some((low, high)) => (low, Some(high)),
+            None => (vm, None),
+        };
+        let min: u32 = low.trim().parse().ok()?;
+
+        match high.map(str::trim) {
+            None => Some(Multiplicity { min, max: Some(min), step: 1 }),
+            Some("n") => Some(Multiplicity { min, max: None, step: 1 }),
+            Some(high) if high.ends_with('n') => {
+                let step: u32 = high.trim_end_matches('n').parse().ok()?;
+                Some(Multiplicity { min, max: None, step: step.max(1) })
+            }
+            Some(high) => Some(Multiplicity { min, max: Some(high.parse().ok()?), step: 1 }),
+        }
+    }
+
+    /// Whether a value with `count` values satisfies this multiplicity. Empty
+    /// values are always accepted (type 2 attributes may be zero length).
+    pub fn accepts(&self, count: u32) -> bool {
+        count == 0
+            || (count >= self.min
+                && self.max.map_or(true, |max| count <= max)
+                && count % self.step == 0)
+    }
+}
+
+impl std::fmt::Display for Multiplicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", self.min),
+            Some(max) => write!(f, "{}-{}", self.min, max),
+            None if self.step > 1 => write!(f, "{}-{}n", self.min, self.step),
+            None => write!(f, "{}-n", self.min),
+        }
+    }
+}
+
+/// Group used for table lookups; repeating groups map to their base group
+fn base_group(group: u16) -> u16 {
+    match group & 0xFF00 {
+        0x5000 | 0x6000 if group & 1 == 0 => group & 0xFF00,
+        _ => group,
+    }
+}
+
+/// Look up an attribute. Returns `None` for private and unknown tags.
+pub fn lookup(tag: Tag) -> Option<DictionaryEntry> {
+    if tag.group() % 2 == 1 {
+        return None;
+    }
+    let entry = StandardDataDictionary.by_tag(tag)?;
+
+    let vrs = match entry.vr {
+        VirtualVr::Exact(vr) => vec![vr],
+        VirtualVr::Xs => vec![VR::US, VR::SS],
+        VirtualVr::Ox | VirtualVr::Px => vec![VR::OB, VR::OW],
+        _ => vec![VR::US, VR::SS, VR::OW],
+    };
+    let attribute = (u32::from(base_group(tag.group())) << 16) | u32::from(tag.element());
+    let table = super::dictionary_table::ATTRIBUTES;
+    let row = table.binary_search_by_key(&attribute, |(key, ..)| *key).ok().map(|i| table[i]);
+
+    Some(DictionaryEntry {
+        keyword: entry.alias.to_string(),
+        name: row.map_or_else(|| entry.alias.to_string(), |(_, name, ..)| name.to_string()),
+        vrs,
+        vm: row.and_then(|(_, _, vm, _)| Multiplicity::parse(vm)),
+        retired: row.is_some_and(|(.., retired)| retired),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiplicity() {
+        let pairs = Multiplicity::parse("2-2n").unwrap();
+        assert!(pairs.accepts(4));
+        assert!(!pairs.accepts(3));
+        assert_eq!(pairs.to_string(), "2-2n");
+
+        let range = Multiplicity::parse("1-2").unwrap();
+        assert!(range.accepts(2));
+        assert!(!range.accepts(3));
+        assert!(Multiplicity::parse("1-n").unwrap().accepts(12));
+        assert!(Multiplicity::parse("1").unwrap().accepts(0));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let entry = lookup(Tag(0x0020, 0x0037)).unwrap();
+        assert_eq!(entry.keyword, "ImageOrientationPatient");
+        assert_eq!(entry.vrs, vec![VR::DS]);
+        assert_eq!(entry.vm.unwrap().to_string(), "6");
+
+        let overlay = lookup(Tag(0x6002, 0x0050)).unwrap();
+        assert_eq!(overlay.name, "Overlay Origin");
+        assert_eq!(overlay.vm.unwrap().to_string(), "2");
+
+        assert_eq!(lookup(Tag(0x0008, 0x9007)).unwrap().vm, Multiplicity::parse("4"));
+        assert_eq!(lookup(Tag(0x0018, 0x0086)).unwrap().vm, Multiplicity::parse("1-n"));
+        assert_eq!(lookup(Tag(0x0028, 0x140F)).unwrap().name, "RGB LUT Transfer Function");
+        assert!(lookup(Tag(0x0028, 0x0040)).unwrap().retired);
+        assert!(lookup(Tag(0x5004, 0x3000)).unwrap().retired);
+        assert_eq!(lookup(Tag(0x0028, 0x0106)).unwrap().vr_label(), "US or SS");
+        assert!(lookup(Tag(0x0009, 0x1001)).is_none());
+    }
+
+    #[test]
+    fn test_lookup_across_groups() {
+        let samples = [
+            (Tag(0x0008, 0x0081), "Institution Address", "1"),
+            (Tag(0x0010, 0x0040), "Patient's Sex", "1"),
+            (Tag(0x0018, 0x1020), "Software Versions", "1-n"),
+            (Tag(0x0020, 0x4000), "Image Comments", "1"),
+            (Tag(0x0028, 0x0030), "Pixel Spacing", "2"),
+            (Tag(0x0040, 0x0009), "Scheduled Procedure Step ID", "1"),
+            (Tag(0x0054, 0x0081), "Number of Slices", "1"),
+            (Tag(0x0070, 0x0001), "Graphic Annotation Sequence", "1"),
+            (Tag(0x3006, 0x0020), "Structure Set ROI Sequence", "1"),
+            (Tag(0x300A, 0x0002), "RT Plan Label", "1"),
+            (Tag(0x7FE0, 0x0010), "Pixel Data", "1"),
+        ];
+        for (tag, name, vm) in samples {
+            let entry = lookup(tag).unwrap();
+            assert_eq!(entry.name, name);
+            assert_eq!(entry.vm, Multiplicity::parse(vm), "{}", name);
+            assert!(!entry.retired, "{}", name);
+        }
+        assert!(lookup(Tag(0x0008, 0x0010)).unwrap().retired);
+    }
+
+    #[test]
+    fn test_table_sorted() {
+        let table = super::super::dictionary_table::ATTRIBUTES;
+        assert!(table.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
@@ -0,0 +1,779 @@
+// Attribute names, value multiplicities and retired flags from DICOM PS3.6.
+// Regenerate with `python3 scripts/generate_dictionary.py` instead of editing.
+
+/// (tag, name, VM, retired), sorted by tag. Tags are `group << 16 | element`;
+/// repeating groups (50xx, 60xx) are listed under their base group.
+pub(super) const ATTRIBUTES: &[(u32, &str, &str, bool)] = &[
+    (0x00020000, "File Meta Information Group Length", "1", false),
+    (0x00020001, "File Meta Information Version", "1", false),
+    (0x00020002, "Media Storage SOP Class UID", "1", false),
+    (0x00020003, "Media Storage SOP Instance UID", "1", false),
+    (0x00020010, "Transfer Syntax UID", "1", false),
+    (0x00020012, "Implementation Class UID", "1", false),
+    (0x00020013, "Implementation Version Name", "1", false),
+    (0x00020016, "Source Application Entity Title", "1", false),
+    (0x00020017, "Sending Application Entity Title", "1", false),
+    (0x00020018, "Receiving Application Entity Title", "1", false),
+    (0x00020100, "Private Information Creator UID", "1", false),
+    (0x00020102, "Private Information", "1", false),
+    (0x00080001, "Length to End", "1", true),
+    (0x00080005, "Specific Character Set", "1-n", false),
+    (0x00080006, "Language Code Sequence", "1", false),
+    (0x00080008, "Image Type", "2-n", false),
+    (0x00080010, "Recognition Code", "1", true),
+    (0x00080012, "Instance Creation Date", "1", false),
+    (0x00080013, "Instance Creation Time", "1", false),
+    (0x00080014, "Instance Creator UID", "1", false),
+    (0x00080015, "Instance Coercion DateTime", "1", false),
+    (0x00080016, "SOP Class UID", "1", false),
+    (0x00080018, "SOP Instance UID", "1", false),
+    (0x0008001A, "Related General SOP Class UID", "1-n", false),
+    (0x0008001B, "Original Specialized SOP Class UID", "1", false),
+    (0x00080020, "Study Date", "1", false),
+    (0x00080021, "Series Date", "1", false),
+    (0x00080022, "Acquisition Date", "1", false),
+    (0x00080023, "Content Date", "1", false),
+    (0x00080024, "Overlay Date", "1", true),
+    (0x00080025, "Curve Date", "1", true),
+    (0x0008002A, "Acquisition DateTime", "1", false),
+    (0x00080030, "Study Time", "1", false),
+    (0x00080031, "Series Time", "1", false),
+    (0x00080032, "Acquisition Time", "1", false),
+    (0x00080033, "Content Time", "1", false),
+    (0x00080034, "Overlay Time", "1", true),
+    (0x00080035, "Curve Time", "1", true),
+    (0x00080040, "Data Set Type", "1", true),
+    (0x00080041, "Data Set Subtype", "1", true),
+    (0x00080042, "Nuclear Medicine Series Type", "1", true),
+    (0x00080050, "Accession Number", "1", false),
+    (0x00080051, "Issuer of Accession Number Sequence", "1", false),
+    (0x00080052, "Query/Retrieve Level", "1", false),
+    (0x00080053, "Query/Retrieve View", "1", false),
+    (0x00080054, "Retrieve AE Title", "1-n", false),
+    (0x00080055, "Station AE Title", "1-n", false),
+    (0x00080056, "Instance Availability", "1", false),
+    (0x00080058, "Failed SOP Instance UID List", "1-n", false),
+    (0x00080060, "Modality", "1", false),
+    (0x00080061, "Modalities in Study", "1-n", false),
+    (0x00080062, "SOP Classes in Study", "1-n", false),
+    (0x00080064, "Conversion Type", "1", false),
+    (0x00080068, "Presentation Intent Type", "1", false),
+    (0x00080070, "Manufacturer", "1", false),
+    (0x00080080, "Institution Name", "1", false),
+    (0x00080081, "Institution Address", "1", false),
+    (0x00080082, "Institution Code Sequence", "1", false),
+    (0x00080090, "Referring Physician's Name", "1", false),
+    (0x00080092, "Referring Physician's Address", "1", false),
+    (0x00080094, "Referring Physician's Telephone Numbers", "1-n", false),
+    (0x00080096, "Referring Physician Identification Sequence", "1", false),
+    (0x00080100, "Code Value", "1", false),
+    (0x00080102, "Coding Scheme Designator", "1", false),
+    (0x00080103, "Coding Scheme Version", "1", false),
+    (0x00080104, "Code Meaning", "1", false),
+    (0x00080105, "Mapping Resource", "1", false),
+    (0x00080106, "Context Group Version", "1", false),
+    (0x00080107, "Context Group Local Version", "1", false),
+    (0x0008010B, "Context Group Extension Flag", "1", false),
+    (0x0008010C, "Coding Scheme UID", "1", false),
+    (0x0008010D, "Context Group Extension Creator UID", "1", false),
+    (0x0008010F, "Context Identifier", "1", false),
+    (0x00080110, "Coding Scheme Identification Sequence", "1", false),
+    (0x00080115, "Coding Scheme Name", "1", false),
+    (0x00080117, "Context UID", "1", false),
+    (0x00080118, "Mapping Resource UID", "1", false),
+    (0x00080119, "Long Code Value", "1", false),
+    (0x00080120, "URN Code Value", "1", false),
+    (0x00080201, "Timezone Offset From UTC", "1", false),
+    (0x00080300, "Private Data Element Characteristics Sequence", "1", false),
+    (0x00081000, "Network ID", "1", true),
+    (0x00081010, "Station Name", "1", false),
+    (0x00081030, "Study Description", "1", false),
+    (0x00081032, "Procedure Code Sequence", "1", false),
+    (0x0008103E, "Series Description", "1", false),
+    (0x0008103F, "Series Description Code Sequence", "1", false),
+    (0x00081040, "Institutional Department Name", "1", false),
+    (0x00081048, "Physician(s) of Record", "1-n", false),
+    (0x00081049, "Physician(s) of Record Identification Sequence", "1", false),
+    (0x00081050, "Performing Physician's Name", "1-n", false),
+    (0x00081052, "Performing Physician Identification Sequence", "1", false),
+    (0x00081060, "Name of Physician(s) Reading Study", "1-n", false),
+    (0x00081062, "Physician(s) Reading Study Identification Sequence", "1", false),
+    (0x00081070, "Operators' Name", "1-n", false),
+    (0x00081072, "Operator Identification Sequence", "1", false),
+    (0x00081080, "Admitting Diagnoses Description", "1-n", false),
+    (0x00081084, "Admitting Diagnoses Code Sequence", "1", false),
+    (0x00081090, "Manufacturer's Model Name", "1", false),
+    (0x00081100, "Referenced Results Sequence", "1", true),
+    (0x00081110, "Referenced Study Sequence", "1", false),
+    (0x00081111, "Referenced Performed Procedure Step Sequence", "1", false),
+    (0x00081115, "Referenced Series Sequence", "1", false),
+    (0x00081120, "Referenced Patient Sequence", "1", false),
+    (0x00081125, "Referenced Visit Sequence", "1", false),
+    (0x00081130, "Referenced Overlay Sequence", "1", true),
+    (0x00081140, "Referenced Image Sequence", "1", false),
+    (0x00081145, "Referenced Curve Sequence", "1", true),
+    (0x00081150, "Referenced SOP Class UID", "1", false),
+    (0x00081155, "Referenced SOP Instance UID", "1", false),
+    (0x00081160, "Referenced Frame Number", "1-n", false),
+    (0x00081161, "Simple Frame List", "1-n", false),
+    (0x00081162, "Calculated Frame List", "3-3n", false),
+    (0x00081163, "Time Range", "2", false),
+    (0x00081190, "Retrieve URL", "1", false),
+    (0x00081195, "Transaction UID", "1", false),
+    (0x00081197, "Failure Reason", "1", false),
+    (0x00081198, "Failed SOP Sequence", "1", false),
+    (0x00081199, "Referenced SOP Sequence", "1", false),
+    (0x00081250, "Related Series Sequence", "1", false),
+    (0x00082111, "Derivation Description", "1", false),
+    (0x00082112, "Source Image Sequence", "1", false),
+    (0x00082218, "Anatomic Region Sequence", "1", false),
+    (0x00083001, "Alternate Representation Sequence", "1", false),
+    (0x00089007, "Frame Type", "4", false),
+    (0x00089092, "Referenced Image Evidence Sequence", "1", false),
+    (0x00089121, "Referenced Raw Data Sequence", "1", false),
+    (0x00089123, "Creator-Version UID", "1", false),
+    (0x00089124, "Derivation Image Sequence", "1", false),
+    (0x00089205, "Pixel Presentation", "1", false),
+    (0x00089206, "Volumetric Properties", "1", false),
+    (0x00089207, "Volume Based Calculation Technique", "1", false),
+    (0x00089208, "Complex Image Component", "1", false),
+    (0x00089209, "Acquisition Contrast", "1", false),
+    (0x00100010, "Patient's Name", "1", false),
+    (0x00100020, "Patient ID", "1", false),
+    (0x00100021, "Issuer of Patient ID", "1", false),
+    (0x00100022, "Type of Patient ID", "1", false),
+    (0x00100024, "Issuer of Patient ID Qualifiers Sequence", "1", false),
+    (0x00100030, "Patient's Birth Date", "1", false),
+    (0x00100032, "Patient's Birth Time", "1", false),
+    (0x00100040, "Patient's Sex", "1", false),
+    (0x00100050, "Patient's Insurance Plan Code Sequence", "1", false),
+    (0x00100200, "Quality Control Subject", "1", false),
+    (0x00101000, "Other Patient IDs", "1-n", true),
+    (0x00101001, "Other Patient Names", "1-n", false),
+    (0x00101002, "Other Patient IDs Sequence", "1", false),
+    (0x00101005, "Patient's Birth Name", "1", false),
+    (0x00101010, "Patient's Age", "1", false),
+    (0x00101020, "Patient's Size", "1", false),
+    (0x00101030, "Patient's Weight", "1", false),
+    (0x00101040, "Patient's Address", "1", false),
+    (0x00101050, "Insurance Plan Identification", "1-n", true),
+    (0x00101060, "Patient's Mother's Birth Name", "1", false),
+    (0x00101080, "Military Rank", "1", false),
+    (0x00101081, "Branch of Service", "1", false),
+    (0x00101090, "Medical Record Locator", "1", true),
+    (0x00102000, "Medical Alerts", "1-n", false),
+    (0x00102110, "Allergies", "1-n", false),
+    (0x00102150, "Country of Residence", "1", false),
+    (0x00102152, "Region of Residence", "1", false),
+    (0x00102154, "Patient's Telephone Numbers", "1-n", false),
+    (0x00102160, "Ethnic Group", "1", false),
+    (0x00102180, "Occupation", "1", false),
+    (0x001021A0, "Smoking Status", "1", false),
+    (0x001021B0, "Additional Patient History", "1", false),
+    (0x001021C0, "Pregnancy Status", "1", false),
+    (0x001021D0, "Last Menstrual Date", "1", false),
+    (0x001021F0, "Patient's Religious Preference", "1", false),
+    (0x00102201, "Patient Species Description", "1", false),
+    (0x00102203, "Patient's Sex Neutered", "1", false),
+    (0x00104000, "Patient Comments", "1", false),
+    (0x00120010, "Clinical Trial Sponsor Name", "1", false),
+    (0x00120020, "Clinical Trial Protocol ID", "1", false),
+    (0x00120021, "Clinical Trial Protocol Name", "1", false),
+    (0x00120030, "Clinical Trial Site ID", "1", false),
+    (0x00120031, "Clinical Trial Site Name", "1", false),
+    (0x00120040, "Clinical Trial Subject ID", "1", false),
+    (0x00120042, "Clinical Trial Subject Reading ID", "1", false),
+    (0x00120050, "Clinical Trial Time Point ID", "1", false),
+    (0x00120051, "Clinical Trial Time Point Description", "1", false),
+    (0x00120062, "Patient Identity Removed", "1", false),
+    (0x00120063, "De-identification Method", "1-n", false),
+    (0x00120064, "De-identification Method Code Sequence", "1", false),
+    (0x00120071, "Clinical Trial Series ID", "1", false),
+    (0x00120072, "Clinical Trial Series Description", "1", false),
+    (0x00120081, "Clinical Trial Protocol Ethics Committee Name", "1", false),
+    (0x00120082, "Clinical Trial Protocol Ethics Committee Approval Number", "1", false),
+    (0x00180010, "Contrast/Bolus Agent", "1", false),
+    (0x00180015, "Body Part Examined", "1", false),
+    (0x00180020, "Scanning Sequence", "1-n", false),
+    (0x00180021, "Sequence Variant", "1-n", false),
+    (0x00180022, "Scan Options", "1-n", false),
+    (0x00180023, "MR Acquisition Type", "1", false),
+    (0x00180024, "Sequence Name", "1", false),
+    (0x00180025, "Angio Flag", "1", false),
+    (0x00180030, "Radionuclide", "1-n", true),
+    (0x00180040, "Cine Rate", "1", false),
+    (0x00180050, "Slice Thickness", "1", false),
+    (0x00180060, "KVP", "1", false),
+    (0x00180070, "Counts Accumulated", "1", false),
+    (0x00180071, "Acquisition Termination Condition", "1", false),
+    (0x00180080, "Repetition Time", "1", false),
+    (0x00180081, "Echo Time", "1", false),
+    (0x00180082, "Inversion Time", "1", false),
+    (0x00180083, "Number of Averages", "1", false),
+    (0x00180084, "Imaging Frequency", "1", false),
+    (0x00180085, "Imaged Nucleus", "1", false),
+    (0x00180086, "Echo Number(s)", "1-n", false),
+    (0x00180087, "Magnetic Field Strength", "1", false),
+    (0x00180088, "Spacing Between Slices", "1", false),
+    (0x00180089, "Number of Phase Encoding Steps", "1", false),
+    (0x00180090, "Data Collection Diameter", "1", false),
+    (0x00180091, "Echo Train Length", "1", false),
+    (0x00180093, "Percent Sampling", "1", false),
+    (0x00180094, "Percent Phase Field of View", "1", false),
+    (0x00180095, "Pixel Bandwidth", "1", false),
+    (0x00181000, "Device Serial Number", "1", false),
+    (0x00181004, "Plate ID", "1", false),
+    (0x00181010, "Secondary Capture Device ID", "1", false),
+    (0x00181012, "Date of Secondary Capture", "1", false),
+    (0x00181014, "Time of Secondary Capture", "1", false),
+    (0x00181016, "Secondary Capture Device Manufacturer", "1", false),
+    (0x00181018, "Secondary Capture Device Manufacturer's Model Name", "1", false),
+    (0x00181019, "Secondary Capture Device Software Versions", "1-n", false),
+    (0x00181020, "Software Versions", "1-n", false),
+    (0x00181030, "Protocol Name", "1", false),
+    (0x00181040, "Contrast/Bolus Route", "1", false),
+    (0x00181041, "Contrast/Bolus Volume", "1", false),
+    (0x00181044, "Contrast/Bolus Total Dose", "1", false),
+    (0x00181046, "Contrast Flow Rate", "1-n", false),
+    (0x00181047, "Contrast Flow Duration", "1-n", false),
+    (0x00181048, "Contrast/Bolus Ingredient", "1", false),
+    (0x00181049, "Contrast/Bolus Ingredient Concentration", "1", false),
+    (0x00181050, "Spatial Resolution", "1", false),
+    (0x00181060, "Trigger Time", "1", false),
+    (0x00181063, "Frame Time", "1", false),
+    (0x00181065, "Frame Time Vector", "1-n", false),
+    (0x00181088, "Heart Rate", "1", false),
+    (0x00181100, "Reconstruction Diameter", "1", false),
+    (0x00181110, "Distance Source to Detector", "1", false),
+    (0x00181111, "Distance Source to Patient", "1", false),
+    (0x00181120, "Gantry/Detector Tilt", "1", false),
+    (0x00181130, "Table Height", "1", false),
+    (0x00181140, "Rotation Direction", "1", false),
+    (0x00181149, "Field of View Dimension(s)", "1-2", false),
+    (0x00181150, "Exposure Time", "1", false),
+    (0x00181151, "X-Ray Tube Current", "1", false),
+    (0x00181152, "Exposure", "1", false),
+    (0x00181153, "Exposure in µAs", "1", false),
+    (0x00181155, "Radiation Setting", "1", false),
+    (0x0018115A, "Radiation Mode", "1", false),
+    (0x00181160, "Filter Type", "1", false),
+    (0x00181162, "Intensifier Size", "1", false),
+    (0x00181164, "Imager Pixel Spacing", "2", false),
+    (0x00181166, "Grid", "1-n", false),
+    (0x00181170, "Generator Power", "1", false),
+    (0x00181180, "Collimator/grid Name", "1", false),
+    (0x00181181, "Collimator Type", "1", false),
+    (0x00181190, "Focal Spot(s)", "1-n", false),
+    (0x00181191, "Anode Target Material", "1", false),
+    (0x00181200, "Date of Last Calibration", "1-n", false),
+    (0x00181201, "Time of Last Calibration", "1-n", false),
+    (0x00181210, "Convolution Kernel", "1-n", false),
+    (0x00181242, "Actual Frame Duration", "1", false),
+    (0x00181250, "Receive Coil Name", "1", false),
+    (0x00181251, "Transmit Coil Name", "1", false),
+    (0x00181310, "Acquisition Matrix", "4", false),
+    (0x00181312, "In-plane Phase Encoding Direction", "1", false),
+    (0x00181314, "Flip Angle", "1", false),
+    (0x00181316, "SAR", "1", false),
+    (0x00181318, "dB/dt", "1", false),
+    (0x00181400, "Acquisition Device Processing Description", "1", false),
+    (0x00181401, "Acquisition Device Processing Code", "1", false),
+    (0x00181405, "Relative X-Ray Exposure", "1", false),
+    (0x00185100, "Patient Position", "1", false),
+    (0x00185101, "View Position", "1", false),
+    (0x00186011, "Sequence of Ultrasound Regions", "1", false),
+    (0x00187022, "Detector Element Spacing", "2", false),
+    (0x00187026, "Detector Active Dimension(s)", "1-2", false),
+    (0x00187028, "Detector Active Origin", "2", false),
+    (0x00187030, "Field of View Origin", "2", false),
+    (0x00189004, "Content Qualification", "1", false),
+    (0x00189005, "Pulse Sequence Name", "1", false),
+    (0x00189073, "Acquisition Duration", "1", false),
+    (0x00189087, "Diffusion b-value", "1", false),
+    (0x00189089, "Diffusion Gradient Orientation", "3", false),
+    (0x00189306, "Single Collimation Width", "1", false),
+    (0x00189307, "Total Collimation Width", "1", false),
+    (0x00189309, "Table Speed", "1", false),
+    (0x00189310, "Table Feed per Rotation", "1", false),
+    (0x00189311, "Spiral Pitch Factor", "1", false),
+    (0x00189313, "Data Collection Center (Patient)", "3", false),
+    (0x00189318, "Reconstruction Target Center (Patient)", "3", false),
+    (0x00189322, "Reconstruction Pixel Spacing", "2", false),
+    (0x00189323, "Exposure Modulation Type", "1-n", false),
+    (0x00189345, "CTDIvol", "1", false),
+    (0x0020000D, "Study Instance UID", "1", false),
+    (0x0020000E, "Series Instance UID", "1", false),
+    (0x00200010, "Study ID", "1", false),
+    (0x00200011, "Series Number", "1", false),
+    (0x00200012, "Acquisition Number", "1", false),
+    (0x00200013, "Instance Number", "1", false),
+    (0x00200014, "Isotope Number", "1", true),
+    (0x00200015, "Phase Number", "1", true),
+    (0x00200016, "Interval Number", "1", true),
+    (0x00200017, "Time Slot Number", "1", true),
+    (0x00200018, "Angle Number", "1", true),
+    (0x00200019, "Item Number", "1", false),
+    (0x00200020, "Patient Orientation", "2", false),
+    (0x00200022, "Overlay Number", "1", true),
+    (0x00200024, "Curve Number", "1", true),
+    (0x00200026, "LUT Number", "1", true),
+    (0x00200030, "Image Position", "3", true),
+    (0x00200032, "Image Position (Patient)", "3", false),
+    (0x00200035, "Image Orientation", "6", true),
+    (0x00200037, "Image Orientation (Patient)", "6", false),
+    (0x00200050, "Location", "1", true),
+    (0x00200052, "Frame of Reference UID", "1", false),
+    (0x00200060, "Laterality", "1", false),
+    (0x00200062, "Image Laterality", "1", false),
+    (0x00200070, "Image Geometry Type", "1", true),
+    (0x00200080, "Masking Image", "1-n", true),
+    (0x00200100, "Temporal Position Identifier", "1", false),
+    (0x00200105, "Number of Temporal Positions", "1", false),
+    (0x00200110, "Temporal Resolution", "1", false),
+    (0x00200200, "Synchronization Frame of Reference UID", "1", false),
+    (0x00201000, "Series in Study", "1", true),
+    (0x00201001, "Acquisitions in Series", "1", true),
+    (0x00201002, "Images in Acquisition", "1", false),
+    (0x00201003, "Images in Series", "1", true),
+    (0x00201004, "Acquisitions in Study", "1", true),
+    (0x00201005, "Images in Study", "1", true),
+    (0x00201020, "Reference", "1-n", true),
+    (0x00201040, "Position Reference Indicator", "1", false),
+    (0x00201041, "Slice Location", "1", false),
+    (0x00201070, "Other Study Numbers", "1-n", true),
+    (0x00201200, "Number of Patient Related Studies", "1", false),
+    (0x00201202, "Number of Patient Related Series", "1", false),
+    (0x00201204, "Number of Patient Related Instances", "1", false),
+    (0x00201206, "Number of Study Related Series", "1", false),
+    (0x00201208, "Number of Study Related Instances", "1", false),
+    (0x00201209, "Number of Series Related Instances", "1", false),
+    (0x00203401, "Modifying Device ID", "1", true),
+    (0x00203402, "Modified Image ID", "1", true),
+    (0x00203403, "Modified Image Date", "1", true),
+    (0x00203404, "Modifying Device Manufacturer", "1", true),
+    (0x00203405, "Modified Image Time", "1", true),
+    (0x00203406, "Modified Image Description", "1", true),
+    (0x00204000, "Image Comments", "1", false),
+    (0x00205000, "Original Image Identification", "1-n", true),
+    (0x00205002, "Original Image Identification Nomenclature", "1-n", true),
+    (0x00209056, "Stack ID", "1", false),
+    (0x00209057, "In-Stack Position Number", "1", false),
+    (0x00209071, "Frame Anatomy Sequence", "1", false),
+    (0x00209072, "Frame Laterality", "1", false),
+    (0x00209111, "Frame Content Sequence", "1", false),
+    (0x00209113, "Plane Position Sequence", "1", false),
+    (0x00209116, "Plane Orientation Sequence", "1", false),
+    (0x00209128, "Temporal Position Index", "1", false),
+    (0x00209153, "Nominal Cardiac Trigger Delay Time", "1", false),
+    (0x00209156, "Frame Acquisition Number", "1", false),
+    (0x00209157, "Dimension Index Values", "1-n", false),
+    (0x00209158, "Frame Comments", "1", false),
+    (0x00209161, "Concatenation UID", "1", false),
+    (0x00209162, "In-concatenation Number", "1", false),
+    (0x00209163, "In-concatenation Total Number", "1", false),
+    (0x00209164, "Dimension Organization UID", "1", false),
+    (0x00209165, "Dimension Index Pointer", "1", false),
+    (0x00209167, "Functional Group Pointer", "1", false),
+    (0x00209221, "Dimension Organization Sequence", "1", false),
+    (0x00209222, "Dimension Index Sequence", "1", false),
+    (0x00209228, "Concatenation Frame Offset Number", "1", false),
+    (0x00209238, "Functional Group Private Creator", "1", false),
+    (0x00209421, "Dimension Description Label", "1", false),
+    (0x00280002, "Samples per Pixel", "1", false),
+    (0x00280003, "Samples per Pixel Used", "1", false),
+    (0x00280004, "Photometric Interpretation", "1", false),
+    (0x00280005, "Image Dimensions", "1", true),
+    (0x00280006, "Planar Configuration", "1", false),
+    (0x00280008, "Number of Frames", "1", false),
+    (0x00280009, "Frame Increment Pointer", "1-n", false),
+    (0x0028000A, "Frame Dimension Pointer", "1-n", false),
+    (0x00280010, "Rows", "1", false),
+    (0x00280011, "Columns", "1", false),
+    (0x00280012, "Planes", "1", true),
+    (0x00280014, "Ultrasound Color Data Present", "1", false),
+    (0x00280030, "Pixel Spacing", "2", false),
+    (0x00280031, "Zoom Factor", "2", false),
+    (0x00280032, "Zoom Center", "2", false),
+    (0x00280034, "Pixel Aspect Ratio", "2", false),
+    (0x00280040, "Image Format", "1", true),
+    (0x00280050, "Manipulated Image", "1-n", true),
+    (0x00280051, "Corrected Image", "1-n", false),
+    (0x0028005F, "Compression Recognition Code", "1", true),
+    (0x00280060, "Compression Code", "1", true),
+    (0x00280061, "Compression Originator", "1", true),
+    (0x00280062, "Compression Label", "1", true),
+    (0x00280063, "Compression Description", "1", true),
+    (0x00280065, "Compression Sequence", "1-n", true),
+    (0x00280066, "Compression Step Pointers", "1-n", true),
+    (0x00280068, "Repeat Interval", "1", true),
+    (0x00280069, "Bits Grouped", "1", true),
+    (0x00280070, "Perimeter Table", "1-n", true),
+    (0x00280071, "Perimeter Value", "1", true),
+    (0x00280080, "Predictor Rows", "1", true),
+    (0x00280081, "Predictor Columns", "1", true),
+    (0x00280082, "Predictor Constants", "1-n", true),
+    (0x00280090, "Blocked Pixels", "1", true),
+    (0x00280091, "Block Rows", "1", true),
+    (0x00280092, "Block Columns", "1", true),
+    (0x00280093, "Row Overlap", "1", true),
+    (0x00280094, "Column Overlap", "1", true),
+    (0x00280100, "Bits Allocated", "1", false),
+    (0x00280101, "Bits Stored", "1", false),
+    (0x00280102, "High Bit", "1", false),
+    (0x00280103, "Pixel Representation", "1", false),
+    (0x00280104, "Smallest Valid Pixel Value", "1", true),
+    (0x00280105, "Largest Valid Pixel Value", "1", true),
+    (0x00280106, "Smallest Image Pixel Value", "1", false),
+    (0x00280107, "Largest Image Pixel Value", "1", false),
+    (0x00280108, "Smallest Pixel Value in Series", "1", false),
+    (0x00280109, "Largest Pixel Value in Series", "1", false),
+    (0x00280110, "Smallest Image Pixel Value in Plane", "1", true),
+    (0x00280111, "Largest Image Pixel Value in Plane", "1", true),
+    (0x00280120, "Pixel Padding Value", "1", false),
+    (0x00280121, "Pixel Padding Range Limit", "1", false),
+    (0x00280122, "Float Pixel Padding Value", "1", false),
+    (0x00280123, "Double Float Pixel Padding Value", "1", false),
+    (0x00280124, "Float Pixel Padding Range Limit", "1", false),
+    (0x00280125, "Double Float Pixel Padding Range Limit", "1", false),
+    (0x00280200, "Image Location", "1", true),
+    (0x00280300, "Quality Control Image", "1", false),
+    (0x00280301, "Burned In Annotation", "1", false),
+    (0x00280302, "Recognizable Visual Features", "1", false),
+    (0x00280303, "Longitudinal Temporal Information Modified", "1", false),
+    (0x00280304, "Referenced Color Palette Instance UID", "1", false),
+    (0x00280400, "Transform Label", "1", true),
+    (0x00280401, "Transform Version Number", "1", true),
+    (0x00280402, "Number of Transform Steps", "1", true),
+    (0x00280403, "Sequence of Compressed Data", "1-n", true),
+    (0x00280404, "Details of Coefficients", "1-n", true),
+    (0x00280700, "DCT Label", "1", true),
+    (0x00280701, "Data Block Description", "1-n", true),
+    (0x00280702, "Data Block", "1-n", true),
+    (0x00280710, "Normalization Factor Format", "1", true),
+    (0x00280720, "Zonal Map Number Format", "1", true),
+    (0x00280721, "Zonal Map Location", "1-n", true),
+    (0x00280722, "Zonal Map Format", "1", true),
+    (0x00280730, "Adaptive Map Format", "1", true),
+    (0x00280740, "Code Number Format", "1", true),
+    (0x00280A02, "Pixel Spacing Calibration Type", "1", false),
+    (0x00280A04, "Pixel Spacing Calibration Description", "1", false),
+    (0x00281040, "Pixel Intensity Relationship", "1", false),
+    (0x00281041, "Pixel Intensity Relationship Sign", "1", false),
+    (0x00281050, "Window Center", "1-n", false),
+    (0x00281051, "Window Width", "1-n", false),
+    (0x00281052, "Rescale Intercept", "1", false),
+    (0x00281053, "Rescale Slope", "1", false),
+    (0x00281054, "Rescale Type", "1", false),
+    (0x00281055, "Window Center & Width Explanation", "1-n", false),
+    (0x00281056, "VOI LUT Function", "1", false),
+    (0x00281080, "Gray Scale", "1", true),
+    (0x00281090, "Recommended Viewing Mode", "1", false),
+    (0x00281100, "Gray Lookup Table Descriptor", "3", true),
+    (0x00281101, "Red Palette Color Lookup Table Descriptor", "3", false),
+    (0x00281102, "Green Palette Color Lookup Table Descriptor", "3", false),
+    (0x00281103, "Blue Palette Color Lookup Table Descriptor", "3", false),
+    (0x00281104, "Alpha Palette Color Lookup Table Descriptor", "3", false),
+    (0x00281111, "Large Red Palette Color Lookup Table Descriptor", "4", true),
+    (0x00281112, "Large Green Palette Color Lookup Table Descriptor", "4", true),
+    (0x00281113, "Large Blue Palette Color Lookup Table Descriptor", "4", true),
+    (0x00281199, "Palette Color Lookup Table UID", "1", false),
+    (0x00281200, "Gray Lookup Table Data", "1-n or 1", true),
+    (0x00281201, "Red Palette Color Lookup Table Data", "1", false),
+    (0x00281202, "Green Palette Color Lookup Table Data", "1", false),
+    (0x00281203, "Blue Palette Color Lookup Table Data", "1", false),
+    (0x00281204, "Alpha Palette Color Lookup Table Data", "1", false),
+    (0x00281221, "Segmented Red Palette Color Lookup Table Data", "1", false),
+    (0x00281222, "Segmented Green Palette Color Lookup Table Data", "1", false),
+    (0x00281223, "Segmented Blue Palette Color Lookup Table Data", "1", false),
+    (0x00281300, "Breast Implant Present", "1", false),
+    (0x00281350, "Partial View", "1", false),
+    (0x00281351, "Partial View Description", "1", false),
+    (0x00281401, "Data Frame Assignment Sequence", "1", false),
+    (0x00281402, "Data Path Assignment", "1", false),
+    (0x00281403, "Bits Mapped to Color Lookup Table", "1", false),
+    (0x00281404, "Blending LUT 1 Sequence", "1", false),
+    (0x00281405, "Blending LUT 1 Transfer Function", "1", false),
+    (0x00281406, "Blending Weight Constant", "1", false),
+    (0x00281407, "Blending Lookup Table Descriptor", "3", false),
+    (0x00281408, "Blending Lookup Table Data", "1", false),
+    (0x0028140B, "Enhanced Palette Color Lookup Table Sequence", "1", false),
+    (0x0028140C, "Blending LUT 2 Sequence", "1", false),
+    (0x0028140D, "Blending LUT 2 Transfer Function", "1", false),
+    (0x0028140E, "Data Path ID", "1", false),
+    (0x0028140F, "RGB LUT Transfer Function", "1", false),
+    (0x00281410, "Alpha LUT Transfer Function", "1", false),
+    (0x00282000, "ICC Profile", "1", false),
+    (0x00282110, "Lossy Image Compression", "1", false),
+    (0x00282112, "Lossy Image Compression Ratio", "1-n", false),
+    (0x00282114, "Lossy Image Compression Method", "1-n", false),
+    (0x00283000, "Modality LUT Sequence", "1", false),
+    (0x00283002, "LUT Descriptor", "3", false),
+    (0x00283003, "LUT Explanation", "1", false),
+    (0x00283004, "Modality LUT Type", "1", false),
+    (0x00283006, "LUT Data", "1-n", false),
+    (0x00283010, "VOI LUT Sequence", "1", false),
+    (0x00283110, "Softcopy VOI LUT Sequence", "1", false),
+    (0x00285000, "Bi-Plane Acquisition Sequence", "1", true),
+    (0x00286010, "Representative Frame Number", "1", false),
+    (0x00286020, "Frame Numbers of Interest (FOI)", "1-n", false),
+    (0x00286022, "Frame of Interest Description", "1-n", false),
+    (0x00286023, "Frame of Interest Type", "1-n", false),
+    (0x00286040, "R Wave Pointer", "1-n", false),
+    (0x00286100, "Mask Subtraction Sequence", "1", false),
+    (0x00287FE0, "Pixel Data Provider URL", "1", false),
+    (0x00289001, "Data Point Rows", "1", false),
+    (0x00289002, "Data Point Columns", "1", false),
+    (0x00289003, "Signal Domain Columns", "1", false),
+    (0x00289099, "Largest Monochrome Pixel Value", "1", true),
+    (0x00289108, "Data Representation", "1", false),
+    (0x00289110, "Pixel Measures Sequence", "1", false),
+    (0x00289132, "Frame VOI LUT Sequence", "1", false),
+    (0x00289145, "Pixel Value Transformation Sequence", "1", false),
+    (0x00289235, "Signal Domain Rows", "1", false),
+    (0x00289411, "Display Filter Percentage", "1", false),
+    (0x00289415, "Frame Pixel Shift Sequence", "1", false),
+    (0x00289416, "Subtraction Item ID", "1", false),
+    (0x00289422, "Pixel Intensity Relationship LUT Sequence", "1", false),
+    (0x00289443, "Frame Pixel Data Properties Sequence", "1", false),
+    (0x00289444, "Geometrical Properties", "1", false),
+    (0x00289445, "Geometric Maximum Distortion", "1", false),
+    (0x00289446, "Image Processing Applied", "1-n", false),
+    (0x00289454, "Mask Selection Mode", "1", false),
+    (0x00289474, "LUT Function", "1", false),
+    (0x00289478, "Mask Visibility Percentage", "1", false),
+    (0x00289501, "Pixel Shift Sequence", "1", false),
+    (0x00289502, "Region Pixel Shift Sequence", "1", false),
+    (0x00289503, "Vertices of the Region", "2-2n", false),
+    (0x00289505, "Multi-frame Presentation Sequence", "1", false),
+    (0x00289506, "Pixel Shift Frame Range", "2-2n", false),
+    (0x00289507, "LUT Frame Range", "2-2n", false),
+    (0x00289520, "Image to Equipment Mapping Matrix", "16", false),
+    (0x00289537, "Equipment Coordinate System Identification", "1", false),
+    (0x0032000A, "Study Status ID", "1", true),
+    (0x00320012, "Study ID Issuer", "1", true),
+    (0x00321032, "Requesting Physician", "1", false),
+    (0x00321033, "Requesting Service", "1", false),
+    (0x00321060, "Requested Procedure Description", "1", false),
+    (0x00321064, "Requested Procedure Code Sequence", "1", false),
+    (0x00321070, "Requested Contrast Agent", "1", false),
+    (0x00324000, "Study Comments", "1", true),
+    (0x00380010, "Admission ID", "1", false),
+    (0x00380300, "Current Patient Location", "1", false),
+    (0x00380400, "Patient's Institution Residence", "1", false),
+    (0x00380500, "Patient State", "1", false),
+    (0x00384000, "Visit Comments", "1", false),
+    (0x00400001, "Scheduled Station AE Title", "1-n", false),
+    (0x00400002, "Scheduled Procedure Step Start Date", "1", false),
+    (0x00400003, "Scheduled Procedure Step Start Time", "1", false),
+    (0x00400006, "Scheduled Performing Physician's Name", "1", false),
+    (0x00400007, "Scheduled Procedure Step Description", "1", false),
+    (0x00400009, "Scheduled Procedure Step ID", "1", false),
+    (0x00400010, "Scheduled Station Name", "1-n", false),
+    (0x00400011, "Scheduled Procedure Step Location", "1", false),
+    (0x00400020, "Scheduled Procedure Step Status", "1", false),
+    (0x00400100, "Scheduled Procedure Step Sequence", "1", false),
+    (0x00400241, "Performed Station AE Title", "1", false),
+    (0x00400242, "Performed Station Name", "1", false),
+    (0x00400243, "Performed Location", "1", false),
+    (0x00400244, "Performed Procedure Step Start Date", "1", false),
+    (0x00400245, "Performed Procedure Step Start Time", "1", false),
+    (0x00400250, "Performed Procedure Step End Date", "1", false),
+    (0x00400251, "Performed Procedure Step End Time", "1", false),
+    (0x00400252, "Performed Procedure Step Status", "1", false),
+    (0x00400253, "Performed Procedure Step ID", "1", false),
+    (0x00400254, "Performed Procedure Step Description", "1", false),
+    (0x00400260, "Performed Protocol Code Sequence", "1", false),
+    (0x00400275, "Request Attributes Sequence", "1", false),
+    (0x004008EA, "Measurement Units Code Sequence", "1", false),
+    (0x00401001, "Requested Procedure ID", "1", false),
+    (0x0040A010, "Relationship Type", "1", false),
+    (0x0040A040, "Value Type", "1", false),
+    (0x0040A043, "Concept Name Code Sequence", "1", false),
+    (0x0040A0B0, "Referenced Waveform Channels", "2-2n", false),
+    (0x0040A120, "DateTime", "1", false),
+    (0x0040A121, "Date", "1", false),
+    (0x0040A122, "Time", "1", false),
+    (0x0040A123, "Person Name", "1", false),
+    (0x0040A124, "UID", "1", false),
+    (0x0040A130, "Temporal Range Type", "1", false),
+    (0x0040A132, "Referenced Sample Positions", "1-n", false),
+    (0x0040A138, "Referenced Time Offsets", "1-n", false),
+    (0x0040A13A, "Referenced DateTime", "1-n", false),
+    (0x0040A160, "Text Value", "1", false),
+    (0x0040A168, "Concept Code Sequence", "1", false),
+    (0x0040A170, "Purpose of Reference Code Sequence", "1", false),
+    (0x0040A300, "Measured Value Sequence", "1", false),
+    (0x0040A30A, "Numeric Value", "1-n", false),
+    (0x0040A491, "Completion Flag", "1", false),
+    (0x0040A493, "Verification Flag", "1", false),
+    (0x0040A504, "Content Template Sequence", "1", false),
+    (0x0040A730, "Content Sequence", "1", false),
+    (0x0040DB00, "Template Identifier", "1", false),
+    (0x00540010, "Energy Window Vector", "1-n", false),
+    (0x00540011, "Number of Energy Windows", "1", false),
+    (0x00540016, "Radiopharmaceutical Information Sequence", "1", false),
+    (0x00540020, "Detector Vector", "1-n", false),
+    (0x00540021, "Number of Detectors", "1", false),
+    (0x00540050, "Rotation Vector", "1-n", false),
+    (0x00540051, "Number of Rotations", "1", false),
+    (0x00540060, "R-R Interval Vector", "1-n", false),
+    (0x00540061, "Number of R-R Intervals", "1", false),
+    (0x00540080, "Slice Vector", "1-n", false),
+    (0x00540081, "Number of Slices", "1", false),
+    (0x00540090, "Angular View Vector", "1-n", false),
+    (0x00540400, "Image ID", "1", false),
+    (0x00540410, "Patient Orientation Code Sequence", "1", false),
+    (0x00540414, "Patient Gantry Relationship Code Sequence", "1", false),
+    (0x00541000, "Series Type", "2", false),
+    (0x00541001, "Units", "1", false),
+    (0x00541002, "Counts Source", "1", false),
+    (0x00541101, "Attenuation Correction Method", "1", false),
+    (0x00541102, "Decay Correction", "1", false),
+    (0x00541103, "Reconstruction Method", "1", false),
+    (0x00541300, "Frame Reference Time", "1", false),
+    (0x00541321, "Decay Factor", "1", false),
+    (0x00541322, "Dose Calibration Factor", "1", false),
+    (0x00541323, "Scatter Fraction Factor", "1", false),
+    (0x00541324, "Dead Time Factor", "1", false),
+    (0x00541330, "Image Index", "1", false),
+    (0x00700001, "Graphic Annotation Sequence", "1", false),
+    (0x00700002, "Graphic Layer", "1", false),
+    (0x00700003, "Bounding Box Annotation Units", "1", false),
+    (0x00700004, "Anchor Point Annotation Units", "1", false),
+    (0x00700005, "Graphic Annotation Units", "1", false),
+    (0x00700006, "Unformatted Text Value", "1", false),
+    (0x00700008, "Text Object Sequence", "1", false),
+    (0x00700009, "Graphic Object Sequence", "1", false),
+    (0x00700010, "Bounding Box Top Left Hand Corner", "2", false),
+    (0x00700011, "Bounding Box Bottom Right Hand Corner", "2", false),
+    (0x00700012, "Bounding Box Text Horizontal Justification", "1", false),
+    (0x00700014, "Anchor Point", "2", false),
+    (0x00700015, "Anchor Point Visibility", "1", false),
+    (0x00700020, "Graphic Dimensions", "1", false),
+    (0x00700021, "Number of Graphic Points", "1", false),
+    (0x00700022, "Graphic Data", "2-n", false),
+    (0x00700023, "Graphic Type", "1", false),
+    (0x00700024, "Graphic Filled", "1", false),
+    (0x00700041, "Image Horizontal Flip", "1", false),
+    (0x00700042, "Image Rotation", "1", false),
+    (0x00700052, "Displayed Area Top Left Hand Corner", "2", false),
+    (0x00700053, "Displayed Area Bottom Right Hand Corner", "2", false),
+    (0x0070005A, "Displayed Area Selection Sequence", "1", false),
+    (0x00700060, "Graphic Layer Sequence", "1", false),
+    (0x00700062, "Graphic Layer Order", "1", false),
+    (0x00700068, "Graphic Layer Description", "1", false),
+    (0x00700080, "Content Label", "1", false),
+    (0x00700081, "Content Description", "1", false),
+    (0x00700082, "Presentation Creation Date", "1", false),
+    (0x00700083, "Presentation Creation Time", "1", false),
+    (0x00700084, "Content Creator's Name", "1", false),
+    (0x00700100, "Presentation Size Mode", "1", false),
+    (0x00700101, "Presentation Pixel Spacing", "2", false),
+    (0x00700102, "Presentation Pixel Aspect Ratio", "2", false),
+    (0x00700103, "Presentation Pixel Magnification Ratio", "1", false),
+    (0x00880130, "Storage Media File-Set ID", "1", false),
+    (0x00880140, "Storage Media File-Set UID", "1", false),
+    (0x04000550, "Modified Attributes Sequence", "1", false),
+    (0x04000561, "Original Attributes Sequence", "1", false),
+    (0x04000562, "Attribute Modification DateTime", "1", false),
+    (0x04000563, "Modifying System", "1", false),
+    (0x04000564, "Source of Previous Values", "1", false),
+    (0x04000565, "Reason for the Attribute Modification", "1", false),
+    (0x30040002, "Dose Units", "1", false),
+    (0x30040004, "Dose Type", "1", false),
+    (0x3004000A, "Dose Summation Type", "1", false),
+    (0x3004000C, "Grid Frame Offset Vector", "2-n", false),
+    (0x3004000E, "Dose Grid Scaling", "1", false),
+    (0x30060002, "Structure Set Label", "1", false),
+    (0x30060004, "Structure Set Name", "1", false),
+    (0x30060008, "Structure Set Date", "1", false),
+    (0x30060009, "Structure Set Time", "1", false),
+    (0x30060010, "Referenced Frame of Reference Sequence", "1", false),
+    (0x30060020, "Structure Set ROI Sequence", "1", false),
+    (0x30060022, "ROI Number", "1", false),
+    (0x30060024, "Referenced Frame of Reference UID", "1", false),
+    (0x30060026, "ROI Name", "1", false),
+    (0x30060036, "ROI Generation Algorithm", "1", false),
+    (0x30060039, "ROI Contour Sequence", "1", false),
+    (0x30060040, "Contour Sequence", "1", false),
+    (0x30060042, "Contour Geometric Type", "1", false),
+    (0x30060046, "Number of Contour Points", "1", false),
+    (0x30060048, "Contour Number", "1", false),
+    (0x30060050, "Contour Data", "3-3n", false),
+    (0x30060080, "RT ROI Observations Sequence", "1", false),
+    (0x30060084, "Referenced ROI Number", "1", false),
+    (0x300600A4, "RT ROI Interpreted Type", "1", false),
+    (0x300A0002, "RT Plan Label", "1", false),
+    (0x300A0003, "RT Plan Name", "1", false),
+    (0x300A0006, "RT Plan Date", "1", false),
+    (0x300A0007, "RT Plan Time", "1", false),
+    (0x300A000C, "RT Plan Geometry", "1", false),
+    (0x300A00BE, "Leaf Position Boundaries", "3-n", false),
+    (0x300A011C, "Leaf/Jaw Positions", "2-2n", false),
+    (0x50000005, "Curve Dimensions", "1", true),
+    (0x50000010, "Number of Points", "1", true),
+    (0x50000020, "Type of Data", "1", true),
+    (0x50000022, "Curve Description", "1", true),
+    (0x50000030, "Axis Units", "1-n", true),
+    (0x50000040, "Axis Labels", "1-n", true),
+    (0x50000103, "Data Value Representation", "1", true),
+    (0x50000104, "Minimum Coordinate Value", "1-n", true),
+    (0x50000105, "Maximum Coordinate Value", "1-n", true),
+    (0x50000106, "Curve Range", "1-n", true),
+    (0x50000110, "Curve Data Descriptor", "1-n", true),
+    (0x50000112, "Coordinate Start Value", "1-n", true),
+    (0x50000114, "Coordinate Step Value", "1-n", true),
+    (0x50001001, "Curve Activation Layer", "1", true),
+    (0x50002000, "Audio Type", "1", true),
+    (0x50002002, "Audio Sample Format", "1", true),
+    (0x50002004, "Number of Channels", "1", true),
+    (0x50002006, "Number of Samples", "1", true),
+    (0x50002008, "Sample Rate", "1", true),
+    (0x5000200A, "Total Time", "1", true),
+    (0x5000200C, "Audio Sample Data", "1", true),
+    (0x5000200E, "Audio Comments", "1", true),
+    (0x50002500, "Curve Label", "1", true),
+    (0x50002600, "Curve Referenced Overlay Sequence", "1", true),
+    (0x50002610, "Curve Referenced Overlay Group", "1", true),
+    (0x50003000, "Curve Data", "1", true),
+    (0x60000010, "Overlay Rows", "1", false),
+    (0x60000011, "Overlay Columns", "1", false),
+    (0x60000012, "Overlay Planes", "1", true),
+    (0x60000015, "Number of Frames in Overlay", "1", false),
+    (0x60000022, "Overlay Description", "1", false),
+    (0x60000040, "Overlay Type", "1", false),
+    (0x60000045, "Overlay Subtype", "1", false),
+    (0x60000050, "Overlay Origin", "2", false),
+    (0x60000051, "Image Frame Origin", "1", false),
+    (0x60000052, "Overlay Plane Origin", "1", true),
+    (0x60000100, "Overlay Bits Allocated", "1", false),
+    (0x60000102, "Overlay Bit Position", "1", false),
+    (0x60000110, "Overlay Format", "1", true),
+    (0x60000200, "Overlay Location", "1", true),
+    (0x60000800, "Overlay Code Label", "1-n", true),
+    (0x60000802, "Overlay Number of Tables", "1", true),
+    (0x60000803, "Overlay Code Table Location", "1-n", true),
+    (0x60000804, "Overlay Bits For Code Word", "1", true),
+    (0x60001001, "Overlay Activation Layer", "1", false),
+    (0x60001100, "Overlay Descriptor - Gray", "1", true),
+    (0x60001101, "Overlay Descriptor - Red", "1", true),
+    (0x60001102, "Overlay Descriptor - Green", "1", true),
+    (0x60001103, "Overlay Descriptor - Blue", "1", true),
+    (0x60001200, "Overlays - Gray", "1-n", true),
+    (0x60001201, "Overlays - Red", "1-n", true),
+    (0x60001202, "Overlays - Green", "1-n", true),
+    (0x60001203, "Overlays - Blue", "1-n", true),
+    (0x60001301, "ROI Area", "1", false),
+    (0x60001302, "ROI Mean", "1", false),
+    (0x60001303, "ROI Standard Deviation", "1", false),
+    (0x60001500, "Overlay Label", "1", false),
+    (0x60003000, "Overlay Data", "1", false),
+    (0x60004000, "Overlay Comments", "1", true),
+    (0x7FE00001, "Extended Offset Table", "1", false),
+    (0x7FE00002, "Extended Offset Table Lengths", "1", false),
+    (0x7FE00008, "Float Pixel Data", "1", false),
+    (0x7FE00009, "Double Float Pixel Data", "1", false),
+    (0x7FE00010, "Pixel Data", "1", false),
+    (0xFFFAFFFA, "Digital Signatures Sequence", "1", false),
+    (0xFFFCFFFC, "Data Set Trailing Padding", "1", false),
+];
@@ -0,0 +1,596 @@
+// DICOM JSON model (PS3.18 Annex F) conversion to and from InMemDicomObject
+
+use anyhow::Result;
+use dicom_core::header::Header;
+use dicom_core::value::{DataSetSequence, PrimitiveValue, Value};
+use dicom_core::{Length, Tag, VR};
+use dicom_object::mem::InMemElement;
+use dicom_object::InMemDicomObject;
+use serde_json::{json, Map, Number};
+use std::str::FromStr;
+
+/// Options controlling how binary values are written
+#[derive(Debug, Clone, Default)]
+pub struct JsonOptions {
+    /// Binary values larger than this many bytes are written as BulkDataURI
+    /// instead of InlineBinary (only when `bulk_data_uri_prefix` is set)
+    pub bulk_data_threshold: Option<usize>,
+    /// Prefix for generated BulkDataURI values; the attribute path is appended
+    pub bulk_data_uri_prefix: Option<String>,
+}
+
+/// A BulkDataURI found while parsing; the referenced value is left empty in the object
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BulkDataReference {
+    /// Attribute path, e.g. "7FE00010" or "00540016.0.00181072"
+    pub path: String,
+    pub vr: String,
+    pub uri: String,
+}
+
+/// Convert a data set to a DICOM JSON object, inlining all binary values.
+/// Fails on encapsulated pixel data, which can only be written as a BulkDataURI.
+pub fn to_json(obj: &InMemDicomObject) -> Result<serde_json::Value> {
+    to_json_with_options(obj, &JsonOptions::default())
+}
+
+/// Convert a data set to a DICOM JSON object
+pub fn to_json_with_options(obj: &InMemDicomObject, options: &JsonOptions) -> Result<serde_json::Value> {
+    Ok(serde_json::Value::Object(dataset_to_json(obj, options, "")?))
+}
+
+/// Parse a DICOM JSON object into a data set. BulkDataURI values are left empty.
+pub fn from_json(value: &serde_json::Value) -> Result<InMemDicomObject> {
+    let mut bulk_data = Vec::new();
+    json_to_dataset(value, "", &mut bulk_data)
+}
+
+/// Parse a DICOM JSON object, also returning the BulkDataURI references it contains
+pub fn from_json_with_bulk_data(
+    value: &serde_json::Value,
+) -> Result<(InMemDicomObject, Vec<BulkDataReference>)> {
+    let mut bulk_data = Vec::new();
+    let obj = json_to_dataset(value, "", &mut bulk_data)?;
+    Ok((obj, bulk_data))
+}
+
+/// Parse a DICOM JSON array (as returned by QIDO-RS and WADO-RS metadata)
+pub fn from_json_array(value: &serde_json::Value) -> Result<Vec<InMemDicomObject>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Expected a JSON array of DICOM data sets"))?
+        .iter()
+        .map(from_json)
+        .collect()
+}
+
+/// Format a tag as used for DICOM JSON keys ("GGGGEEEE")
+pub fn tag_key(tag: Tag) -> String {
+    format!("{:04X}{:04X}", tag.group(), tag.element())
+}
+
+/// Parse a DICOM JSON key ("GGGGEEEE") into a tag
+pub fn parse_tag_key(key: &str) -> Result<Tag> {
+    if key.len() != 8 {
+        return Err(anyhow::anyhow!("Invalid DICOM JSON tag key: {}", key));
+    }
+    let group = u16::from_str_radix(&key[0..4], 16)
+        .map_err(|_| anyhow::anyhow!("Invalid DICOM JSON tag key: {}", key))?;
+    let element = u16::from_str_radix(&key[4..8], 16)
+        .map_err(|_| anyhow::anyhow!("Invalid DICOM JSON tag key: {}", key))?;
+    Ok(Tag(group, element))
+}
+
+fn dataset_to_json(
+    obj: &InMemDicomObject,
+    options: &JsonOptions,
+    path: &str,
+) -> Result<Map<String, serde_json::Value>> {
+    let mut map = Map::new();
+
+    for elem in obj.iter() {
+        let key = tag_key(elem.tag());
+        let elem_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        map.insert(key, element_to_json(elem, options, &elem_path)?);
+    }
+
+    Ok(map)
+}
+
+fn element_to_json(elem: &InMemElement, options: &JsonOptions, path: &str) -> Result<serde_json::Value> {
+    let vr = elem.vr();
+    let mut attr = Map::new();
+    attr.insert("vr".to_string(), json!(vr.to_string()));
+
+    match elem.value() {
+        Value::Sequence(seq) => {
+            let items: Vec<serde_json::Value> = seq
+                .items()
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    dataset_to_json(item, options, &format!("{}.{}", path, i))
+                        .map(serde_json::Value::Object)
+                })
+                .collect::<Result<_>>()?;
+            if !items.is_empty() {
+                attr.insert("Value".to_string(), serde_json::Value::Array(items));
+            }
+        }
+        Value::PixelSequence(_) => {
+            // Encapsulated pixel data can only be referenced, not inlined
+            let prefix = options.bulk_data_uri_prefix.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("Encapsulated pixel data at {} needs a BulkDataURI prefix", path)
+            })?;
+            attr.insert("BulkDataURI".to_string(), json!(format!("{}/{}", prefix, path)));
+        }
+        Value::Primitive(value) => {
+            if is_binary_vr(vr) {
+                let bytes = value.to_bytes();
+                if bytes.is_empty() {
+                    return Ok(serde_json::Value::Object(attr));
+                }
+                let use_uri = match (&options.bulk_data_uri_prefix, options.bulk_data_threshold) {
+                    (Some(_), Some(threshold)) => bytes.len() > threshold,
+                    (Some(_), None) => true,
+                    _ => false,
+                };
+                if use_uri {
+                    let prefix = options.bulk_data_uri_prefix.as_deref().unwrap_or_default();
+                    attr.insert("BulkDataURI".to_string(), json!(format!("{}/{}", prefix, path)));
+                } else {
+                    use base64::{Engine as _, engine::general_purpose};
+                    attr.insert(
+                        "InlineBinary".to_string(),
+                        json!(general_purpose::STANDARD.encode(&bytes)),
+                    );
+                }
+            } else {
+                let values = primitive_to_json_values(vr, value);
+                if !values.is_empty() {
+                    attr.insert("Value".to_string(), serde_json::Value::Array(values));
+                }
+            }
+        }
+    }
+
+    Ok(serde_json::Value::Object(attr))
+}
+
+fn primitive_to_json_values(vr: VR, value: &PrimitiveValue) -> Vec<serde_json::Value> {
+    if let PrimitiveValue::Empty = value {
+        return Vec::new();
+    }
+
+    match vr {
+        VR::PN => split_strings(vr, value)
+            .into_iter()
+            .map(|name| match name {
+                Some(name) => person_name_to_json(&name),
+                None => serde_json::Value::Null,
+            })
+            .collect(),
+        VR::AT => match value {
+            PrimitiveValue::Tags(tags) => tags.iter().map(|t| json!(tag_key(*t))).collect(),
+            _ => split_strings(vr, value)
+                .into_iter()
+                .map(|s| s.map(|s| json!(s)).unwrap_or(serde_json::Value::Null))
+                .collect(),
+        },
+        VR::DS | VR::FL | VR::FD => split_strings_or_numbers(vr, value, |s| {
+            s.parse::<f64>().ok().and_then(Number::from_f64)
+        }),
+        VR::IS | VR::SL | VR::SS | VR::SV | VR::UL | VR::US | VR::UV => {
+            split_strings_or_numbers(vr, value, |s| {
+                s.parse::<i64>()
+                    .map(Number::from)
+                    .ok()
+                    .or_else(|| s.parse::<u64>().map(Number::from).ok())
+            })
+        }
+        _ => split_strings(vr, value)
+            .into_iter()
+            .map(|s| s.map(|s| json!(s)).unwrap_or(serde_json::Value::Null))
+            .collect(),
+    }
+}
+
+/// Numeric VRs: use the string form so DS/IS keep their textual precision, then
+/// convert each value to a JSON number, falling back to a string if it does not parse
+fn split_strings_or_numbers<F>(vr: VR, value: &PrimitiveValue, parse: F) -> Vec<serde_json::Value>
+where
+    F: Fn(&str) -> Option<Number>,
+{
+    split_strings(vr, value)
+        .into_iter()
+        .map(|s| match s {
+            Some(s) => parse(&s)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::String(s)),
+            None => serde_json::Value::Null,
+        })
+        .collect()
+}
+
+/// Split a value into its individual (trimmed) string values; empty values become None
+fn split_strings(vr: VR, value: &PrimitiveValue) -> Vec<Option<String>> {
+    let single_valued = matches!(vr, VR::LT | VR::ST | VR::UT | VR::UR);
+
+    value
+        .to_multi_str()
+        .iter()
+        .flat_map(|s| {
+            if single_valued {
+                vec![s.to_string()]
+            } else {
+                s.split('\\').map(|v| v.to_string()).collect()
+            }
+        })
+        .map(|s| {
+            let trimmed = if single_valued {
+                s.trim_end_matches(|c| c == ' ' || c == '\0').to_string()
+            } else {
+                s.trim_matches(|c| c == ' ' || c == '\0').to_string()
+            };
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(trimmed)
+            }
+        })
+        .collect()
+}
+
+fn person_name_to_json(name: &str) -> serde_json::Value {
+    let mut groups = Map::new();
+    for (key, component) in ["Alphabetic", "Ideographic", "Phonetic"]
+        .iter()
+        .zip(name.split('='))
+    {
+        if !component.is_empty() {
+            groups.insert(key.to_string(), json!(component));
+        }
+    }
+    serde_json::Value::Object(groups)
+}
+
+fn person_name_from_json(value: &serde_json::Value) -> String {
+    let component = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let joined = [component("Alphabetic"), component("Ideographic"), component("Phonetic")].join("=");
+    joined.trim_end_matches('=').to_string()
+}
+
+fn is_binary_vr(vr: VR) -> bool {
+    matches!(
+        vr,
+        VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN
+    )
+}
+
+fn json_to_dataset(
+    value: &serde_json::Value,
+    path: &str,
+    bulk_data: &mut Vec<BulkDataReference>,
+) -> Result<InMemDicomObject> {
+    let map = value
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("Expected a DICOM JSON object at '{}'", path))?;
+
+    let mut obj = InMemDicomObject::new_empty();
+
+    for (key, attr) in map {
+        let tag = parse_tag_key(key)?;
+        let elem_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        obj.put_element(json_to_element(tag, attr, &elem_path, bulk_data)?);
+    }
+
+    Ok(obj)
+}
+
+fn json_to_element(
+    tag: Tag,
+    attr: &serde_json::Value,
+    path: &str,
+    bulk_data: &mut Vec<BulkDataReference>,
+) -> Result<InMemElement> {
+    let vr_str = attr
+        .get("vr")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing vr for attribute {}", path))?;
+    let vr = VR::from_str(vr_str)
+        .map_err(|_| anyhow::anyhow!("Unknown VR '{}' for attribute {}", vr_str, path))?;
+
+    if let Some(uri) = attr.get("BulkDataURI").and_then(|v| v.as_str()) {
+        bulk_data.push(BulkDataReference {
+            path: path.to_string(),
+            vr: vr_str.to_string(),
+            uri: uri.to_string(),
+        });
+        return Ok(InMemElement::new(tag, vr, Value::Primitive(PrimitiveValue::Empty)));
+    }
+
+    if let Some(inline) = attr.get("InlineBinary").and_then(|v| v.as_str()) {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let bytes = general_purpose::STANDARD
+            .decode(inline)
+            .map_err(|e| anyhow::anyhow!("Invalid InlineBinary for {}: {}", path, e))?;
+        return Ok(InMemElement::new(tag, vr, Value::Primitive(binary_value(vr, &bytes, path)?)));
+    }
+
+    let values = match attr.get("Value") {
+        Some(serde_json::Value::Array(values)) => values.as_slice(),
+        Some(_) => return Err(anyhow::anyhow!("Value of {} must be an array", path)),
+        None => &[],
+    };
+
+    if vr == VR::SQ {
+        let items = values
+            .iter()
+            .enumerate()
+            .map(|(i, item)| json_to_dataset(item, &format!("{}.{}", path, i), bulk_data))
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(InMemElement::new(
+            tag,
+            vr,
+            Value::Sequence(DataSetSequence::new(items, Length::UNDEFINED)),
+        ));
+    }
+
+    Ok(InMemElement::new(
+        tag,
+        vr,
+        Value::Primitive(json_values_to_primitive(vr, values, path)?),
+    ))
+}
+
+fn json_values_to_primitive(
+    vr: VR,
+    values: &[serde_json::Value],
+    path: &str,
+) -> Result<PrimitiveValue> {
+    if values.is_empty() {
+        return Ok(PrimitiveValue::Empty);
+    }
+
+    let value = match vr {
+        VR::PN => PrimitiveValue::Strs(
+            values
+                .iter()
+                .map(|v| if v.is_null() { String::new() } else { person_name_from_json(v) })
+                .collect(),
+        ),
+        VR::AT => PrimitiveValue::Tags(
+            values
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .ok_or_else(|| anyhow::anyhow!("AT value of {} must be a string", path))
+                        .and_then(parse_tag_key)
+                })
+                .collect::<Result<_>>()?,
+        ),
+        VR::FL => PrimitiveValue::F32(
+            numbers(values, path)?.into_iter().map(|n| n as f32).collect(),
+        ),
+        VR::FD => PrimitiveValue::F64(numbers(values, path)?.into_iter().collect()),
+        VR::SS => PrimitiveValue::I16(integers(vr, values, path)?),
+        VR::US => PrimitiveValue::U16(integers(vr, values, path)?),
+        VR::SL => PrimitiveValue::I32(integers(vr, values, path)?),
+        VR::UL => PrimitiveValue::U32(integers(vr, values, path)?),
+        VR::SV => PrimitiveValue::I64(integers(vr, values, path)?),
+        VR::UV => PrimitiveValue::U64(integers(vr, values, path)?),
+        // DS, IS and all textual VRs are stored as strings
+        _ => PrimitiveValue::Strs(
+            values
+                .iter()
+                .map(|v| match v {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+        ),
+    };
+
+    Ok(value)
+}
+
+/// Binary numeric values have no empty value, so a null is an error
+fn numbers(values: &[serde_json::Value], path: &str) -> Result<Vec<f64>> {
+    values
+        .iter()
+        .map(|v| match v {
+            serde_json::Value::Number(n) => n
+                .as_f64()
+                .ok_or_else(|| anyhow::anyhow!("Invalid number in {}", path)),
+            serde_json::Value::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid number '{}' in {}", s, path)),
+            serde_json::Value::Null => Err(anyhow::anyhow!("Empty value in {} is not allowed for a binary VR", path)),
+            _ => Err(anyhow::anyhow!("Expected a number in {}", path)),
+        })
+        .collect()
+}
+
+/// Integer values of a binary VR, rejecting any that don't fit its range
+fn integers<T: TryFrom<i128>>(vr: VR, values: &[serde_json::Value], path: &str) -> Result<Vec<T>> {
+    values
+        .iter()
+        .map(|v| {
+            let n = integer_value(v, path)?;
+            T::try_from(n).map_err(|_| anyhow::anyhow!("Value {} in {} is out of range for {}", n, path, vr))
+        })
+        .collect()
+}
+
+fn integer_value(value: &serde_json::Value, path: &str) -> Result<i128> {
+    match value {
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .ok_or_else(|| anyhow::anyhow!("Invalid integer {} in {}", n, path)),
+        serde_json::Value::String(s) => s
+            .trim()
+            .parse::<i128>()
+            .map_err(|_| anyhow::anyhow!("Invalid integer '{}' in {}", s, path)),
+        serde_json::Value::Null => Err(anyhow::anyhow!("Empty value in {} is not allowed for a binary VR", path)),
+        _ => Err(anyhow::anyhow!("Expected an integer in {}", path)),
+    }
+}
+
+/// Interpret little endian bytes according to the binary VR
+fn binary_value(vr: VR, bytes: &[u8], path: &str) -> Result<PrimitiveValue> {
+    let width = match vr {
+        VR::OW => 2,
+        VR::OL | VR::OF => 4,
+        VR::OD | VR::OV => 8,
+        _ => 1,
+    };
+    if bytes.len() % width != 0 {
+        return Err(anyhow::anyhow!(
+            "InlineBinary of {} has {} bytes, not a multiple of {} for {}",
+            path,
+            bytes.len(),
+            width,
+            vr
+        ));
+    }
+
+    Ok(match vr {
+        VR::OW => PrimitiveValue::U16(
+            bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect(),
+        ),
+        VR::OL => PrimitiveValue::U32(
+            bytes
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+        ),
+        VR::OF => PrimitiveValue::F32(
+            bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+        ),
+        VR::OD => PrimitiveValue::F64(
+            bytes
+                .chunks_exact(8)
+                .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        ),
+        VR::OV => PrimitiveValue::U64(
+            bytes
+                .chunks_exact(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        ),
+        _ => PrimitiveValue::U8(bytes.iter().copied().collect()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_dictionary_std::tags;
+
+    #[test]
+    fn test_person_name_groups() {
+        let value = person_name_to_json("Yamada^Tarou=山田^太郎=やまだ^たろう");
+        assert_eq!(value["Alphabetic"], "Yamada^Tarou");
+        assert_eq!(value["Ideographic"], "山田^太郎");
+        assert_eq!(value["Phonetic"], "やまだ^たろう");
+        assert_eq!(person_name_from_json(&value), "Yamada^Tarou=山田^太郎=やまだ^たろう");
+
+        let alphabetic_only = json!({ "Alphabetic": "Doe^John" });
+        assert_eq!(person_name_from_json(&alphabetic_only), "Doe^John");
+    }
+
+    #[test]
+    fn test_roundtrip_with_sequence_and_binary() {
+        let input = json!({
+            "00100010": { "vr": "PN", "Value": [{ "Alphabetic": "Doe^John" }] },
+            "00280010": { "vr": "US", "Value": [512] },
+            "00281050": { "vr": "DS", "Value": [40.5] },
+            "00400275": { "vr": "SQ", "Value": [
+                { "00321060": { "vr": "LO", "Value": ["CT HEAD"] } }
+            ]},
+            "00091010": { "vr": "OB", "InlineBinary": "AQID" }
+        });
+
+        let obj = from_json(&input).unwrap();
+        assert_eq!(obj.element(tags::ROWS).unwrap().to_int::<u16>().unwrap(), 512);
+
+        let output = to_json(&obj).unwrap();
+        assert_eq!(output["00100010"]["Value"][0]["Alphabetic"], "Doe^John");
+        assert_eq!(output["00280010"]["Value"][0], 512);
+        assert_eq!(output["00281050"]["Value"][0], 40.5);
+        assert_eq!(output["00400275"]["Value"][0]["00321060"]["Value"][0], "CT HEAD");
+        assert_eq!(output["00091010"]["InlineBinary"], "AQID");
+    }
+
+    #[test]
+    fn test_bulk_data_uri() {
+        let input = json!({
+            "7FE00010": { "vr": "OW", "BulkDataURI": "http://server/bulk/7FE00010" }
+        });
+
+        let (obj, bulk) = from_json_with_bulk_data(&input).unwrap();
+        assert_eq!(bulk.len(), 1);
+        assert_eq!(bulk[0].path, "7FE00010");
+        assert_eq!(bulk[0].uri, "http://server/bulk/7FE00010");
+        assert!(obj.element(tags::PIXEL_DATA).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_invalid_binary_values() {
+        let out_of_range = json!({ "00280010": { "vr": "US", "Value": [70000] } });
+        let err = from_json(&out_of_range).unwrap_err().to_string();
+        assert!(err.contains("00280010"), "{}", err);
+
+        assert!(from_json(&json!({ "00186020": { "vr": "SL", "Value": [-1] } })).is_ok());
+        assert!(from_json(&json!({ "00280010": { "vr": "US", "Value": [-1] } })).is_err());
+        assert!(from_json(&json!({ "00280030": { "vr": "FD", "Value": [1.0, null] } })).is_err());
+
+        // Three bytes can't be OW
+        assert!(from_json(&json!({ "00091010": { "vr": "OW", "InlineBinary": "AQID" } })).is_err());
+    }
+
+    #[test]
+    fn test_pixel_sequence_needs_bulk_data_prefix() {
+        use dicom_core::value::PixelFragmentSequence;
+
+        let obj = InMemDicomObject::from_element_iter([InMemElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            Value::PixelSequence(PixelFragmentSequence::new(vec![], vec![vec![0xFF, 0xD8]])),
+        )]);
+        assert!(to_json(&obj).is_err());
+
+        let options = JsonOptions {
+            bulk_data_uri_prefix: Some("http://server/bulk".to_string()),
+            ..Default::default()
+        };
+        let output = to_json_with_options(&obj, &options).unwrap();
+        assert_eq!(output["7FE00010"]["BulkDataURI"], "http://server/bulk/7FE00010");
+    }
+}
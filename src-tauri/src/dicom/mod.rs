@@ -1,11 +1,20 @@
 pub mod parser;
 pub mod pixeldata;
 pub mod tags;
+pub mod dictionary;
+mod dictionary_table;
+pub mod path;
+pub mod private;
+pub mod vendor;
+pub mod vr;
 pub mod anonymizer;
+pub mod json;
+pub mod batch;
+pub mod validator;
+pub mod consistency;
 
 use anyhow::Result;
-use dicom_object::{InMemDicomObject, DicomObject};
-use dicom_core::header::Header;
+use dicom_object::InMemDicomObject;
 use std::path::Path;
 
 /// Load a DICOM file from disk
@@ -15,54 +24,115 @@ pub fn load_dicom_file<P: AsRef<Path>>(path: P) -> Result<InMemDicomObject> {
     Ok(file_obj.into_inner())
 }
 
-/// Save a DICOM object to disk
+/// Parse a DICOM Part 10 object held in memory, with or without the 128-byte preamble
+pub fn read_dicom_bytes(bytes: &[u8]) -> Result<dicom_object::DefaultDicomObject> {
+    let data = if bytes.len() >= 132 && &bytes[128..132] == b"DICM" {
+        &bytes[128..]
+    } else {
+        bytes
+    };
+
+    Ok(dicom_object::from_reader(data)?)
+}
+
+/// Implementation Class UID written into the File Meta of every file we save
+pub const IMPLEMENTATION_CLASS_UID: &str = "2.25.41608340278233821353773717915292147616";
+
+/// Implementation Version Name written alongside it (SH, at most 16 characters)
+pub const IMPLEMENTATION_VERSION_NAME: &str = concat!("DICOMFLOW_", env!("CARGO_PKG_VERSION"));
+
+/// Save a DICOM object to disk. When overwriting a file its transfer syntax is kept.
 pub fn save_dicom_file<P: AsRef<Path>>(obj: &InMemDicomObject, path: P) -> Result<()> {
-    use dicom_object::FileDicomObject;
-    use dicom_object::mem::InMemElement;
+    let original = read_transfer_syntax(&path);
+    save_dicom_file_as(obj, path, original.as_deref())
+}
 
-    // Get meta information or create default
-    let meta = obj.meta()
-        .cloned()
-        .unwrap_or_else(|| {
-            // Create a minimal valid file meta information
-            use dicom_object::meta::FileMetaTableBuilder;
-            use dicom_core::dicom_value;
-            use dicom_dictionary_std::uids;
-
-            FileMetaTableBuilder::new()
-                // Explicit VR Little Endian
-                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
-                .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
-                .media_storage_sop_instance_uid(
-                    obj.element_by_name("SOPInstanceUID")
-                        .ok()
-                        .and_then(|e| e.to_str().ok())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| "1.2.3.4.5.6.7.8.9".to_string())
-                )
-                .build()
-                .expect("Failed to create file meta table")
-        });
-
-    // Create a FileDicomObject with meta information
-    let mut new_obj = FileDicomObject::new_empty_with_meta(meta);
-
-    // Copy all elements from InMemDicomObject to FileDicomObject
-    for elem in obj.iter() {
-        let new_elem = InMemElement::new(
-            elem.tag(),
-            elem.vr(),
-            elem.value().clone(),
-        );
-        new_obj.put_element(new_elem);
-    }
+/// Save a DICOM object to disk in `transfer_syntax` (usually the one the
+/// dataset was read with), falling back to Explicit VR Little Endian when it
+/// can't be written. The File Meta is rebuilt from the dataset.
+pub fn save_dicom_file_as<P: AsRef<Path>>(
+    obj: &InMemDicomObject,
+    path: P,
+    transfer_syntax: Option<&str>,
+) -> Result<()> {
+    let transfer_syntax = choose_transfer_syntax(obj, transfer_syntax)?;
+    let file_obj = obj.clone().with_exact_meta(build_file_meta(obj, &transfer_syntax)?);
 
-    // Write to file
-    new_obj.write_to_file(path)?;
+    // Write to a temporary file and rename it over the target
+    crate::utils::file_helpers::write_atomic(path, |temp| {
+        file_obj.write_to_file(temp)?;
+        Ok(())
+    })?;
 
     Ok(())
 }
 
+/// File Meta Information for `obj`: media storage SOP class and instance from
+/// the dataset's SOP Class/Instance UIDs, plus our implementation identifiers
+pub fn build_file_meta(obj: &InMemDicomObject, transfer_syntax: &str) -> Result<dicom_object::meta::FileMetaTable> {
+    use dicom_dictionary_std::tags;
+    use dicom_object::meta::FileMetaTableBuilder;
+
+    let uid = |tag, name| {
+        obj.element(tag)
+            .ok()
+            .and_then(|e| e.to_str().ok())
+            .map(|s| s.trim_end_matches('\0').trim().to_string())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Cannot write file meta: dataset has no {}", name))
+    };
+
+    Ok(FileMetaTableBuilder::new()
+        .transfer_syntax(transfer_syntax)
+        .media_storage_sop_class_uid(uid(tags::SOP_CLASS_UID, "SOP Class UID")?)
+        .media_storage_sop_instance_uid(uid(tags::SOP_INSTANCE_UID, "SOP Instance UID")?)
+        .implementation_class_uid(IMPLEMENTATION_CLASS_UID)
+        .implementation_version_name(IMPLEMENTATION_VERSION_NAME)
+        .build()?)
+}
+
+/// Transfer syntax of a Part 10 file, read from its meta group only
+pub fn read_transfer_syntax<P: AsRef<Path>>(path: P) -> Option<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut head = [0u8; 132];
+    file.read_exact(&mut head).ok()?;
+    // The meta reader expects to start at the "DICM" magic code
+    let offset = if &head[128..132] == b"DICM" { 128 } else { 0 };
+    file.seek(SeekFrom::Start(offset)).ok()?;
+
+    let meta = dicom_object::meta::FileMetaTable::from_reader(std::io::BufReader::new(file)).ok()?;
+    Some(meta.transfer_syntax().trim_end_matches('\0').to_string())
+}
+
+/// Transfer syntax to write `obj` with: `preferred` when it is known, writable
+/// and matches how the pixel data is stored, else Explicit VR Little Endian.
+/// Encapsulated pixel data is never transcoded, so it needs its own syntax.
+fn choose_transfer_syntax(obj: &InMemDicomObject, preferred: Option<&str>) -> Result<String> {
+    use dicom_core::value::Value;
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+    use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+
+    let encapsulated = obj
+        .element(tags::PIXEL_DATA)
+        .map_or(false, |e| matches!(e.value(), Value::PixelSequence(_)));
+
+    let preferred = preferred
+        .map(|uid| uid.trim_end_matches('\0').trim())
+        .and_then(|uid| TransferSyntaxRegistry.get(uid))
+        .filter(|ts| !ts.is_unsupported() && ts.uid() != uids::DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN);
+
+    match preferred {
+        Some(ts) if ts.is_encapsulated_pixel_data() == encapsulated => Ok(ts.uid().to_string()),
+        _ if encapsulated => Err(anyhow::anyhow!(
+            "Pixel data is encapsulated but its transfer syntax is unknown or cannot be written"
+        )),
+        _ => Ok(uids::EXPLICIT_VR_LITTLE_ENDIAN.to_string()),
+    }
+}
+
 /// Extract basic metadata from a DICOM object
 pub fn extract_metadata(obj: &InMemDicomObject) -> Result<DicomMetadata> {
     use dicom_dictionary_std::tags;
@@ -127,3 +197,66 @@ pub struct DicomMetadata {
     pub study_date: Option<String>,
     pub modality: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::VR;
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_object::mem::InMemElement;
+
+    fn object(sop_instance_uid: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            InMemElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from(uids::CT_IMAGE_STORAGE)),
+            InMemElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(sop_instance_uid)),
+            InMemElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+        ])
+    }
+
+    #[test]
+    fn test_build_file_meta_follows_dataset() {
+        let meta = build_file_meta(&object("2.25.1234"), uids::IMPLICIT_VR_LITTLE_ENDIAN).unwrap();
+        assert_eq!(meta.media_storage_sop_class_uid(), uids::CT_IMAGE_STORAGE);
+        assert_eq!(meta.media_storage_sop_instance_uid(), "2.25.1234");
+        assert_eq!(meta.implementation_class_uid(), IMPLEMENTATION_CLASS_UID);
+        assert!(IMPLEMENTATION_VERSION_NAME.len() <= 16);
+
+        let mut no_uid = object("2.25.1234");
+        no_uid.remove_element(tags::SOP_INSTANCE_UID);
+        assert!(build_file_meta(&no_uid, uids::EXPLICIT_VR_LITTLE_ENDIAN).is_err());
+    }
+
+    #[test]
+    fn test_choose_transfer_syntax() {
+        let obj = object("2.25.1234");
+        assert_eq!(
+            choose_transfer_syntax(&obj, Some(uids::IMPLICIT_VR_LITTLE_ENDIAN)).unwrap(),
+            uids::IMPLICIT_VR_LITTLE_ENDIAN
+        );
+        assert_eq!(choose_transfer_syntax(&obj, None).unwrap(), uids::EXPLICIT_VR_LITTLE_ENDIAN);
+        assert_eq!(choose_transfer_syntax(&obj, Some("1.2.3.4")).unwrap(), uids::EXPLICIT_VR_LITTLE_ENDIAN);
+        // Native pixel data can't be labelled with a compressed syntax
+        assert_eq!(
+            choose_transfer_syntax(&obj, Some(uids::JPEG_BASELINE8_BIT)).unwrap(),
+            uids::EXPLICIT_VR_LITTLE_ENDIAN
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_transfer_syntax_and_updates_meta() {
+        let dir = std::env::temp_dir().join(format!("save-meta-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ct.dcm");
+
+        save_dicom_file_as(&object("2.25.1"), &path, Some(uids::IMPLICIT_VR_LITTLE_ENDIAN)).unwrap();
+
+        // Overwriting with a new SOP Instance UID keeps the syntax and updates the meta
+        save_dicom_file(&object("2.25.2"), &path).unwrap();
+        let saved = dicom_object::open_file(&path).unwrap();
+        assert_eq!(saved.meta().transfer_syntax().trim_end_matches('\0'), uids::IMPLICIT_VR_LITTLE_ENDIAN);
+        assert_eq!(saved.meta().media_storage_sop_instance_uid().trim_end_matches('\0'), "2.25.2");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,16 +4,14 @@ use anyhow::Result;
 use dicom_object::InMemDicomObject;
 use std::path::Path;
 
-/// Parse a DICOM file and validate its structure
-pub fn parse_and_validate<P: AsRef<Path>>(path: P) -> Result<InMemDicomObject> {
+/// Parse a DICOM file and validate it against its IOD, see `validator`
+pub fn parse_and_validate<P: AsRef<Path>>(path: P) -> Result<(InMemDicomObject, super::validator::FileValidation)> {
+    let path_str = path.as_ref().to_string_lossy().to_string();
     let file_obj = dicom_object::open_file(path)?;
 
-    // TODO: Add validation logic
-    // - Check required tags
-    // - Validate VR values
-    // - Check IOD conformance
+    let validation = super::validator::validate_file_object(&path_str, &file_obj);
 
-    Ok(file_obj.into_inner())
+    Ok((file_obj.into_inner(), validation))
 }
 
 /// DICOM file with its path
@@ -131,6 +129,18 @@ pub fn scan_directory_fast<P: AsRef<Path>>(dir: P) -> Result<Vec<MinimalFileInfo
                         .and_then(|e| e.to_str().ok())
                         .map(|s| s.to_string());
 
+                    let instance_number = obj.element(tags::INSTANCE_NUMBER)
+                        .ok()
+                        .and_then(|e| e.to_int::<i32>().ok());
+
+                    let image_position = obj.element(tags::IMAGE_POSITION_PATIENT)
+                        .ok()
+                        .and_then(|e| e.to_multi_float64().ok());
+
+                    let image_orientation = obj.element(tags::IMAGE_ORIENTATION_PATIENT)
+                        .ok()
+                        .and_then(|e| e.to_multi_float64().ok());
+
                     Some(MinimalFileInfo {
                         path: path.to_string_lossy().to_string(),
                         study_instance_uid: study_uid,
@@ -140,6 +150,9 @@ pub fn scan_directory_fast<P: AsRef<Path>>(dir: P) -> Result<Vec<MinimalFileInfo
                         patient_id,
                         study_date,
                         modality,
+                        instance_number,
+                        image_position,
+                        image_orientation,
                     })
                 }
                 Err(_) => None,
@@ -160,6 +173,11 @@ pub struct MinimalFileInfo {
     pub patient_id: Option<String>,
     pub study_date: Option<String>,
     pub modality: Option<String>,
+    pub instance_number: Option<i32>,
+    /// Image Position (Patient), for slice spacing checks
+    pub image_position: Option<Vec<f64>>,
+    /// Image Orientation (Patient)
+    pub image_orientation: Option<Vec<f64>>,
 }
 
 #[cfg(test)]
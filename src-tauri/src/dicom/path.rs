@@ -0,0 +1,164 @@
+// Attribute paths into nested sequences, e.g. "(0040,0275)[0].(0032,1060)"
+
+use anyhow::Result;
+use dicom_core::Tag;
+use serde::{Deserialize, Serialize};
+
+/// One step of a path: an attribute, and for sequences the item to descend into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathSegment {
+    pub tag: Tag,
+    pub item: Option<usize>,
+}
+
+/// Path to an attribute or a sequence item. Every segment but the last must
+/// select an item; a trailing index addresses the item itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TagPath {
+    pub segments: Vec<PathSegment>,
+}
+
+impl TagPath {
+    pub fn root(tag: Tag) -> Self {
+        TagPath { segments: vec![PathSegment { tag, item: None }] }
+    }
+
+    /// Parse "(0040,0275)[0].(0032,1060)"; "00400275[0].00321060" and a plain
+    /// "(0010,0010)" are accepted too
+    pub fn parse(path: &str) -> Result<Self> {
+        let segments = path
+            .split('.')
+            .map(|segment| parse_segment(segment.trim()))
+            .collect::<Result<Vec<_>>>()?;
+
+        if segments.is_empty() {
+            return Err(anyhow::anyhow!("Empty tag path"));
+        }
+        if let Some(pos) = segments[..segments.len() - 1].iter().position(|s| s.item.is_none()) {
+            return Err(anyhow::anyhow!(
+                "Invalid tag path {}: segment {} needs an item index",
+                path,
+                pos + 1
+            ));
+        }
+
+        Ok(TagPath { segments })
+    }
+
+    /// Path of an attribute inside item `item` of the sequence at this path
+    pub fn child(&self, item: usize, tag: Tag) -> Self {
+        let mut segments = self.segments.clone();
+        if let Some(last) = segments.last_mut() {
+            last.item = Some(item);
+        }
+        segments.push(PathSegment { tag, item: None });
+        TagPath { segments }
+    }
+
+    /// Attribute addressed by the last segment
+    pub fn tag(&self) -> Tag {
+        self.segments[self.segments.len() - 1].tag
+    }
+
+    /// Whether the path ends at a sequence item rather than an attribute
+    pub fn is_item(&self) -> bool {
+        self.segments[self.segments.len() - 1].item.is_some()
+    }
+}
+
+impl std::fmt::Display for TagPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "({:04X},{:04X})", segment.tag.group(), segment.tag.element())?;
+            if let Some(item) = segment.item {
+                write!(f, "[{}]", item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for TagPath {
+    type Error = anyhow::Error;
+
+    fn try_from(path: String) -> Result<Self> {
+        TagPath::parse(&path)
+    }
+}
+
+impl From<TagPath> for String {
+    fn from(path: TagPath) -> String {
+        path.to_string()
+    }
+}
+
+fn parse_segment(segment: &str) -> Result<PathSegment> {
+    let (tag_part, item) = match segment.split_once('[') {
+        Some((tag_part, rest)) => {
+            let index = rest
+                .strip_suffix(']')
+                .and_then(|i| i.trim().parse::<usize>().ok())
+                .ok_or_else(|| anyhow::anyhow!("Invalid item index in {}", segment))?;
+            (tag_part, Some(index))
+        }
+        None => (segment, None),
+    };
+
+    Ok(PathSegment { tag: parse_tag(tag_part)?, item })
+}
+
+/// Parse "(0010,0010)", "0010,0010" or "00100010"
+pub fn parse_tag(tag: &str) -> Result<Tag> {
+    let cleaned: String = tag
+        .chars()
+        .filter(|c| !matches!(c, '(' | ')' | ',' | ' '))
+        .collect();
+
+    // Checked before slicing, which would panic inside a multi-byte character
+    if cleaned.len() != 8 || !cleaned.is_ascii() {
+        return Err(anyhow::anyhow!("Invalid tag format: {}", tag));
+    }
+
+    let group = u16::from_str_radix(&cleaned[0..4], 16)
+        .map_err(|_| anyhow::anyhow!("Invalid group number: {}", &cleaned[0..4]))?;
+    let element = u16::from_str_radix(&cleaned[4..8], 16)
+        .map_err(|_| anyhow::anyhow!("Invalid element number: {}", &cleaned[4..8]))?;
+
+    Ok(Tag(group, element))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let path = TagPath::parse("(0040,0275)[0].(0032,1060)").unwrap();
+        assert_eq!(path.segments.len(), 2);
+        assert_eq!(path.segments[0], PathSegment { tag: Tag(0x0040, 0x0275), item: Some(0) });
+        assert_eq!(path.tag(), Tag(0x0032, 0x1060));
+        assert!(!path.is_item());
+        assert_eq!(path.to_string(), "(0040,0275)[0].(0032,1060)");
+
+        let compact = TagPath::parse("00400275[1].00321060").unwrap();
+        assert_eq!(compact.segments[0].item, Some(1));
+
+        let item = TagPath::parse("(0040,0275)[2]").unwrap();
+        assert!(item.is_item());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_paths() {
+        assert!(TagPath::parse("(0040,0275).(0032,1060)").is_err());
+        assert!(TagPath::parse("(0040,0275)[x].(0032,1060)").is_err());
+        assert!(TagPath::parse("(0040,027)").is_err());
+        assert!(parse_tag("00€00000").is_err());
+        assert!(parse_tag("(0010,€0)").is_err());
+        // Eight bytes, with the slice boundary inside the multi-byte character
+        assert!(parse_tag("00€000").is_err());
+    }
+}
@@ -69,6 +69,172 @@ pub fn load_and_decode_to_png_base64<P: AsRef<Path>>(
     Ok(base64_string)
 }
 
+/// Output settings for `render_frame`
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Zero-based frame index
+    pub frame: u32,
+    /// Window center and width; the file's default VOI LUT is used when unset
+    pub window: Option<(f32, f32)>,
+    /// Maximum output size; the image is scaled down keeping its aspect ratio
+    pub max_size: Option<(u32, u32)>,
+    pub format: image::ImageFormat,
+    /// JPEG quality (1-100)
+    pub quality: Option<u8>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            frame: 0,
+            window: None,
+            max_size: None,
+            format: image::ImageFormat::Jpeg,
+            quality: None,
+        }
+    }
+}
+
+/// Render one frame of a DICOM file to encoded image bytes (JPEG or PNG)
+pub fn render_frame<P: AsRef<Path>>(path: P, options: &RenderOptions) -> Result<Vec<u8>> {
+    render_object(&dicom_object::open_file(path)?, options)
+}
+
+/// Render one frame of a loaded object to encoded image bytes (JPEG or PNG)
+pub fn render_object(file_obj: &FileDicomObject<InMemDicomObject>, options: &RenderOptions) -> Result<Vec<u8>> {
+    use image::codecs::jpeg::JpegEncoder;
+    use std::io::Cursor;
+
+    let decoded = file_obj.decode_pixel_data()?;
+
+    let voi = match options.window {
+        Some((center, width)) => VoiLutOption::Custom(WindowLevel {
+            center: center as f64,
+            width: width as f64,
+        }),
+        None => VoiLutOption::Default,
+    };
+    let convert = ConvertOptions::new().with_voi_lut(voi).force_8bit();
+
+    let mut dynamic_image = decoded.to_dynamic_image_with_options(options.frame, &convert)?;
+    if let Some((width, height)) = options.max_size {
+        if dynamic_image.width() > width || dynamic_image.height() > height {
+            dynamic_image = dynamic_image.thumbnail(width, height);
+        }
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    match (options.format, options.quality) {
+        (image::ImageFormat::Jpeg, Some(quality)) => {
+            JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100)).encode_image(&dynamic_image)?;
+        }
+        (format, _) => dynamic_image.write_to(&mut Cursor::new(&mut bytes), format)?,
+    }
+
+    Ok(bytes)
+}
+
+/// Build a single-frame object from instance metadata and the uncompressed bytes
+/// of one of its frames (Explicit VR Little Endian), so it can be rendered locally
+pub fn single_frame_object(mut metadata: InMemDicomObject, frame: Vec<u8>) -> Result<FileDicomObject<InMemDicomObject>> {
+    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    let bits_allocated = metadata.element(tags::BITS_ALLOCATED)?.to_int::<u16>()?;
+    let vr = if bits_allocated > 8 { VR::OW } else { VR::OB };
+    metadata.put(DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, PrimitiveValue::from("1")));
+    metadata.put(DataElement::new(tags::PIXEL_DATA, vr, PrimitiveValue::from(frame)));
+
+    let meta = crate::dicom::build_file_meta(&metadata, "1.2.840.10008.1.2.1")?;
+    Ok(metadata.with_exact_meta(meta))
+}
+
+/// Pixel data of a single frame as stored, with its media type
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Extract the stored bytes of one frame (zero-based) without decoding them
+pub fn raw_frame(obj: &FileDicomObject<InMemDicomObject>, frame: u32) -> Result<RawFrame> {
+    use dicom_core::value::Value;
+    use dicom_dictionary_std::tags;
+
+    let ts = obj.meta().transfer_syntax().trim_end_matches('\0').to_string();
+    let number_of_frames = obj
+        .element(tags::NUMBER_OF_FRAMES)
+        .ok()
+        .and_then(|e| e.to_int::<u32>().ok())
+        .unwrap_or(1);
+    if frame >= number_of_frames {
+        return Err(anyhow::anyhow!(
+            "Frame {} out of range ({} frames)",
+            frame + 1,
+            number_of_frames
+        ));
+    }
+
+    let pixel_data = obj.element(tags::PIXEL_DATA)?;
+    match pixel_data.value() {
+        Value::PixelSequence(sequence) => {
+            let fragments = sequence.fragments();
+            // One fragment per frame is the common case; a single frame may span fragments
+            let data = if fragments.len() == number_of_frames as usize {
+                fragments[frame as usize].clone()
+            } else if number_of_frames == 1 {
+                fragments.concat()
+            } else {
+                return Err(anyhow::anyhow!(
+                    "Cannot map {} fragments onto {} frames",
+                    fragments.len(),
+                    number_of_frames
+                ));
+            };
+
+            Ok(RawFrame {
+                media_type: encapsulated_media_type(&ts).to_string(),
+                data,
+            })
+        }
+        _ => {
+            let rows = obj.element(tags::ROWS)?.to_int::<usize>()?;
+            let columns = obj.element(tags::COLUMNS)?.to_int::<usize>()?;
+            let samples = obj
+                .element(tags::SAMPLES_PER_PIXEL)
+                .ok()
+                .and_then(|e| e.to_int::<usize>().ok())
+                .unwrap_or(1);
+            let bits_allocated = obj.element(tags::BITS_ALLOCATED)?.to_int::<usize>()?;
+
+            let frame_size = rows * columns * samples * bits_allocated / 8;
+            let bytes = pixel_data.to_bytes()?;
+            let start = frame as usize * frame_size;
+            let data = bytes
+                .get(start..start + frame_size)
+                .ok_or_else(|| anyhow::anyhow!("Pixel data is shorter than expected"))?
+                .to_vec();
+
+            Ok(RawFrame {
+                media_type: format!("application/octet-stream; transfer-syntax={}", ts),
+                data,
+            })
+        }
+    }
+}
+
+/// Media type of compressed frames for a transfer syntax (PS3.18 Table 8.7.3-2)
+fn encapsulated_media_type(transfer_syntax: &str) -> &'static str {
+    match transfer_syntax {
+        "1.2.840.10008.1.2.4.50" | "1.2.840.10008.1.2.4.51" | "1.2.840.10008.1.2.4.57"
+        | "1.2.840.10008.1.2.4.70" => "image/jpeg",
+        "1.2.840.10008.1.2.4.80" | "1.2.840.10008.1.2.4.81" => "image/jls",
+        "1.2.840.10008.1.2.4.90" | "1.2.840.10008.1.2.4.91" => "image/jp2",
+        "1.2.840.10008.1.2.5" => "image/x-dicom-rle",
+        _ => "application/octet-stream",
+    }
+}
+
 /// Get default window center and width from DICOM file
 pub fn get_default_windowing(obj: &InMemDicomObject) -> Option<(f32, f32)> {
     use dicom_dictionary_std::tags;
@@ -160,4 +326,32 @@ mod tests {
         assert_eq!(bone.center, 400.0);
         assert_eq!(bone.width, 1800.0);
     }
+
+    #[test]
+    fn test_render_single_frame_object() {
+        use dicom_core::{DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::tags;
+
+        let metadata = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from("1.2.840.10008.5.1.4.1.1.7")),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3.4")),
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, PrimitiveValue::from(1_u16)),
+            DataElement::new(tags::PHOTOMETRIC_INTERPRETATION, VR::CS, PrimitiveValue::from("MONOCHROME2")),
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, PrimitiveValue::from(8_u16)),
+            DataElement::new(tags::BITS_STORED, VR::US, PrimitiveValue::from(8_u16)),
+            DataElement::new(tags::HIGH_BIT, VR::US, PrimitiveValue::from(7_u16)),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, PrimitiveValue::from(0_u16)),
+        ]);
+
+        let obj = single_frame_object(metadata, vec![0, 64, 128, 255]).unwrap();
+        let options = RenderOptions {
+            window: Some((128.0, 256.0)),
+            format: image::ImageFormat::Png,
+            ..Default::default()
+        };
+        let png = render_object(&obj, &options).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
 }
@@ -0,0 +1,300 @@
+// Private data dictionary: vendor attributes identified by their private creator
+//
+// A private element (gggg,xxEE) belongs to the block reserved by the creator
+// string in (gggg,00xx), so the same element number means different things
+// under different creators. Entries are keyed by creator, group and the low
+// byte EE of the element number.
+
+use anyhow::Result;
+use dicom_core::header::Header;
+use dicom_core::{DataElement, Tag, VR};
+use dicom_object::InMemDicomObject;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivateDictEntry {
+    pub creator: String,
+    /// Odd group number, e.g. 0x0029
+    pub group: u16,
+    /// Low byte of the element number: (0029,xx10) has element 0x10
+    pub element: u8,
+    pub vr: String,
+    pub vm: String,
+    pub name: String,
+}
+
+/// Bundled entries: (creator, group, element, VR, VM, name)
+const BUILTIN: &[(&str, u16, u8, &str, &str, &str)] = &[
+    // Siemens
+    ("SIEMENS CSA HEADER", 0x0029, 0x08, "CS", "1", "CSA Image Header Type"),
+    ("SIEMENS CSA HEADER", 0x0029, 0x09, "LO", "1", "CSA Image Header Version"),
+    ("SIEMENS CSA HEADER", 0x0029, 0x10, "OB", "1", "CSA Image Header Info"),
+    ("SIEMENS CSA HEADER", 0x0029, 0x18, "CS", "1", "CSA Series Header Type"),
+    ("SIEMENS CSA HEADER", 0x0029, 0x19, "LO", "1", "CSA Series Header Version"),
+    ("SIEMENS CSA HEADER", 0x0029, 0x20, "OB", "1", "CSA Series Header Info"),
+    ("SIEMENS MEDCOM HEADER2", 0x0029, 0x60, "LO", "1", "Series Workflow Status"),
+    ("SIEMENS MR HEADER", 0x0019, 0x08, "CS", "1", "CSA Image Header Type"),
+    ("SIEMENS MR HEADER", 0x0019, 0x09, "LO", "1", "CSA Image Header Version"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0A, "US", "1", "Number of Images in Mosaic"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0B, "DS", "1", "Slice Measurement Duration"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0C, "IS", "1", "B Value"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0D, "CS", "1", "Diffusion Directionality"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0E, "FD", "3", "Diffusion Gradient Direction"),
+    ("SIEMENS MR HEADER", 0x0019, 0x0F, "SH", "1", "Gradient Mode"),
+    ("SIEMENS MR HEADER", 0x0019, 0x11, "SH", "1", "Flow Compensation"),
+    ("SIEMENS MR HEADER", 0x0019, 0x12, "SL", "3", "Table Position Origin"),
+    ("SIEMENS MR HEADER", 0x0019, 0x13, "SL", "3", "Ima Abs Table Position"),
+    ("SIEMENS MR HEADER", 0x0019, 0x14, "IS", "3", "Ima Rel Table Position"),
+    ("SIEMENS MR HEADER", 0x0019, 0x15, "FD", "3", "Slice Position PCS"),
+    ("SIEMENS MR HEADER", 0x0019, 0x16, "DS", "1", "Time After Start"),
+    ("SIEMENS MR HEADER", 0x0019, 0x17, "DS", "1", "Slice Resolution"),
+    ("SIEMENS MR HEADER", 0x0019, 0x18, "IS", "1", "Real Dwell Time"),
+    ("SIEMENS MR HEADER", 0x0019, 0x27, "FD", "6", "B Matrix"),
+    ("SIEMENS MR HEADER", 0x0019, 0x28, "FD", "1", "Bandwidth per Pixel Phase Encode"),
+    ("SIEMENS MR HEADER", 0x0019, 0x29, "FD", "1-n", "Mosaic Ref Acq Times"),
+    ("SIEMENS MR HEADER", 0x0051, 0x08, "CS", "1", "CSA Image Header Type"),
+    ("SIEMENS MR HEADER", 0x0051, 0x09, "LO", "1", "CSA Image Header Version"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0A, "LO", "1", "Time of Acquisition"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0B, "LO", "1", "Acquisition Matrix Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0C, "LO", "1", "Field of View"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0D, "SH", "1", "Slice Position Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0E, "LO", "1", "Image Orientation"),
+    ("SIEMENS MR HEADER", 0x0051, 0x0F, "LO", "1", "Coil String"),
+    ("SIEMENS MR HEADER", 0x0051, 0x11, "LO", "1", "PAT Mode Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x12, "SH", "1", "Table Position Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x13, "SH", "1", "Positive PCS Directions"),
+    ("SIEMENS MR HEADER", 0x0051, 0x16, "LO", "1", "Image Type Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x17, "SH", "1", "Slice Thickness Text"),
+    ("SIEMENS MR HEADER", 0x0051, 0x19, "LO", "1", "Scan Options Text"),
+    // GE
+    ("GEMS_IDEN_01", 0x0009, 0x01, "LO", "1", "Full Fidelity"),
+    ("GEMS_IDEN_01", 0x0009, 0x02, "SH", "1", "Suite ID"),
+    ("GEMS_IDEN_01", 0x0009, 0x04, "SH", "1", "Product ID"),
+    ("GEMS_IDEN_01", 0x0009, 0x27, "SL", "1", "Image Actual Date"),
+    ("GEMS_ACQU_01", 0x0019, 0x0F, "DS", "1", "Horizontal Frame of Reference"),
+    ("GEMS_ACQU_01", 0x0019, 0x23, "DS", "1", "Table Speed"),
+    ("GEMS_ACQU_01", 0x0019, 0x24, "DS", "1", "Mid Scan Time"),
+    ("GEMS_ACQU_01", 0x0019, 0x27, "DS", "1", "Rotation Speed"),
+    ("GEMS_ACQU_01", 0x0019, 0x9E, "LO", "1", "Internal Pulse Sequence Name"),
+    ("GEMS_ACQU_01", 0x0019, 0xBB, "DS", "1", "Diffusion Gradient Direction X"),
+    ("GEMS_ACQU_01", 0x0019, 0xBC, "DS", "1", "Diffusion Gradient Direction Y"),
+    ("GEMS_ACQU_01", 0x0019, 0xBD, "DS", "1", "Diffusion Gradient Direction Z"),
+    ("GEMS_RELA_01", 0x0021, 0x03, "SS", "1", "Series from which Prescribed"),
+    ("GEMS_RELA_01", 0x0021, 0x05, "SH", "1", "Genesis Version"),
+    ("GEMS_RELA_01", 0x0021, 0x07, "UL", "1", "Series Record Checksum"),
+    ("GEMS_PARM_01", 0x0043, 0x2C, "SS", "1", "Effective Echo Spacing"),
+    ("GEMS_PARM_01", 0x0043, 0x39, "IS", "4", "Slop Int 6-9 (B Value)"),
+    ("GEMS_PARM_01", 0x0043, 0x83, "DS", "1-2", "ASSET R Factors"),
+    // Philips
+    ("Philips Imaging DD 001", 0x2001, 0x01, "FL", "1", "Chemical Shift"),
+    ("Philips Imaging DD 001", 0x2001, 0x03, "FL", "1", "Diffusion B-Factor"),
+    ("Philips Imaging DD 001", 0x2001, 0x04, "CS", "1", "Diffusion Direction"),
+    ("Philips Imaging DD 001", 0x2001, 0x08, "IS", "1", "Phase Number"),
+    ("Philips Imaging DD 001", 0x2001, 0x09, "FL", "1", "Image Prepulse Delay"),
+    ("Philips Imaging DD 001", 0x2001, 0x0A, "IS", "1", "Slice Number MR"),
+    ("Philips Imaging DD 001", 0x2001, 0x0B, "CS", "1", "Slice Orientation"),
+    ("Philips Imaging DD 001", 0x2001, 0x10, "CS", "1", "Cardiac Sync"),
+    ("Philips Imaging DD 001", 0x2001, 0x13, "SL", "1", "EPI Factor"),
+    ("Philips Imaging DD 001", 0x2001, 0x17, "SL", "1", "Number of Phases MR"),
+    ("Philips Imaging DD 001", 0x2001, 0x18, "SL", "1", "Number of Slices MR"),
+    ("Philips Imaging DD 001", 0x2001, 0x63, "CS", "1", "Examination Source"),
+    ("Philips MR Imaging DD 001", 0x2005, 0x0D, "FL", "1", "Scale Intercept"),
+    ("Philips MR Imaging DD 001", 0x2005, 0x0E, "FL", "1", "Scale Slope"),
+    ("Philips MR Imaging DD 001", 0x2005, 0xB0, "FL", "1", "Diffusion Direction RL"),
+    ("Philips MR Imaging DD 001", 0x2005, 0xB1, "FL", "1", "Diffusion Direction AP"),
+    ("Philips MR Imaging DD 001", 0x2005, 0xB2, "FL", "1", "Diffusion Direction FH"),
+];
+
+pub fn builtin_entries() -> Vec<PrivateDictEntry> {
+    BUILTIN
+        .iter()
+        .map(|(creator, group, element, vr, vm, name)| PrivateDictEntry {
+            creator: creator.to_string(),
+            group: *group,
+            element: *element,
+            vr: vr.to_string(),
+            vm: vm.to_string(),
+            name: name.to_string(),
+        })
+        .collect()
+}
+
+fn user_dictionary_path() -> PathBuf {
+    crate::utils::get_app_data_dir().join("private_dictionary.json")
+}
+
+fn user_cache() -> &'static RwLock<Vec<PrivateDictEntry>> {
+    static CACHE: OnceLock<RwLock<Vec<PrivateDictEntry>>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let entries = std::fs::read_to_string(user_dictionary_path())
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        RwLock::new(entries)
+    })
+}
+
+/// Entries added by the user, stored next to the application data
+pub fn user_entries() -> Vec<PrivateDictEntry> {
+    user_cache().read().map(|e| e.clone()).unwrap_or_default()
+}
+
+/// Replace the user's entries. They take precedence over bundled entries for
+/// the same creator, group and element.
+pub fn save_user_entries(entries: Vec<PrivateDictEntry>) -> Result<()> {
+    for entry in &entries {
+        if entry.group % 2 == 0 || entry.creator.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "Private entries need an odd group and a creator: ({:04X},xx{:02X})",
+                entry.group,
+                entry.element
+            ));
+        }
+        entry.vr.parse::<VR>().map_err(|_| anyhow::anyhow!("Unknown VR: {}", entry.vr))?;
+    }
+
+    let path = user_dictionary_path();
+    crate::utils::ensure_dir(&crate::utils::get_app_data_dir())?;
+    let json = serde_json::to_string_pretty(&entries)?;
+    crate::utils::file_helpers::write_atomic(&path, |temp| Ok(std::fs::write(temp, &json)?))?;
+    *index()
+        .write()
+        .map_err(|_| anyhow::anyhow!("Private dictionary lock poisoned"))? = build_index(&entries);
+    *user_cache()
+        .write()
+        .map_err(|_| anyhow::anyhow!("Private dictionary lock poisoned"))? = entries;
+
+    tracing::info!("Saved user private dictionary to {:?}", path);
+    Ok(())
+}
+
+/// Tag of the creator element reserving the block of a private element:
+/// (0029,0010) for (0029,1010). `None` for creator elements themselves.
+pub fn creator_tag(tag: Tag) -> Option<Tag> {
+    let block = tag.element() >> 8;
+    (tag.group() % 2 == 1 && block >= 0x10).then(|| Tag(tag.group(), block))
+}
+
+/// Whether `tag` is a private creator element (gggg,0010-00FF)
+pub fn is_creator_element(tag: Tag) -> bool {
+    tag.group() % 2 == 1 && (0x0010..=0x00FF).contains(&tag.element())
+}
+
+/// Creator string of the block `tag` belongs to, looked up in `dataset`
+pub fn private_creator(dataset: &InMemDicomObject, tag: Tag) -> Option<String> {
+    let creator = dataset.element(creator_tag(tag)?).ok()?.to_str().ok()?;
+    Some(creator.trim_end_matches(['\0', ' ']).to_string())
+}
+
+/// Creator, group and element low byte
+type EntryKey = (String, u16, u8);
+
+/// Bundled and user entries by key. User entries replace bundled ones, and the
+/// first of several user entries for a key wins.
+fn build_index(user: &[PrivateDictEntry]) -> HashMap<EntryKey, PrivateDictEntry> {
+    builtin_entries()
+        .into_iter()
+        .chain(user.iter().rev().cloned())
+        .map(|e| ((e.creator.trim().to_string(), e.group, e.element), e))
+        .collect()
+}
+
+/// Lookup index, built on first use and rebuilt when the user dictionary is saved
+fn index() -> &'static RwLock<HashMap<EntryKey, PrivateDictEntry>> {
+    static INDEX: OnceLock<RwLock<HashMap<EntryKey, PrivateDictEntry>>> = OnceLock::new();
+    INDEX.get_or_init(|| RwLock::new(build_index(&user_entries())))
+}
+
+/// Look up a private element by its creator. User entries win over bundled ones.
+pub fn lookup(creator: &str, tag: Tag) -> Option<PrivateDictEntry> {
+    let key = (creator.trim().to_string(), tag.group(), (tag.element() & 0xFF) as u8);
+    index().read().ok()?.get(&key).cloned()
+}
+
+/// Decode the raw bytes of an implicit-VR private element that was read as UN,
+/// using the VR from the private dictionary. Implicit VR is always little endian.
+pub fn decode_unknown(elem: &DataElement<InMemDicomObject>, vr: VR) -> Option<String> {
+    use dicom_core::value::Value;
+
+    let Value::Primitive(value) = elem.value() else {
+        return None;
+    };
+    let bytes = value.to_bytes();
+
+    let numbers = |size: usize, f: &dyn Fn(&[u8]) -> String| -> Option<String> {
+        (bytes.len() % size == 0)
+            .then(|| bytes.chunks(size).map(f).collect::<Vec<_>>().join("\\"))
+    };
+
+    match vr {
+        VR::US => numbers(2, &|b| u16::from_le_bytes([b[0], b[1]]).to_string()),
+        VR::SS => numbers(2, &|b| i16::from_le_bytes([b[0], b[1]]).to_string()),
+        VR::UL => numbers(4, &|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()),
+        VR::SL => numbers(4, &|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()),
+        VR::FL => numbers(4, &|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()),
+        VR::FD => numbers(8, &|b| {
+            f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]).to_string()
+        }),
+        VR::OB | VR::OW | VR::OF | VR::OD | VR::OL | VR::OV | VR::UN | VR::SQ => None,
+        _ => Some(
+            String::from_utf8_lossy(&bytes)
+                .trim_end_matches(['\0', ' '])
+                .to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_object::mem::InMemElement;
+
+    #[test]
+    fn test_creator_resolution() {
+        let dataset = InMemDicomObject::from_element_iter([
+            InMemElement::new(Tag(0x0029, 0x0010), VR::LO, PrimitiveValue::from("SIEMENS CSA HEADER")),
+            InMemElement::new(Tag(0x0029, 0x0011), VR::LO, PrimitiveValue::from("SIEMENS MEDCOM HEADER2 ")),
+        ]);
+
+        assert_eq!(creator_tag(Tag(0x0029, 0x1010)), Some(Tag(0x0029, 0x0010)));
+        assert_eq!(creator_tag(Tag(0x0029, 0x0010)), None);
+        assert_eq!(private_creator(&dataset, Tag(0x0029, 0x1160)).as_deref(), Some("SIEMENS MEDCOM HEADER2"));
+
+        let csa = lookup("SIEMENS CSA HEADER", Tag(0x0029, 0x1010)).unwrap();
+        assert_eq!(csa.name, "CSA Image Header Info");
+        // Same element under another creator is a different attribute
+        assert!(lookup("SIEMENS MEDCOM HEADER2", Tag(0x0029, 0x1110)).is_none());
+    }
+
+    #[test]
+    fn test_index_prefers_user_entries() {
+        let entry = |name: &str| PrivateDictEntry {
+            creator: "SIEMENS CSA HEADER ".to_string(),
+            group: 0x0029,
+            element: 0x10,
+            vr: "OB".to_string(),
+            vm: "1".to_string(),
+            name: name.to_string(),
+        };
+        let index = build_index(&[entry("First"), entry("Second")]);
+
+        let key = ("SIEMENS CSA HEADER".to_string(), 0x0029, 0x10);
+        assert_eq!(index[&key].name, "First");
+        assert_eq!(build_index(&[])[&key].name, "CSA Image Header Info");
+    }
+
+    #[test]
+    fn test_decode_unknown() {
+        let b_value = InMemElement::new(Tag(0x0019, 0x100C), VR::UN, PrimitiveValue::U8(b"1000".to_vec().into()));
+        assert_eq!(decode_unknown(&b_value, VR::IS).as_deref(), Some("1000"));
+
+        let mut bytes = 1.0f64.to_le_bytes().to_vec();
+        bytes.extend(0.5f64.to_le_bytes());
+        let direction = InMemElement::new(Tag(0x0019, 0x100E), VR::UN, PrimitiveValue::U8(bytes.into()));
+        assert_eq!(decode_unknown(&direction, VR::FD).as_deref(), Some("1\\0.5"));
+    }
+}
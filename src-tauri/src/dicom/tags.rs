@@ -1,19 +1,42 @@
 // DICOM tag extraction and manipulation
 
 use anyhow::Result;
-use dicom_core::{DataElement, Tag, VR};
+use super::dictionary::{self, Multiplicity};
+use super::private;
+use super::path::{PathSegment, TagPath};
+use dicom_core::{DataElement, Length, Tag, VR};
 use dicom_core::header::Header;
 use dicom_object::InMemDicomObject;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Extract all tags from a DICOM object as a structured format
+/// Extract all tags from a DICOM object as a tree, with sequence items nested
+/// under their sequence
 pub fn extract_all_tags(obj: &InMemDicomObject) -> Vec<DicomTag> {
     obj.iter()
-        .map(|elem| DicomTag::from_element(elem))
+        .map(|elem| DicomTag::from_element_at(elem, obj, TagPath::root(elem.tag())))
         .collect()
 }
 
+/// Depth-first flattening of a tag tree
+pub fn flatten_tags(tags: &[DicomTag]) -> Vec<&DicomTag> {
+    let mut flat = Vec::new();
+    for tag in tags {
+        flat.push(tag);
+        for item in &tag.items {
+            flat.extend(flatten_tags(&item.tags));
+        }
+    }
+    flat
+}
+
+/// Tag tree with vendor blobs (Siemens CSA headers, Philips private sequences) decoded
+pub fn extract_tags_with_vendor_data(obj: &InMemDicomObject) -> Vec<DicomTag> {
+    let mut tags = extract_all_tags(obj);
+    super::vendor::annotate(&mut tags, &super::vendor::decode(obj));
+    tags
+}
+
 /// Convert tags to JSON
 pub fn tags_to_json(obj: &InMemDicomObject) -> Result<String> {
     let tags = extract_all_tags(obj);
@@ -26,26 +49,84 @@ pub fn tags_to_xml(obj: &InMemDicomObject) -> Result<String> {
     Ok(String::from("<?xml version=\"1.0\"?>\n<dicom></dicom>"))
 }
 
-/// Update a tag value in a DICOM object
+/// Update a tag value in a DICOM object. The value is parsed and validated
+/// according to the attribute's VR; see `update_tag_at`.
 pub fn update_tag(obj: &mut InMemDicomObject, tag: Tag, value: String) -> Result<()> {
-    use dicom_object::mem::InMemElement;
-    use dicom_core::value::{PrimitiveValue, Value};
+    update_tag_at(obj, &TagPath::root(tag), value, None)
+}
 
-    // Get existing element to determine VR
-    let existing_vr = obj.element(tag)
-        .map(|e| e.vr())
-        .unwrap_or(VR::LO); // Default to Long String if tag doesn't exist
+/// Whether the data set declares a character set beyond the default repertoire
+pub fn has_extended_charset(obj: &InMemDicomObject) -> bool {
+    use dicom_dictionary_std::tags;
 
-    // Create new element with updated value
-    let new_element = InMemElement::new(
-        tag,
-        existing_vr,
-        Value::Primitive(PrimitiveValue::Str(value)),
-    );
+    obj.element(tags::SPECIFIC_CHARACTER_SET)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map_or(false, |charset| {
+            charset
+                .split('\\')
+                .any(|c| !c.trim().is_empty() && c.trim() != "ISO_IR 6")
+        })
+}
+
+/// VR to write `tag` with: the existing element's, else the standard or private dictionary's
+fn resolve_vr(item: &InMemDicomObject, tag: Tag) -> Option<VR> {
+    if let Ok(existing) = item.element(tag) {
+        if existing.vr() != VR::UN {
+            return Some(existing.vr());
+        }
+    }
+    if let Some(entry) = dictionary::lookup(tag) {
+        return entry.vrs.first().copied();
+    }
+    let creator = private::private_creator(item, tag)?;
+    private::lookup(&creator, tag)?.vr.parse().ok()
+}
+
+/// Expected multiplicity of `tag`, when a dictionary knows it
+fn expected_multiplicity(item: &InMemDicomObject, tag: Tag) -> Option<Multiplicity> {
+    if let Some(entry) = dictionary::lookup(tag) {
+        return entry.vm;
+    }
+    let creator = private::private_creator(item, tag)?;
+    Multiplicity::parse(&private::lookup(&creator, tag)?.vm)
+}
+
+/// Parse `value` for the attribute's VR and store it, rejecting values that don't fit the VR
+fn put_typed_value(
+    item: &mut InMemDicomObject,
+    tag: Tag,
+    value: &str,
+    vr: Option<VR>,
+    extended_charset: bool,
+) -> Result<()> {
+    use dicom_object::mem::InMemElement;
+
+    let vr = vr.or_else(|| resolve_vr(item, tag)).ok_or_else(|| {
+        anyhow::anyhow!(
+            "({:04X},{:04X}) is not in the data dictionary; specify its VR",
+            tag.group(),
+            tag.element()
+        )
+    })?;
+    let parsed = super::vr::parse_value(vr, value, extended_charset)?;
 
-    // Insert or replace the element
-    obj.put_element(new_element);
+    // Real data doesn't always follow the dictionary (e.g. Image Type with four
+    // values), so a mismatch is stored and flagged rather than rejected
+    if let Some(vm) = expected_multiplicity(item, tag) {
+        let count = parsed.multiplicity();
+        if !vm.accepts(count) {
+            tracing::warn!(
+                "({:04X},{:04X}) takes {} value(s), storing {}",
+                tag.group(),
+                tag.element(),
+                vm,
+                count
+            );
+        }
+    }
 
+    item.put_element(InMemElement::new(tag, vr, parsed));
     Ok(())
 }
 
@@ -55,18 +136,197 @@ pub fn delete_tag(obj: &mut InMemDicomObject, tag: Tag) -> Result<()> {
     Ok(())
 }
 
-/// Search tags by keyword
+/// Get the attribute at `path`, with its sequence items when it is a sequence
+pub fn get_tag_at(obj: &InMemDicomObject, path: &TagPath) -> Result<DicomTag> {
+    if path.is_item() {
+        return Err(anyhow::anyhow!("{} addresses a sequence item, not an attribute", path));
+    }
+
+    let (parents, _) = path.segments.split_at(path.segments.len() - 1);
+    let item = item_at(obj, parents)?;
+    let elem = item
+        .element(path.tag())
+        .map_err(|_| anyhow::anyhow!("Tag not found: {}", path))?;
+
+    Ok(DicomTag::from_element_at(elem, item, path.clone()))
+}
+
+/// Update the attribute at `path`. Sequences along the path must exist; the
+/// attribute itself is created if missing. The value is parsed according to
+/// `vr`, or the VR of the existing element or dictionary entry, and rejected
+/// when it doesn't fit that VR. A value multiplicity outside the dictionary's
+/// is logged and stored; `DicomTag::vm_mismatch` flags it.
+pub fn update_tag_at(
+    obj: &mut InMemDicomObject,
+    path: &TagPath,
+    value: String,
+    vr: Option<VR>,
+) -> Result<()> {
+    if path.is_item() {
+        return Err(anyhow::anyhow!("{} addresses a sequence item, not an attribute", path));
+    }
+
+    // The character set is declared once, at the top level
+    let extended_charset = has_extended_charset(obj);
+    let (parents, _) = path.segments.split_at(path.segments.len() - 1);
+    with_item_mut(obj, parents, |item| {
+        put_typed_value(item, path.tag(), &value, vr, extended_charset)
+    })
+}
+
+/// Delete the attribute at `path`, or the sequence item when the path ends with an index
+pub fn delete_tag_at(obj: &mut InMemDicomObject, path: &TagPath) -> Result<()> {
+    let (parents, last) = path.segments.split_at(path.segments.len() - 1);
+    let last = last[0];
+
+    with_item_mut(obj, parents, |item| match last.item {
+        None => delete_tag(item, last.tag),
+        Some(index) => {
+            let mut items = sequence_items(item, last.tag)?;
+            if index >= items.len() {
+                return Err(anyhow::anyhow!("Item {} not found in {}", index, path));
+            }
+            items.remove(index);
+            put_sequence(item, last.tag, items);
+            Ok(())
+        }
+    })
+}
+
+/// Element at an attribute path
+pub fn element_at<'a>(obj: &'a InMemDicomObject, path: &TagPath) -> Option<&'a dicom_object::mem::InMemElement> {
+    if path.is_item() {
+        return None;
+    }
+    let (parents, _) = path.segments.split_at(path.segments.len() - 1);
+    item_at(obj, parents).ok()?.element(path.tag()).ok()
+}
+
+/// Sequence item at a path ending with an item index
+pub fn sequence_item_at<'a>(obj: &'a InMemDicomObject, path: &TagPath) -> Option<&'a InMemDicomObject> {
+    if !path.is_item() {
+        return None;
+    }
+    item_at(obj, &path.segments).ok()
+}
+
+/// Put an element as-is at an attribute path, without parsing or validation
+pub fn put_element_at(obj: &mut InMemDicomObject, path: &TagPath, elem: dicom_object::mem::InMemElement) -> Result<()> {
+    let (parents, _) = path.segments.split_at(path.segments.len() - 1);
+    with_item_mut(obj, parents, |item| {
+        item.put_element(elem);
+        Ok(())
+    })
+}
+
+/// Insert a sequence item at the index the path ends with, creating the sequence if needed
+pub fn insert_item_at(obj: &mut InMemDicomObject, path: &TagPath, new_item: InMemDicomObject) -> Result<()> {
+    let (parents, last) = path.segments.split_at(path.segments.len() - 1);
+    let last = last[0];
+    let index = last
+        .item
+        .ok_or_else(|| anyhow::anyhow!("{} does not address a sequence item", path))?;
+
+    with_item_mut(obj, parents, |item| {
+        let mut items = item
+            .element(last.tag)
+            .ok()
+            .and_then(|elem| elem.items())
+            .map(|items| items.to_vec())
+            .unwrap_or_default();
+        if index > items.len() {
+            return Err(anyhow::anyhow!("Cannot insert item {} into {} items", index, items.len()));
+        }
+        items.insert(index, new_item);
+        put_sequence(item, last.tag, items);
+        Ok(())
+    })
+}
+
+/// Dataset reached by following `segments`, each of which selects an item
+fn item_at<'a>(obj: &'a InMemDicomObject, segments: &[PathSegment]) -> Result<&'a InMemDicomObject> {
+    let mut current = obj;
+    for segment in segments {
+        let index = segment.item.unwrap_or(0);
+        current = current
+            .element(segment.tag)
+            .ok()
+            .and_then(|elem| elem.items())
+            .and_then(|items| items.get(index))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Item {} of sequence ({:04X},{:04X}) not found",
+                    index,
+                    segment.tag.group(),
+                    segment.tag.element()
+                )
+            })?;
+    }
+    Ok(current)
+}
+
+/// Run `f` on the dataset reached by following `segments`, writing the changed
+/// sequence items back on the way out
+fn with_item_mut<R>(
+    obj: &mut InMemDicomObject,
+    segments: &[PathSegment],
+    f: impl FnOnce(&mut InMemDicomObject) -> Result<R>,
+) -> Result<R> {
+    let Some((first, rest)) = segments.split_first() else {
+        return f(obj);
+    };
+
+    let index = first.item.unwrap_or(0);
+    let mut items = sequence_items(obj, first.tag)?;
+    let item = items.get_mut(index).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Item {} of sequence ({:04X},{:04X}) not found",
+            index,
+            first.tag.group(),
+            first.tag.element()
+        )
+    })?;
+
+    let result = with_item_mut(item, rest, f)?;
+    put_sequence(obj, first.tag, items);
+    Ok(result)
+}
+
+fn sequence_items(obj: &InMemDicomObject, tag: Tag) -> Result<Vec<InMemDicomObject>> {
+    obj.element(tag)
+        .ok()
+        .and_then(|elem| elem.items())
+        .map(|items| items.to_vec())
+        .ok_or_else(|| {
+            anyhow::anyhow!("({:04X},{:04X}) is not a sequence", tag.group(), tag.element())
+        })
+}
+
+fn put_sequence(obj: &mut InMemDicomObject, tag: Tag, items: Vec<InMemDicomObject>) {
+    use dicom_core::value::{DataSetSequence, Value};
+    use dicom_object::mem::InMemElement;
+
+    obj.put_element(InMemElement::new(
+        tag,
+        VR::SQ,
+        Value::Sequence(DataSetSequence::new(items, Length::UNDEFINED)),
+    ));
+}
+
+/// Search tags by keyword, including attributes nested in sequences
 pub fn search_tags(obj: &InMemDicomObject, query: &str) -> Vec<DicomTag> {
     let all_tags = extract_all_tags(obj);
     let query_lower = query.to_lowercase();
 
-    all_tags
+    flatten_tags(&all_tags)
         .into_iter()
         .filter(|tag| {
             tag.name.to_lowercase().contains(&query_lower)
+                || tag.keyword.to_lowercase().contains(&query_lower)
                 || tag.value.to_lowercase().contains(&query_lower)
                 || format!("{:?}", tag.tag).to_lowercase().contains(&query_lower)
         })
+        .map(|tag| DicomTag { items: Vec::new(), ..tag.clone() })
         .collect()
 }
 
@@ -74,32 +334,299 @@ pub fn search_tags(obj: &InMemDicomObject, query: &str) -> Vec<DicomTag> {
 pub struct DicomTag {
     pub tag: String,        // e.g., "(0010,0010)"
     pub name: String,       // e.g., "Patient Name"
+    #[serde(default)]
+    pub keyword: String,    // e.g., "PatientName"; empty for private/unknown tags
     pub vr: String,         // e.g., "PN"
-    pub vm: String,         // e.g., "1"
+    pub vm: String,         // e.g., "1", counted from the value
     pub value: String,      // String representation of value
     pub is_private: bool,
+    #[serde(default)]
+    pub retired: bool,
+    /// Dictionary VR, e.g. "PN" or "US or SS"
+    #[serde(default)]
+    pub expected_vr: Option<String>,
+    /// Dictionary VM, e.g. "1-n"
+    #[serde(default)]
+    pub expected_vm: Option<String>,
+    #[serde(default)]
+    pub vr_mismatch: bool,
+    #[serde(default)]
+    pub vm_mismatch: bool,
+    /// Creator string of the private block, e.g. "SIEMENS CSA HEADER"
+    #[serde(default)]
+    pub private_creator: Option<String>,
+    /// Path addressing this attribute, e.g. "(0040,0275)[0].(0032,1060)"
+    #[serde(default)]
+    pub path: String,
+    /// Items of a sequence (SQ) attribute
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<SequenceItem>,
+    /// Decoded vendor blob or private sequence, see `vendor::annotate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<super::vendor::VendorHeader>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceItem {
+    pub index: usize,
+    pub tags: Vec<DicomTag>,
 }
 
 impl DicomTag {
+    /// Build a tag without its surrounding dataset; private creators are not resolved
     pub fn from_element(elem: &DataElement<InMemDicomObject>) -> Self {
+        Self::from_element_at(elem, &InMemDicomObject::new_empty(), TagPath::root(elem.tag()))
+    }
+
+    /// Build the tag at `path` in `dataset`, recursing into sequence items
+    pub fn from_element_at(
+        elem: &DataElement<InMemDicomObject>,
+        dataset: &InMemDicomObject,
+        path: TagPath,
+    ) -> Self {
         let tag = elem.tag();
         let vr = elem.vr();
         let value = elem.to_str().map(|v| v.to_string()).unwrap_or_else(|_| String::new());
+        let vm = value_multiplicity(elem);
+        let is_private = tag.group() % 2 == 1; // Private tags have odd group numbers
 
-        DicomTag {
+        let mut dicom_tag = DicomTag {
             tag: format!("({:04X},{:04X})", tag.group(), tag.element()),
-            name: tag.to_string(), // TODO: Get proper tag name from dictionary
+            name: String::new(),
+            keyword: String::new(),
             vr: format!("{:?}", vr),
-            vm: "1".to_string(), // TODO: Calculate actual VM
+            vm: vm.to_string(),
             value,
-            is_private: tag.group() % 2 == 1, // Private tags have odd group numbers
+            is_private,
+            retired: false,
+            expected_vr: None,
+            expected_vm: None,
+            vr_mismatch: false,
+            vm_mismatch: false,
+            private_creator: None,
+            path: path.to_string(),
+            items: Vec::new(),
+            decoded: None,
+        };
+
+        if let Some(items) = elem.items() {
+            dicom_tag.items = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| SequenceItem {
+                    index,
+                    tags: item
+                        .iter()
+                        .map(|child| Self::from_element_at(child, item, path.child(index, child.tag())))
+                        .collect(),
+                })
+                .collect();
+        }
+
+        match dictionary::lookup(tag) {
+            Some(entry) => {
+                dicom_tag.vr_mismatch = !entry.vrs.contains(&vr);
+                dicom_tag.vm_mismatch = entry.vm.is_some_and(|m| !m.accepts(vm));
+                dicom_tag.expected_vr = Some(entry.vr_label());
+                dicom_tag.expected_vm = entry.vm.map(|m| m.to_string());
+                dicom_tag.retired = entry.retired;
+                dicom_tag.name = entry.name;
+                dicom_tag.keyword = entry.keyword;
+            }
+            None if private::is_creator_element(tag) => {
+                dicom_tag.name = "Private Creator".to_string();
+            }
+            None if is_private => {
+                let creator = private::private_creator(dataset, tag);
+                match creator.as_deref().and_then(|c| private::lookup(c, tag)) {
+                    Some(entry) => dicom_tag.apply_private_entry(elem, entry),
+                    None => dicom_tag.name = "Private Tag".to_string(),
+                }
+                dicom_tag.private_creator = creator;
+            }
+            None => dicom_tag.name = "Unknown Tag".to_string(),
+        }
+
+        dicom_tag
+    }
+
+    /// Fill in a private element from the private dictionary. Elements read as UN
+    /// from implicit VR files are decoded with the dictionary VR.
+    fn apply_private_entry(&mut self, elem: &DataElement<InMemDicomObject>, entry: private::PrivateDictEntry) {
+        let mut vm = value_multiplicity(elem);
+
+        if let Ok(expected) = entry.vr.parse::<VR>() {
+            if elem.vr() == VR::UN {
+                if let Some(decoded) = private::decode_unknown(elem, expected) {
+                    vm = if decoded.is_empty() { 0 } else { decoded.split('\\').count() as u32 };
+                    self.value = decoded;
+                    self.vm = vm.to_string();
+                }
+                self.vr = format!("{:?}", expected);
+            } else {
+                self.vr_mismatch = elem.vr() != expected;
+            }
         }
+
+        self.vm_mismatch = Multiplicity::parse(&entry.vm).map_or(false, |m| !m.accepts(vm));
+        self.expected_vr = Some(entry.vr);
+        self.expected_vm = Some(entry.vm);
+        self.name = entry.name;
+    }
+}
+
+/// Number of values in an element. Strings count backslash-separated values;
+/// binary, text and sequence values count as one.
+fn value_multiplicity(elem: &DataElement<InMemDicomObject>) -> u32 {
+    use dicom_core::value::{PrimitiveValue, Value};
+
+    match elem.value() {
+        Value::Primitive(PrimitiveValue::Empty) => 0,
+        Value::Primitive(_) if matches!(
+            elem.vr(),
+            VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN | VR::LT | VR::ST | VR::UT | VR::UR
+        ) => 1,
+        Value::Primitive(PrimitiveValue::Str(s)) if s.trim().is_empty() => 0,
+        Value::Primitive(PrimitiveValue::Str(s)) => s.split('\\').count() as u32,
+        Value::Primitive(PrimitiveValue::Strs(values)) => values
+            .iter()
+            .map(|s| s.split('\\').count() as u32)
+            .sum(),
+        Value::Primitive(p) => p.multiplicity(),
+        _ => 1,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_object::mem::InMemElement;
+
+    #[test]
+    fn test_from_element_uses_dictionary() {
+        let elem = InMemElement::new(
+            Tag(0x0028, 0x0030),
+            VR::DS,
+            PrimitiveValue::Strs(["0.5".to_string(), "0.5".to_string()].into()),
+        );
+        let tag = DicomTag::from_element(&elem);
+
+        assert_eq!(tag.name, "Pixel Spacing");
+        assert_eq!(tag.keyword, "PixelSpacing");
+        assert_eq!(tag.vm, "2");
+        assert_eq!(tag.expected_vm.as_deref(), Some("2"));
+        assert!(!tag.vr_mismatch && !tag.vm_mismatch);
+    }
+
+    #[test]
+    fn test_from_element_flags_mismatches() {
+        let elem = InMemElement::new(
+            Tag(0x0020, 0x0037),
+            VR::LO,
+            PrimitiveValue::from("1\\0\\0"),
+        );
+        let tag = DicomTag::from_element(&elem);
+
+        assert_eq!(tag.vm, "3");
+        assert!(tag.vr_mismatch);
+        assert!(tag.vm_mismatch);
+
+        let private = DicomTag::from_element(&InMemElement::new(
+            Tag(0x0029, 0x0010),
+            VR::LO,
+            PrimitiveValue::from("SIEMENS CSA HEADER"),
+        ));
+        assert_eq!(private.name, "Private Creator");
+        assert!(private.expected_vr.is_none());
+    }
+
+    #[test]
+    fn test_private_tags_resolved_by_creator() {
+        let obj = InMemDicomObject::from_element_iter([
+            InMemElement::new(Tag(0x0019, 0x0010), VR::LO, PrimitiveValue::from("SIEMENS MR HEADER")),
+            InMemElement::new(Tag(0x0019, 0x100C), VR::UN, PrimitiveValue::U8(b"1000".to_vec().into())),
+            InMemElement::new(Tag(0x0019, 0x10F0), VR::LO, PrimitiveValue::from("x")),
+        ]);
+        let tags = extract_all_tags(&obj);
+
+        assert_eq!(tags[1].name, "B Value");
+        assert_eq!(tags[1].private_creator.as_deref(), Some("SIEMENS MR HEADER"));
+        assert_eq!(tags[1].vr, "IS");
+        assert_eq!(tags[1].value, "1000");
+        assert_eq!(tags[2].name, "Private Tag");
+    }
+
+    fn object_with_sequence() -> InMemDicomObject {
+        use dicom_core::value::{DataSetSequence, Value};
+
+        let item = |code: &str| {
+            InMemDicomObject::from_element_iter([InMemElement::new(
+                Tag(0x0032, 0x1060),
+                VR::LO,
+                PrimitiveValue::from(code),
+            )])
+        };
+        InMemDicomObject::from_element_iter([InMemElement::new(
+            Tag(0x0040, 0x0275),
+            VR::SQ,
+            Value::Sequence(DataSetSequence::new(vec![item("CT HEAD"), item("CT NECK")], Length::UNDEFINED)),
+        )])
+    }
 
-    // Add tests
+    #[test]
+    fn test_tag_tree_has_nested_items() {
+        let tags = extract_all_tags(&object_with_sequence());
+
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].items.len(), 2);
+        let nested = &tags[0].items[1].tags[0];
+        assert_eq!(nested.path, "(0040,0275)[1].(0032,1060)");
+        assert_eq!(nested.value, "CT NECK");
+        assert_eq!(flatten_tags(&tags).len(), 3);
+    }
+
+    #[test]
+    fn test_update_and_delete_at_path() {
+        let mut obj = object_with_sequence();
+        let path = TagPath::parse("(0040,0275)[1].(0032,1060)").unwrap();
+
+        update_tag_at(&mut obj, &path, "MR KNEE".to_string(), None).unwrap();
+        assert_eq!(get_tag_at(&obj, &path).unwrap().value, "MR KNEE");
+
+        delete_tag_at(&mut obj, &TagPath::parse("(0040,0275)[0]").unwrap()).unwrap();
+        let remaining = TagPath::parse("(0040,0275)[0].(0032,1060)").unwrap();
+        assert_eq!(get_tag_at(&obj, &remaining).unwrap().value, "MR KNEE");
+
+        delete_tag_at(&mut obj, &remaining).unwrap();
+        assert!(get_tag_at(&obj, &remaining).is_err());
+        let missing_item = TagPath::parse("(0040,0275)[3].(0032,1060)").unwrap();
+        assert!(update_tag_at(&mut obj, &missing_item, "X".to_string(), None).is_err());
+    }
+
+    #[test]
+    fn test_update_tag_parses_by_vr() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        update_tag(&mut obj, Tag(0x0028, 0x0010), "512".to_string()).unwrap();
+        let rows = obj.element(Tag(0x0028, 0x0010)).unwrap();
+        assert_eq!(rows.vr(), VR::US);
+        assert_eq!(rows.to_int::<u16>().unwrap(), 512);
+
+        update_tag(&mut obj, Tag(0x0028, 0x0030), "0.5\\0.5".to_string()).unwrap();
+        assert_eq!(obj.element(Tag(0x0028, 0x0030)).unwrap().value().multiplicity(), 2);
+
+        // Unexpected multiplicity is kept and flagged
+        update_tag(&mut obj, Tag(0x0028, 0x0030), "0.5".to_string()).unwrap();
+        let spacing = obj.element(Tag(0x0028, 0x0030)).unwrap();
+        assert_eq!(spacing.value().multiplicity(), 1);
+        assert!(DicomTag::from_element(spacing).vm_mismatch);
+
+        // Bad date, unknown tag without a VR
+        assert!(update_tag(&mut obj, Tag(0x0008, 0x0020), "2024-01-01".to_string()).is_err());
+        assert!(update_tag(&mut obj, Tag(0x0009, 0x1001), "x".to_string()).is_err());
+
+        let path = TagPath::root(Tag(0x0009, 0x1001));
+        update_tag_at(&mut obj, &path, "x".to_string(), Some(VR::LO)).unwrap();
+    }
 }
@@ -0,0 +1,986 @@
+// IOD conformance validation in the spirit of dciodvfy (PS3.3 module tables)
+//
+// Module requirements are checked for the top-level dataset of the IODs listed
+// in `IODS`; other SOP classes get the modules common to all composite IODs.
+// VR, VM and value format are checked for every attribute, including those in
+// sequence items.
+
+use super::dictionary;
+use super::path::TagPath;
+use super::tags;
+use dicom_core::value::Value;
+use dicom_core::{Tag, VR};
+use dicom_dictionary_std::{tags as std_tags, uids};
+use dicom_object::mem::InMemElement;
+use dicom_object::meta::FileMetaTable;
+use dicom_object::InMemDicomObject;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Attribute path, e.g. "(0010,0010)"; `None` for findings about the whole file
+    pub path: Option<String>,
+    /// Module whose requirement was violated
+    pub module: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileValidation {
+    pub path: String,
+    pub sop_class_uid: Option<String>,
+    /// IOD the file was checked against; `None` when only common modules were checked
+    pub iod: Option<String>,
+    /// No IOD is defined for the SOP class, so only the modules common to all
+    /// composite IODs were checked: a file without errors isn't known to conform
+    pub common_modules_only: bool,
+    pub findings: Vec<Finding>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// One finding and the number of files it occurred in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindingSummary {
+    pub severity: Severity,
+    pub path: Option<String>,
+    pub message: String,
+    pub files: usize,
+}
+
+/// Findings of many files, e.g. all instances of a study
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub file_count: usize,
+    pub files_with_errors: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Files of which only the common modules were checked
+    pub common_modules_only: usize,
+    /// Distinct findings, most frequent first
+    pub summary: Vec<FindingSummary>,
+    pub files: Vec<FileValidation>,
+}
+
+/// When a conditional (1C/2C) attribute is required
+#[derive(Debug, Clone, Copy)]
+enum Condition {
+    Absent(Tag),
+    /// The first value of the attribute equals the given one
+    Equals(Tag, &'static str),
+    NotEquals(Tag, &'static str),
+    GreaterThan(Tag, i64),
+    SopClassIn(&'static [&'static str]),
+    /// Some text in the dataset needs a character set beyond ASCII
+    NonAsciiText,
+}
+
+impl Condition {
+    fn holds(&self, obj: &InMemDicomObject) -> bool {
+        match *self {
+            Condition::Absent(tag) => obj.element(tag).is_err(),
+            Condition::Equals(tag, value) => first_value(obj, tag).as_deref() == Some(value),
+            Condition::NotEquals(tag, value) => first_value(obj, tag).as_deref() != Some(value),
+            Condition::GreaterThan(tag, limit) => first_value(obj, tag)
+                .and_then(|v| v.parse::<i64>().ok())
+                .map_or(false, |v| v > limit),
+            Condition::SopClassIn(classes) => first_value(obj, std_tags::SOP_CLASS_UID)
+                .map_or(false, |uid| classes.contains(&uid.as_str())),
+            Condition::NonAsciiText => obj.iter().any(|elem| {
+                is_text(elem.vr()) && elem.to_str().map_or(false, |s| !s.is_ascii())
+            }),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let name = attribute_name;
+        match *self {
+            Condition::Absent(tag) => format!("{} is absent", name(tag)),
+            Condition::Equals(tag, value) => format!("{} is {}", name(tag), value),
+            Condition::NotEquals(tag, value) => format!("{} is not {}", name(tag), value),
+            Condition::GreaterThan(tag, limit) => format!("{} is greater than {}", name(tag), limit),
+            Condition::SopClassIn(_) => "required for this SOP class".to_string(),
+            Condition::NonAsciiText => "text uses characters beyond ASCII".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Requirement {
+    Type1,
+    Type1C(Condition),
+    Type2,
+    Type2C(Condition),
+    Type3,
+}
+
+/// Allowed values of an attribute. Values outside enumerated values are errors,
+/// outside defined terms warnings.
+#[derive(Debug, Clone, Copy)]
+enum Terms {
+    Enumerated(&'static [&'static str]),
+    Defined(&'static [&'static str]),
+    /// Enumerated values per value position, e.g. Image Type values 1 and 2
+    EnumeratedPerValue(&'static [&'static [&'static str]]),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Attribute {
+    tag: Tag,
+    requirement: Requirement,
+    terms: Option<Terms>,
+}
+
+const fn attr(tag: Tag, requirement: Requirement) -> Attribute {
+    Attribute { tag, requirement, terms: None }
+}
+
+const fn attr_terms(tag: Tag, requirement: Requirement, terms: Terms) -> Attribute {
+    Attribute { tag, requirement, terms: Some(terms) }
+}
+
+struct Module {
+    name: &'static str,
+    attributes: &'static [Attribute],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Usage {
+    Mandatory,
+    /// Checked only when one of its attributes is present
+    UserOptional,
+}
+
+struct Iod {
+    name: &'static str,
+    sop_classes: &'static [&'static str],
+    modules: &'static [(&'static Module, Usage)],
+}
+
+use Requirement::*;
+
+const SEXES: &[&str] = &["M", "F", "O"];
+const MODALITIES: &[&str] = &[
+    "AR", "ASMT", "AU", "BDUS", "BI", "BMD", "CR", "CT", "CTPROTOCOL", "DG", "DOC", "DX", "ECG",
+    "EPS", "ES", "FID", "GM", "HC", "HD", "IO", "IOL", "IVOCT", "IVUS", "KER", "KO", "LEN", "LS",
+    "MG", "MR", "M3D", "NM", "OAM", "OCT", "OP", "OPM", "OPT", "OPTBSV", "OPTENF", "OPV", "OSS",
+    "OT", "PLAN", "PR", "PT", "PX", "REG", "RESP", "RF", "RG", "RTDOSE", "RTIMAGE", "RTPLAN",
+    "RTRECORD", "RTSTRUCT", "RWV", "SEG", "SM", "SMR", "SR", "SRF", "STAIN", "TEXTUREMAP", "TG",
+    "US", "VA", "XA", "XC",
+];
+const PATIENT_POSITIONS: &[&str] = &[
+    "HFP", "HFS", "HFDR", "HFDL", "FFDR", "FFDL", "FFP", "FFS", "LFP", "LFS", "RFP", "RFS",
+    "AFDR", "AFDL", "PFDR", "PFDL",
+];
+const PHOTOMETRIC_INTERPRETATIONS: &[&str] = &[
+    "MONOCHROME1", "MONOCHROME2", "PALETTE COLOR", "RGB", "YBR_FULL", "YBR_FULL_422",
+    "YBR_PARTIAL_420", "YBR_ICT", "YBR_RCT",
+];
+const MONOCHROME: &[&str] = &["MONOCHROME1", "MONOCHROME2"];
+const ZERO_OR_ONE: &[&str] = &["0", "1"];
+const IMAGE_TYPE: &[&[&str]] = &[&["ORIGINAL", "DERIVED"], &["PRIMARY", "SECONDARY"]];
+
+static PATIENT: Module = Module {
+    name: "Patient",
+    attributes: &[
+        attr(std_tags::PATIENT_NAME, Type2),
+        attr(std_tags::PATIENT_ID, Type2),
+        attr(std_tags::PATIENT_BIRTH_DATE, Type2),
+        attr_terms(std_tags::PATIENT_SEX, Type2, Terms::Enumerated(SEXES)),
+    ],
+};
+
+static GENERAL_STUDY: Module = Module {
+    name: "General Study",
+    attributes: &[
+        attr(std_tags::STUDY_INSTANCE_UID, Type1),
+        attr(std_tags::STUDY_DATE, Type2),
+        attr(std_tags::STUDY_TIME, Type2),
+        attr(std_tags::REFERRING_PHYSICIAN_NAME, Type2),
+        attr(std_tags::STUDY_ID, Type2),
+        attr(std_tags::ACCESSION_NUMBER, Type2),
+        attr(std_tags::STUDY_DESCRIPTION, Type3),
+    ],
+};
+
+static GENERAL_SERIES: Module = Module {
+    name: "General Series",
+    attributes: &[
+        attr_terms(std_tags::MODALITY, Type1, Terms::Defined(MODALITIES)),
+        attr(std_tags::SERIES_INSTANCE_UID, Type1),
+        attr(std_tags::SERIES_NUMBER, Type2),
+        attr_terms(
+            std_tags::PATIENT_POSITION,
+            Type2C(Condition::SopClassIn(&[uids::CT_IMAGE_STORAGE, uids::MR_IMAGE_STORAGE])),
+            Terms::Defined(PATIENT_POSITIONS),
+        ),
+        attr(std_tags::SERIES_DESCRIPTION, Type3),
+    ],
+};
+
+static FRAME_OF_REFERENCE: Module = Module {
+    name: "Frame of Reference",
+    attributes: &[
+        attr(std_tags::FRAME_OF_REFERENCE_UID, Type1),
+        attr(std_tags::POSITION_REFERENCE_INDICATOR, Type2),
+    ],
+};
+
+static GENERAL_EQUIPMENT: Module = Module {
+    name: "General Equipment",
+    attributes: &[
+        attr(std_tags::MANUFACTURER, Type2),
+        attr(std_tags::INSTITUTION_NAME, Type3),
+        attr(std_tags::MANUFACTURER_MODEL_NAME, Type3),
+    ],
+};
+
+static SC_EQUIPMENT: Module = Module {
+    name: "SC Equipment",
+    attributes: &[attr_terms(
+        std_tags::CONVERSION_TYPE,
+        Type1,
+        Terms::Defined(&["DV", "DI", "DF", "WSD", "SD", "SI", "DRW", "SYN"]),
+    )],
+};
+
+static GENERAL_IMAGE: Module = Module {
+    name: "General Image",
+    attributes: &[
+        attr(std_tags::INSTANCE_NUMBER, Type2),
+        attr(std_tags::PATIENT_ORIENTATION, Type2C(Condition::Absent(std_tags::IMAGE_ORIENTATION_PATIENT))),
+        attr(std_tags::CONTENT_DATE, Type3),
+        attr(std_tags::CONTENT_TIME, Type3),
+        attr_terms(std_tags::IMAGE_TYPE, Type3, Terms::EnumeratedPerValue(IMAGE_TYPE)),
+    ],
+};
+
+static IMAGE_PLANE: Module = Module {
+    name: "Image Plane",
+    attributes: &[
+        attr(std_tags::PIXEL_SPACING, Type1),
+        attr(std_tags::IMAGE_ORIENTATION_PATIENT, Type1),
+        attr(std_tags::IMAGE_POSITION_PATIENT, Type1),
+        attr(std_tags::SLICE_THICKNESS, Type2),
+    ],
+};
+
+const PALETTE: Condition = Condition::Equals(std_tags::PHOTOMETRIC_INTERPRETATION, "PALETTE COLOR");
+
+static IMAGE_PIXEL: Module = Module {
+    name: "Image Pixel",
+    attributes: &[
+        attr(std_tags::SAMPLES_PER_PIXEL, Type1),
+        attr_terms(std_tags::PHOTOMETRIC_INTERPRETATION, Type1, Terms::Defined(PHOTOMETRIC_INTERPRETATIONS)),
+        attr(std_tags::ROWS, Type1),
+        attr(std_tags::COLUMNS, Type1),
+        attr(std_tags::BITS_ALLOCATED, Type1),
+        attr(std_tags::BITS_STORED, Type1),
+        attr(std_tags::HIGH_BIT, Type1),
+        attr_terms(std_tags::PIXEL_REPRESENTATION, Type1, Terms::Enumerated(ZERO_OR_ONE)),
+        attr_terms(
+            std_tags::PLANAR_CONFIGURATION,
+            Type1C(Condition::GreaterThan(std_tags::SAMPLES_PER_PIXEL, 1)),
+            Terms::Enumerated(ZERO_OR_ONE),
+        ),
+        attr(std_tags::PIXEL_DATA, Type1C(Condition::Absent(std_tags::PIXEL_DATA_PROVIDER_URL))),
+        attr(std_tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR, Type1C(PALETTE)),
+        attr(std_tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR, Type1C(PALETTE)),
+        attr(std_tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR, Type1C(PALETTE)),
+        attr(std_tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA, Type1C(PALETTE)),
+        attr(std_tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA, Type1C(PALETTE)),
+        attr(std_tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA, Type1C(PALETTE)),
+    ],
+};
+
+static CT_IMAGE: Module = Module {
+    name: "CT Image",
+    attributes: &[
+        attr_terms(std_tags::IMAGE_TYPE, Type1, Terms::EnumeratedPerValue(IMAGE_TYPE)),
+        attr_terms(std_tags::SAMPLES_PER_PIXEL, Type1, Terms::Enumerated(&["1"])),
+        attr_terms(std_tags::PHOTOMETRIC_INTERPRETATION, Type1, Terms::Enumerated(MONOCHROME)),
+        attr_terms(std_tags::BITS_ALLOCATED, Type1, Terms::Enumerated(&["16"])),
+        attr_terms(std_tags::BITS_STORED, Type1, Terms::Enumerated(&["12", "13", "14", "15", "16"])),
+        attr(std_tags::RESCALE_INTERCEPT, Type1),
+        attr(std_tags::RESCALE_SLOPE, Type1),
+        attr(std_tags::KVP, Type2),
+        attr(std_tags::ACQUISITION_NUMBER, Type2),
+    ],
+};
+
+static MR_IMAGE: Module = Module {
+    name: "MR Image",
+    attributes: &[
+        attr_terms(std_tags::IMAGE_TYPE, Type1, Terms::EnumeratedPerValue(IMAGE_TYPE)),
+        attr_terms(std_tags::SAMPLES_PER_PIXEL, Type1, Terms::Enumerated(&["1"])),
+        attr_terms(std_tags::PHOTOMETRIC_INTERPRETATION, Type1, Terms::Enumerated(MONOCHROME)),
+        attr_terms(std_tags::BITS_ALLOCATED, Type1, Terms::Enumerated(&["16"])),
+        attr_terms(std_tags::SCANNING_SEQUENCE, Type1, Terms::Defined(&["SE", "IR", "GR", "EP", "RM"])),
+        attr_terms(
+            std_tags::SEQUENCE_VARIANT,
+            Type1,
+            Terms::Defined(&["SK", "MTC", "SS", "TRSS", "SP", "MP", "OSP", "NONE"]),
+        ),
+        attr(std_tags::SCAN_OPTIONS, Type2),
+        attr_terms(std_tags::MR_ACQUISITION_TYPE, Type2, Terms::Enumerated(&["2D", "3D"])),
+        attr(std_tags::REPETITION_TIME, Type2C(Condition::NotEquals(std_tags::SCANNING_SEQUENCE, "EP"))),
+        attr(std_tags::ECHO_TIME, Type2),
+        attr(std_tags::ECHO_TRAIN_LENGTH, Type2),
+        attr(std_tags::MAGNETIC_FIELD_STRENGTH, Type3),
+    ],
+};
+
+static CR_SERIES: Module = Module {
+    name: "CR Series",
+    attributes: &[
+        attr(std_tags::BODY_PART_EXAMINED, Type2),
+        attr_terms(
+            std_tags::VIEW_POSITION,
+            Type2,
+            Terms::Defined(&["AP", "PA", "LL", "RL", "RLD", "LLD", "RLO", "LLO"]),
+        ),
+    ],
+};
+
+static CR_IMAGE: Module = Module {
+    name: "CR Image",
+    attributes: &[attr_terms(std_tags::PHOTOMETRIC_INTERPRETATION, Type1, Terms::Enumerated(MONOCHROME))],
+};
+
+static SOP_COMMON: Module = Module {
+    name: "SOP Common",
+    attributes: &[
+        attr(std_tags::SOP_CLASS_UID, Type1),
+        attr(std_tags::SOP_INSTANCE_UID, Type1),
+        attr(std_tags::SPECIFIC_CHARACTER_SET, Type1C(Condition::NonAsciiText)),
+    ],
+};
+
+/// Modules every composite IOD has; used for SOP classes without a definition
+static COMMON_MODULES: &[(&Module, Usage)] = &[
+    (&PATIENT, Usage::Mandatory),
+    (&GENERAL_STUDY, Usage::Mandatory),
+    (&GENERAL_SERIES, Usage::Mandatory),
+    (&GENERAL_EQUIPMENT, Usage::UserOptional),
+    (&SOP_COMMON, Usage::Mandatory),
+];
+
+static IODS: &[Iod] = &[
+    Iod {
+        name: "CT Image",
+        sop_classes: &[uids::CT_IMAGE_STORAGE],
+        modules: &[
+            (&PATIENT, Usage::Mandatory),
+            (&GENERAL_STUDY, Usage::Mandatory),
+            (&GENERAL_SERIES, Usage::Mandatory),
+            (&FRAME_OF_REFERENCE, Usage::Mandatory),
+            (&GENERAL_EQUIPMENT, Usage::Mandatory),
+            (&GENERAL_IMAGE, Usage::Mandatory),
+            (&IMAGE_PLANE, Usage::Mandatory),
+            (&IMAGE_PIXEL, Usage::Mandatory),
+            (&CT_IMAGE, Usage::Mandatory),
+            (&SOP_COMMON, Usage::Mandatory),
+        ],
+    },
+    Iod {
+        name: "MR Image",
+        sop_classes: &[uids::MR_IMAGE_STORAGE],
+        modules: &[
+            (&PATIENT, Usage::Mandatory),
+            (&GENERAL_STUDY, Usage::Mandatory),
+            (&GENERAL_SERIES, Usage::Mandatory),
+            (&FRAME_OF_REFERENCE, Usage::Mandatory),
+            (&GENERAL_EQUIPMENT, Usage::Mandatory),
+            (&GENERAL_IMAGE, Usage::Mandatory),
+            (&IMAGE_PLANE, Usage::Mandatory),
+            (&IMAGE_PIXEL, Usage::Mandatory),
+            (&MR_IMAGE, Usage::Mandatory),
+            (&SOP_COMMON, Usage::Mandatory),
+        ],
+    },
+    Iod {
+        name: "Computed Radiography Image",
+        sop_classes: &[uids::COMPUTED_RADIOGRAPHY_IMAGE_STORAGE],
+        modules: &[
+            (&PATIENT, Usage::Mandatory),
+            (&GENERAL_STUDY, Usage::Mandatory),
+            (&GENERAL_SERIES, Usage::Mandatory),
+            (&CR_SERIES, Usage::Mandatory),
+            (&GENERAL_EQUIPMENT, Usage::Mandatory),
+            (&GENERAL_IMAGE, Usage::Mandatory),
+            (&IMAGE_PIXEL, Usage::Mandatory),
+            (&CR_IMAGE, Usage::Mandatory),
+            (&SOP_COMMON, Usage::Mandatory),
+        ],
+    },
+    Iod {
+        name: "Secondary Capture Image",
+        sop_classes: &[uids::SECONDARY_CAPTURE_IMAGE_STORAGE],
+        modules: &[
+            (&PATIENT, Usage::Mandatory),
+            (&GENERAL_STUDY, Usage::Mandatory),
+            (&GENERAL_SERIES, Usage::Mandatory),
+            (&FRAME_OF_REFERENCE, Usage::UserOptional),
+            (&GENERAL_EQUIPMENT, Usage::UserOptional),
+            (&SC_EQUIPMENT, Usage::Mandatory),
+            (&GENERAL_IMAGE, Usage::Mandatory),
+            (&IMAGE_PIXEL, Usage::Mandatory),
+            (&SOP_COMMON, Usage::Mandatory),
+        ],
+    },
+];
+
+fn attribute_name(tag: Tag) -> String {
+    dictionary::lookup(tag)
+        .map(|entry| format!("{} {}", entry.name, TagPath::root(tag)))
+        .unwrap_or_else(|| TagPath::root(tag).to_string())
+}
+
+fn is_text(vr: VR) -> bool {
+    matches!(vr, VR::LO | VR::LT | VR::PN | VR::SH | VR::ST | VR::UC | VR::UT)
+}
+
+/// First value of an attribute, without padding
+fn first_value(obj: &InMemDicomObject, tag: Tag) -> Option<String> {
+    let value = obj.element(tag).ok()?.to_str().ok()?;
+    let first = value.split('\\').next()?.trim_matches(|c: char| c == ' ' || c == '\0');
+    (!first.is_empty()).then(|| first.to_string())
+}
+
+fn is_empty(elem: &InMemElement) -> bool {
+    match elem.value() {
+        Value::Sequence(seq) => seq.items().is_empty(),
+        Value::PixelSequence(_) => false,
+        Value::Primitive(value) => value.calculate_byte_len() == 0
+            || (is_string_vr(elem.vr()) && elem.to_str().map_or(true, |s| s.trim_matches(['\0', ' ']).is_empty())),
+    }
+}
+
+fn is_string_vr(vr: VR) -> bool {
+    matches!(
+        vr,
+        VR::AE | VR::AS | VR::CS | VR::DA | VR::DS | VR::DT | VR::IS | VR::LO | VR::LT | VR::PN
+            | VR::SH | VR::ST | VR::TM | VR::UC | VR::UI | VR::UR | VR::UT
+    )
+}
+
+/// IOD definition for a SOP class
+fn iod_for(sop_class_uid: &str) -> Option<&'static Iod> {
+    IODS.iter().find(|iod| iod.sop_classes.contains(&sop_class_uid))
+}
+
+/// Check the presence and values of one module's attributes
+fn check_module(obj: &InMemDicomObject, module: &Module, findings: &mut Vec<Finding>) {
+    for attribute in module.attributes {
+        let elem = obj.element(attribute.tag).ok();
+        let mut finding = |severity, message: String| {
+            findings.push(Finding {
+                severity,
+                path: Some(TagPath::root(attribute.tag).to_string()),
+                module: Some(module.name.to_string()),
+                message,
+            })
+        };
+        let name = attribute_name(attribute.tag);
+
+        match (attribute.requirement, elem) {
+            (Type1, None) => finding(Severity::Error, format!("Missing Type 1 attribute {}", name)),
+            (Type1C(condition), None) if condition.holds(obj) => finding(
+                Severity::Error,
+                format!("Missing Type 1C attribute {} (required when {})", name, condition.describe()),
+            ),
+            (Type1 | Type1C(_), Some(elem)) if is_empty(elem) => {
+                finding(Severity::Error, format!("Type 1 attribute {} is empty", name))
+            }
+            (Type2, None) => finding(Severity::Error, format!("Missing Type 2 attribute {}", name)),
+            (Type2C(condition), None) if condition.holds(obj) => finding(
+                Severity::Error,
+                format!("Missing Type 2C attribute {} (required when {})", name, condition.describe()),
+            ),
+            _ => {}
+        }
+
+        let (Some(elem), Some(terms)) = (elem, attribute.terms) else {
+            continue;
+        };
+        let Ok(value) = elem.to_str() else {
+            continue;
+        };
+        for (index, value) in value.split('\\').enumerate() {
+            let value = value.trim_matches(|c: char| c == ' ' || c == '\0');
+            if value.is_empty() {
+                continue;
+            }
+            match terms {
+                Terms::Enumerated(allowed) if !allowed.contains(&value) => finding(
+                    Severity::Error,
+                    format!("{} has {:?}, which is not an enumerated value ({})", name, value, allowed.join(", ")),
+                ),
+                Terms::Defined(allowed) if !allowed.contains(&value) => finding(
+                    Severity::Warning,
+                    format!("{} has {:?}, which is not a defined term", name, value),
+                ),
+                Terms::EnumeratedPerValue(positions) => {
+                    if let Some(allowed) = positions.get(index).filter(|allowed| !allowed.contains(&value)) {
+                        finding(
+                            Severity::Error,
+                            format!(
+                                "Value {} of {} is {:?}, which is not an enumerated value ({})",
+                                index + 1,
+                                name,
+                                value,
+                                allowed.join(", ")
+                            ),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// VR, VM, value format and retired checks for every attribute, nested ones included
+fn check_attributes(obj: &InMemDicomObject, findings: &mut Vec<Finding>) {
+    let extended_charset = tags::has_extended_charset(obj);
+    let tree = tags::extract_all_tags(obj);
+
+    for tag in tags::flatten_tags(&tree) {
+        let mut finding = |severity, message: String| {
+            findings.push(Finding { severity, path: Some(tag.path.clone()), module: None, message })
+        };
+        let label = if tag.keyword.is_empty() { tag.name.clone() } else { tag.keyword.clone() };
+
+        if tag.vr_mismatch {
+            let severity = if tag.vr == "UN" { Severity::Warning } else { Severity::Error };
+            finding(
+                severity,
+                format!("{} has VR {}, expected {}", label, tag.vr, tag.expected_vr.as_deref().unwrap_or("?")),
+            );
+        }
+        // Real data often departs from the dictionary VM, e.g. four Image Type values
+        if tag.vm_mismatch {
+            finding(
+                Severity::Warning,
+                format!(
+                    "{} has {} values, expected VM {}",
+                    label,
+                    tag.vm,
+                    tag.expected_vm.as_deref().unwrap_or("?")
+                ),
+            );
+        }
+        if tag.retired {
+            finding(Severity::Info, format!("{} is retired", label));
+        }
+
+        let Ok(vr) = tag.vr.parse::<VR>() else {
+            continue;
+        };
+        if !is_string_vr(vr) || tag.value.is_empty() {
+            continue;
+        }
+        let values: Vec<&str> = if matches!(vr, VR::LT | VR::ST | VR::UT | VR::UR) {
+            vec![tag.value.as_str()]
+        } else {
+            tag.value.split('\\').collect()
+        };
+        for value in values {
+            // Padding is part of the encoding, not the value
+            let value = value.trim_end_matches(['\0', ' ']);
+            if value.is_empty() {
+                continue;
+            }
+            if let Err(e) = super::vr::validate_string(vr, value, extended_charset) {
+                finding(Severity::Error, e.to_string());
+            }
+        }
+    }
+}
+
+/// File Meta Information consistency with the dataset (PS3.10 7.1)
+fn check_file_meta(obj: &InMemDicomObject, meta: &FileMetaTable, findings: &mut Vec<Finding>) {
+    use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+    use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+
+    let trim = |s: &str| s.trim_end_matches(['\0', ' ']).to_string();
+    let mut finding = |severity, tag: Tag, message: String| {
+        findings.push(Finding {
+            severity,
+            path: Some(TagPath::root(tag).to_string()),
+            module: Some("File Meta Information".to_string()),
+            message,
+        })
+    };
+
+    let pairs = [
+        (std_tags::MEDIA_STORAGE_SOP_CLASS_UID, meta.media_storage_sop_class_uid(), std_tags::SOP_CLASS_UID),
+        (
+            std_tags::MEDIA_STORAGE_SOP_INSTANCE_UID,
+            meta.media_storage_sop_instance_uid(),
+            std_tags::SOP_INSTANCE_UID,
+        ),
+    ];
+    for (meta_tag, meta_value, dataset_tag) in pairs {
+        let meta_value = trim(meta_value);
+        let dataset_value = first_value(obj, dataset_tag);
+        if dataset_value.as_deref() != Some(meta_value.as_str()) {
+            finding(
+                Severity::Error,
+                meta_tag,
+                format!(
+                    "{} is {:?} but {} is {:?}",
+                    attribute_name(meta_tag),
+                    meta_value,
+                    attribute_name(dataset_tag),
+                    dataset_value.unwrap_or_default()
+                ),
+            );
+        }
+    }
+
+    for (tag, value) in [
+        (std_tags::TRANSFER_SYNTAX_UID, meta.transfer_syntax()),
+        (std_tags::IMPLEMENTATION_CLASS_UID, meta.implementation_class_uid()),
+    ] {
+        if let Err(e) = super::vr::validate_string(VR::UI, &trim(value), false) {
+            finding(Severity::Error, tag, e.to_string());
+        }
+    }
+
+    let transfer_syntax = trim(meta.transfer_syntax());
+    match TransferSyntaxRegistry.get(&transfer_syntax) {
+        None => finding(
+            Severity::Warning,
+            std_tags::TRANSFER_SYNTAX_UID,
+            format!("Unknown transfer syntax {}", transfer_syntax),
+        ),
+        Some(ts) => {
+            let encapsulated = obj
+                .element(std_tags::PIXEL_DATA)
+                .ok()
+                .map(|e| matches!(e.value(), Value::PixelSequence(_)));
+            if encapsulated.map_or(false, |encapsulated| encapsulated != ts.is_encapsulated_pixel_data()) {
+                finding(
+                    Severity::Error,
+                    std_tags::TRANSFER_SYNTAX_UID,
+                    format!("Pixel data encoding does not match transfer syntax {}", ts.name()),
+                );
+            }
+        }
+    }
+}
+
+/// Validate a dataset and, when read from a file, its File Meta. Returns the
+/// name of the IOD checked against and the findings, errors first.
+pub fn validate(obj: &InMemDicomObject, meta: Option<&FileMetaTable>) -> (Option<&'static str>, Vec<Finding>) {
+    let mut findings = Vec::new();
+
+    let sop_class = first_value(obj, std_tags::SOP_CLASS_UID);
+    let iod = sop_class.as_deref().and_then(iod_for);
+    let modules = match iod {
+        Some(iod) => iod.modules,
+        None => {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                path: None,
+                module: None,
+                message: format!(
+                    "No IOD definition for SOP class {}; only the common modules were checked, not conformance to its IOD",
+                    sop_class.as_deref().unwrap_or("(none)")
+                ),
+            });
+            COMMON_MODULES
+        }
+    };
+
+    for (module, usage) in modules {
+        let present = module.attributes.iter().any(|a| obj.element(a.tag).is_ok());
+        if *usage == Usage::Mandatory || present {
+            check_module(obj, module, &mut findings);
+        }
+    }
+    check_attributes(obj, &mut findings);
+    if let Some(meta) = meta {
+        check_file_meta(obj, meta, &mut findings);
+    }
+
+    // Attributes shared by several modules would otherwise be reported twice
+    let mut seen = std::collections::HashSet::new();
+    findings.retain(|f| seen.insert((f.path.clone(), f.message.clone())));
+    findings.sort_by_key(|f| f.severity);
+
+    (iod.map(|iod| iod.name), findings)
+}
+
+/// Validate a file read with its File Meta
+pub fn validate_file_object(path: &str, file_obj: &dicom_object::DefaultDicomObject) -> FileValidation {
+    let (iod, findings) = validate(file_obj, Some(file_obj.meta()));
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+
+    FileValidation {
+        path: path.to_string(),
+        sop_class_uid: first_value(file_obj, std_tags::SOP_CLASS_UID),
+        iod: iod.map(str::to_string),
+        common_modules_only: iod.is_none(),
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        findings,
+    }
+}
+
+pub fn validate_file<P: AsRef<Path>>(path: P) -> anyhow::Result<FileValidation> {
+    super::parser::parse_and_validate(path).map(|(_, validation)| validation)
+}
+
+/// Validate many files in parallel and aggregate their findings. Unreadable
+/// files are reported with an error.
+pub fn validate_files(paths: &[PathBuf]) -> ValidationReport {
+    use rayon::prelude::*;
+
+    let files: Vec<FileValidation> = paths
+        .par_iter()
+        .map(|path| {
+            validate_file(path).unwrap_or_else(|e| FileValidation {
+                path: path.to_string_lossy().to_string(),
+                sop_class_uid: None,
+                iod: None,
+                common_modules_only: false,
+                findings: vec![Finding {
+                    severity: Severity::Error,
+                    path: None,
+                    module: None,
+                    message: format!("Cannot read file: {}", e),
+                }],
+                errors: 1,
+                warnings: 0,
+            })
+        })
+        .collect();
+
+    summarize(files)
+}
+
+/// Aggregate per-file results into counts and a summary of distinct findings
+pub fn summarize(files: Vec<FileValidation>) -> ValidationReport {
+    use std::collections::HashMap;
+
+    let mut counts: HashMap<(Severity, Option<String>, String), usize> = HashMap::new();
+    for file in &files {
+        for finding in &file.findings {
+            *counts
+                .entry((finding.severity, finding.path.clone(), finding.message.clone()))
+                .or_default() += 1;
+        }
+    }
+
+    let mut summary: Vec<FindingSummary> = counts
+        .into_iter()
+        .map(|((severity, path, message), files)| FindingSummary { severity, path, message, files })
+        .collect();
+    summary.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then(b.files.cmp(&a.files))
+            .then(a.path.cmp(&b.path))
+    });
+
+    let report = ValidationReport {
+        file_count: files.len(),
+        files_with_errors: files.iter().filter(|f| f.errors > 0).count(),
+        errors: files.iter().map(|f| f.errors).sum(),
+        warnings: files.iter().map(|f| f.warnings).sum(),
+        common_modules_only: files.iter().filter(|f| f.common_modules_only).count(),
+        summary,
+        files,
+    };
+
+    tracing::info!(
+        "Validated {} files: {} errors, {} warnings, {} checked against common modules only",
+        report.file_count,
+        report.errors,
+        report.warnings,
+        report.common_modules_only
+    );
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+
+    fn ct_image() -> InMemDicomObject {
+        let elem = |tag, vr, value: PrimitiveValue| InMemElement::new(tag, vr, value);
+        InMemDicomObject::from_element_iter([
+            elem(std_tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from(uids::CT_IMAGE_STORAGE)),
+            elem(std_tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from("2.25.1")),
+            elem(std_tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from("2.25.2")),
+            elem(std_tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from("2.25.3")),
+            elem(std_tags::FRAME_OF_REFERENCE_UID, VR::UI, PrimitiveValue::from("2.25.4")),
+            elem(std_tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+            elem(std_tags::PATIENT_ID, VR::LO, PrimitiveValue::from("PID-1")),
+            elem(std_tags::PATIENT_BIRTH_DATE, VR::DA, PrimitiveValue::from("19700101")),
+            elem(std_tags::PATIENT_SEX, VR::CS, PrimitiveValue::from("M")),
+            elem(std_tags::STUDY_DATE, VR::DA, PrimitiveValue::from("20240102")),
+            elem(std_tags::STUDY_TIME, VR::TM, PrimitiveValue::from("101500")),
+            elem(std_tags::REFERRING_PHYSICIAN_NAME, VR::PN, PrimitiveValue::Empty),
+            elem(std_tags::STUDY_ID, VR::SH, PrimitiveValue::from("1")),
+            elem(std_tags::ACCESSION_NUMBER, VR::SH, PrimitiveValue::from("A1")),
+            elem(std_tags::MODALITY, VR::CS, PrimitiveValue::from("CT")),
+            elem(std_tags::SERIES_NUMBER, VR::IS, PrimitiveValue::from("1")),
+            elem(std_tags::PATIENT_POSITION, VR::CS, PrimitiveValue::from("HFS")),
+            elem(std_tags::POSITION_REFERENCE_INDICATOR, VR::LO, PrimitiveValue::Empty),
+            elem(std_tags::MANUFACTURER, VR::LO, PrimitiveValue::from("ACME")),
+            elem(std_tags::INSTANCE_NUMBER, VR::IS, PrimitiveValue::from("1")),
+            elem(
+                std_tags::IMAGE_TYPE,
+                VR::CS,
+                PrimitiveValue::Strs(vec!["ORIGINAL".to_string(), "PRIMARY".to_string(), "AXIAL".to_string()].into()),
+            ),
+            elem(std_tags::PIXEL_SPACING, VR::DS, PrimitiveValue::Strs(vec!["0.5".to_string(), "0.5".to_string()].into())),
+            elem(
+                std_tags::IMAGE_ORIENTATION_PATIENT,
+                VR::DS,
+                PrimitiveValue::Strs(["1", "0", "0", "0", "1", "0"].iter().map(|s| s.to_string()).collect::<Vec<_>>().into()),
+            ),
+            elem(
+                std_tags::IMAGE_POSITION_PATIENT,
+                VR::DS,
+                PrimitiveValue::Strs(["0", "0", "0"].iter().map(|s| s.to_string()).collect::<Vec<_>>().into()),
+            ),
+            elem(std_tags::SLICE_THICKNESS, VR::DS, PrimitiveValue::from("1")),
+            elem(std_tags::SAMPLES_PER_PIXEL, VR::US, PrimitiveValue::from(1_u16)),
+            elem(std_tags::PHOTOMETRIC_INTERPRETATION, VR::CS, PrimitiveValue::from("MONOCHROME2")),
+            elem(std_tags::ROWS, VR::US, PrimitiveValue::from(2_u16)),
+            elem(std_tags::COLUMNS, VR::US, PrimitiveValue::from(2_u16)),
+            elem(std_tags::BITS_ALLOCATED, VR::US, PrimitiveValue::from(16_u16)),
+            elem(std_tags::BITS_STORED, VR::US, PrimitiveValue::from(12_u16)),
+            elem(std_tags::HIGH_BIT, VR::US, PrimitiveValue::from(11_u16)),
+            elem(std_tags::PIXEL_REPRESENTATION, VR::US, PrimitiveValue::from(0_u16)),
+            elem(std_tags::RESCALE_INTERCEPT, VR::DS, PrimitiveValue::from("-1024")),
+            elem(std_tags::RESCALE_SLOPE, VR::DS, PrimitiveValue::from("1")),
+            elem(std_tags::KVP, VR::DS, PrimitiveValue::from("120")),
+            elem(std_tags::ACQUISITION_NUMBER, VR::IS, PrimitiveValue::from("1")),
+            elem(std_tags::PIXEL_DATA, VR::OW, PrimitiveValue::U16(vec![0_u16; 4].into())),
+        ])
+    }
+
+    fn messages(findings: &[Finding], severity: Severity) -> Vec<String> {
+        findings.iter().filter(|f| f.severity == severity).map(|f| f.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_conformant_ct_image() {
+        let (iod, findings) = validate(&ct_image(), None);
+        assert_eq!(iod, Some("CT Image"));
+        assert!(messages(&findings, Severity::Error).is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn test_module_requirements() {
+        let mut obj = ct_image();
+        obj.remove_element(std_tags::PATIENT_ID);
+        obj.remove_element(std_tags::RESCALE_SLOPE);
+        obj.put(InMemElement::new(std_tags::MODALITY, VR::CS, PrimitiveValue::Empty));
+        obj.put(InMemElement::new(std_tags::PATIENT_SEX, VR::CS, PrimitiveValue::from("X")));
+        obj.put(InMemElement::new(std_tags::SAMPLES_PER_PIXEL, VR::US, PrimitiveValue::from(3_u16)));
+        obj.remove_element(std_tags::PHOTOMETRIC_INTERPRETATION);
+
+        let (_, findings) = validate(&obj, None);
+        let errors = messages(&findings, Severity::Error);
+        let has = |text: &str| errors.iter().any(|m| m.contains(text));
+        assert!(has("Missing Type 2 attribute Patient ID"));
+        assert!(has("Missing Type 1 attribute Rescale Slope"));
+        assert!(has("Type 1 attribute Modality (0008,0060) is empty"));
+        assert!(has("\"X\", which is not an enumerated value"));
+        // Planar Configuration becomes required with 3 samples per pixel
+        assert!(has("Missing Type 1C attribute Planar Configuration"));
+        // Photometric Interpretation is required by both Image Pixel and CT Image, reported once
+        assert_eq!(
+            errors.iter().filter(|m| m.starts_with("Missing Type 1 attribute Photometric Interpretation")).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_value_checks() {
+        let mut obj = ct_image();
+        obj.put(InMemElement::new(std_tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.02.3")));
+        obj.put(InMemElement::new(std_tags::STUDY_DATE, VR::DA, PrimitiveValue::from("20241350")));
+        obj.put(InMemElement::new(std_tags::PATIENT_POSITION, VR::CS, PrimitiveValue::from("SITTING")));
+
+        let (_, findings) = validate(&obj, None);
+        let paths: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .filter_map(|f| f.path.as_deref())
+            .collect();
+        assert!(paths.contains(&"(0020,000E)"));
+        assert!(paths.contains(&"(0008,0020)"));
+        assert!(messages(&findings, Severity::Warning).iter().any(|m| m.contains("not a defined term")));
+    }
+
+    #[test]
+    fn test_unknown_sop_class_and_vm_are_warnings() {
+        let mut obj = ct_image();
+        obj.put(InMemElement::new(std_tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from("1.2.840.10008.5.1.4.1.1.88.22")));
+        obj.put(InMemElement::new(std_tags::PIXEL_SPACING, VR::DS, PrimitiveValue::from("0.5")));
+
+        let (iod, findings) = validate(&obj, None);
+        assert_eq!(iod, None);
+        let warnings = messages(&findings, Severity::Warning);
+        assert!(warnings.iter().any(|m| m.contains("only the common modules were checked")));
+        assert!(warnings.iter().any(|m| m.contains("expected VM 2")));
+        assert!(!messages(&findings, Severity::Error).iter().any(|m| m.contains("expected VM")));
+    }
+
+    #[test]
+    fn test_file_meta_consistency() {
+        let obj = ct_image();
+        let mut meta = crate::dicom::build_file_meta(&obj, uids::EXPLICIT_VR_LITTLE_ENDIAN).unwrap();
+        let (_, findings) = validate(&obj, Some(&meta));
+        assert!(messages(&findings, Severity::Error).is_empty(), "{:?}", findings);
+
+        meta.media_storage_sop_instance_uid = "2.25.99".to_string();
+        let (_, findings) = validate(&obj, Some(&meta));
+        assert!(findings
+            .iter()
+            .any(|f| f.severity == Severity::Error && f.path.as_deref() == Some("(0002,0003)")));
+    }
+
+    #[test]
+    fn test_summarize_counts_files() {
+        let file = |path: &str, message: &str| FileValidation {
+            path: path.to_string(),
+            sop_class_uid: None,
+            iod: None,
+            common_modules_only: true,
+            findings: vec![Finding {
+                severity: Severity::Error,
+                path: Some("(0010,0020)".to_string()),
+                module: None,
+                message: message.to_string(),
+            }],
+            errors: 1,
+            warnings: 0,
+        };
+        let report = summarize(vec![file("a", "Missing"), file("b", "Missing"), file("c", "Other")]);
+        assert_eq!(report.errors, 3);
+        assert_eq!(report.files_with_errors, 3);
+        assert_eq!(report.common_modules_only, 3);
+        assert_eq!(report.summary[0].files, 2);
+        assert_eq!(report.summary[0].message, "Missing");
+    }
+}
@@ -0,0 +1,497 @@
+// Decoding of vendor-specific private data: Siemens CSA headers, GE private
+// attributes and Philips private sequences
+
+use super::private;
+use super::tags::DicomTag;
+use anyhow::Result;
+use dicom_core::header::Header;
+use dicom_core::{DataElement, Tag, VR};
+use dicom_dictionary_std::tags;
+use dicom_object::InMemDicomObject;
+use serde::{Deserialize, Serialize};
+
+/// A decoded key with its values; structured values carry their parts as children
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VendorField {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub vr: String,
+    pub values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<VendorField>,
+}
+
+/// A decoded blob or private sequence and the attribute it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendorHeader {
+    /// e.g. "Siemens CSA Image Header"
+    pub source: String,
+    /// Tag path of the source attribute
+    pub path: String,
+    pub fields: Vec<VendorField>,
+}
+
+/// A commonly needed value pulled out of vendor data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendorSummaryField {
+    pub label: String,
+    pub value: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VendorReport {
+    pub headers: Vec<VendorHeader>,
+    pub summary: Vec<VendorSummaryField>,
+}
+
+const CSA_CREATOR: &str = "SIEMENS CSA HEADER";
+const SIEMENS_MR_CREATOR: &str = "SIEMENS MR HEADER";
+const GE_ACQU_CREATOR: &str = "GEMS_ACQU_01";
+const GE_PARM_CREATOR: &str = "GEMS_PARM_01";
+const PHILIPS_CREATOR: &str = "Philips Imaging DD 001";
+const PHILIPS_MR_CREATOR: &str = "Philips MR Imaging DD 001";
+
+/// Decode all vendor data found in a data set
+pub fn decode(obj: &InMemDicomObject) -> VendorReport {
+    let mut report = VendorReport::default();
+
+    for (element, source) in [(0x10, "Siemens CSA Image Header"), (0x20, "Siemens CSA Series Header")] {
+        let Some(elem) = private_element(obj, CSA_CREATOR, 0x0029, element) else {
+            continue;
+        };
+        match parse_csa(&element_bytes(elem)) {
+            Ok(fields) => report.headers.push(VendorHeader {
+                source: source.to_string(),
+                path: tag_path(elem.tag()),
+                fields,
+            }),
+            Err(e) => tracing::warn!("Could not decode {}: {}", source, e),
+        }
+    }
+
+    for elem in obj.iter() {
+        let tag = elem.tag();
+        if elem.vr() != VR::SQ || !matches!(tag.group(), 0x2001 | 0x2005) {
+            continue;
+        }
+        let creator = private::private_creator(obj, tag).unwrap_or_default();
+        if !creator.starts_with("Philips") {
+            continue;
+        }
+        let name = private::lookup(&creator, tag)
+            .map(|entry| entry.name)
+            .unwrap_or_else(|| tag_path(tag));
+        report.headers.push(VendorHeader {
+            source: format!("Philips {}", name),
+            path: tag_path(tag),
+            fields: sequence_fields(elem.items().unwrap_or_default()),
+        });
+    }
+
+    report.summary = summarize(obj, &report.headers);
+    report
+}
+
+/// Attach decoded headers to the top-level tags they were decoded from
+pub fn annotate(tags: &mut [DicomTag], report: &VendorReport) {
+    for tag in tags.iter_mut() {
+        if let Some(header) = report.headers.iter().find(|h| h.path == tag.path) {
+            tag.decoded = Some(header.clone());
+        }
+    }
+}
+
+/// Parse a Siemens CSA header (CSA1, or CSA2 starting with "SV10")
+pub fn parse_csa(data: &[u8]) -> Result<Vec<VendorField>> {
+    let mut reader = CsaReader { data, pos: 0 };
+    let csa2 = data.starts_with(b"SV10");
+    if csa2 {
+        reader.skip(8)?;
+    }
+
+    let n_tags = reader.u32()?;
+    reader.u32()?; // unused, always 77
+    if n_tags == 0 || n_tags > 128 {
+        return Err(anyhow::anyhow!("Not a CSA header ({} tags)", n_tags));
+    }
+
+    let mut fields = Vec::with_capacity(n_tags as usize);
+    let mut first_n_items = 0;
+
+    for tag_no in 0..n_tags {
+        let name = c_string(reader.bytes(64)?);
+        let vm = reader.i32()?;
+        let vr = c_string(reader.bytes(4)?);
+        let _syngodt = reader.i32()?;
+        let n_items = reader.i32()?;
+        reader.i32()?; // 77 or 205
+
+        if !(0..1000).contains(&n_items) {
+            return Err(anyhow::anyhow!("Invalid item count {} for {}", n_items, name));
+        }
+        if tag_no == 0 {
+            first_n_items = n_items;
+        }
+        let n_values = if vm == 0 { n_items } else { vm };
+
+        let mut values = Vec::new();
+        for item_no in 0..n_items {
+            let x0 = reader.i32()?;
+            let x1 = reader.i32()?;
+            reader.skip(8)?;
+
+            // CSA1 stores the length offset by the first tag's item count
+            let item_len = if csa2 {
+                x1
+            } else {
+                x0.checked_sub(first_n_items)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid item length {} for {}", x0, name))?
+            };
+            if item_len < 0 || reader.pos + item_len as usize > data.len() {
+                if csa2 {
+                    return Err(anyhow::anyhow!("Item of {} runs past the end of the header", name));
+                }
+                break;
+            }
+
+            let item = reader.bytes(item_len as usize)?;
+            reader.skip((4 - item_len as usize % 4) % 4).ok();
+
+            if item_no < n_values {
+                let value = c_string(item);
+                if !value.is_empty() {
+                    values.push(value);
+                }
+            }
+        }
+
+        let children = match values.first() {
+            Some(text) if text.contains("### ASCCONV BEGIN") => parse_ascconv(text),
+            _ => Vec::new(),
+        };
+        if !children.is_empty() {
+            values.clear();
+        }
+
+        fields.push(VendorField { name, vr, values, children });
+    }
+
+    Ok(fields)
+}
+
+/// Key/value lines of the ASCCONV block in a Siemens protocol, e.g.
+/// `sKSpace.lBaseResolution = 128`
+fn parse_ascconv(text: &str) -> Vec<VendorField> {
+    let Some(start) = text.find("### ASCCONV BEGIN") else {
+        return Vec::new();
+    };
+    let block = &text[start..];
+    let block = &block[..block.find("### ASCCONV END").unwrap_or(block.len())];
+
+    block
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let line = line.split('#').next()?.trim();
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_matches('"').to_string();
+            Some(VendorField {
+                name: key.trim().to_string(),
+                vr: String::new(),
+                values: vec![value],
+                children: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+struct CsaReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CsaReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos + len;
+        if end > self.data.len() {
+            return Err(anyhow::anyhow!("CSA header truncated at byte {}", self.pos));
+        }
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        let b = self.bytes(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// Text up to the first NUL, trimmed
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+fn tag_path(tag: Tag) -> String {
+    format!("({:04X},{:04X})", tag.group(), tag.element())
+}
+
+fn element_bytes(elem: &DataElement<InMemDicomObject>) -> Vec<u8> {
+    use dicom_core::value::Value;
+
+    match elem.value() {
+        Value::Primitive(value) => value.to_bytes().into_owned(),
+        _ => Vec::new(),
+    }
+}
+
+/// Element (gggg,xxEE) in the block reserved by `creator`, wherever the block is
+fn private_element<'a>(
+    obj: &'a InMemDicomObject,
+    creator: &str,
+    group: u16,
+    element: u8,
+) -> Option<&'a DataElement<InMemDicomObject>> {
+    let block = (0x10..=0xFFu16).find(|block| {
+        obj.element(Tag(group, *block))
+            .ok()
+            .and_then(|e| e.to_str().ok())
+            .map_or(false, |c| c.trim_end_matches(['\0', ' ']) == creator)
+    })?;
+    obj.element(Tag(group, (block << 8) | u16::from(element))).ok()
+}
+
+/// Value of a private element as text, decoding implicit VR elements read as UN
+fn private_value(obj: &InMemDicomObject, creator: &str, group: u16, element: u8) -> Option<String> {
+    let elem = private_element(obj, creator, group, element)?;
+    if elem.vr() == VR::UN {
+        let vr = private::lookup(creator, elem.tag())?.vr.parse::<VR>().ok()?;
+        return private::decode_unknown(elem, vr);
+    }
+    elem.to_str().ok().map(|v| v.trim().to_string())
+}
+
+/// Items of a private sequence as fields named from the standard and private dictionaries
+fn sequence_fields(items: &[InMemDicomObject]) -> Vec<VendorField> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| VendorField {
+            name: format!("Item {}", index + 1),
+            vr: String::new(),
+            values: Vec::new(),
+            children: item
+                .iter()
+                .map(|elem| {
+                    let tag = DicomTag::from_element_at(elem, item, super::path::TagPath::root(elem.tag()));
+                    let name = if tag.name == "Private Tag" || tag.name == "Unknown Tag" {
+                        tag.tag.clone()
+                    } else {
+                        format!("{} {}", tag.tag, tag.name)
+                    };
+                    VendorField {
+                        name,
+                        vr: tag.vr,
+                        values: if tag.value.is_empty() { Vec::new() } else { vec![tag.value] },
+                        children: sequence_fields(elem.items().unwrap_or_default()),
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn csa_value(headers: &[VendorHeader], source: &str, name: &str) -> Option<Vec<String>> {
+    headers
+        .iter()
+        .filter(|h| h.source == source)
+        .flat_map(|h| h.fields.iter())
+        .find(|f| f.name == name && !f.values.is_empty())
+        .map(|f| f.values.clone())
+}
+
+/// Pull slice timing, diffusion and phase encoding information out of vendor data
+fn summarize(obj: &InMemDicomObject, headers: &[VendorHeader]) -> Vec<VendorSummaryField> {
+    let mut summary = Vec::new();
+    let mut push = |label: &str, value: String, source: &str| {
+        if !value.is_empty() && !summary.iter().any(|s: &VendorSummaryField| s.label == label) {
+            summary.push(VendorSummaryField {
+                label: label.to_string(),
+                value,
+                source: source.to_string(),
+            });
+        }
+    };
+    let csa = "Siemens CSA Image Header";
+
+    // Siemens
+    if let Some(b) = csa_value(headers, csa, "B_value") {
+        push("B Value", b.join("\\"), csa);
+    }
+    if let Some(b) = private_value(obj, SIEMENS_MR_CREATOR, 0x0019, 0x0C) {
+        push("B Value", b, SIEMENS_MR_CREATOR);
+    }
+    if let Some(dir) = csa_value(headers, csa, "DiffusionGradientDirection") {
+        push("Diffusion Direction", dir.join("\\"), csa);
+    }
+    if let Some(dir) = private_value(obj, SIEMENS_MR_CREATOR, 0x0019, 0x0E) {
+        push("Diffusion Direction", dir, SIEMENS_MR_CREATOR);
+    }
+    if let Some(times) = csa_value(headers, csa, "MosaicRefAcqTimes") {
+        push("Slice Timing (ms)", times.join("\\"), csa);
+    }
+    if let Some(times) = private_value(obj, SIEMENS_MR_CREATOR, 0x0019, 0x29) {
+        push("Slice Timing (ms)", times, SIEMENS_MR_CREATOR);
+    }
+    if let Some(bandwidth) = csa_value(headers, csa, "BandwidthPerPixelPhaseEncode") {
+        push("Bandwidth per Pixel Phase Encode", bandwidth.join("\\"), csa);
+    }
+
+    let in_plane = obj
+        .element(tags::IN_PLANE_PHASE_ENCODING_DIRECTION)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let polarity = csa_value(headers, csa, "PhaseEncodingDirectionPositive")
+        .and_then(|v| v.first().cloned());
+    match (in_plane, polarity) {
+        (Some(axis), Some(positive)) => {
+            let sign = if positive.trim() == "1" { "+" } else { "-" };
+            push("Phase Encoding Direction", format!("{}{}", axis, sign), csa);
+        }
+        (Some(axis), None) => push("Phase Encoding Direction", axis, "(0018,1312)"),
+        _ => {}
+    }
+
+    // GE: the b value is the first of Slop_int_6..9, sometimes offset by 1e9
+    if let Some(slop) = private_value(obj, GE_PARM_CREATOR, 0x0043, 0x39) {
+        if let Some(b) = slop.split('\\').next().and_then(|b| b.trim().parse::<i64>().ok()) {
+            push("B Value", (b % 1_000_000_000).to_string(), GE_PARM_CREATOR);
+        }
+    }
+    let ge_direction: Vec<String> = [0xBB, 0xBC, 0xBD]
+        .iter()
+        .filter_map(|element| private_value(obj, GE_ACQU_CREATOR, 0x0019, *element))
+        .collect();
+    if ge_direction.len() == 3 {
+        push("Diffusion Direction", ge_direction.join("\\"), GE_ACQU_CREATOR);
+    }
+
+    // Philips
+    if let Some(b) = private_value(obj, PHILIPS_CREATOR, 0x2001, 0x03) {
+        push("B Value", b, PHILIPS_CREATOR);
+    }
+    let philips_direction: Vec<String> = [0xB0, 0xB1, 0xB2]
+        .iter()
+        .filter_map(|element| private_value(obj, PHILIPS_MR_CREATOR, 0x2005, *element))
+        .collect();
+    if philips_direction.len() == 3 {
+        push("Diffusion Direction", philips_direction.join("\\"), PHILIPS_MR_CREATOR);
+    }
+    if let Some(slope) = private_value(obj, PHILIPS_MR_CREATOR, 0x2005, 0x0E) {
+        push("Scale Slope", slope, PHILIPS_MR_CREATOR);
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_object::mem::InMemElement;
+
+    /// Build a CSA2 header from (name, VR, values)
+    fn csa2(tags: &[(&str, &str, &[&str])]) -> Vec<u8> {
+        let mut out = b"SV10\x04\x03\x02\x01".to_vec();
+        out.extend((tags.len() as u32).to_le_bytes());
+        out.extend(77u32.to_le_bytes());
+        for (name, vr, values) in tags {
+            let mut name_bytes = name.as_bytes().to_vec();
+            name_bytes.resize(64, 0);
+            out.extend(name_bytes);
+            out.extend((values.len() as i32).to_le_bytes()); // vm
+            let mut vr_bytes = vr.as_bytes().to_vec();
+            vr_bytes.resize(4, 0);
+            out.extend(vr_bytes);
+            out.extend(0i32.to_le_bytes()); // syngodt
+            out.extend((values.len() as i32).to_le_bytes()); // items
+            out.extend(77i32.to_le_bytes());
+            for value in values.iter() {
+                let mut data = value.as_bytes().to_vec();
+                data.push(0);
+                let len = data.len() as i32;
+                for x in [len, len, 77, len] {
+                    out.extend(x.to_le_bytes());
+                }
+                data.resize((data.len() + 3) / 4 * 4, 0);
+                out.extend(data);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_csa2() {
+        let data = csa2(&[
+            ("B_value", "IS", &["1000"]),
+            ("DiffusionGradientDirection", "FD", &["0.5", "-0.5", "0.7071"]),
+            ("MrPhoenixProtocol", "UN", &["### ASCCONV BEGIN ###\nsKSpace.lBaseResolution\t = \t128\ntProtocolName = \"\"ep2d_diff\"\" # comment\n### ASCCONV END ###"]),
+        ]);
+
+        let fields = parse_csa(&data).unwrap();
+
+        assert_eq!(fields[0].name, "B_value");
+        assert_eq!(fields[0].values, vec!["1000"]);
+        assert_eq!(fields[1].values.len(), 3);
+        assert!(fields[2].values.is_empty());
+        assert_eq!(fields[2].children[0].name, "sKSpace.lBaseResolution");
+        assert_eq!(fields[2].children[0].values, vec!["128"]);
+        assert_eq!(fields[2].children[1].values, vec!["ep2d_diff"]);
+    }
+
+    #[test]
+    fn test_decode_summarizes_siemens_headers() {
+        let data = csa2(&[
+            ("B_value", "IS", &["800"]),
+            ("PhaseEncodingDirectionPositive", "IS", &["0"]),
+            ("MosaicRefAcqTimes", "FD", &["0", "52.5", "105"]),
+        ]);
+        let obj = InMemDicomObject::from_element_iter([
+            InMemElement::new(tags::IN_PLANE_PHASE_ENCODING_DIRECTION, VR::CS, PrimitiveValue::from("COL")),
+            InMemElement::new(Tag(0x0029, 0x0011), VR::LO, PrimitiveValue::from("SIEMENS CSA HEADER")),
+            InMemElement::new(Tag(0x0029, 0x1110), VR::OB, PrimitiveValue::U8(data.into())),
+        ]);
+
+        let report = decode(&obj);
+
+        assert_eq!(report.headers.len(), 1);
+        assert_eq!(report.headers[0].path, "(0029,1110)");
+        let value = |label: &str| report.summary.iter().find(|s| s.label == label).map(|s| s.value.clone());
+        assert_eq!(value("B Value").as_deref(), Some("800"));
+        assert_eq!(value("Slice Timing (ms)").as_deref(), Some("0\\52.5\\105"));
+        assert_eq!(value("Phase Encoding Direction").as_deref(), Some("COL-"));
+    }
+
+    #[test]
+    fn test_parse_csa_rejects_garbage() {
+        assert!(parse_csa(b"not a csa header at all").is_err());
+
+        // CSA1 item length that underflows once the item count is subtracted
+        let mut csa1 = csa2(&[("B_value", "IS", &["1000"])])[8..].to_vec();
+        csa1[92..96].copy_from_slice(&i32::MIN.to_le_bytes());
+        assert!(parse_csa(&csa1).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+// Parsing and validation of user-entered values according to their VR (PS3.5 6.2)
+
+use anyhow::Result;
+use dicom_core::value::PrimitiveValue;
+use dicom_core::VR;
+
+/// VRs whose value is a single string that may contain backslashes
+fn is_single_valued(vr: VR) -> bool {
+    matches!(vr, VR::LT | VR::ST | VR::UT | VR::UR)
+}
+
+/// Parse `input` into a value of `vr`. Multiple values are separated by a
+/// backslash. `extended_charset` allows non-ASCII text, which the default
+/// character repertoire forbids.
+pub fn parse_value(vr: VR, input: &str, extended_charset: bool) -> Result<PrimitiveValue> {
+    if input.is_empty() {
+        return Ok(PrimitiveValue::Empty);
+    }
+
+    let values: Vec<&str> = if is_single_valued(vr) {
+        vec![input]
+    } else {
+        input.split('\\').collect()
+    };
+
+    match vr {
+        VR::US => numbers(vr, &values).map(|v| PrimitiveValue::U16(v.into())),
+        VR::SS => numbers(vr, &values).map(|v| PrimitiveValue::I16(v.into())),
+        VR::UL => numbers(vr, &values).map(|v| PrimitiveValue::U32(v.into())),
+        VR::SL => numbers(vr, &values).map(|v| PrimitiveValue::I32(v.into())),
+        VR::UV => numbers(vr, &values).map(|v| PrimitiveValue::U64(v.into())),
+        VR::SV => numbers(vr, &values).map(|v| PrimitiveValue::I64(v.into())),
+        VR::FL => numbers(vr, &values).map(|v| PrimitiveValue::F32(v.into())),
+        VR::FD => numbers(vr, &values).map(|v| PrimitiveValue::F64(v.into())),
+        VR::AT => values
+            .iter()
+            .map(|v| {
+                super::path::parse_tag(v.trim())
+                    .map_err(|_| anyhow::anyhow!("AT value {:?} is not a tag such as (0010,0010)", v))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|tags| PrimitiveValue::Tags(tags.into())),
+        VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN => Err(anyhow::anyhow!(
+            "{:?} values are binary and can't be edited as text",
+            vr
+        )),
+        VR::SQ => Err(anyhow::anyhow!("Sequences can't be set from text; edit their items instead")),
+        _ => {
+            for value in &values {
+                validate_string(vr, value, extended_charset)?;
+            }
+            Ok(PrimitiveValue::Strs(
+                values.iter().map(|v| v.to_string()).collect::<Vec<_>>().into(),
+            ))
+        }
+    }
+}
+
+fn numbers<T: std::str::FromStr>(vr: VR, values: &[&str]) -> Result<Vec<T>> {
+    values
+        .iter()
+        .map(|v| {
+            v.trim().parse::<T>().map_err(|_| {
+                anyhow::anyhow!("{:?} value {:?} is not a number in the range of {:?}", vr, v, vr)
+            })
+        })
+        .collect()
+}
+
+/// Maximum length in characters of one value
+fn max_length(vr: VR) -> Option<usize> {
+    match vr {
+        VR::AE | VR::CS | VR::DS | VR::SH => Some(16),
+        VR::AS => Some(4),
+        VR::DA => Some(8),
+        VR::DT => Some(26),
+        VR::IS => Some(12),
+        VR::LO | VR::UI => Some(64),
+        VR::LT => Some(10240),
+        VR::ST => Some(1024),
+        VR::TM => Some(14),
+        _ => None,
+    }
+}
+
+/// Validate one string value (no backslash-separated parts)
+pub fn validate_string(vr: VR, value: &str, extended_charset: bool) -> Result<()> {
+    let invalid = |reason: &str| anyhow::anyhow!("Invalid {:?} value {:?}: {}", vr, value, reason);
+
+    if let Some(max) = max_length(vr) {
+        if value.chars().count() > max {
+            return Err(invalid(&format!("longer than {} characters", max)));
+        }
+    }
+
+    // Control characters allowed by PS3.5 6.1.3; ESC is needed for ISO 2022 escapes
+    let allowed_control: &[char] = match vr {
+        VR::LT | VR::ST | VR::UT => &['\t', '\n', '\x0C', '\r', '\x1B'],
+        VR::LO | VR::PN | VR::SH | VR::UC => &['\x1B'],
+        _ => &[],
+    };
+    if let Some(c) = value.chars().find(|c| c.is_control() && !allowed_control.contains(c)) {
+        return Err(invalid(&format!("control character {:?} is not allowed", c)));
+    }
+    if !extended_charset && !value.is_ascii() {
+        return Err(invalid(
+            "non-ASCII characters need a Specific Character Set (0008,0005) such as ISO_IR 192",
+        ));
+    }
+
+    let trimmed = value.trim();
+    match vr {
+        VR::CS => {
+            if !trimmed.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == ' ' || c == '_') {
+                return Err(invalid("only uppercase letters, digits, space and underscore are allowed"));
+            }
+        }
+        VR::AS => {
+            let bytes = trimmed.as_bytes();
+            if bytes.len() != 4
+                || !bytes[..3].iter().all(u8::is_ascii_digit)
+                || !matches!(bytes[3], b'D' | b'W' | b'M' | b'Y')
+            {
+                return Err(invalid("expected nnnD, nnnW, nnnM or nnnY, e.g. 045Y"));
+            }
+        }
+        VR::DA => validate_date(trimmed).map_err(|reason| invalid(&reason))?,
+        VR::TM => validate_time(trimmed).map_err(|reason| invalid(&reason))?,
+        VR::DT => validate_datetime(trimmed).map_err(|reason| invalid(&reason))?,
+        VR::DS => {
+            if !trimmed.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))
+                || trimmed.parse::<f64>().is_err()
+            {
+                return Err(invalid("not a decimal number"));
+            }
+        }
+        VR::IS => {
+            if trimmed.parse::<i32>().is_err() {
+                return Err(invalid("not an integer between -2147483648 and 2147483647"));
+            }
+        }
+        VR::UI => validate_uid(value).map_err(|reason| invalid(&reason))?,
+        VR::PN => {
+            let groups: Vec<&str> = value.split('=').collect();
+            if groups.len() > 3 {
+                return Err(invalid("at most 3 component groups (alphabetic=ideographic=phonetic)"));
+            }
+            for group in groups {
+                if group.chars().count() > 64 {
+                    return Err(invalid("component groups are limited to 64 characters"));
+                }
+                if group.split('^').count() > 5 {
+                    return Err(invalid("at most 5 components: family^given^middle^prefix^suffix"));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn all_digits(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// YYYYMMDD
+fn validate_date(value: &str) -> std::result::Result<(), String> {
+    if value.len() != 8 || !all_digits(value) {
+        return Err("expected a date as YYYYMMDD".to_string());
+    }
+    let (year, month, day) = (&value[0..4], &value[4..6], &value[6..8]);
+    chrono::NaiveDate::from_ymd_opt(
+        year.parse().unwrap_or(0),
+        month.parse().unwrap_or(0),
+        day.parse().unwrap_or(0),
+    )
+    .map(|_| ())
+    .ok_or_else(|| format!("{}-{}-{} is not a calendar date", year, month, day))
+}
+
+/// HH[MM[SS[.FFFFFF]]]
+fn validate_time(value: &str) -> std::result::Result<(), String> {
+    let (main, fraction) = match value.split_once('.') {
+        Some((main, fraction)) => (main, Some(fraction)),
+        None => (value, None),
+    };
+    if !matches!(main.len(), 2 | 4 | 6) || !all_digits(main) {
+        return Err("expected a time as HH, HHMM, HHMMSS or HHMMSS.FFFFFF".to_string());
+    }
+    if let Some(fraction) = fraction {
+        if main.len() != 6 || fraction.is_empty() || fraction.len() > 6 || !all_digits(fraction) {
+            return Err("fractional seconds need HHMMSS and 1-6 digits".to_string());
+        }
+    }
+    check_time_fields(main)
+}
+
+fn check_time_fields(digits: &str) -> std::result::Result<(), String> {
+    let limits = [("hour", 23), ("minute", 59), ("second", 60)];
+    for (i, (name, max)) in limits.iter().enumerate() {
+        if let Some(field) = digits.get(i * 2..i * 2 + 2) {
+            if field.parse::<u32>().unwrap_or(u32::MAX) > *max {
+                return Err(format!("{} {} is out of range", name, field));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// YYYY[MM[DD[HH[MM[SS[.FFFFFF]]]]]][&ZZXX]
+fn validate_datetime(value: &str) -> std::result::Result<(), String> {
+    let (main, offset) = match value.get(4..).and_then(|rest| rest.find(['+', '-'])) {
+        Some(pos) => value.split_at(pos + 4),
+        None => (value, ""),
+    };
+    let (digits, fraction) = match main.split_once('.') {
+        Some((digits, fraction)) => (digits, Some(fraction)),
+        None => (main, None),
+    };
+
+    if !matches!(digits.len(), 4 | 6 | 8 | 10 | 12 | 14) || !all_digits(digits) {
+        return Err("expected YYYY[MM[DD[HH[MM[SS[.FFFFFF]]]]]][+/-ZZXX]".to_string());
+    }
+    if let Some(fraction) = fraction {
+        if digits.len() != 14 || fraction.is_empty() || fraction.len() > 6 || !all_digits(fraction) {
+            return Err("fractional seconds need a full date and time and 1-6 digits".to_string());
+        }
+    }
+
+    let month: u32 = digits.get(4..6).map_or(1, |m| m.parse().unwrap_or(0));
+    let day: u32 = digits.get(6..8).map_or(1, |d| d.parse().unwrap_or(0));
+    if chrono::NaiveDate::from_ymd_opt(digits[0..4].parse().unwrap_or(0), month, day).is_none() {
+        return Err("not a calendar date".to_string());
+    }
+    if digits.len() > 8 {
+        check_time_fields(&digits[8..])?;
+    }
+
+    if !offset.is_empty() {
+        let valid = offset.len() == 5
+            && all_digits(&offset[1..])
+            && offset[1..3].parse::<u32>().map_or(false, |h| h <= 14)
+            && offset[3..5].parse::<u32>().map_or(false, |m| m < 60);
+        if !valid {
+            return Err(format!("UTC offset {} must be +HHMM or -HHMM", offset));
+        }
+    }
+
+    Ok(())
+}
+
+/// Dot-separated numeric components without leading zeros, at most 64 characters
+fn validate_uid(value: &str) -> std::result::Result<(), String> {
+    // UIDs are padded with NUL, not space
+    let uid = value.trim_end_matches('\0');
+    if uid.is_empty() {
+        return Err("UID is empty".to_string());
+    }
+    for component in uid.split('.') {
+        if component.is_empty() || !all_digits(component) {
+            return Err("UIDs are digits separated by single dots".to_string());
+        }
+        if component.len() > 1 && component.starts_with('0') {
+            return Err(format!("component {} has a leading zero", component));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_binary_vrs() {
+        assert_eq!(parse_value(VR::US, "512\\512", false).unwrap(), PrimitiveValue::U16(vec![512, 512].into()));
+        assert_eq!(parse_value(VR::FD, "0.5", false).unwrap(), PrimitiveValue::F64(vec![0.5].into()));
+        assert_eq!(
+            parse_value(VR::AT, "(0028,0030)", false).unwrap(),
+            PrimitiveValue::Tags(vec![dicom_core::Tag(0x0028, 0x0030)].into())
+        );
+        assert!(parse_value(VR::US, "70000", false).is_err());
+        assert!(parse_value(VR::SS, "abc", false).is_err());
+        assert!(parse_value(VR::OB, "00", false).is_err());
+    }
+
+    #[test]
+    fn test_validate_formats() {
+        assert!(parse_value(VR::DA, "20240229", false).is_ok());
+        assert!(parse_value(VR::DA, "20230229", false).is_err());
+        assert!(parse_value(VR::TM, "235959.123", false).is_ok());
+        assert!(parse_value(VR::TM, "2460", false).is_err());
+        assert!(parse_value(VR::DT, "20240101120000.5+0100", false).is_ok());
+        assert!(parse_value(VR::DT, "2024011312-2500", false).is_err());
+        assert!(parse_value(VR::UI, "1.2.840.10008.1.2", false).is_ok());
+        assert!(parse_value(VR::UI, "1.02.3", false).is_err());
+        assert!(parse_value(VR::AS, "045Y", false).is_ok());
+        assert!(parse_value(VR::AS, "45Y", false).is_err());
+        assert!(parse_value(VR::PN, "Doe^John^^Dr", false).is_ok());
+        assert!(parse_value(VR::PN, "a^b^c^d^e^f", false).is_err());
+        assert!(parse_value(VR::CS, "ORIGINAL\\PRIMARY", false).is_ok());
+        assert!(parse_value(VR::CS, "original", false).is_err());
+    }
+
+    #[test]
+    fn test_length_and_repertoire() {
+        assert!(parse_value(VR::SH, "12345678901234567", false).is_err());
+        assert!(parse_value(VR::LO, "Müller", false).is_err());
+        assert!(parse_value(VR::LO, "Müller", true).is_ok());
+        assert!(parse_value(VR::LT, "line one\r\nline\\two", false).is_ok());
+        assert!(parse_value(VR::LO, "tab\there", false).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+// OAuth2 token acquisition and caching for DICOMweb endpoints
+
+use super::AuthType;
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Tokens are renewed when they expire within this margin
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Lifetime assumed when the token endpoint does not return `expires_in`
+const DEFAULT_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Limit for a whole token request, so a hung identity provider can't stall requests
+const TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+    /// Latest refresh token; servers may rotate it on every refresh
+    refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        self.expires_at > Instant::now() + EXPIRY_MARGIN
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// A refresh token that the identity provider replaced during a refresh grant
+#[derive(Debug, Clone)]
+pub struct RotatedRefreshToken {
+    pub token_url: String,
+    pub client_id: String,
+    /// Tokens superseded by this one: the configured token and the one just sent
+    pub replaced: Vec<String>,
+    pub refresh_token: String,
+}
+
+impl RotatedRefreshToken {
+    pub fn replaces(&self, token: &str) -> bool {
+        self.replaced.iter().any(|t| t == token)
+    }
+}
+
+type RotationHook = Box<dyn Fn(RotatedRefreshToken) + Send + Sync>;
+
+static ROTATION_HOOK: OnceLock<RotationHook> = OnceLock::new();
+
+/// Register where rotated refresh tokens are persisted; set once at startup
+pub fn on_refresh_token_rotated(hook: impl Fn(RotatedRefreshToken) + Send + Sync + 'static) {
+    if ROTATION_HOOK.set(Box::new(hook)).is_err() {
+        tracing::warn!("Refresh token rotation hook is already registered");
+    }
+}
+
+type TokenSlot = Arc<Mutex<Option<CachedToken>>>;
+
+/// Process-wide token cache so separate commands against the same endpoint share tokens.
+/// Each token source has its own lock, so renewing one never blocks another.
+fn slot(key: &str) -> TokenSlot {
+    static CACHE: OnceLock<std::sync::Mutex<HashMap<String, TokenSlot>>> = OnceLock::new();
+    CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(key.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Cache key identifying the token source of an auth configuration. The client
+/// secret is part of it (hashed), so a corrected secret isn't served the token
+/// cached for the old one.
+fn cache_key(auth: &AuthType) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    match auth {
+        AuthType::OAuth2ClientCredentials { token_url, client_id, client_secret, scope } => Some(format!(
+            "cc|{}|{}|{}|{:x}",
+            token_url,
+            client_id,
+            scope.as_deref().unwrap_or(""),
+            Sha256::digest(client_secret.as_bytes())
+        )),
+        AuthType::OAuth2RefreshToken { token_url, client_id, refresh_token, .. } => {
+            Some(format!("rt|{}|{}|{}", token_url, client_id, refresh_token))
+        }
+        _ => None,
+    }
+}
+
+/// Return a valid access token for an OAuth2 auth configuration, fetching or
+/// refreshing it when the cached one is missing or about to expire
+pub async fn access_token(client: &Client, auth: &AuthType) -> Result<String> {
+    let key = cache_key(auth)
+        .ok_or_else(|| anyhow::anyhow!("Auth type does not use OAuth2 tokens"))?;
+
+    // Holding the source's lock while fetching keeps concurrent requests from racing to renew
+    let slot = slot(&key);
+    let mut cached = slot.lock().await;
+
+    if let Some(token) = cached.as_ref().filter(|t| t.is_fresh()) {
+        return Ok(token.access_token.clone());
+    }
+
+    let previous_refresh = cached.as_ref().and_then(|t| t.refresh_token.clone());
+    let token = tokio::time::timeout(TOKEN_TIMEOUT, fetch_token(client, auth, previous_refresh))
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!("OAuth2 token request timed out after {} seconds", TOKEN_TIMEOUT.as_secs())
+        })??;
+    let access_token = token.access_token.clone();
+    *cached = Some(token);
+
+    Ok(access_token)
+}
+
+/// Drop the cached token, e.g. after the server rejected it with 401
+pub async fn invalidate(auth: &AuthType) {
+    if let Some(key) = cache_key(auth) {
+        if let Some(token) = slot(&key).lock().await.as_mut() {
+            token.expires_at = Instant::now();
+        }
+    }
+}
+
+async fn fetch_token(
+    client: &Client,
+    auth: &AuthType,
+    previous_refresh: Option<String>,
+) -> Result<CachedToken> {
+    let (token_url, client_id, client_secret, mut form) = match auth {
+        AuthType::OAuth2ClientCredentials { token_url, client_id, client_secret, scope } => {
+            let mut form = vec![("grant_type", "client_credentials".to_string())];
+            if let Some(scope) = scope {
+                form.push(("scope", scope.clone()));
+            }
+            (token_url, client_id, Some(client_secret), form)
+        }
+        AuthType::OAuth2RefreshToken { token_url, client_id, client_secret, refresh_token } => {
+            let refresh = previous_refresh.unwrap_or_else(|| refresh_token.clone());
+            let form = vec![
+                ("grant_type", "refresh_token".to_string()),
+                ("refresh_token", refresh),
+            ];
+            (token_url, client_id, client_secret.as_ref(), form)
+        }
+        _ => return Err(anyhow::anyhow!("Auth type does not use OAuth2 tokens")),
+    };
+
+    tracing::info!("Requesting OAuth2 token from {}", token_url);
+
+    let mut req = client.post(token_url);
+    match client_secret {
+        Some(secret) => req = req.basic_auth(client_id, Some(secret)),
+        None => form.push(("client_id", client_id.clone())),
+    }
+
+    let response = req.form(&form).send().await?;
+    let status = response.status();
+
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow::anyhow!(
+            "OAuth2 token request failed with status {}: {}",
+            status.as_u16(),
+            body
+        ));
+    }
+
+    let token: TokenResponse = response.json().await?;
+    let lifetime = token
+        .expires_in
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_LIFETIME);
+
+    let sent_refresh = form.iter().find(|(k, _)| *k == "refresh_token").map(|(_, v)| v.clone());
+    if let (Some(rotated), Some(sent), AuthType::OAuth2RefreshToken { refresh_token: configured, .. }) =
+        (&token.refresh_token, &sent_refresh, auth)
+    {
+        if rotated != sent {
+            if let Some(hook) = ROTATION_HOOK.get() {
+                hook(RotatedRefreshToken {
+                    token_url: token_url.clone(),
+                    client_id: client_id.clone(),
+                    replaced: vec![configured.clone(), sent.clone()],
+                    refresh_token: rotated.clone(),
+                });
+            }
+        }
+    }
+
+    let refresh_token = token.refresh_token.or(sent_refresh);
+
+    Ok(CachedToken {
+        access_token: token.access_token,
+        expires_at: Instant::now() + lifetime,
+        refresh_token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_client_credentials_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "abc",
+                "token_type": "Bearer",
+                "expires_in": 3600
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let auth = AuthType::OAuth2ClientCredentials {
+            token_url: format!("{}/token", server.uri()),
+            client_id: "cached-client".to_string(),
+            client_secret: "secret".to_string(),
+            scope: None,
+        };
+
+        let client = Client::new();
+        assert_eq!(access_token(&client, &auth).await.unwrap(), "abc");
+        assert_eq!(access_token(&client, &auth).await.unwrap(), "abc");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_renewed_before_expiry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "short-lived",
+                "expires_in": 30,
+                "refresh_token": "rotated"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let auth = AuthType::OAuth2RefreshToken {
+            token_url: format!("{}/token", server.uri()),
+            client_id: "refresh-client".to_string(),
+            client_secret: None,
+            refresh_token: "initial".to_string(),
+        };
+
+        // expires_in is inside the renewal margin, so every call renews
+        let client = Client::new();
+        access_token(&client, &auth).await.unwrap();
+        access_token(&client, &auth).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let second = String::from_utf8_lossy(&requests[1].body).to_string();
+        assert!(second.contains("refresh_token=rotated"));
+    }
+
+    #[tokio::test]
+    async fn test_slow_token_source_does_not_block_others() {
+        let slow = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "access_token": "slow", "expires_in": 3600 }))
+                    .set_delay(Duration::from_secs(3)),
+            )
+            .mount(&slow)
+            .await;
+        let fast = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "access_token": "fast", "expires_in": 3600 })),
+            )
+            .mount(&fast)
+            .await;
+
+        let auth = |server: &MockServer| AuthType::OAuth2ClientCredentials {
+            token_url: format!("{}/token", server.uri()),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            scope: None,
+        };
+        let slow_auth = auth(&slow);
+        let fast_auth = auth(&fast);
+
+        let client = Client::new();
+        let slow_client = client.clone();
+        let pending = tokio::spawn(async move { access_token(&slow_client, &slow_auth).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let started = Instant::now();
+        assert_eq!(access_token(&client, &fast_auth).await.unwrap(), "fast");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(pending.await.unwrap().unwrap(), "slow");
+    }
+
+    #[test]
+    fn test_cache_key_includes_refresh_token() {
+        let auth = |refresh: &str| AuthType::OAuth2RefreshToken {
+            token_url: "https://idp/token".to_string(),
+            client_id: "client".to_string(),
+            client_secret: None,
+            refresh_token: refresh.to_string(),
+        };
+        assert_ne!(cache_key(&auth("a")), cache_key(&auth("b")));
+    }
+
+    #[test]
+    fn test_cache_key_includes_client_secret() {
+        let auth = |secret: &str| AuthType::OAuth2ClientCredentials {
+            token_url: "https://idp/token".to_string(),
+            client_id: "client".to_string(),
+            client_secret: secret.to_string(),
+            scope: None,
+        };
+        let key = cache_key(&auth("old")).unwrap();
+        assert_ne!(Some(key.clone()), cache_key(&auth("new")));
+        assert!(!key.contains("old"));
+    }
+}
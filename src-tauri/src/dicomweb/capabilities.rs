@@ -0,0 +1,526 @@
+// DICOMweb capabilities discovery and endpoint validation
+
+use super::client::DicomWebClient;
+use super::{DicomWebRequest, DicomWebResponse};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    QidoStudies,
+    QidoSeries,
+    QidoInstances,
+    WadoMetadata,
+    WadoRendered,
+    WadoThumbnail,
+    Stow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupportStatus {
+    Supported,
+    /// The server answered that the resource does not exist or is not implemented
+    Unsupported,
+    /// The probe failed for another reason (auth, server error, network)
+    Failed,
+    /// The probe was not run, e.g. because no instance was available to test with
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCheck {
+    pub feature: Feature,
+    pub status: SupportStatus,
+    pub http_status: Option<u16>,
+    pub detail: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Per-feature support report, saved on the endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointCapabilities {
+    pub inspected_at: String,
+    /// Content type of the capabilities document (e.g. WADL), if the server provided one
+    pub document_type: Option<String>,
+    /// Resource paths advertised in the capabilities document
+    pub advertised_resources: Vec<String>,
+    pub checks: Vec<FeatureCheck>,
+}
+
+impl EndpointCapabilities {
+    /// Whether the feature passed its probe; `None` if it was not probed
+    pub fn supports(&self, feature: Feature) -> Option<bool> {
+        self.checks
+            .iter()
+            .find(|c| c.feature == feature)
+            .and_then(|c| match c.status {
+                SupportStatus::Supported => Some(true),
+                SupportStatus::Unsupported | SupportStatus::Failed => Some(false),
+                SupportStatus::Skipped => None,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InspectOptions {
+    /// Upload a synthetic Secondary Capture instance (patient "DICOMFLOW^PROBE") to
+    /// test STOW-RS. Off by default because it leaves a test study on the server.
+    #[serde(default)]
+    pub probe_stow: bool,
+}
+
+/// UIDs of an existing instance, found through QIDO and used to probe WADO
+#[derive(Debug, Default)]
+struct Sample {
+    study: Option<String>,
+    series: Option<String>,
+    instance: Option<String>,
+}
+
+/// Fetch the capabilities document and probe each DICOMweb feature
+pub async fn inspect(client: &DicomWebClient, options: &InspectOptions) -> Result<EndpointCapabilities> {
+    tracing::info!("Inspecting DICOMweb endpoint {}", client.endpoint().name);
+
+    let (document_type, advertised_resources) = fetch_capabilities_document(client).await;
+    let mut checks = Vec::new();
+    let mut sample = Sample::default();
+
+    let (check, results) = probe_json(client, Feature::QidoStudies, "qido-rs/studies?limit=1".to_string()).await;
+    checks.push(check);
+    sample.study = first_value(&results, "0020000D");
+
+    let endpoint = match &sample.study {
+        Some(study) => format!("qido-rs/studies/{}/series?limit=1", study),
+        None => "qido-rs/series?limit=1".to_string(),
+    };
+    let (check, results) = probe_json(client, Feature::QidoSeries, endpoint).await;
+    checks.push(check);
+    sample.series = first_value(&results, "0020000E");
+
+    let endpoint = match (&sample.study, &sample.series) {
+        (Some(study), Some(series)) => {
+            format!("qido-rs/studies/{}/series/{}/instances?limit=1", study, series)
+        }
+        _ => "qido-rs/instances?limit=1".to_string(),
+    };
+    let (check, results) = probe_json(client, Feature::QidoInstances, endpoint).await;
+    checks.push(check);
+    sample.instance = first_value(&results, "00080018");
+    // Unscoped instance queries return the study and series of the instance too
+    sample.study = sample.study.or_else(|| first_value(&results, "0020000D"));
+    sample.series = sample.series.or_else(|| first_value(&results, "0020000E"));
+
+    match (&sample.study, &sample.series, &sample.instance) {
+        (Some(study), Some(series), Some(instance)) => {
+            let path = format!("wado-rs/studies/{}/series/{}/instances/{}", study, series, instance);
+            checks.push(probe_json(client, Feature::WadoMetadata, format!("{}/metadata", path)).await.0);
+            checks.push(probe_image(client, Feature::WadoRendered, format!("{}/rendered", path)).await);
+            checks.push(probe_image(client, Feature::WadoThumbnail, format!("{}/thumbnail", path)).await);
+        }
+        _ => {
+            for feature in [Feature::WadoMetadata, Feature::WadoRendered, Feature::WadoThumbnail] {
+                checks.push(skipped(feature, "No instance found through QIDO-RS to probe with"));
+            }
+        }
+    }
+
+    if options.probe_stow {
+        checks.push(probe_stow(client).await);
+    } else {
+        checks.push(skipped(Feature::Stow, "STOW-RS probe not enabled"));
+    }
+
+    Ok(EndpointCapabilities {
+        inspected_at: chrono::Utc::now().to_rfc3339(),
+        document_type,
+        advertised_resources,
+        checks,
+    })
+}
+
+fn request(method: &str, endpoint: String, accept: &str) -> DicomWebRequest {
+    let mut headers = HashMap::new();
+    headers.insert("Accept".to_string(), accept.to_string());
+
+    DicomWebRequest {
+        method: method.to_string(),
+        endpoint,
+        headers,
+        body: None,
+    }
+}
+
+fn header<'a>(response: &'a DicomWebResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn skipped(feature: Feature, detail: &str) -> FeatureCheck {
+    FeatureCheck {
+        feature,
+        status: SupportStatus::Skipped,
+        http_status: None,
+        detail: Some(detail.to_string()),
+        elapsed_ms: 0,
+    }
+}
+
+/// Classify an HTTP status; `None` means the request succeeded
+fn classify_status(status: u16) -> Option<(SupportStatus, String)> {
+    match status {
+        200..=299 => None,
+        401 | 403 => Some((SupportStatus::Failed, "Authentication was rejected".to_string())),
+        404 | 405 | 501 => Some((
+            SupportStatus::Unsupported,
+            format!("Server answered {}", status),
+        )),
+        406 | 415 => Some((
+            SupportStatus::Unsupported,
+            format!("Media type not supported ({})", status),
+        )),
+        _ => Some((SupportStatus::Failed, format!("Server answered {}", status))),
+    }
+}
+
+/// Run a probe request and classify the result; `validate` checks a 2xx response
+async fn probe<F>(
+    client: &DicomWebClient,
+    feature: Feature,
+    request: DicomWebRequest,
+    validate: F,
+) -> (FeatureCheck, Option<DicomWebResponse>)
+where
+    F: FnOnce(&DicomWebResponse) -> std::result::Result<Option<String>, String>,
+{
+    let start = Instant::now();
+    let result = client.execute(request).await;
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let check = FeatureCheck {
+                feature,
+                status: SupportStatus::Failed,
+                http_status: None,
+                detail: Some(e.to_string()),
+                elapsed_ms,
+            };
+            return (check, None);
+        }
+    };
+
+    let (status, detail) = match classify_status(response.status) {
+        Some((status, detail)) => (status, Some(detail)),
+        None => match validate(&response) {
+            Ok(detail) => (SupportStatus::Supported, detail),
+            Err(problem) => (SupportStatus::Failed, Some(problem)),
+        },
+    };
+
+    let check = FeatureCheck {
+        feature,
+        status,
+        http_status: Some(response.status),
+        detail,
+        elapsed_ms,
+    };
+    (check, Some(response))
+}
+
+/// Probe a resource that returns a DICOM JSON array, returning the parsed results
+async fn probe_json(client: &DicomWebClient, feature: Feature, endpoint: String) -> (FeatureCheck, Vec<Value>) {
+    let mut results = Vec::new();
+    let (check, _) = probe(
+        client,
+        feature,
+        request("GET", endpoint, "application/dicom+json"),
+        |response| {
+            if response.status == 204 {
+                return Ok(Some("No matches".to_string()));
+            }
+            match serde_json::from_str::<Value>(&response.body) {
+                Ok(Value::Array(items)) => {
+                    results = items;
+                    Ok(None)
+                }
+                Ok(_) => Err("Response is not a DICOM JSON array".to_string()),
+                Err(e) => Err(format!("Response is not valid JSON: {}", e)),
+            }
+        },
+    )
+    .await;
+
+    (check, results)
+}
+
+async fn probe_image(client: &DicomWebClient, feature: Feature, endpoint: String) -> FeatureCheck {
+    probe(client, feature, request("GET", endpoint, "image/jpeg, image/png"), |response| {
+        match header(response, "content-type") {
+            Some(content_type) if content_type.starts_with("image/") => Ok(Some(content_type.to_string())),
+            other => Err(format!(
+                "Expected an image, got {}",
+                other.unwrap_or("no content type")
+            )),
+        }
+    })
+    .await
+    .0
+}
+
+async fn probe_stow(client: &DicomWebClient) -> FeatureCheck {
+    let (study_uid, instance) = match synthetic_instance() {
+        Ok(generated) => generated,
+        Err(e) => {
+            return FeatureCheck {
+                feature: Feature::Stow,
+                status: SupportStatus::Failed,
+                http_status: None,
+                detail: Some(format!("Could not build test instance: {}", e)),
+                elapsed_ms: 0,
+            }
+        }
+    };
+
+    let request = super::stow::store_request(None, vec![instance]);
+    probe(client, Feature::Stow, request, |response| {
+        if response.body.trim().is_empty() {
+            return Ok(None);
+        }
+        let value: Value = serde_json::from_str(&response.body)
+            .map_err(|e| format!("Store response is not valid JSON: {}", e))?;
+        let stored = super::stow::StowResponse::from_json(&value);
+        match stored.failed.first() {
+            Some(failed) => Err(failed.reason.clone()),
+            None => Ok(Some(format!("Stored test study {}", study_uid))),
+        }
+    })
+    .await
+    .0
+}
+
+/// A minimal 8x8 Secondary Capture instance with freshly generated UIDs
+fn synthetic_instance() -> Result<(String, Vec<u8>)> {
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_object::InMemDicomObject;
+
+    let uid = || format!("2.25.{}", uuid::Uuid::new_v4().as_u128());
+    let study_uid = uid();
+    let today = chrono::Local::now().format("%Y%m%d").to_string();
+
+    let obj = InMemDicomObject::from_element_iter([
+        DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)),
+        DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(uid())),
+        DataElement::new(tags::STUDY_DATE, VR::DA, PrimitiveValue::from(today)),
+        DataElement::new(tags::MODALITY, VR::CS, PrimitiveValue::from("OT")),
+        DataElement::new(tags::CONVERSION_TYPE, VR::CS, PrimitiveValue::from("WSD")),
+        DataElement::new(tags::SERIES_DESCRIPTION, VR::LO, PrimitiveValue::from("DICOMweb capability probe")),
+        DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("DICOMFLOW^PROBE")),
+        DataElement::new(tags::PATIENT_ID, VR::LO, PrimitiveValue::from("DICOMFLOW-PROBE")),
+        DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from(study_uid.clone())),
+        DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from(uid())),
+        DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, PrimitiveValue::from(1_u16)),
+        DataElement::new(tags::PHOTOMETRIC_INTERPRETATION, VR::CS, PrimitiveValue::from("MONOCHROME2")),
+        DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(8_u16)),
+        DataElement::new(tags::COLUMNS, VR::US, PrimitiveValue::from(8_u16)),
+        DataElement::new(tags::BITS_ALLOCATED, VR::US, PrimitiveValue::from(8_u16)),
+        DataElement::new(tags::BITS_STORED, VR::US, PrimitiveValue::from(8_u16)),
+        DataElement::new(tags::HIGH_BIT, VR::US, PrimitiveValue::from(7_u16)),
+        DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, PrimitiveValue::from(0_u16)),
+        DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(vec![0_u8; 64])),
+    ]);
+
+    let meta = crate::dicom::build_file_meta(&obj, uids::EXPLICIT_VR_LITTLE_ENDIAN)?;
+    let file_obj = obj.with_exact_meta(meta);
+    let mut bytes = Vec::new();
+    file_obj.write_all(&mut bytes)?;
+
+    Ok((study_uid, bytes))
+}
+
+/// Ask the service roots for a capabilities document (PS3.18 8.9) and list the
+/// resources it advertises. Servers without one are not an error.
+async fn fetch_capabilities_document(client: &DicomWebClient) -> (Option<String>, Vec<String>) {
+    let mut document_type = None;
+    let mut resources: Vec<String> = Vec::new();
+
+    for root in ["", "qido-rs", "wado-rs", "stow-rs"] {
+        let request = request(
+            "OPTIONS",
+            root.to_string(),
+            "application/vnd.sun.wadl+xml, application/dicom+json, application/json",
+        );
+        let Ok(response) = client.execute(request).await else {
+            continue;
+        };
+        if !(200..300).contains(&response.status) || response.body.trim().is_empty() {
+            continue;
+        }
+
+        let content_type = header(&response, "content-type").unwrap_or("").to_string();
+        let found = if content_type.contains("wadl") || response.body.contains("<application") {
+            wadl_resources(&response.body)
+        } else {
+            Vec::new()
+        };
+
+        if document_type.is_none() {
+            document_type = Some(content_type);
+        }
+        for resource in found {
+            if !resources.contains(&resource) {
+                resources.push(resource);
+            }
+        }
+    }
+
+    (document_type, resources)
+}
+
+/// Full paths of the `<resource path="...">` elements of a WADL document
+fn wadl_resources(wadl: &str) -> Vec<String> {
+    let mut stack: Vec<String> = Vec::new();
+    let mut paths = Vec::new();
+    let mut rest = wadl;
+
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        // Ignore namespace prefixes such as <wadl:resource>
+        let name_end = tag.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(tag.len());
+        let name = tag[..name_end].rsplit(':').next().unwrap_or("");
+
+        if name == "resource" {
+            let path = attribute(tag, "path").unwrap_or_default();
+            stack.push(path.trim_matches('/').to_string());
+            let full = stack.iter().filter(|p| !p.is_empty()).cloned().collect::<Vec<_>>().join("/");
+            if !full.is_empty() {
+                paths.push(full);
+            }
+            if tag.ends_with('/') {
+                stack.pop();
+            }
+        } else if tag.starts_with('/') && tag[1..].rsplit(':').next() == Some("resource") {
+            stack.pop();
+        }
+    }
+
+    paths
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')?;
+    Some(tag[start..start + end].to_string())
+}
+
+fn first_value(results: &[Value], tag: &str) -> Option<String> {
+    results
+        .first()?
+        .get(tag)?
+        .get("Value")?
+        .get(0)?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicomweb::DicomWebEndpoint;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_wadl_resources() {
+        let wadl = r#"<?xml version="1.0"?>
+            <application xmlns="http://wadl.dev.java.net/2009/02">
+              <resources base="http://pacs/dicomweb">
+                <resource path="studies">
+                  <method name="GET"/>
+                  <resource path="{study}">
+                    <resource path="metadata"/>
+                  </resource>
+                </resource>
+              </resources>
+            </application>"#;
+
+        assert_eq!(
+            wadl_resources(wadl),
+            vec!["studies", "studies/{study}", "studies/{study}/metadata"]
+        );
+    }
+
+    #[test]
+    fn test_synthetic_instance() {
+        let (study_uid, bytes) = synthetic_instance().unwrap();
+        assert_eq!(&bytes[128..132], b"DICM");
+
+        let obj = crate::dicom::read_dicom_bytes(&bytes).unwrap();
+        let study = obj.element_by_name("StudyInstanceUID").unwrap().to_str().unwrap().to_string();
+        assert_eq!(study.trim_end_matches('\0'), study_uid);
+    }
+
+    #[tokio::test]
+    async fn test_inspect_report() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "0020000D": { "vr": "UI", "Value": ["1.2.3"] } }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies/1.2.3/series"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "0020000E": { "vr": "UI", "Value": ["1.2.3.4"] } }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies/1.2.3/series/1.2.3.4/instances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "00080018": { "vr": "UI", "Value": ["1.2.3.4.5"] } }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wado-rs/studies/1.2.3/series/1.2.3.4/instances/1.2.3.4.5/metadata"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/wado-rs/studies/1.2.3/series/1.2.3.4/instances/1.2.3.4.5/rendered"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0xFF, 0xD8], "image/jpeg"))
+            .mount(&server)
+            .await;
+        // Thumbnails are not mounted, so the mock server answers 404
+
+        let client = DicomWebClient::new(DicomWebEndpoint::for_test(server.uri())).unwrap();
+        let report = inspect(&client, &InspectOptions::default()).await.unwrap();
+
+        assert_eq!(report.supports(Feature::QidoStudies), Some(true));
+        assert_eq!(report.supports(Feature::QidoInstances), Some(true));
+        assert_eq!(report.supports(Feature::WadoMetadata), Some(true));
+        assert_eq!(report.supports(Feature::WadoRendered), Some(true));
+        assert_eq!(report.supports(Feature::WadoThumbnail), Some(false));
+        assert_eq!(report.supports(Feature::Stow), None);
+        assert!(report.advertised_resources.is_empty());
+    }
+}
@@ -1,77 +1,355 @@
 // DICOMweb HTTP client
 
-use super::{AuthType, DicomWebEndpoint, DicomWebRequest, DicomWebResponse};
+use super::http::RetryPolicy;
+use super::{AuthType, DicomWebEndpoint, DicomWebRawResponse, DicomWebRequest, DicomWebResponse};
+use crate::database::DbPool;
 use anyhow::Result;
 use reqwest::Client;
 use std::collections::HashMap;
+use std::time::Duration;
 
+#[derive(Clone)]
 pub struct DicomWebClient {
     client: Client,
     endpoint: DicomWebEndpoint,
+    history: Option<DbPool>,
 }
 
 impl DicomWebClient {
-    pub fn new(endpoint: DicomWebEndpoint) -> Self {
-        Self {
-            client: Client::new(),
+    /// Create a client for an endpoint; fails if its proxy or TLS settings are invalid
+    pub fn new(endpoint: DicomWebEndpoint) -> Result<Self> {
+        Ok(Self {
+            client: super::http::build_client(&endpoint.http)?,
             endpoint,
-        }
+            history: None,
+        })
+    }
+
+    /// Record every request made by this client in the request history
+    pub fn with_history(mut self, pool: DbPool) -> Self {
+        self.history = Some(pool);
+        self
+    }
+
+    /// Endpoint this client talks to
+    pub fn endpoint(&self) -> &DicomWebEndpoint {
+        &self.endpoint
     }
 
     /// Execute a DICOMweb request
     pub async fn execute(&self, request: DicomWebRequest) -> Result<DicomWebResponse> {
-        let url = format!("{}/{}", self.endpoint.base_url, request.endpoint);
+        let raw = self.execute_raw(request).await?;
+
+        Ok(DicomWebResponse {
+            status: raw.status,
+            headers: raw.headers,
+            body: String::from_utf8_lossy(&raw.body).into_owned(),
+        })
+    }
+
+    /// Execute a DICOMweb request, keeping the response body as bytes
+    pub async fn execute_raw(&self, request: DicomWebRequest) -> Result<DicomWebRawResponse> {
+        let start = std::time::Instant::now();
+        let mut result = self.send_with_retry(&request).await;
+
+        // A rejected OAuth2 token may have been revoked early; renew once and retry
+        if matches!(&result, Ok(r) if r.status == 401) && self.endpoint.auth_type.is_oauth2() {
+            tracing::info!("Access token rejected by {}, renewing", self.endpoint.name);
+            super::auth::invalidate(&self.endpoint.auth_type).await;
+            result = self.send_with_retry(&request).await;
+        }
+
+        if let Some(pool) = &self.history {
+            let entry = super::history::HistoryEntry::new(&self.endpoint, &request, &result, start.elapsed());
+            if let Err(e) = super::history::record(pool, &entry).await {
+                tracing::warn!("Failed to record request history: {}", e);
+            }
+        }
+
+        result
+    }
+
+    /// Send a request, retrying while the server answers 429 or 503
+    async fn send_with_retry(&self, request: &DicomWebRequest) -> Result<DicomWebRawResponse> {
+        let policy = &self.endpoint.http.retry;
+        let mut attempt = 0;
+
+        loop {
+            let response = self.send(request).await?;
+            if attempt >= policy.max_retries || !RetryPolicy::should_retry(response.status) {
+                return Ok(response);
+            }
+
+            let retry_after = response
+                .headers
+                .get("retry-after")
+                .and_then(|value| super::http::parse_retry_after(value));
+            let delay = policy.delay(attempt, retry_after);
+            tracing::info!(
+                "{} answered {}, retrying in {} ms",
+                self.endpoint.name,
+                response.status,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send(&self, request: &DicomWebRequest) -> Result<DicomWebRawResponse> {
+        let url = super::resolve_url(&self.endpoint.base_url, &request.endpoint);
 
         let mut req = match request.method.as_str() {
             "GET" => self.client.get(&url),
             "POST" => self.client.post(&url),
+            "PUT" => self.client.put(&url),
             "DELETE" => self.client.delete(&url),
+            "OPTIONS" => self.client.request(reqwest::Method::OPTIONS, &url),
             _ => return Err(anyhow::anyhow!("Unsupported method: {}", request.method)),
         };
 
-        // Add authentication
-        req = self.add_auth(req);
-
-        // Add headers
-        for (key, value) in &request.headers {
-            req = req.header(key, value);
-        }
+        req = req.headers(self.headers(request).await?);
 
         // Add body if present
-        if let Some(body) = request.body {
-            req = req.body(body);
+        if let Some(body) = &request.body {
+            req = req.body(body.clone());
         }
 
-        // Execute request
-        let response = req.send().await?;
+        // Execute request; the read timeout applies to the response head and to each body chunk
+        let read_timeout = self.endpoint.http.read_timeout();
+        let mut response = within(read_timeout, req.send()).await?;
         let status = response.status().as_u16();
         let headers: HashMap<String, String> = response
             .headers()
             .iter()
             .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
             .collect();
-        let body = response.text().await?;
+        let mut body = Vec::new();
+        while let Some(chunk) = within(read_timeout, response.chunk()).await? {
+            body.extend_from_slice(&chunk);
+        }
 
-        Ok(DicomWebResponse {
+        Ok(DicomWebRawResponse {
             status,
             headers,
             body,
         })
     }
 
-    fn add_auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        match &self.endpoint.auth_type {
-            AuthType::None => req,
-            AuthType::Basic { username, password } => req.basic_auth(username, Some(password)),
-            AuthType::Bearer { token } => req.bearer_auth(token),
-            AuthType::Custom => req, // Custom headers already added
+    /// Value of the Authorization header for this endpoint, if its auth type uses one.
+    /// Also used for connections made outside reqwest, such as WebSockets.
+    pub async fn authorization(&self) -> Result<Option<String>> {
+        use base64::Engine;
+
+        Ok(match &self.endpoint.auth_type {
+            AuthType::None => None,
+            AuthType::Basic { username, password } => {
+                let credentials = format!("{}:{}", username, password);
+                Some(format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(credentials)
+                ))
+            }
+            AuthType::Bearer { token } => Some(format!("Bearer {}", token)),
+            auth @ (AuthType::OAuth2ClientCredentials { .. } | AuthType::OAuth2RefreshToken { .. }) => {
+                let token = super::auth::access_token(&self.client, auth).await?;
+                Some(format!("Bearer {}", token))
+            }
+            AuthType::Custom => None, // Custom headers are added from the endpoint
+        })
+    }
+
+    /// Headers of a request: the endpoint's, then authentication, then the
+    /// request's own, each replacing any earlier value of the same name
+    async fn headers(&self, request: &DicomWebRequest) -> Result<reqwest::header::HeaderMap> {
+        use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+
+        let mut headers = HeaderMap::new();
+        let mut insert = |key: &str, value: &str| -> Result<()> {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|_| anyhow::anyhow!("Invalid header name: {}", key))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| anyhow::anyhow!("Invalid value for header {}", key))?;
+            headers.insert(name, value);
+            Ok(())
+        };
+
+        for (key, value) in &self.endpoint.headers {
+            insert(key, value)?;
+        }
+        if let Some(value) = self.authorization().await? {
+            insert(AUTHORIZATION.as_str(), &value)?;
+        }
+        for (key, value) in &request.headers {
+            insert(key, value)?;
         }
+        Ok(headers)
+    }
+}
+
+/// Await a reqwest future, failing if it takes longer than `limit`
+async fn within<T>(
+    limit: Option<Duration>,
+    future: impl std::future::Future<Output = reqwest::Result<T>>,
+) -> Result<T> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, future)
+            .await
+            .map_err(|_| anyhow::anyhow!("Server sent no data for {} seconds", limit.as_secs()))?
+            .map_err(Into::into),
+        None => Ok(future.await?),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_endpoint_headers_applied() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .and(header("X-Api-Key", "key123"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "key123".to_string());
+        let client = DicomWebClient::new(DicomWebEndpoint {
+            auth_type: AuthType::Custom,
+            headers,
+            ..DicomWebEndpoint::for_test(server.uri())
+        }).unwrap();
+
+        let response = client
+            .execute(DicomWebRequest {
+                method: "GET".to_string(),
+                endpoint: "qido-rs/studies".to_string(),
+                headers: HashMap::new(),
+                body: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_headers_replace_instead_of_repeating() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let headers = HashMap::from([
+            ("Authorization".to_string(), "Bearer stale".to_string()),
+            ("Accept".to_string(), "application/json".to_string()),
+        ]);
+        let client = DicomWebClient::new(DicomWebEndpoint {
+            auth_type: AuthType::Bearer { token: "current".to_string() },
+            headers,
+            ..DicomWebEndpoint::for_test(server.uri())
+        }).unwrap();
+
+        client
+            .execute(DicomWebRequest {
+                method: "GET".to_string(),
+                endpoint: "qido-rs/studies".to_string(),
+                headers: HashMap::from([("accept".to_string(), "application/dicom+json".to_string())]),
+                body: None,
+            })
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let values = |name: &str| -> Vec<String> {
+            requests[0]
+                .headers
+                .iter()
+                .filter(|(key, _)| key.as_str() == name)
+                .flat_map(|(_, values)| values.iter().map(|v| v.as_str().to_string()))
+                .collect()
+        };
+        assert_eq!(values("authorization"), ["Bearer current"]);
+        assert_eq!(values("accept"), ["application/dicom+json"]);
+    }
 
-    // Add tests with mock HTTP server
+    #[tokio::test]
+    async fn test_oauth2_bearer_applied() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "tok-1",
+                "expires_in": 3600
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .and(header("Authorization", "Bearer tok-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = DicomWebClient::new(DicomWebEndpoint {
+            auth_type: AuthType::OAuth2ClientCredentials {
+                token_url: format!("{}/token", server.uri()),
+                client_id: "client-test-oauth2-bearer".to_string(),
+                client_secret: "secret".to_string(),
+                scope: Some("dicomweb".to_string()),
+            },
+            ..DicomWebEndpoint::for_test(server.uri())
+        }).unwrap();
+
+        let response = client
+            .execute(DicomWebRequest {
+                method: "GET".to_string(),
+                endpoint: "qido-rs/studies".to_string(),
+                headers: HashMap::new(),
+                body: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_throttling() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = DicomWebClient::new(DicomWebEndpoint::for_test(server.uri())).unwrap();
+
+        let response = client
+            .execute(DicomWebRequest {
+                method: "GET".to_string(),
+                endpoint: "qido-rs/studies".to_string(),
+                headers: HashMap::new(),
+                body: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+    }
 }
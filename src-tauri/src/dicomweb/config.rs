@@ -1,11 +1,18 @@
 // DICOMweb configuration utilities
 
-use super::DicomWebEndpoint;
+use super::http::ClientCertificate;
+use super::{AuthType, DicomWebEndpoint};
 use crate::database::DbPool;
+use crate::dimse::PacsEndpoint;
+use crate::utils::secrets::{self, SecretVault};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
-/// Load all saved DICOMweb endpoints
-pub async fn load_dicomweb_endpoints(pool: &DbPool) -> Result<Vec<DicomWebEndpoint>> {
+/// Prefix of secrets re-encrypted with an export passphrase
+const EXPORT_ENC_PREFIX: &str = "enc:";
+
+/// Load all saved DICOMweb endpoints with their secrets decrypted
+pub async fn load_dicomweb_endpoints(pool: &DbPool, vault: &SecretVault) -> Result<Vec<DicomWebEndpoint>> {
     let rows = sqlx::query_scalar::<_, String>(
         "SELECT config_json FROM connections WHERE connection_type = 'dicomweb' ORDER BY name"
     )
@@ -15,36 +22,46 @@ pub async fn load_dicomweb_endpoints(pool: &DbPool) -> Result<Vec<DicomWebEndpoi
     let mut endpoints = Vec::new();
     for json_str in rows {
         let endpoint: DicomWebEndpoint = serde_json::from_str(&json_str)?;
-        endpoints.push(endpoint);
+        endpoints.push(unseal_endpoint(pool, vault, endpoint).await?);
     }
 
     Ok(endpoints)
 }
 
-/// Save a DICOMweb endpoint
-pub async fn save_dicomweb_endpoint(pool: &DbPool, endpoint: &DicomWebEndpoint) -> Result<()> {
-    let json_str = serde_json::to_string(endpoint)?;
+/// Save a DICOMweb endpoint, moving its secrets into the encrypted store
+pub async fn save_dicomweb_endpoint(pool: &DbPool, vault: &SecretVault, endpoint: &DicomWebEndpoint) -> Result<()> {
+    let previous_refs = stored_references(pool, &endpoint.name).await?;
+    let sealed = seal_endpoint(pool, vault, endpoint.clone()).await?;
+    let json_str = serde_json::to_string(&sealed)?;
 
     // Insert or update based on name
     sqlx::query(
         "INSERT INTO connections (name, connection_type, config_json, created_at, updated_at)
          VALUES (?, 'dicomweb', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
-         ON CONFLICT(id) DO UPDATE SET
+         ON CONFLICT(connection_type, name) DO UPDATE SET
              config_json = excluded.config_json,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE name = ? AND connection_type = 'dicomweb'"
+             updated_at = CURRENT_TIMESTAMP"
     )
     .bind(&endpoint.name)
     .bind(&json_str)
-    .bind(&endpoint.name)
     .execute(pool)
     .await?;
 
+    // Drop secrets that the new config no longer references
+    let current_refs = references(&sealed);
+    for reference in previous_refs {
+        if !current_refs.contains(&reference) {
+            vault.delete(pool, &reference).await?;
+        }
+    }
+
     Ok(())
 }
 
 /// Delete a DICOMweb endpoint
-pub async fn delete_dicomweb_endpoint(pool: &DbPool, name: &str) -> Result<()> {
+pub async fn delete_dicomweb_endpoint(pool: &DbPool, vault: &SecretVault, name: &str) -> Result<()> {
+    let refs = stored_references(pool, name).await?;
+
     sqlx::query(
         "DELETE FROM connections WHERE name = ? AND connection_type = 'dicomweb'"
     )
@@ -52,5 +69,481 @@ pub async fn delete_dicomweb_endpoint(pool: &DbPool, name: &str) -> Result<()> {
     .execute(pool)
     .await?;
 
+    for reference in refs {
+        vault.delete(pool, &reference).await?;
+    }
+
     Ok(())
 }
+
+/// Move plaintext secrets left in saved configs (written before secrets were
+/// encrypted) into the encrypted store; returns how many endpoints were sealed
+pub async fn seal_stored_endpoints(pool: &DbPool, vault: &SecretVault) -> Result<usize> {
+    let rows = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, config_json FROM connections WHERE connection_type = 'dicomweb'"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut sealed_count = 0;
+    for (id, json_str) in rows {
+        let Ok(endpoint) = serde_json::from_str::<DicomWebEndpoint>(&json_str) else {
+            continue;
+        };
+        let original = serde_json::to_string(&endpoint)?;
+        let sealed = serde_json::to_string(&seal_endpoint(pool, vault, endpoint).await?)?;
+        if sealed == original {
+            continue;
+        }
+
+        sqlx::query("UPDATE connections SET config_json = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(&sealed)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        sealed_count += 1;
+    }
+
+    Ok(sealed_count)
+}
+
+/// Write a refresh token rotated by the identity provider back to every saved
+/// endpoint that still holds a token it replaces; returns how many were updated
+pub async fn store_rotated_refresh_token(
+    pool: &DbPool,
+    vault: &SecretVault,
+    rotated: &super::auth::RotatedRefreshToken,
+) -> Result<usize> {
+    let rows = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, config_json FROM connections WHERE connection_type = 'dicomweb'"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut updated = 0;
+    for (id, json_str) in rows {
+        let Ok(mut endpoint) = serde_json::from_str::<DicomWebEndpoint>(&json_str) else {
+            continue;
+        };
+        let AuthType::OAuth2RefreshToken { token_url, client_id, refresh_token, .. } = &mut endpoint.auth_type else {
+            continue;
+        };
+        if *token_url != rotated.token_url || *client_id != rotated.client_id {
+            continue;
+        }
+
+        let stored = refresh_token.clone();
+        let current = if secrets::is_reference(&stored) {
+            vault.resolve(pool, &stored).await?
+        } else {
+            stored.clone()
+        };
+        if !rotated.replaces(&current) {
+            continue;
+        }
+
+        *refresh_token = vault.store(pool, &rotated.refresh_token).await?;
+        sqlx::query("UPDATE connections SET config_json = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(serde_json::to_string(&endpoint)?)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if secrets::is_reference(&stored) {
+            vault.delete(pool, &stored).await?;
+        }
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+/// Mutable references to every secret-bearing field of an endpoint
+fn secret_fields_mut(endpoint: &mut DicomWebEndpoint) -> Vec<&mut String> {
+    let mut fields: Vec<&mut String> = match &mut endpoint.auth_type {
+        AuthType::Basic { password, .. } => vec![password],
+        AuthType::Bearer { token } => vec![token],
+        AuthType::OAuth2ClientCredentials { client_secret, .. } => vec![client_secret],
+        AuthType::OAuth2RefreshToken { client_secret, refresh_token, .. } => {
+            let mut fields = vec![refresh_token];
+            if let Some(secret) = client_secret {
+                fields.push(secret);
+            }
+            fields
+        }
+        AuthType::None | AuthType::Custom => Vec::new(),
+    };
+
+    for (name, value) in endpoint.headers.iter_mut() {
+        if super::is_sensitive_header(name) {
+            fields.push(value);
+        }
+    }
+
+    if let Some(password) = endpoint.http.proxy.as_mut().and_then(|p| p.password.as_mut()) {
+        fields.push(password);
+    }
+    if let Some(ClientCertificate::Pkcs12 { password, .. }) = &mut endpoint.http.client_certificate {
+        fields.push(password);
+    }
+
+    fields
+}
+
+/// Secret references held by a sealed endpoint
+pub fn references(endpoint: &DicomWebEndpoint) -> Vec<String> {
+    let mut endpoint = endpoint.clone();
+    secret_fields_mut(&mut endpoint)
+        .into_iter()
+        .filter(|v| secrets::is_reference(v))
+        .map(|v| v.clone())
+        .collect()
+}
+
+async fn stored_references(pool: &DbPool, name: &str) -> Result<Vec<String>> {
+    let existing = sqlx::query_scalar::<_, String>(
+        "SELECT config_json FROM connections WHERE name = ? AND connection_type = 'dicomweb'"
+    )
+    .bind(name)
+    .fetch_all(pool)
+    .await?;
+
+    let mut refs = Vec::new();
+    for json_str in existing {
+        if let Ok(endpoint) = serde_json::from_str::<DicomWebEndpoint>(&json_str) {
+            refs.extend(references(&endpoint));
+        }
+    }
+    Ok(refs)
+}
+
+/// Replace plaintext secrets with references into the encrypted store
+pub async fn seal_endpoint(pool: &DbPool, vault: &SecretVault, mut endpoint: DicomWebEndpoint) -> Result<DicomWebEndpoint> {
+    for field in secret_fields_mut(&mut endpoint) {
+        if !field.is_empty() && !secrets::is_reference(field) {
+            *field = vault.store(pool, field).await?;
+        }
+    }
+    Ok(endpoint)
+}
+
+/// Replace secret references with their decrypted values
+pub async fn unseal_endpoint(pool: &DbPool, vault: &SecretVault, mut endpoint: DicomWebEndpoint) -> Result<DicomWebEndpoint> {
+    for field in secret_fields_mut(&mut endpoint) {
+        if secrets::is_reference(field) {
+            *field = vault.resolve(pool, field).await?;
+        }
+    }
+    Ok(endpoint)
+}
+
+/// Keep the stored secret wherever an edited endpoint left a secret field blank,
+/// so redacted configs from the frontend can be saved without re-entering secrets
+pub fn merge_secrets(mut edited: DicomWebEndpoint, stored: &DicomWebEndpoint) -> DicomWebEndpoint {
+    let mut stored = stored.clone();
+
+    if std::mem::discriminant(&edited.auth_type) == std::mem::discriminant(&stored.auth_type) {
+        let mut edited_auth = DicomWebEndpoint {
+            headers: Default::default(),
+            http: Default::default(),
+            ..edited.clone()
+        };
+        let mut stored_auth = DicomWebEndpoint {
+            headers: Default::default(),
+            http: Default::default(),
+            ..stored.clone()
+        };
+        for (new, old) in secret_fields_mut(&mut edited_auth)
+            .into_iter()
+            .zip(secret_fields_mut(&mut stored_auth))
+        {
+            if new.is_empty() {
+                *new = old.clone();
+            }
+        }
+        edited.auth_type = edited_auth.auth_type;
+    }
+
+    for (name, value) in edited.headers.iter_mut() {
+        if value.is_empty() {
+            if let Some(old) = stored.headers.remove(name) {
+                *value = old;
+            }
+        }
+    }
+
+    if let (Some(new), Some(old)) = (&mut edited.http.proxy, &stored.http.proxy) {
+        if new.password.as_deref() == Some("") {
+            new.password = old.password.clone();
+        }
+    }
+    if let (
+        Some(ClientCertificate::Pkcs12 { password: new, .. }),
+        Some(ClientCertificate::Pkcs12 { password: old, .. }),
+    ) = (&mut edited.http.client_certificate, &stored.http.client_certificate)
+    {
+        if new.is_empty() {
+            *new = old.clone();
+        }
+    }
+
+    edited
+}
+
+/// Blank out all secrets, e.g. before sending a config to the frontend or a file
+pub fn redact_endpoint(mut endpoint: DicomWebEndpoint) -> DicomWebEndpoint {
+    for field in secret_fields_mut(&mut endpoint) {
+        field.clear();
+    }
+    endpoint
+}
+
+/// How secrets are represented in an exported profile file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExportedSecrets {
+    /// Secrets were removed and must be re-entered after import
+    Redacted,
+    /// Secrets are encrypted with a key derived from an export passphrase and this salt
+    Encrypted { salt: String },
+}
+
+/// Portable file format for sharing connection profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionProfiles {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub secrets: ExportedSecrets,
+    pub dicomweb: Vec<DicomWebEndpoint>,
+    pub dimse: Vec<PacsEndpoint>,
+}
+
+/// Export all saved connections. With a passphrase the secrets are re-encrypted
+/// under that passphrase; without one they are redacted.
+pub async fn export_profiles(
+    pool: &DbPool,
+    vault: &SecretVault,
+    passphrase: Option<&str>,
+) -> Result<ConnectionProfiles> {
+    let endpoints = load_dicomweb_endpoints(pool, vault).await?;
+    let dimse = crate::dimse::config::load_pacs_endpoints(pool).await?;
+
+    let (secrets_mode, dicomweb) = match passphrase {
+        Some(passphrase) => {
+            let salt = secrets::random_token(16);
+            let key = secrets::derive_key(passphrase, salt.as_bytes())?;
+
+            let mut encrypted = Vec::new();
+            for mut endpoint in endpoints {
+                for field in secret_fields_mut(&mut endpoint) {
+                    if !field.is_empty() {
+                        *field = format!("{}{}", EXPORT_ENC_PREFIX, secrets::encrypt(&key, field)?);
+                    }
+                }
+                encrypted.push(endpoint);
+            }
+            (ExportedSecrets::Encrypted { salt }, encrypted)
+        }
+        None => (
+            ExportedSecrets::Redacted,
+            endpoints.into_iter().map(redact_endpoint).collect(),
+        ),
+    };
+
+    Ok(ConnectionProfiles {
+        format_version: 1,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        secrets: secrets_mode,
+        dicomweb,
+        dimse,
+    })
+}
+
+/// Outcome of a profile import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileImport {
+    pub imported: usize,
+    /// Names of connections that were not imported because one with that name already exists
+    pub skipped: Vec<String>,
+}
+
+async fn connection_names(pool: &DbPool, connection_type: &str) -> Result<std::collections::HashSet<String>> {
+    let names = sqlx::query_scalar::<_, String>("SELECT name FROM connections WHERE connection_type = ?")
+        .bind(connection_type)
+        .fetch_all(pool)
+        .await?;
+    Ok(names.into_iter().collect())
+}
+
+/// Import connections from an exported profile file, storing secrets in the local vault.
+/// Existing connections are never overwritten; imports whose name is taken are skipped
+/// and reported.
+pub async fn import_profiles(
+    pool: &DbPool,
+    vault: &SecretVault,
+    profiles: ConnectionProfiles,
+    passphrase: Option<&str>,
+) -> Result<ProfileImport> {
+    let key = match (&profiles.secrets, passphrase) {
+        (ExportedSecrets::Encrypted { salt }, Some(passphrase)) => {
+            Some(secrets::derive_key(passphrase, salt.as_bytes())?)
+        }
+        (ExportedSecrets::Encrypted { .. }, None) => {
+            return Err(anyhow::anyhow!("This profile file is encrypted; a passphrase is required"));
+        }
+        (ExportedSecrets::Redacted, _) => None,
+    };
+
+    let mut result = ProfileImport { imported: 0, skipped: Vec::new() };
+
+    let mut taken = connection_names(pool, "dicomweb").await?;
+    for mut endpoint in profiles.dicomweb {
+        if !taken.insert(endpoint.name.clone()) {
+            result.skipped.push(endpoint.name);
+            continue;
+        }
+        for field in secret_fields_mut(&mut endpoint) {
+            let decrypted = match (field.strip_prefix(EXPORT_ENC_PREFIX), &key) {
+                (Some(encrypted), Some(key)) => Some(
+                    secrets::decrypt(key, encrypted)
+                        .map_err(|_| anyhow::anyhow!("Wrong passphrase for this profile file"))?,
+                ),
+                _ => None,
+            };
+
+            if let Some(plaintext) = decrypted {
+                *field = plaintext;
+            } else if secrets::is_reference(field) {
+                // References are only meaningful in the database that created them
+                field.clear();
+            }
+        }
+        save_dicomweb_endpoint(pool, vault, &endpoint).await?;
+        result.imported += 1;
+    }
+
+    let mut taken = connection_names(pool, "dimse").await?;
+    for endpoint in profiles.dimse {
+        if !taken.insert(endpoint.name.clone()) {
+            result.skipped.push(endpoint.name);
+            continue;
+        }
+        crate::dimse::config::save_pacs_endpoint(pool, &endpoint).await?;
+        result.imported += 1;
+    }
+
+    if !result.skipped.is_empty() {
+        tracing::warn!("Skipped importing existing connections: {}", result.skipped.join(", "));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_secret_fields_and_redaction() {
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "k".to_string());
+        headers.insert("Accept-Language".to_string(), "en".to_string());
+
+        let endpoint = DicomWebEndpoint {
+            name: "test".to_string(),
+            base_url: "http://localhost".to_string(),
+            auth_type: AuthType::Basic {
+                username: "user".to_string(),
+                password: "pass".to_string(),
+            },
+            headers,
+            wado_uri: None,
+            http: Default::default(),
+            capabilities: None,
+        };
+
+        let redacted = redact_endpoint(endpoint);
+        match &redacted.auth_type {
+            AuthType::Basic { username, password } => {
+                assert_eq!(username, "user");
+                assert!(password.is_empty());
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(redacted.headers["X-Api-Key"], "");
+        assert_eq!(redacted.headers["Accept-Language"], "en");
+    }
+
+    #[test]
+    fn test_transport_secrets_redacted_and_merged() {
+        use crate::dicomweb::http::{HttpOptions, ProxyConfig};
+
+        let stored = DicomWebEndpoint {
+            name: "test".to_string(),
+            base_url: "https://pacs".to_string(),
+            auth_type: AuthType::None,
+            headers: HashMap::new(),
+            wado_uri: None,
+            http: HttpOptions {
+                proxy: Some(ProxyConfig {
+                    url: "http://proxy:3128".to_string(),
+                    username: Some("user".to_string()),
+                    password: Some("proxy-pass".to_string()),
+                    no_proxy: None,
+                }),
+                client_certificate: Some(ClientCertificate::Pkcs12 {
+                    path: "/certs/client.p12".to_string(),
+                    password: "p12-pass".to_string(),
+                }),
+                ..Default::default()
+            },
+            capabilities: None,
+        };
+
+        let redacted = redact_endpoint(stored.clone());
+        assert_eq!(redacted.http.proxy.as_ref().unwrap().password.as_deref(), Some(""));
+
+        let merged = merge_secrets(redacted, &stored);
+        assert_eq!(merged.http.proxy.unwrap().password.as_deref(), Some("proxy-pass"));
+        match merged.http.client_certificate {
+            Some(ClientCertificate::Pkcs12 { password, .. }) => assert_eq!(password, "p12-pass"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_existing_names() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let vault = SecretVault::locked();
+
+        let endpoint = |name: &str, base_url: &str| DicomWebEndpoint {
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            auth_type: AuthType::None,
+            headers: HashMap::new(),
+            wado_uri: None,
+            http: Default::default(),
+            capabilities: None,
+        };
+        save_dicomweb_endpoint(&pool, &vault, &endpoint("pacs", "https://local")).await.unwrap();
+
+        let profiles = ConnectionProfiles {
+            format_version: 1,
+            exported_at: String::new(),
+            secrets: ExportedSecrets::Redacted,
+            dicomweb: vec![endpoint("pacs", "https://imported"), endpoint("archive", "https://archive")],
+            dimse: Vec::new(),
+        };
+        let result = import_profiles(&pool, &vault, profiles, None).await.unwrap();
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.skipped, vec!["pacs".to_string()]);
+
+        let endpoints = load_dicomweb_endpoints(&pool, &vault).await.unwrap();
+        let pacs = endpoints.iter().find(|e| e.name == "pacs").unwrap();
+        assert_eq!(pacs.base_url, "https://local");
+    }
+}
@@ -0,0 +1,418 @@
+// DICOMweb request history - recording, filtering and export for replay
+
+use super::{AuthType, DicomWebEndpoint, DicomWebRawResponse, DicomWebRequest};
+use crate::database::models::Request;
+use crate::database::DbPool;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Request and response bodies are truncated to this many bytes
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Placeholder written in place of secret header and query parameter values
+pub const MASK: &str = "********";
+
+/// A request/response pair ready to be written to the `requests` table
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub request_type: String,
+    pub connection_name: String,
+    pub base_url: String,
+    pub endpoint: String,
+    pub method: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    /// The body was cut at `MAX_BODY_BYTES` and can't be replayed
+    pub body_truncated: bool,
+    pub response_status: Option<u16>,
+    pub response_headers: Option<BTreeMap<String, String>>,
+    pub response_body: Option<String>,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        endpoint: &DicomWebEndpoint,
+        request: &DicomWebRequest,
+        result: &Result<DicomWebRawResponse>,
+        elapsed: Duration,
+    ) -> Self {
+        let mut headers = BTreeMap::new();
+
+        // Describe the auth that was applied without recording the credential
+        match &endpoint.auth_type {
+            AuthType::None | AuthType::Custom => {}
+            AuthType::Basic { .. } => {
+                headers.insert("Authorization".to_string(), format!("Basic {}", MASK));
+            }
+            _ => {
+                headers.insert("Authorization".to_string(), format!("Bearer {}", MASK));
+            }
+        }
+        for (key, value) in endpoint.headers.iter().chain(request.headers.iter()) {
+            headers.insert(key.clone(), mask_header(key, value));
+        }
+
+        let (response_status, response_headers, response_body, error) = match result {
+            Ok(response) => (
+                Some(response.status),
+                Some(response.headers.iter().map(|(k, v)| (k.clone(), mask_header(k, v))).collect()),
+                Some(summarize_body(response.content_type(), &response.body).0),
+                None,
+            ),
+            Err(e) => (None, None, None, Some(e.to_string())),
+        };
+
+        let body = request.body.as_ref().map(|b| summarize_body(request.content_type(), b));
+
+        Self {
+            request_type: classify(&request.endpoint).to_string(),
+            connection_name: endpoint.name.clone(),
+            base_url: endpoint.base_url.clone(),
+            endpoint: mask_query(&request.endpoint),
+            method: request.method.clone(),
+            headers,
+            body_truncated: body.as_ref().is_some_and(|(_, truncated)| *truncated),
+            body: body.map(|(text, _)| text),
+            response_status,
+            response_headers,
+            response_body,
+            duration_ms: elapsed.as_millis() as i64,
+            error,
+        }
+    }
+}
+
+/// Classify a request path into a service type
+pub fn classify(endpoint: &str) -> &'static str {
+    let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+    if path.starts_with("qido-rs") {
+        "qido"
+    } else if path.starts_with("wado-rs") || query.contains("requestType=WADO") {
+        "wado"
+    } else if path.starts_with("stow-rs") {
+        "stow"
+    } else if path.contains("workitems") {
+        "ups"
+    } else {
+        "other"
+    }
+}
+
+fn mask_header(name: &str, value: &str) -> String {
+    if !super::is_sensitive_header(name) {
+        return value.to_string();
+    }
+    // Keep the auth scheme so the entry still shows how the request authenticated
+    match value.split_once(' ') {
+        Some((scheme, _)) if name.eq_ignore_ascii_case("authorization") => {
+            format!("{} {}", scheme, MASK)
+        }
+        _ => MASK.to_string(),
+    }
+}
+
+/// Query parameters that carry credentials, e.g. `access_token` or `api_key`
+fn is_sensitive_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase().replace('_', "-");
+    super::is_sensitive_header(&name)
+        || name.contains("password")
+        || matches!(name.as_str(), "key" | "sig" | "signature")
+}
+
+/// Mask the values of credential query parameters in a request path
+fn mask_query(endpoint: &str) -> String {
+    let Some((path, query)) = endpoint.split_once('?') else {
+        return endpoint.to_string();
+    };
+
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, _)) if is_sensitive_param(name) => format!("{}={}", name, MASK),
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", path, params.join("&"))
+}
+
+/// Drop masked query parameters so the replaying client's own credentials apply
+fn strip_masked_params(endpoint: &str) -> String {
+    let Some((path, query)) = endpoint.split_once('?') else {
+        return endpoint.to_string();
+    };
+
+    let params: Vec<&str> = query.split('&').filter(|param| !param.contains(MASK)).collect();
+    if params.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, params.join("&"))
+    }
+}
+
+/// Cut text at `MAX_BODY_BYTES`, returning whether anything was cut
+fn truncate(text: &str) -> (String, bool) {
+    if text.len() <= MAX_BODY_BYTES {
+        return (text.to_string(), false);
+    }
+    let mut end = MAX_BODY_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (format!("{}\n... [truncated, {} bytes total]", &text[..end], text.len()), true)
+}
+
+/// Whether bodies of this content type are stored as text rather than summarized
+fn is_textual(content_type: &str) -> bool {
+    content_type.contains("json")
+        || content_type.contains("xml")
+        || content_type.starts_with("text/")
+        || content_type.is_empty()
+}
+
+/// Keep textual bodies (JSON, XML, text) and summarize binary ones;
+/// also returns whether a textual body was truncated
+fn summarize_body(content_type: Option<&str>, body: &[u8]) -> (String, bool) {
+    let content_type = content_type.unwrap_or("");
+
+    match std::str::from_utf8(body) {
+        Ok(text) if is_textual(content_type) => truncate(text),
+        _ => (format!("<{} bytes of {}>", body.len(), content_type), false),
+    }
+}
+
+/// Insert an entry into the `requests` table
+pub async fn record(pool: &DbPool, entry: &HistoryEntry) -> Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO requests (request_type, connection_name, base_url, endpoint, method,
+                               headers_json, body, body_truncated, response_status,
+                               response_headers_json, response_body, duration_ms, error)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&entry.request_type)
+    .bind(&entry.connection_name)
+    .bind(&entry.base_url)
+    .bind(&entry.endpoint)
+    .bind(&entry.method)
+    .bind(serde_json::to_string(&entry.headers)?)
+    .bind(&entry.body)
+    .bind(entry.body_truncated)
+    .bind(entry.response_status.map(|s| s as i32))
+    .bind(entry.response_headers.as_ref().map(serde_json::to_string).transpose()?)
+    .bind(&entry.response_body)
+    .bind(entry.duration_ms)
+    .bind(&entry.error)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Filter for listing history entries; all fields are optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilter {
+    pub request_type: Option<String>,
+    pub method: Option<String>,
+    pub connection_name: Option<String>,
+    /// Substring matched against the request path
+    pub search: Option<String>,
+    /// Only failed requests (status >= 400 or transport errors)
+    #[serde(default)]
+    pub errors_only: bool,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// List history entries, newest first
+pub async fn list(pool: &DbPool, filter: &HistoryFilter) -> Result<Vec<Request>> {
+    let search = filter.search.as_ref().map(|s| format!("%{}%", s));
+
+    let rows = sqlx::query_as::<_, Request>(
+        "SELECT * FROM requests
+         WHERE (? IS NULL OR request_type = ?)
+           AND (? IS NULL OR method = ?)
+           AND (? IS NULL OR connection_name = ?)
+           AND (? IS NULL OR endpoint LIKE ?)
+           AND (? = 0 OR error IS NOT NULL OR response_status >= 400)
+         ORDER BY id DESC
+         LIMIT ? OFFSET ?"
+    )
+    .bind(&filter.request_type)
+    .bind(&filter.request_type)
+    .bind(&filter.method)
+    .bind(&filter.method)
+    .bind(&filter.connection_name)
+    .bind(&filter.connection_name)
+    .bind(&search)
+    .bind(&search)
+    .bind(filter.errors_only)
+    .bind(filter.limit.unwrap_or(100) as i64)
+    .bind(filter.offset.unwrap_or(0) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn get(pool: &DbPool, id: i64) -> Result<Request> {
+    sqlx::query_as::<_, Request>("SELECT * FROM requests WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("History entry {} not found", id))
+}
+
+pub async fn clear(pool: &DbPool) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM requests").execute(pool).await?;
+    Ok(result.rows_affected())
+}
+
+/// Rebuild the request of a history entry. Masked headers and query parameters are
+/// dropped so the replaying client's own auth and endpoint headers apply instead.
+pub fn to_request(entry: &Request) -> Result<DicomWebRequest> {
+    let headers: BTreeMap<String, String> = serde_json::from_str(&entry.headers_json)?;
+
+    if entry.body_truncated {
+        return Err(anyhow::anyhow!(
+            "The body of this request was truncated when it was recorded and cannot be replayed"
+        ));
+    }
+
+    // Binary bodies (e.g. STOW-RS uploads) are only kept as a summary
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("");
+    if entry.body.is_some() && !is_textual(content_type) {
+        return Err(anyhow::anyhow!(
+            "The {} body of this request was not recorded and cannot be replayed",
+            content_type
+        ));
+    }
+
+    Ok(DicomWebRequest {
+        method: entry.method.clone(),
+        endpoint: strip_masked_params(&entry.endpoint),
+        headers: headers
+            .into_iter()
+            .filter(|(_, value)| !value.contains(MASK))
+            .collect(),
+        body: entry.body.clone().map(String::into_bytes),
+    })
+}
+
+fn full_url(entry: &Request) -> String {
+    super::resolve_url(&entry.base_url, &entry.endpoint)
+}
+
+/// Render a history entry as a curl command line
+pub fn to_curl(entry: &Request) -> Result<String> {
+    let headers: BTreeMap<String, String> = serde_json::from_str(&entry.headers_json)?;
+
+    let mut parts = vec![format!("curl -X {} {}", entry.method, shell_quote(&full_url(entry)))];
+    for (key, value) in &headers {
+        parts.push(format!("-H {}", shell_quote(&format!("{}: {}", key, value))));
+    }
+    if let Some(body) = &entry.body {
+        parts.push(format!("--data-binary {}", shell_quote(body)));
+    }
+
+    Ok(parts.join(" \\\n  "))
+}
+
+/// Render a history entry in the `.http` request file format
+pub fn to_http_file(entry: &Request) -> Result<String> {
+    let headers: BTreeMap<String, String> = serde_json::from_str(&entry.headers_json)?;
+
+    let mut out = format!("{} {}\n", entry.method, full_url(entry));
+    for (key, value) in &headers {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    if let Some(body) = &entry.body {
+        out.push('\n');
+        out.push_str(body);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(headers: &str) -> Request {
+        Request {
+            id: 1,
+            request_type: "qido".to_string(),
+            connection_name: Some("test".to_string()),
+            base_url: "http://localhost:8080/dicomweb/".to_string(),
+            endpoint: "qido-rs/studies?PatientID=123".to_string(),
+            method: "GET".to_string(),
+            headers_json: headers.to_string(),
+            body: None,
+            body_truncated: false,
+            response_status: Some(200),
+            response_headers_json: None,
+            response_body: Some("[]".to_string()),
+            duration_ms: Some(12),
+            error: None,
+            created_at: "2025-01-22 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("qido-rs/studies?limit=1"), "qido");
+        assert_eq!(classify("wado-rs/studies/1.2/metadata"), "wado");
+        assert_eq!(classify("stow-rs/studies"), "stow");
+        assert_eq!(classify("workitems?00741000=SCHEDULED"), "ups");
+    }
+
+    #[test]
+    fn test_mask_header() {
+        assert_eq!(mask_header("Authorization", "Bearer abc.def"), format!("Bearer {}", MASK));
+        assert_eq!(mask_header("X-Api-Key", "k"), MASK);
+        assert_eq!(mask_header("Accept", "application/dicom+json"), "application/dicom+json");
+    }
+
+    #[test]
+    fn test_mask_query() {
+        let masked = mask_query("qido-rs/studies?PatientID=123&access_token=abc&api_key=k");
+        assert_eq!(masked, format!("qido-rs/studies?PatientID=123&access_token={0}&api_key={0}", MASK));
+        assert_eq!(strip_masked_params(&masked), "qido-rs/studies?PatientID=123");
+        assert_eq!(mask_query("qido-rs/studies"), "qido-rs/studies");
+    }
+
+    #[test]
+    fn test_truncated_body_not_replayed() {
+        let (body, truncated) = truncate(&"x".repeat(MAX_BODY_BYTES + 1));
+        assert!(truncated);
+
+        let mut entry = entry(r#"{"Content-Type":"application/dicom+json"}"#);
+        entry.method = "POST".to_string();
+        entry.body = Some(body);
+        entry.body_truncated = true;
+        assert!(to_request(&entry).is_err());
+    }
+
+    #[test]
+    fn test_curl_export() {
+        let entry = entry(r#"{"Accept":"application/dicom+json","Authorization":"Bearer ********"}"#);
+        let curl = to_curl(&entry).unwrap();
+        assert!(curl.starts_with("curl -X GET 'http://localhost:8080/dicomweb/qido-rs/studies?PatientID=123'"));
+        assert!(curl.contains("-H 'Accept: application/dicom+json'"));
+
+        let request = to_request(&entry).unwrap();
+        assert!(request.headers.contains_key("Accept"));
+        assert!(!request.headers.contains_key("Authorization"));
+    }
+}
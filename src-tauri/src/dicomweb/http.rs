@@ -0,0 +1,243 @@
+// HTTP transport settings for DICOMweb endpoints: timeouts, retries, proxy and TLS
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_read_timeout() -> u64 {
+    60
+}
+
+/// Per-endpoint HTTP settings. Every field has a default, so endpoints saved before
+/// these settings existed keep working.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpOptions {
+    /// Seconds allowed to establish the TCP/TLS connection
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout_secs: u64,
+    /// Seconds the server may stay silent, while waiting for the response or between
+    /// body chunks; 0 disables it. Long transfers are fine as long as data keeps flowing.
+    #[serde(default = "default_read_timeout")]
+    pub read_timeout_secs: u64,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// PEM file with additional CA certificates to trust
+    #[serde(default)]
+    pub ca_bundle_path: Option<String>,
+    /// Client certificate for mutual TLS
+    #[serde(default)]
+    pub client_certificate: Option<ClientCertificate>,
+    /// Accept any server certificate and hostname. Only meant for lab servers with
+    /// self-signed certificates.
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout(),
+            read_timeout_secs: default_read_timeout(),
+            retry: RetryPolicy::default(),
+            proxy: None,
+            ca_bundle_path: None,
+            client_certificate: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl HttpOptions {
+    pub fn read_timeout(&self) -> Option<Duration> {
+        (self.read_timeout_secs > 0).then(|| Duration::from_secs(self.read_timeout_secs))
+    }
+}
+
+/// Retry with exponential backoff when the server is throttling (429) or
+/// temporarily unavailable (503)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    /// Upper bound for a single wait, including waits requested through Retry-After
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn should_retry(status: u16) -> bool {
+        status == 429 || status == 503
+    }
+
+    /// Wait before retry number `attempt` (0-based); the server's Retry-After wins over backoff
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = Duration::from_millis(self.initial_backoff_ms.saturating_mul(1 << attempt.min(16)));
+        retry_after
+            .unwrap_or(backoff)
+            .min(Duration::from_millis(self.max_backoff_ms))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy URL, e.g. `http://proxy.example.org:3128`
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Comma-separated hosts that bypass the proxy
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum ClientCertificate {
+    /// PEM certificate chain and PKCS#8 private key
+    Pem { cert_path: String, key_path: String },
+    /// PKCS#12 (.p12/.pfx) bundle protected by a password
+    Pkcs12 { path: String, password: String },
+}
+
+/// Build the reqwest client for an endpoint
+pub fn build_client(options: &HttpOptions) -> Result<Client> {
+    let mut builder = Client::builder().connect_timeout(Duration::from_secs(options.connect_timeout_secs));
+
+    if let Some(proxy) = &options.proxy {
+        let mut p = reqwest::Proxy::all(&proxy.url)
+            .map_err(|e| anyhow::anyhow!("Invalid proxy URL {}: {}", proxy.url, e))?;
+        if let Some(username) = proxy.username.as_deref().filter(|u| !u.is_empty()) {
+            p = p.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
+        }
+        if let Some(no_proxy) = &proxy.no_proxy {
+            p = p.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+        }
+        builder = builder.proxy(p);
+    }
+
+    if let Some(path) = &options.ca_bundle_path {
+        let pem = read_file(path, "CA bundle")?;
+        let blocks = pem_certificates(&pem);
+        if blocks.is_empty() {
+            return Err(anyhow::anyhow!("No certificates found in CA bundle {}", path));
+        }
+        for block in blocks {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(block.as_bytes())?);
+        }
+    }
+
+    if let Some(certificate) = &options.client_certificate {
+        let identity = match certificate {
+            ClientCertificate::Pem { cert_path, key_path } => reqwest::Identity::from_pkcs8_pem(
+                &read_file(cert_path, "client certificate")?,
+                &read_file(key_path, "client key")?,
+            ),
+            ClientCertificate::Pkcs12 { path, password } => {
+                reqwest::Identity::from_pkcs12_der(&read_file(path, "client certificate")?, password)
+            }
+        }
+        .map_err(|e| anyhow::anyhow!("Invalid client certificate: {}", e))?;
+        builder = builder.identity(identity);
+    }
+
+    if options.accept_invalid_certs {
+        tracing::warn!("TLS certificate verification is disabled for this endpoint");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder.build()?)
+}
+
+fn read_file(path: &str, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read {} {}: {}", what, path, e))
+}
+
+/// Individual `CERTIFICATE` blocks of a PEM bundle
+fn pem_certificates(pem: &[u8]) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let text = String::from_utf8_lossy(pem);
+    let mut blocks = Vec::new();
+    let mut rest = text.as_ref();
+    while let Some(start) = rest.find(BEGIN) {
+        let Some(end) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + end + END.len();
+        blocks.push(format!("{}\n", &rest[start..end]));
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+/// Parse a Retry-After header, given either as delay seconds or as an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let later = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let wait = parse_retry_after(&later).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0, None), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, None), Duration::from_millis(2000));
+        assert_eq!(policy.delay(10, None), Duration::from_millis(30_000));
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(3))), Duration::from_secs(3));
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(600))), Duration::from_millis(30_000));
+    }
+
+    #[test]
+    fn test_http_options_defaults() {
+        let options: HttpOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.connect_timeout_secs, 10);
+        assert_eq!(options.read_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(options.retry.max_retries, 3);
+        assert!(!options.accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_missing_ca_bundle_rejected() {
+        let options = HttpOptions {
+            ca_bundle_path: Some("/nonexistent/ca.pem".to_string()),
+            ..Default::default()
+        };
+        let err = build_client(&options).unwrap_err();
+        assert!(err.to_string().contains("CA bundle"));
+    }
+}
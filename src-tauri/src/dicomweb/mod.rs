@@ -1,18 +1,72 @@
+pub mod auth;
+pub mod capabilities;
 pub mod client;
 pub mod qido;
 pub mod wado;
 pub mod stow;
 pub mod config;
+pub mod history;
+pub mod http;
+pub mod multipart;
+pub mod server;
+pub mod ups;
+pub mod wado_uri;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Header names whose values are treated as secrets
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "cookie", "api-key", "apikey", "token", "secret"];
+
+/// Whether a header carries credentials and must be masked or encrypted
+pub fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_HEADERS.iter().any(|s| name.contains(s))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DicomWebEndpoint {
     pub name: String,
     pub base_url: String,
     pub auth_type: AuthType,
+    /// Extra headers sent with every request to this endpoint
+    #[serde(default)]
     pub headers: HashMap<String, String>,
+    /// WADO-URI service URL, absolute or relative to `base_url`; defaults to `wado`
+    #[serde(default)]
+    pub wado_uri: Option<String>,
+    /// Timeouts, retries, proxy and TLS settings
+    #[serde(default)]
+    pub http: http::HttpOptions,
+    /// Result of the last endpoint inspection
+    #[serde(default)]
+    pub capabilities: Option<capabilities::EndpointCapabilities>,
+}
+
+#[cfg(test)]
+impl DicomWebEndpoint {
+    /// Unauthenticated endpoint with default settings, for tests against mock servers
+    pub fn for_test(base_url: impl Into<String>) -> Self {
+        Self {
+            name: "mock".to_string(),
+            base_url: base_url.into(),
+            auth_type: AuthType::None,
+            headers: HashMap::new(),
+            wado_uri: None,
+            http: Default::default(),
+            capabilities: None,
+        }
+    }
+}
+
+/// Full URL of a request endpoint. Endpoints are relative to the base URL unless
+/// they are absolute URLs themselves, as WADO-URI services often are.
+pub fn resolve_url(base_url: &str, endpoint: &str) -> String {
+    if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        endpoint.to_string()
+    } else {
+        format!("{}/{}", base_url.trim_end_matches('/'), endpoint)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,15 +74,51 @@ pub enum AuthType {
     None,
     Basic { username: String, password: String },
     Bearer { token: String },
+    /// OAuth2 client-credentials grant; tokens are fetched and renewed automatically
+    OAuth2ClientCredentials {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+    },
+    /// OAuth2 refresh-token grant for user-delegated access
+    OAuth2RefreshToken {
+        token_url: String,
+        client_id: String,
+        client_secret: Option<String>,
+        refresh_token: String,
+    },
+    /// Authentication is provided entirely through the endpoint's custom headers
     Custom,
 }
 
+impl AuthType {
+    /// Whether this auth type obtains its credentials from an OAuth2 token endpoint
+    pub fn is_oauth2(&self) -> bool {
+        matches!(
+            self,
+            AuthType::OAuth2ClientCredentials { .. } | AuthType::OAuth2RefreshToken { .. }
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DicomWebRequest {
     pub method: String,
     pub endpoint: String,
     pub headers: HashMap<String, String>,
-    pub body: Option<String>,
+    /// Request body; JSON, XML or binary multipart payloads
+    pub body: Option<Vec<u8>>,
+}
+
+impl DicomWebRequest {
+    /// Content-Type header of the request, if present
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,3 +127,18 @@ pub struct DicomWebResponse {
     pub headers: HashMap<String, String>,
     pub body: String,
 }
+
+/// Response with the body kept as raw bytes, for binary and multipart payloads
+#[derive(Debug, Clone)]
+pub struct DicomWebRawResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl DicomWebRawResponse {
+    /// Content-Type header of the response, if present
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get("content-type").map(|s| s.as_str())
+    }
+}
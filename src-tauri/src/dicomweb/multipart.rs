@@ -0,0 +1,190 @@
+// multipart/related encoding and decoding (PS3.18 section 8.6)
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// A single body part of a multipart/related message
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl MultipartPart {
+    /// Content-Type of this part, if present
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get("content-type").map(|s| s.as_str())
+    }
+}
+
+/// Extract a parameter (e.g. `boundary`, `type`) from a Content-Type header value
+pub fn content_type_param(content_type: &str, name: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case(name) {
+            Some(value.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Check whether a Content-Type header denotes a multipart/related body
+pub fn is_multipart_related(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .map(|media| media.trim().eq_ignore_ascii_case("multipart/related"))
+        .unwrap_or(false)
+}
+
+/// Split a multipart/related body into its parts using the boundary from `content_type`
+pub fn parse(content_type: &str, body: &[u8]) -> Result<Vec<MultipartPart>> {
+    let boundary = content_type_param(content_type, "boundary")
+        .ok_or_else(|| anyhow::anyhow!("No boundary in Content-Type: {}", content_type))?;
+
+    parse_with_boundary(&boundary, body)
+}
+
+/// Split a multipart body into its parts given an explicit boundary
+pub fn parse_with_boundary(boundary: &str, body: &[u8]) -> Result<Vec<MultipartPart>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut parts = Vec::new();
+
+    let mut pos = find(body, &delimiter, 0)
+        .ok_or_else(|| anyhow::anyhow!("Multipart boundary '{}' not found in body", boundary))?;
+
+    loop {
+        pos += delimiter.len();
+
+        // Closing delimiter "--boundary--"
+        if body[pos..].starts_with(b"--") {
+            break;
+        }
+
+        // Skip transport padding and the CRLF that ends the delimiter line
+        pos = skip_line(body, pos);
+
+        let next = find(body, &delimiter, pos)
+            .ok_or_else(|| anyhow::anyhow!("Unterminated multipart body part"))?;
+
+        // The CRLF preceding a delimiter belongs to the delimiter, not the body
+        let mut end = next;
+        if end >= 2 && &body[end - 2..end] == b"\r\n" {
+            end -= 2;
+        } else if end >= 1 && body[end - 1] == b'\n' {
+            end -= 1;
+        }
+
+        parts.push(parse_part(&body[pos..end.max(pos)])?);
+        pos = next;
+    }
+
+    Ok(parts)
+}
+
+/// Encode body parts as a multipart/related body, returning the body bytes
+pub fn encode(boundary: &str, parts: &[MultipartPart]) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for part in parts {
+        out.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        for (key, value) in &part.headers {
+            out.extend_from_slice(format!("{}: {}\r\n", key, value).as_bytes());
+        }
+        out.extend_from_slice(b"\r\n");
+        out.extend_from_slice(&part.body);
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+    out
+}
+
+/// Generate a boundary string that is unlikely to occur in DICOM payloads
+pub fn generate_boundary() -> String {
+    format!("DICOMFlow-{}", uuid::Uuid::new_v4().simple())
+}
+
+fn parse_part(raw: &[u8]) -> Result<MultipartPart> {
+    let (header_end, body_start) = match find(raw, b"\r\n\r\n", 0) {
+        Some(i) => (i, i + 4),
+        None => match find(raw, b"\n\n", 0) {
+            Some(i) => (i, i + 2),
+            // A part may start with an empty header block
+            None if raw.starts_with(b"\r\n") => (0, 2),
+            None => return Err(anyhow::anyhow!("Malformed multipart part headers")),
+        },
+    };
+
+    let header_text = String::from_utf8_lossy(&raw[..header_end]);
+    let headers = header_text
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_ascii_lowercase(), value.trim().to_string()))
+        })
+        .collect();
+
+    Ok(MultipartPart {
+        headers,
+        body: raw[body_start..].to_vec(),
+    })
+}
+
+fn skip_line(data: &[u8], from: usize) -> usize {
+    match data[from..].iter().position(|&b| b == b'\n') {
+        Some(i) => from + i + 1,
+        None => data.len(),
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() || needle.is_empty() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| from + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_param() {
+        let ct = "multipart/related; type=\"application/dicom\"; boundary=abc123";
+        assert_eq!(content_type_param(ct, "boundary").as_deref(), Some("abc123"));
+        assert_eq!(content_type_param(ct, "type").as_deref(), Some("application/dicom"));
+        assert!(is_multipart_related(ct));
+        assert!(!is_multipart_related("application/dicom"));
+    }
+
+    #[test]
+    fn test_parse_two_parts() {
+        let body = b"--xyz\r\nContent-Type: application/dicom\r\n\r\nFIRST\r\n--xyz\r\nContent-Type: application/dicom\r\n\r\nSEC\r\nOND\r\n--xyz--\r\n";
+        let parts = parse("multipart/related; boundary=xyz", body).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].body, b"FIRST");
+        assert_eq!(parts[1].body, b"SEC\r\nOND");
+        assert_eq!(parts[0].content_type(), Some("application/dicom"));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/dicom".to_string());
+        let parts = vec![
+            MultipartPart { headers: headers.clone(), body: vec![0, 1, 2, 13, 10] },
+            MultipartPart { headers, body: b"DICM".to_vec() },
+        ];
+
+        let encoded = encode("b0undary", &parts);
+        let decoded = parse_with_boundary("b0undary", &encoded).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].body, vec![0, 1, 2, 13, 10]);
+        assert_eq!(decoded[1].body, b"DICM");
+    }
+}
@@ -3,13 +3,35 @@
 use super::client::DicomWebClient;
 use super::DicomWebRequest;
 use anyhow::Result;
+use dicom_core::Tag;
+use dicom_object::InMemDicomObject;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// Default page size used when paging through results without an explicit limit
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Most results a pager fetches in total, whatever the server keeps returning
+const MAX_PAGED_RESULTS: usize = 100_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QidoQuery {
     pub level: QueryLevel,
-    pub params: HashMap<String, String>,
+    /// Scope series and instance queries under a study
+    #[serde(default)]
+    pub study_uid: Option<String>,
+    /// Scope instance queries under a series (requires `study_uid`)
+    #[serde(default)]
+    pub series_uid: Option<String>,
+    pub params: BTreeMap<AttributeKey, String>,
+    /// Additional attributes to return
+    #[serde(default)]
+    pub include_fields: Vec<AttributeKey>,
+    /// Ask the server to return all available attributes (`includefield=all`)
+    #[serde(default)]
+    pub include_all: bool,
+    #[serde(default)]
+    pub fuzzy_matching: bool,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
 }
@@ -31,30 +53,190 @@ impl QueryLevel {
     }
 }
 
-/// Perform QIDO-RS query
-pub async fn query(client: &DicomWebClient, query: QidoQuery) -> Result<Vec<serde_json::Value>> {
-    let mut endpoint = format!("qido-rs/{}", query.level.to_path());
+/// Attribute used as a QIDO-RS matching key or include field, either by
+/// keyword (e.g. "PatientName") or by tag (e.g. "00100010" or "(0010,0010)")
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AttributeKey {
+    Keyword(String),
+    Tag(Tag),
+}
+
+impl AttributeKey {
+    /// Parse and validate a keyword or tag
+    pub fn parse(key: &str) -> Result<Self> {
+        use dicom_core::dictionary::DataDictionary;
+        use dicom_dictionary_std::StandardDataDictionary;
 
-    // Build query string
-    let mut query_params = Vec::new();
-    for (key, value) in &query.params {
-        query_params.push(format!("{}={}", key, value));
+        let key = key.trim();
+        let cleaned: String = key
+            .chars()
+            .filter(|c| !matches!(c, '(' | ')' | ',' | ' '))
+            .collect();
+
+        if cleaned.len() == 8 && cleaned.chars().all(|c| c.is_ascii_hexdigit()) {
+            let group = u16::from_str_radix(&cleaned[0..4], 16)?;
+            let element = u16::from_str_radix(&cleaned[4..8], 16)?;
+            return Ok(AttributeKey::Tag(Tag(group, element)));
+        }
+
+        if StandardDataDictionary.by_name(key).is_some() {
+            Ok(AttributeKey::Keyword(key.to_string()))
+        } else {
+            Err(anyhow::anyhow!("Unknown attribute keyword or tag: {}", key))
+        }
     }
-    if let Some(limit) = query.limit {
-        query_params.push(format!("limit={}", limit));
+
+    /// Resolve the attribute to its tag
+    pub fn tag(&self) -> Option<Tag> {
+        use dicom_core::dictionary::DataDictionary;
+        use dicom_dictionary_std::StandardDataDictionary;
+
+        match self {
+            AttributeKey::Tag(tag) => Some(*tag),
+            AttributeKey::Keyword(keyword) => {
+                StandardDataDictionary.by_name(keyword).map(|entry| entry.tag.inner())
+            }
+        }
     }
-    if let Some(offset) = query.offset {
-        query_params.push(format!("offset={}", offset));
+
+    /// Form used in the query string
+    pub fn as_query_key(&self) -> String {
+        match self {
+            AttributeKey::Keyword(keyword) => keyword.clone(),
+            AttributeKey::Tag(tag) => format!("{:04X}{:04X}", tag.group(), tag.element()),
+        }
     }
+}
 
-    if !query_params.is_empty() {
-        endpoint.push('?');
-        endpoint.push_str(&query_params.join("&"));
+impl From<Tag> for AttributeKey {
+    fn from(tag: Tag) -> Self {
+        AttributeKey::Tag(tag)
     }
+}
 
+impl TryFrom<String> for AttributeKey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        AttributeKey::parse(&value)
+    }
+}
+
+impl From<AttributeKey> for String {
+    fn from(key: AttributeKey) -> Self {
+        key.as_query_key()
+    }
+}
+
+impl QidoQuery {
+    /// Start a query at the given level with no matching keys
+    pub fn new(level: QueryLevel) -> Self {
+        Self {
+            level,
+            study_uid: None,
+            series_uid: None,
+            params: BTreeMap::new(),
+            include_fields: Vec::new(),
+            include_all: false,
+            fuzzy_matching: false,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Resource path, scoped under the study/series when given
+    pub fn path(&self) -> Result<String> {
+        let level = self.level.to_path();
+
+        match (&self.level, &self.study_uid, &self.series_uid) {
+            (QueryLevel::Studies, _, _) => Ok(format!("qido-rs/{}", level)),
+            (_, None, Some(_)) => Err(anyhow::anyhow!(
+                "A series-scoped query also requires the Study Instance UID"
+            )),
+            (QueryLevel::Series, None, None) | (QueryLevel::Instances, None, None) => {
+                Ok(format!("qido-rs/{}", level))
+            }
+            (QueryLevel::Series, Some(study), _) => {
+                Ok(format!("qido-rs/studies/{}/{}", encode(study), level))
+            }
+            (QueryLevel::Instances, Some(study), None) => {
+                Ok(format!("qido-rs/studies/{}/{}", encode(study), level))
+            }
+            (QueryLevel::Instances, Some(study), Some(series)) => Ok(format!(
+                "qido-rs/studies/{}/series/{}/{}",
+                encode(study),
+                encode(series),
+                level
+            )),
+        }
+    }
+
+    /// URL-encoded query string, without the leading '?'
+    pub fn query_string(&self) -> String {
+        let mut pairs: Vec<(String, String)> = self
+            .params
+            .iter()
+            .map(|(key, value)| (key.as_query_key(), value.clone()))
+            .collect();
+
+        if self.include_all {
+            pairs.push(("includefield".to_string(), "all".to_string()));
+        } else {
+            for field in &self.include_fields {
+                pairs.push(("includefield".to_string(), field.as_query_key()));
+            }
+        }
+        if self.fuzzy_matching {
+            pairs.push(("fuzzymatching".to_string(), "true".to_string()));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit".to_string(), limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            pairs.push(("offset".to_string(), offset.to_string()));
+        }
+
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn endpoint(&self) -> Result<String> {
+        let mut endpoint = self.path()?;
+        let query_string = self.query_string();
+        if !query_string.is_empty() {
+            endpoint.push('?');
+            endpoint.push_str(&query_string);
+        }
+        Ok(endpoint)
+    }
+}
+
+/// Percent-encode a path segment or query component
+pub(crate) fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// Results of a QIDO-RS request together with any server warnings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QidoResponse {
+    pub results: Vec<serde_json::Value>,
+    /// Warning headers returned by the server
+    pub warnings: Vec<String>,
+    /// True when the server answered 206 Partial Content (more matches than it returns)
+    pub partial: bool,
+}
+
+/// Perform QIDO-RS query
+pub async fn query(client: &DicomWebClient, query: QidoQuery) -> Result<QidoResponse> {
     let request = DicomWebRequest {
         method: "GET".to_string(),
-        endpoint,
+        endpoint: query.endpoint()?,
         headers: {
             let mut headers = HashMap::new();
             headers.insert("Accept".to_string(), "application/dicom+json".to_string());
@@ -65,21 +247,449 @@ pub async fn query(client: &DicomWebClient, query: QidoQuery) -> Result<Vec<serd
 
     let response = client.execute(request).await?;
 
-    if response.status != 200 {
-        return Err(anyhow::anyhow!(
+    let warnings: Vec<String> = response
+        .headers
+        .iter()
+        .filter(|(key, _)| key.eq_ignore_ascii_case("warning"))
+        .map(|(_, value)| value.clone())
+        .collect();
+
+    match response.status {
+        // No matches
+        204 => Ok(QidoResponse {
+            results: Vec::new(),
+            warnings,
+            partial: false,
+        }),
+        200 | 206 => {
+            if response.status == 206 {
+                tracing::warn!("QIDO-RS returned partial results: {:?}", warnings);
+            }
+            let results: Vec<serde_json::Value> = if response.body.trim().is_empty() {
+                Vec::new()
+            } else {
+                serde_json::from_str(&response.body)?
+            };
+            Ok(QidoResponse {
+                results,
+                warnings,
+                partial: response.status == 206,
+            })
+        }
+        status => Err(anyhow::anyhow!(
             "QIDO-RS query failed with status {}: {}",
-            response.status,
+            status,
             response.body
-        ));
+        )),
     }
+}
 
-    let results: Vec<serde_json::Value> = serde_json::from_str(&response.body)?;
-    Ok(results)
+/// Pages through QIDO-RS results using limit/offset until the server has no more matches
+pub struct QidoPager<'a> {
+    client: &'a DicomWebClient,
+    query: QidoQuery,
+    page_size: u32,
+    exhausted: bool,
+    /// Paging stopped before the server said it was done
+    stopped_early: bool,
+    fetched: usize,
+    /// UIDs of the previous page's first and last results
+    last_page: Option<(String, String)>,
+    warnings: Vec<String>,
+}
+
+/// Most specific UID of a result, to recognise a page the server sends again
+fn result_uid(result: &serde_json::Value) -> String {
+    ["00080018", "0020000E", "0020000D"]
+        .iter()
+        .find_map(|tag| result.get(*tag)?.get("Value")?.get(0)?.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl<'a> QidoPager<'a> {
+    pub fn new(client: &'a DicomWebClient, mut query: QidoQuery) -> Self {
+        let page_size = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        query.limit = Some(page_size);
+        query.offset = Some(query.offset.unwrap_or(0));
+
+        Self {
+            client,
+            query,
+            page_size,
+            exhausted: false,
+            stopped_early: false,
+            fetched: 0,
+            last_page: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn stop_early(&mut self, warning: String) {
+        tracing::warn!("{}", warning);
+        self.warnings.push(warning);
+        self.exhausted = true;
+        self.stopped_early = true;
+    }
+
+    /// Fetch the next page, or `None` once all results have been returned
+    pub async fn next(&mut self) -> Result<Option<Vec<serde_json::Value>>> {
+        if self.exhausted {
+            return Ok(None);
+        }
+
+        let response = query(self.client, self.query.clone()).await?;
+        for warning in response.warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+
+        // Servers may cap a page below the requested limit and answer 206 when
+        // more matches remain, so only a short 200 page or an empty page ends paging
+        let mut results = response.results;
+        let count = results.len() as u32;
+        if count == 0 {
+            self.exhausted = true;
+            return Ok(None);
+        }
+        if count < self.page_size && !response.partial {
+            self.exhausted = true;
+        }
+
+        // A server that ignores offset sends the same page forever, and one that
+        // ignores limit has sent everything it will at once
+        let page = (result_uid(&results[0]), result_uid(&results[results.len() - 1]));
+        if self.last_page.as_ref() == Some(&page) {
+            self.stop_early("Server returned the same QIDO-RS page twice; it may not support offset".to_string());
+            return Ok(None);
+        }
+        if count > self.page_size {
+            self.stop_early(format!(
+                "Server returned {} results for a limit of {}; it may not support paging",
+                count, self.page_size
+            ));
+        }
+        self.last_page = Some(page);
+
+        if self.fetched + results.len() >= MAX_PAGED_RESULTS {
+            results.truncate(MAX_PAGED_RESULTS - self.fetched);
+            if !self.exhausted {
+                self.stop_early(format!("Stopped after {} QIDO-RS results", MAX_PAGED_RESULTS));
+            }
+        }
+        self.fetched += results.len();
+
+        self.query.offset = Some(self.query.offset.unwrap_or(0) + count);
+        Ok(Some(results))
+    }
+
+    /// Warnings collected from all pages so far
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Fetch every remaining page, stopping early once `max_results` is reached
+    pub async fn collect_all(mut self, max_results: Option<usize>) -> Result<QidoResponse> {
+        let mut results = Vec::new();
+        let mut truncated = false;
+
+        while let Some(page) = self.next().await? {
+            results.extend(page);
+            if let Some(max) = max_results {
+                if results.len() >= max {
+                    truncated = results.len() > max || !self.exhausted;
+                    results.truncate(max);
+                    break;
+                }
+            }
+        }
+
+        Ok(QidoResponse {
+            results,
+            warnings: self.warnings,
+            partial: truncated || self.stopped_early,
+        })
+    }
+}
+
+/// Typed record for a study-level QIDO-RS result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudyRecord {
+    pub study_instance_uid: String,
+    pub patient_name: Option<String>,
+    pub patient_id: Option<String>,
+    pub patient_birth_date: Option<String>,
+    pub study_date: Option<String>,
+    pub study_time: Option<String>,
+    pub accession_number: Option<String>,
+    pub study_description: Option<String>,
+    pub modalities_in_study: Vec<String>,
+    pub number_of_series: Option<i32>,
+    pub number_of_instances: Option<i32>,
+    pub retrieve_url: Option<String>,
+}
+
+/// Typed record for a series-level QIDO-RS result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesRecord {
+    pub study_instance_uid: Option<String>,
+    pub series_instance_uid: String,
+    pub modality: Option<String>,
+    pub series_number: Option<i32>,
+    pub series_description: Option<String>,
+    pub number_of_instances: Option<i32>,
+    pub retrieve_url: Option<String>,
+}
+
+/// Typed record for an instance-level QIDO-RS result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceRecord {
+    pub study_instance_uid: Option<String>,
+    pub series_instance_uid: Option<String>,
+    pub sop_instance_uid: String,
+    pub sop_class_uid: Option<String>,
+    pub instance_number: Option<i32>,
+    pub rows: Option<u16>,
+    pub columns: Option<u16>,
+    pub number_of_frames: Option<i32>,
+    pub retrieve_url: Option<String>,
+}
+
+/// A QIDO-RS result converted according to its query level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "level")]
+pub enum QidoRecord {
+    Study(StudyRecord),
+    Series(SeriesRecord),
+    Instance(InstanceRecord),
+}
+
+impl StudyRecord {
+    pub fn from_object(obj: &InMemDicomObject) -> Result<Self> {
+        use dicom_dictionary_std::tags;
+
+        Ok(Self {
+            study_instance_uid: required_str(obj, tags::STUDY_INSTANCE_UID, "StudyInstanceUID")?,
+            patient_name: get_str(obj, tags::PATIENT_NAME),
+            patient_id: get_str(obj, tags::PATIENT_ID),
+            patient_birth_date: get_str(obj, tags::PATIENT_BIRTH_DATE),
+            study_date: get_str(obj, tags::STUDY_DATE),
+            study_time: get_str(obj, tags::STUDY_TIME),
+            accession_number: get_str(obj, tags::ACCESSION_NUMBER),
+            study_description: get_str(obj, tags::STUDY_DESCRIPTION),
+            modalities_in_study: obj
+                .element(tags::MODALITIES_IN_STUDY)
+                .ok()
+                .and_then(|e| e.to_multi_str().ok())
+                .map(|values| values.iter().map(|v| v.trim().to_string()).collect())
+                .unwrap_or_default(),
+            number_of_series: get_int(obj, tags::NUMBER_OF_STUDY_RELATED_SERIES),
+            number_of_instances: get_int(obj, tags::NUMBER_OF_STUDY_RELATED_INSTANCES),
+            retrieve_url: get_str(obj, tags::RETRIEVE_URL),
+        })
+    }
+}
+
+impl SeriesRecord {
+    pub fn from_object(obj: &InMemDicomObject) -> Result<Self> {
+        use dicom_dictionary_std::tags;
+
+        Ok(Self {
+            study_instance_uid: get_str(obj, tags::STUDY_INSTANCE_UID),
+            series_instance_uid: required_str(obj, tags::SERIES_INSTANCE_UID, "SeriesInstanceUID")?,
+            modality: get_str(obj, tags::MODALITY),
+            series_number: get_int(obj, tags::SERIES_NUMBER),
+            series_description: get_str(obj, tags::SERIES_DESCRIPTION),
+            number_of_instances: get_int(obj, tags::NUMBER_OF_SERIES_RELATED_INSTANCES),
+            retrieve_url: get_str(obj, tags::RETRIEVE_URL),
+        })
+    }
+}
+
+impl InstanceRecord {
+    pub fn from_object(obj: &InMemDicomObject) -> Result<Self> {
+        use dicom_dictionary_std::tags;
+
+        Ok(Self {
+            study_instance_uid: get_str(obj, tags::STUDY_INSTANCE_UID),
+            series_instance_uid: get_str(obj, tags::SERIES_INSTANCE_UID),
+            sop_instance_uid: required_str(obj, tags::SOP_INSTANCE_UID, "SOPInstanceUID")?,
+            sop_class_uid: get_str(obj, tags::SOP_CLASS_UID),
+            instance_number: get_int(obj, tags::INSTANCE_NUMBER),
+            rows: get_int(obj, tags::ROWS).map(|v| v as u16),
+            columns: get_int(obj, tags::COLUMNS).map(|v| v as u16),
+            number_of_frames: get_int(obj, tags::NUMBER_OF_FRAMES),
+            retrieve_url: get_str(obj, tags::RETRIEVE_URL),
+        })
+    }
+}
+
+/// Convert raw DICOM JSON results into typed records for the given level
+pub fn to_records(level: &QueryLevel, results: &[serde_json::Value]) -> Result<Vec<QidoRecord>> {
+    results
+        .iter()
+        .map(|value| {
+            let obj = crate::dicom::json::from_json(value)?;
+            Ok(match level {
+                QueryLevel::Studies => QidoRecord::Study(StudyRecord::from_object(&obj)?),
+                QueryLevel::Series => QidoRecord::Series(SeriesRecord::from_object(&obj)?),
+                QueryLevel::Instances => QidoRecord::Instance(InstanceRecord::from_object(&obj)?),
+            })
+        })
+        .collect()
+}
+
+fn get_str(obj: &InMemDicomObject, tag: Tag) -> Option<String> {
+    obj.element(tag)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn get_int(obj: &InMemDicomObject, tag: Tag) -> Option<i32> {
+    obj.element(tag).ok().and_then(|e| e.to_int::<i32>().ok())
+}
+
+fn required_str(obj: &InMemDicomObject, tag: Tag, keyword: &str) -> Result<String> {
+    get_str(obj, tag).ok_or_else(|| anyhow::anyhow!("QIDO-RS result is missing {}", keyword))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // Add tests
+    #[test]
+    fn test_attribute_key_parse() {
+        assert_eq!(
+            AttributeKey::parse("(0010,0010)").unwrap(),
+            AttributeKey::Tag(Tag(0x0010, 0x0010))
+        );
+        assert_eq!(
+            AttributeKey::parse("00100020").unwrap().as_query_key(),
+            "00100020"
+        );
+        assert_eq!(
+            AttributeKey::parse("PatientName").unwrap(),
+            AttributeKey::Keyword("PatientName".to_string())
+        );
+        assert!(AttributeKey::parse("NotAKeyword").is_err());
+    }
+
+    #[test]
+    fn test_query_string_encoding() {
+        let mut query = QidoQuery::new(QueryLevel::Studies);
+        query.params.insert(AttributeKey::parse("PatientName").unwrap(), "Doe^J*".to_string());
+        query.params.insert(AttributeKey::parse("00080020").unwrap(), "20240101-20241231".to_string());
+        query.include_fields.push(AttributeKey::parse("StudyDescription").unwrap());
+        query.fuzzy_matching = true;
+        query.limit = Some(10);
+
+        assert_eq!(
+            query.query_string(),
+            "PatientName=Doe%5EJ*&00080020=20240101-20241231&includefield=StudyDescription\
+             &fuzzymatching=true&limit=10"
+        );
+    }
+
+    #[test]
+    fn test_scoped_paths() {
+        let mut query = QidoQuery::new(QueryLevel::Instances);
+        assert_eq!(query.path().unwrap(), "qido-rs/instances");
+
+        query.study_uid = Some("1.2.3".to_string());
+        assert_eq!(query.path().unwrap(), "qido-rs/studies/1.2.3/instances");
+
+        query.series_uid = Some("1.2.3.4".to_string());
+        assert_eq!(query.path().unwrap(), "qido-rs/studies/1.2.3/series/1.2.3.4/instances");
+
+        query.study_uid = None;
+        assert!(query.path().is_err());
+    }
+
+    #[test]
+    fn test_study_records() {
+        let results = vec![serde_json::json!({
+            "0020000D": { "vr": "UI", "Value": ["1.2.3"] },
+            "00100010": { "vr": "PN", "Value": [{ "Alphabetic": "Doe^Jane" }] },
+            "00080061": { "vr": "CS", "Value": ["CT", "SR"] },
+            "00201206": { "vr": "IS", "Value": [3] }
+        })];
+
+        let records = to_records(&QueryLevel::Studies, &results).unwrap();
+        match &records[0] {
+            QidoRecord::Study(study) => {
+                assert_eq!(study.study_instance_uid, "1.2.3");
+                assert_eq!(study.patient_name.as_deref(), Some("Doe^Jane"));
+                assert_eq!(study.modalities_in_study, vec!["CT", "SR"]);
+                assert_eq!(study.number_of_series, Some(3));
+            }
+            other => panic!("Unexpected record: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pager_follows_partial_pages() {
+        use super::super::DicomWebEndpoint;
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let study = |uid: &str| serde_json::json!({ "0020000D": { "vr": "UI", "Value": [uid] } });
+        // The server caps pages at one result and signals more with 206
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(206).set_body_json(serde_json::json!([study("1.1")])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .and(query_param("offset", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([study("1.2")])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = DicomWebClient::new(DicomWebEndpoint::for_test(server.uri())).unwrap();
+        let mut query = QidoQuery::new(QueryLevel::Studies);
+        query.limit = Some(10);
+
+        let response = QidoPager::new(&client, query).collect_all(None).await.unwrap();
+        assert_eq!(response.results.len(), 2);
+        assert!(!response.partial);
+    }
+
+    #[tokio::test]
+    async fn test_pager_stops_when_offset_is_ignored() {
+        use super::super::DicomWebEndpoint;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let study = |uid: &str| serde_json::json!({ "0020000D": { "vr": "UI", "Value": [uid] } });
+        // Every request gets the same full page, whatever the offset
+        Mock::given(method("GET"))
+            .and(path("/qido-rs/studies"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([study("1.1"), study("1.2")])))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = DicomWebClient::new(DicomWebEndpoint::for_test(server.uri())).unwrap();
+        let mut query = QidoQuery::new(QueryLevel::Studies);
+        query.limit = Some(2);
+
+        let response = QidoPager::new(&client, query).collect_all(None).await.unwrap();
+        assert_eq!(response.results.len(), 2);
+        assert!(response.partial);
+        assert!(response.warnings.iter().any(|w| w.contains("same QIDO-RS page")));
+    }
 }
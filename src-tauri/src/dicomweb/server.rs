@@ -0,0 +1,1204 @@
+// Embedded DICOMweb server exposing the local index over HTTP on localhost
+
+use super::multipart::{self, MultipartPart};
+use super::qido::AttributeKey;
+use crate::database::DbPool;
+use crate::dicom::json::tag_key;
+use crate::dicom::pixeldata::{self, RenderOptions};
+use anyhow::Result;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path as UrlPath, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use dicom_core::Tag;
+use dicom_dictionary_std::tags;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::sync::{oneshot, Mutex};
+
+/// Default thumbnail size when the request has no viewport
+const THUMBNAIL_SIZE: (u32, u32) = (128, 128);
+
+/// Largest STOW-RS request body accepted; uploads are buffered in memory
+const MAX_STOW_BODY_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Failure reason for instances that could not be stored (PS3.4 Annex GG)
+const PROCESSING_FAILURE: u16 = 0x0110;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+    pub port: u16,
+    /// Accept STOW-RS uploads into the local store
+    #[serde(default)]
+    pub allow_stow: bool,
+    /// Directory for STOW-RS uploads; defaults to `dicomweb_store` in the app data directory
+    #[serde(default)]
+    pub storage_path: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 8088,
+            allow_stow: false,
+            storage_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub running: bool,
+    pub base_url: Option<String>,
+    pub config: Option<ServerConfig>,
+}
+
+struct RunningServer {
+    config: ServerConfig,
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// DICOMweb server bound to 127.0.0.1, serving QIDO-RS, WADO-RS and STOW-RS
+/// from the `studies`/`instances` index
+pub struct DicomWebServer {
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl DicomWebServer {
+    pub fn new() -> Self {
+        Self {
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Bind the listener and start serving in the background
+    pub async fn start(&self, pool: DbPool, config: ServerConfig) -> Result<ServerStatus> {
+        let mut running = self.running.lock().await;
+        if let Some(server) = running.as_ref() {
+            return Err(anyhow::anyhow!(
+                "DICOMweb server is already running on {}",
+                server.addr
+            ));
+        }
+
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", config.port)).await?;
+        let addr = listener.local_addr()?;
+
+        let storage_dir = config
+            .storage_path
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| crate::utils::get_app_data_dir().join("dicomweb_store"));
+
+        let state = AppState {
+            pool,
+            allow_stow: config.allow_stow,
+            storage_dir,
+            base_url: format!("http://{}", addr),
+            port: addr.port(),
+        };
+        let app = router(state);
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        tracing::info!("Starting DICOMweb server on http://{}", addr);
+
+        tokio::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .await;
+
+            if let Err(e) = result {
+                tracing::error!("DICOMweb server error: {}", e);
+            }
+            tracing::info!("DICOMweb server stopped");
+        });
+
+        *running = Some(RunningServer { config, addr, shutdown });
+        Ok(status_of(running.as_ref()))
+    }
+
+    /// Stop the server; in-flight requests are allowed to finish
+    pub async fn stop(&self) -> Result<()> {
+        if let Some(server) = self.running.lock().await.take() {
+            tracing::info!("Stopping DICOMweb server on {}", server.addr);
+            let _ = server.shutdown.send(());
+        }
+        Ok(())
+    }
+
+    pub async fn status(&self) -> ServerStatus {
+        status_of(self.running.lock().await.as_ref())
+    }
+}
+
+impl Default for DicomWebServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn status_of(server: Option<&RunningServer>) -> ServerStatus {
+    ServerStatus {
+        running: server.is_some(),
+        base_url: server.map(|s| format!("http://{}", s.addr)),
+        config: server.map(|s| s.config.clone()),
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    pool: DbPool,
+    allow_stow: bool,
+    storage_dir: PathBuf,
+    base_url: String,
+    /// Port the server listens on, for the Host check
+    port: u16,
+}
+
+fn router(state: AppState) -> Router {
+    use tower_http::cors::{AllowOrigin, CorsLayer};
+
+    // STOW-RS bodies are whole studies, far beyond axum's default 2 MB limit
+    let stow = || post(store).layer(DefaultBodyLimit::max(MAX_STOW_BODY_BYTES));
+
+    let instance = "/studies/:study/series/:series/instances/:instance";
+    let routes = Router::new()
+        .route("/studies", get(search_studies).merge(stow()))
+        .route("/studies/:study", get(retrieve).merge(stow()))
+        .route("/studies/:study/metadata", get(metadata))
+        .route("/studies/:study/thumbnail", get(thumbnail))
+        .route("/studies/:study/series", get(search_series))
+        .route("/studies/:study/instances", get(search_instances))
+        .route("/studies/:study/series/:series", get(retrieve))
+        .route("/studies/:study/series/:series/metadata", get(metadata))
+        .route("/studies/:study/series/:series/thumbnail", get(thumbnail))
+        .route("/studies/:study/series/:series/instances", get(search_instances))
+        .route(instance, get(retrieve))
+        .route(&format!("{}/metadata", instance), get(metadata))
+        .route(&format!("{}/rendered", instance), get(rendered))
+        .route(&format!("{}/thumbnail", instance), get(thumbnail))
+        .route(&format!("{}/frames/:frames", instance), get(frames))
+        .route(&format!("{}/frames/:frames/rendered", instance), get(rendered))
+        .route("/series", get(search_series))
+        .route("/instances", get(search_instances));
+
+    // Browser-based viewers on this machine may call the server; other origins may not
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(|origin: &HeaderValue, _| {
+            origin
+                .to_str()
+                .map(is_local_origin)
+                .unwrap_or(false)
+        }))
+        .allow_methods(tower_http::cors::Any)
+        .allow_headers(tower_http::cors::Any);
+
+    // Also serve under the service prefixes DicomWebClient appends, so a saved
+    // endpoint pointing at this server works for QIDO, WADO and STOW alike
+    Router::new()
+        .merge(routes.clone())
+        .nest("/qido-rs", routes.clone())
+        .nest("/wado-rs", routes.clone())
+        .nest("/stow-rs", routes)
+        .layer(axum::middleware::from_fn_with_state(state.clone(), check_host))
+        .layer(cors)
+        .with_state(state)
+}
+
+/// Refuse requests not addressed to this server by a loopback name. Without
+/// this a web page could rebind its own domain to 127.0.0.1 (DNS rebinding)
+/// and read or store studies through the server as a same-origin page.
+async fn check_host(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|h| h.to_str().ok());
+    if !host.map_or(false, |host| is_local_host(host, state.port)) {
+        tracing::warn!("Refused DICOMweb request for host {:?}", host);
+        return (StatusCode::FORBIDDEN, "Host not allowed").into_response();
+    }
+    next.run(request).await
+}
+
+fn is_local_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, p)) if !name.is_empty() && !p.contains(']') => (name, p.parse::<u16>().ok()),
+        _ => (host, None),
+    };
+    // Clients leave out the default port
+    let port_matches = host_port == Some(port) || (host_port.is_none() && port == 80);
+    port_matches && ["127.0.0.1", "localhost", "[::1]"].iter().any(|n| name.eq_ignore_ascii_case(n))
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .unwrap_or("");
+    let host = host.rsplit_once(':').map(|(h, _)| h).unwrap_or(host);
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "tauri.localhost")
+        || origin.starts_with("tauri://")
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+fn bad_request(message: impl Into<String>) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, message.into())
+}
+
+fn not_found(message: impl Into<String>) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, message.into())
+}
+
+fn dicom_json(status: StatusCode, value: Value) -> Response {
+    (status, [(header::CONTENT_TYPE, "application/dicom+json")], value.to_string()).into_response()
+}
+
+fn multipart_response(media_type: &str, parts: Vec<MultipartPart>) -> Response {
+    let boundary = multipart::generate_boundary();
+    let content_type = format!(
+        "multipart/related; type=\"{}\"; boundary={}",
+        media_type, boundary
+    );
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        multipart::encode(&boundary, &parts),
+    )
+        .into_response()
+}
+
+fn part(content_type: &str, body: Vec<u8>) -> MultipartPart {
+    let mut headers = HashMap::new();
+    headers.insert("content-type".to_string(), content_type.to_string());
+    MultipartPart { headers, body }
+}
+
+// ---------------------------------------------------------------------------
+// Index access
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct IndexedInstance {
+    study_instance_uid: String,
+    series_instance_uid: String,
+    sop_instance_uid: String,
+    tags_json: String,
+    file_path: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct IndexedStudy {
+    study_instance_uid: String,
+    patient_id: Option<String>,
+    patient_name: Option<String>,
+    study_date: Option<String>,
+    study_description: Option<String>,
+    modality: Option<String>,
+    series_count: i64,
+    instance_count: i64,
+    tags_json: Option<String>,
+}
+
+async fn find_instances(
+    pool: &DbPool,
+    study: Option<&str>,
+    series: Option<&str>,
+    instance: Option<&str>,
+) -> Result<Vec<IndexedInstance>> {
+    let rows = sqlx::query_as::<_, IndexedInstance>(
+        "SELECT s.study_instance_uid, i.series_instance_uid, i.sop_instance_uid,
+                i.tags_json, i.file_path
+         FROM instances i JOIN studies s ON s.id = i.study_id
+         WHERE (? IS NULL OR s.study_instance_uid = ?)
+           AND (? IS NULL OR i.series_instance_uid = ?)
+           AND (? IS NULL OR i.sop_instance_uid = ?)
+         ORDER BY s.study_instance_uid, i.series_instance_uid, i.instance_number, i.sop_instance_uid"
+    )
+    .bind(study)
+    .bind(study)
+    .bind(series)
+    .bind(series)
+    .bind(instance)
+    .bind(instance)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+async fn find_studies(pool: &DbPool) -> Result<Vec<IndexedStudy>> {
+    let rows = sqlx::query_as::<_, IndexedStudy>(
+        "SELECT s.study_instance_uid, s.patient_id, s.patient_name, s.study_date,
+                s.study_description, s.modality,
+                COUNT(DISTINCT i.series_instance_uid) AS series_count,
+                COUNT(i.id) AS instance_count,
+                (SELECT tags_json FROM instances WHERE study_id = s.id LIMIT 1) AS tags_json
+         FROM studies s LEFT JOIN instances i ON i.study_id = s.id
+         GROUP BY s.id
+         ORDER BY s.study_date DESC, s.study_instance_uid"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Instances addressed by the path parameters; 404 when there are none
+async fn resolve_instances(
+    state: &AppState,
+    params: &HashMap<String, String>,
+) -> ApiResult<Vec<IndexedInstance>> {
+    let rows = find_instances(
+        &state.pool,
+        params.get("study").map(|s| s.as_str()),
+        params.get("series").map(|s| s.as_str()),
+        params.get("instance").map(|s| s.as_str()),
+    )
+    .await?;
+
+    if rows.is_empty() {
+        return Err(not_found("No matching instances in the local store"));
+    }
+    Ok(rows)
+}
+
+/// Attribute values stored in an instance's `tags_json`, keyed by "GGGGEEEE"
+fn indexed_attributes(tags_json: &str) -> HashMap<String, (String, String)> {
+    let parsed: Vec<crate::dicom::tags::DicomTag> = serde_json::from_str(tags_json).unwrap_or_default();
+    parsed
+        .into_iter()
+        .map(|t| {
+            let key: String = t.tag.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+            (key.to_uppercase(), (t.vr, t.value))
+        })
+        .collect()
+}
+
+/// Build a DICOM JSON attribute from a VR and a backslash-separated string value
+fn attribute(vr: &str, value: &str) -> Value {
+    let value = value.trim_end_matches(['\0', ' ']);
+    if value.is_empty() {
+        return json!({ "vr": vr });
+    }
+
+    let values: Vec<Value> = value
+        .split('\\')
+        .map(|v| match vr {
+            "PN" => json!({ "Alphabetic": v }),
+            "IS" | "SL" | "SS" | "UL" | "US" => v
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .unwrap_or_else(|_| Value::from(v)),
+            "DS" | "FL" | "FD" => v
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or_else(|| Value::from(v)),
+            _ => Value::from(v.trim()),
+        })
+        .collect();
+
+    json!({ "vr": vr, "Value": values })
+}
+
+fn put(obj: &mut Map<String, Value>, tag: Tag, vr: &str, value: &str) {
+    obj.insert(tag_key(tag), attribute(vr, value));
+}
+
+fn copy_attributes(
+    obj: &mut Map<String, Value>,
+    attributes: &HashMap<String, (String, String)>,
+    tags: &[Tag],
+) {
+    for tag in tags {
+        let key = tag_key(*tag);
+        if let Some((vr, value)) = attributes.get(&key) {
+            obj.insert(key, attribute(vr, value));
+        }
+    }
+}
+
+fn study_record(state: &AppState, study: &IndexedStudy) -> Map<String, Value> {
+    let mut obj = Map::new();
+    if let Some(tags_json) = &study.tags_json {
+        copy_attributes(
+            &mut obj,
+            &indexed_attributes(tags_json),
+            &[
+                tags::SPECIFIC_CHARACTER_SET,
+                tags::STUDY_TIME,
+                tags::ACCESSION_NUMBER,
+                tags::REFERRING_PHYSICIAN_NAME,
+                tags::PATIENT_BIRTH_DATE,
+                tags::PATIENT_SEX,
+                tags::STUDY_ID,
+            ],
+        );
+    }
+
+    let uid = &study.study_instance_uid;
+    put(&mut obj, tags::STUDY_INSTANCE_UID, "UI", uid);
+    put(&mut obj, tags::PATIENT_ID, "LO", study.patient_id.as_deref().unwrap_or(""));
+    put(&mut obj, tags::PATIENT_NAME, "PN", study.patient_name.as_deref().unwrap_or(""));
+    put(&mut obj, tags::STUDY_DATE, "DA", study.study_date.as_deref().unwrap_or(""));
+    put(&mut obj, tags::STUDY_DESCRIPTION, "LO", study.study_description.as_deref().unwrap_or(""));
+    put(&mut obj, tags::MODALITIES_IN_STUDY, "CS", study.modality.as_deref().unwrap_or(""));
+    put(&mut obj, tags::NUMBER_OF_STUDY_RELATED_SERIES, "IS", &study.series_count.to_string());
+    put(&mut obj, tags::NUMBER_OF_STUDY_RELATED_INSTANCES, "IS", &study.instance_count.to_string());
+    put(&mut obj, tags::RETRIEVE_URL, "UR", &format!("{}/studies/{}", state.base_url, uid));
+    obj
+}
+
+fn series_records(state: &AppState, rows: &[IndexedInstance]) -> Vec<Map<String, Value>> {
+    let mut records: Vec<Map<String, Value>> = Vec::new();
+    let mut counts: Vec<usize> = Vec::new();
+    let mut current: Option<(&str, &str)> = None;
+
+    for row in rows {
+        let key = (row.study_instance_uid.as_str(), row.series_instance_uid.as_str());
+        if current == Some(key) {
+            *counts.last_mut().unwrap() += 1;
+            continue;
+        }
+        current = Some(key);
+
+        let mut obj = Map::new();
+        copy_attributes(
+            &mut obj,
+            &indexed_attributes(&row.tags_json),
+            &[
+                tags::MODALITY,
+                tags::SERIES_NUMBER,
+                tags::SERIES_DESCRIPTION,
+                tags::SERIES_DATE,
+                tags::SERIES_TIME,
+                tags::BODY_PART_EXAMINED,
+            ],
+        );
+        put(&mut obj, tags::STUDY_INSTANCE_UID, "UI", &row.study_instance_uid);
+        put(&mut obj, tags::SERIES_INSTANCE_UID, "UI", &row.series_instance_uid);
+        put(
+            &mut obj,
+            tags::RETRIEVE_URL,
+            "UR",
+            &format!(
+                "{}/studies/{}/series/{}",
+                state.base_url, row.study_instance_uid, row.series_instance_uid
+            ),
+        );
+        records.push(obj);
+        counts.push(1);
+    }
+
+    for (obj, count) in records.iter_mut().zip(counts) {
+        put(obj, tags::NUMBER_OF_SERIES_RELATED_INSTANCES, "IS", &count.to_string());
+    }
+    records
+}
+
+fn instance_record(state: &AppState, row: &IndexedInstance) -> Map<String, Value> {
+    let mut obj = Map::new();
+    copy_attributes(
+        &mut obj,
+        &indexed_attributes(&row.tags_json),
+        &[
+            tags::SOP_CLASS_UID,
+            tags::INSTANCE_NUMBER,
+            tags::ROWS,
+            tags::COLUMNS,
+            tags::BITS_ALLOCATED,
+            tags::NUMBER_OF_FRAMES,
+        ],
+    );
+    put(&mut obj, tags::STUDY_INSTANCE_UID, "UI", &row.study_instance_uid);
+    put(&mut obj, tags::SERIES_INSTANCE_UID, "UI", &row.series_instance_uid);
+    put(&mut obj, tags::SOP_INSTANCE_UID, "UI", &row.sop_instance_uid);
+    put(
+        &mut obj,
+        tags::RETRIEVE_URL,
+        "UR",
+        &format!(
+            "{}/studies/{}/series/{}/instances/{}",
+            state.base_url, row.study_instance_uid, row.series_instance_uid, row.sop_instance_uid
+        ),
+    );
+    obj
+}
+
+// ---------------------------------------------------------------------------
+// QIDO-RS
+
+#[derive(Debug, Default)]
+struct SearchParams {
+    filters: Vec<(Tag, String)>,
+    fuzzy: bool,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl SearchParams {
+    fn parse(query: &[(String, String)]) -> ApiResult<Self> {
+        let mut params = SearchParams::default();
+
+        for (key, value) in query {
+            match key.as_str() {
+                "limit" => {
+                    params.limit = Some(value.parse().map_err(|_| bad_request("Invalid limit"))?);
+                }
+                "offset" => {
+                    params.offset = value.parse().map_err(|_| bad_request("Invalid offset"))?;
+                }
+                "fuzzymatching" => params.fuzzy = value == "true",
+                // All attributes the index holds are returned regardless
+                "includefield" => {}
+                _ => {
+                    let tag = AttributeKey::parse(key)
+                        .ok()
+                        .and_then(|k| k.tag())
+                        .ok_or_else(|| bad_request(format!("Unknown query attribute: {}", key)))?;
+                    params.filters.push((tag, value.clone()));
+                }
+            }
+        }
+
+        Ok(params)
+    }
+
+    /// Apply the attribute filters, then offset and limit
+    fn apply(&self, records: Vec<Map<String, Value>>) -> Value {
+        let matched = records
+            .into_iter()
+            .filter(|obj| {
+                self.filters
+                    .iter()
+                    .all(|(tag, pattern)| matches_attribute(obj, *tag, pattern, self.fuzzy))
+            })
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(Value::Object)
+            .collect();
+
+        Value::Array(matched)
+    }
+}
+
+/// Match an attribute against a QIDO-RS query value: UID lists, date/time ranges
+/// and `*`/`?` wildcards (PS3.4 C.2.2.2)
+fn matches_attribute(obj: &Map<String, Value>, tag: Tag, pattern: &str, fuzzy: bool) -> bool {
+    if pattern.is_empty() || pattern == "*" {
+        return true;
+    }
+
+    let Some(element) = obj.get(&tag_key(tag)) else {
+        return false;
+    };
+    let vr = element["vr"].as_str().unwrap_or("");
+    let values: Vec<String> = element["Value"]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    Value::Object(pn) => pn
+                        .get("Alphabetic")
+                        .and_then(|a| a.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    values.iter().any(|value| match vr {
+        "UI" => pattern.split(['\\', ',']).any(|uid| uid.trim() == value),
+        "DA" | "TM" | "DT" if pattern.contains('-') => {
+            let (from, to) = pattern.split_once('-').unwrap();
+            (from.is_empty() || value.as_str() >= from) && (to.is_empty() || value.as_str() <= to)
+        }
+        "PN" => wildcard_match(&pattern.to_lowercase(), &value.to_lowercase()),
+        _ if fuzzy => wildcard_match(&pattern.to_lowercase(), &value.to_lowercase()),
+        _ => wildcard_match(pattern, value),
+    })
+}
+
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    // matched[j]: pattern[..i] matches value[..j]
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+    for p in &pattern {
+        let mut next = vec![false; value.len() + 1];
+        if *p == '*' {
+            next[0] = matched[0];
+            for j in 1..=value.len() {
+                next[j] = matched[j] || next[j - 1];
+            }
+        } else {
+            for j in 1..=value.len() {
+                next[j] = matched[j - 1] && (*p == '?' || *p == value[j - 1]);
+            }
+        }
+        matched = next;
+    }
+
+    matched[value.len()]
+}
+
+async fn search_studies(
+    State(state): State<AppState>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> ApiResult<Response> {
+    let params = SearchParams::parse(&query)?;
+    let records = find_studies(&state.pool)
+        .await?
+        .iter()
+        .map(|study| study_record(&state, study))
+        .collect();
+
+    Ok(dicom_json(StatusCode::OK, params.apply(records)))
+}
+
+async fn search_series(
+    State(state): State<AppState>,
+    path: Option<UrlPath<HashMap<String, String>>>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> ApiResult<Response> {
+    let params = SearchParams::parse(&query)?;
+    let path = path.map(|p| p.0).unwrap_or_default();
+    let rows = find_instances(&state.pool, path.get("study").map(|s| s.as_str()), None, None).await?;
+
+    Ok(dicom_json(StatusCode::OK, params.apply(series_records(&state, &rows))))
+}
+
+async fn search_instances(
+    State(state): State<AppState>,
+    path: Option<UrlPath<HashMap<String, String>>>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> ApiResult<Response> {
+    let params = SearchParams::parse(&query)?;
+    let path = path.map(|p| p.0).unwrap_or_default();
+    let rows = find_instances(
+        &state.pool,
+        path.get("study").map(|s| s.as_str()),
+        path.get("series").map(|s| s.as_str()),
+        None,
+    )
+    .await?;
+    let records = rows.iter().map(|row| instance_record(&state, row)).collect();
+
+    Ok(dicom_json(StatusCode::OK, params.apply(records)))
+}
+
+// ---------------------------------------------------------------------------
+// WADO-RS
+
+/// Run file loading, decoding or rendering on the blocking pool, off the async workers
+async fn blocking<T, F>(work: F) -> ApiResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> ApiResult<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| ApiError::from(anyhow::Error::from(e)))?
+}
+
+/// Instances are returned as stored; transfer syntax conversion is not offered.
+/// Each file is read only when its part is sent, so a study is never buffered whole.
+async fn retrieve(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    use futures_util::stream::{self, StreamExt};
+
+    let rows = resolve_instances(&state, &path).await?;
+
+    let boundary = multipart::generate_boundary();
+    let content_type = format!(
+        "multipart/related; type=\"application/dicom\"; boundary={}",
+        boundary
+    );
+    let part_header = Bytes::from(format!("--{}\r\ncontent-type: application/dicom\r\n\r\n", boundary));
+    let closing = Bytes::from(format!("--{}--\r\n", boundary));
+
+    let parts = stream::iter(rows).then(move |row| {
+        let part_header = part_header.clone();
+        async move {
+            match tokio::fs::read(&row.file_path).await {
+                Ok(bytes) => vec![Ok(part_header), Ok(Bytes::from(bytes)), Ok(Bytes::from_static(b"\r\n"))],
+                Err(e) => {
+                    // The status has been sent; ending the stream aborts the response
+                    tracing::error!("Failed to read {}: {}", row.file_path, e);
+                    vec![Err(e)]
+                }
+            }
+        }
+    });
+    let body = parts
+        .flat_map(stream::iter)
+        .chain(stream::once(async move { Ok::<_, std::io::Error>(closing) }));
+
+    Ok(([(header::CONTENT_TYPE, content_type)], axum::body::Body::from_stream(body)).into_response())
+}
+
+async fn metadata(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    let rows = resolve_instances(&state, &path).await?;
+
+    let results = blocking(move || {
+        let mut results = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let mut obj = crate::dicom::load_dicom_file(&row.file_path)?;
+            // Pixel data is served through the frames and rendered resources
+            obj.remove_element(tags::PIXEL_DATA);
+            results.push(crate::dicom::json::to_json(&obj)?);
+        }
+        Ok(results)
+    })
+    .await?;
+
+    Ok(dicom_json(StatusCode::OK, Value::Array(results)))
+}
+
+async fn frames(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    let rows = resolve_instances(&state, &path).await?;
+    let numbers = parse_frame_list(path.get("frames").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let file_path = rows[0].file_path.clone();
+    let parts = blocking(move || {
+        let file_obj = dicom_object::open_file(&file_path).map_err(anyhow::Error::from)?;
+        let mut parts = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let frame = pixeldata::raw_frame(&file_obj, number - 1)
+                .map_err(|e| not_found(e.to_string()))?;
+            parts.push(part(&frame.media_type, frame.data));
+        }
+        Ok(parts)
+    })
+    .await?;
+
+    let media_type = parts[0]
+        .content_type()
+        .and_then(|ct| ct.split(';').next())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    Ok(multipart_response(&media_type, parts))
+}
+
+/// Parse a comma-separated list of one-based frame numbers
+fn parse_frame_list(list: &str) -> ApiResult<Vec<u32>> {
+    let numbers = list
+        .split(',')
+        .map(|n| n.trim().parse::<u32>().ok().filter(|n| *n > 0))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(|| bad_request(format!("Invalid frame list: {}", list)))?;
+
+    if numbers.is_empty() {
+        return Err(bad_request("Empty frame list"));
+    }
+    Ok(numbers)
+}
+
+/// Render options from the `window`, `viewport` and `quality` query parameters
+/// and the Accept header (PS3.18 8.3.5)
+fn render_options(query: &[(String, String)], headers: &HeaderMap) -> ApiResult<RenderOptions> {
+    let mut options = RenderOptions::default();
+
+    for (key, value) in query {
+        let numbers: Vec<&str> = value.split(',').collect();
+        match key.as_str() {
+            "window" => {
+                let center = numbers.first().and_then(|v| v.parse().ok());
+                let width = numbers.get(1).and_then(|v| v.parse().ok());
+                match (center, width) {
+                    (Some(center), Some(width)) => options.window = Some((center, width)),
+                    _ => return Err(bad_request(format!("Invalid window: {}", value))),
+                }
+            }
+            "viewport" => {
+                let width = numbers.first().and_then(|v| v.parse().ok());
+                let height = numbers.get(1).and_then(|v| v.parse().ok());
+                match (width, height) {
+                    (Some(width), Some(height)) => options.max_size = Some((width, height)),
+                    _ => return Err(bad_request(format!("Invalid viewport: {}", value))),
+                }
+            }
+            "quality" => {
+                options.quality = Some(value.parse().map_err(|_| bad_request("Invalid quality"))?);
+            }
+            _ => {}
+        }
+    }
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if accept.contains("image/png") && !accept.contains("image/jpeg") {
+        options.format = image::ImageFormat::Png;
+    }
+
+    Ok(options)
+}
+
+fn image_response(options: &RenderOptions, bytes: Vec<u8>) -> Response {
+    let content_type = match options.format {
+        image::ImageFormat::Png => "image/png",
+        _ => "image/jpeg",
+    };
+    ([(header::CONTENT_TYPE, content_type)], bytes).into_response()
+}
+
+async fn rendered(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<HashMap<String, String>>,
+    Query(query): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let rows = resolve_instances(&state, &path).await?;
+    let mut options = render_options(&query, &headers)?;
+
+    if let Some(list) = path.get("frames") {
+        let numbers = parse_frame_list(list)?;
+        if numbers.len() > 1 {
+            return Err(bad_request("Rendering more than one frame is not supported"));
+        }
+        options.frame = numbers[0] - 1;
+    }
+
+    let file_path = rows[0].file_path.clone();
+    let render = options.clone();
+    let bytes = blocking(move || Ok(pixeldata::render_frame(&file_path, &render)?)).await?;
+    Ok(image_response(&options, bytes))
+}
+
+async fn thumbnail(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<HashMap<String, String>>,
+    Query(query): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let rows = resolve_instances(&state, &path).await?;
+    let mut options = render_options(&query, &headers)?;
+    options.max_size = options.max_size.or(Some(THUMBNAIL_SIZE));
+
+    // The middle instance is usually more representative than the first
+    let file_path = rows[rows.len() / 2].file_path.clone();
+    let render = options.clone();
+    let bytes = blocking(move || Ok(pixeldata::render_frame(&file_path, &render)?)).await?;
+    Ok(image_response(&options, bytes))
+}
+
+// ---------------------------------------------------------------------------
+// STOW-RS
+
+async fn store(
+    State(state): State<AppState>,
+    path: Option<UrlPath<HashMap<String, String>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<Response> {
+    if !state.allow_stow {
+        return Err(ApiError(StatusCode::FORBIDDEN, "STOW-RS is disabled on this server".to_string()));
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !multipart::is_multipart_related(content_type) {
+        return Err(ApiError(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Expected a multipart/related request body".to_string(),
+        ));
+    }
+    let parts = multipart::parse(content_type, &body).map_err(|e| bad_request(e.to_string()))?;
+    let path = path.map(|p| p.0).unwrap_or_default();
+    let target_study = path.get("study");
+
+    let mut referenced = Vec::new();
+    let mut failed = Vec::new();
+    for part in &parts {
+        match store_part(&state, part, target_study.map(|s| s.as_str())).await {
+            Ok(item) => referenced.push(Value::Object(item)),
+            Err((item, e)) => {
+                tracing::warn!("STOW-RS instance rejected: {}", e);
+                failed.push(Value::Object(item));
+            }
+        }
+    }
+
+    let mut response = Map::new();
+    if let Some(study) = target_study {
+        put(&mut response, tags::RETRIEVE_URL, "UR", &format!("{}/studies/{}", state.base_url, study));
+    }
+    if !referenced.is_empty() {
+        response.insert(tag_key(tags::REFERENCED_SOP_SEQUENCE), json!({ "vr": "SQ", "Value": referenced }));
+    }
+    if !failed.is_empty() {
+        response.insert(tag_key(tags::FAILED_SOP_SEQUENCE), json!({ "vr": "SQ", "Value": failed }));
+    }
+
+    let status = match (referenced.is_empty(), failed.is_empty()) {
+        (_, true) => StatusCode::OK,
+        (false, false) => StatusCode::ACCEPTED,
+        (true, false) => StatusCode::CONFLICT,
+    };
+    Ok(dicom_json(status, Value::Object(response)))
+}
+
+/// Write one uploaded instance to the storage directory and index it. Returns the
+/// Referenced SOP Sequence item, or a Failed SOP Sequence item with the error.
+async fn store_part(
+    state: &AppState,
+    part: &MultipartPart,
+    target_study: Option<&str>,
+) -> std::result::Result<Map<String, Value>, (Map<String, Value>, anyhow::Error)> {
+    let mut item = Map::new();
+
+    let result: Result<()> = async {
+        if let Some(content_type) = part.content_type() {
+            if !content_type.starts_with("application/dicom") {
+                return Err(anyhow::anyhow!("Unsupported part type: {}", content_type));
+            }
+        }
+
+        let obj = crate::dicom::read_dicom_bytes(&part.body)?;
+        let uid = |tag: Tag| -> Result<String> {
+            Ok(obj.element(tag)?.to_str()?.trim_end_matches('\0').trim().to_string())
+        };
+        let sop_class = uid(tags::SOP_CLASS_UID)?;
+        let sop_instance = uid(tags::SOP_INSTANCE_UID)?;
+        put(&mut item, tags::REFERENCED_SOP_CLASS_UID, "UI", &sop_class);
+        put(&mut item, tags::REFERENCED_SOP_INSTANCE_UID, "UI", &sop_instance);
+
+        // UIDs become directory and file names, so anything but a valid UID is refused
+        crate::utils::file_helpers::safe_uid(&sop_instance)?;
+        let study = uid(tags::STUDY_INSTANCE_UID)?;
+        crate::utils::file_helpers::safe_uid(&study)?;
+        if let Some(target) = target_study {
+            if target != study {
+                return Err(anyhow::anyhow!(
+                    "Instance belongs to study {}, not {}",
+                    study,
+                    target
+                ));
+            }
+        }
+        let series = uid(tags::SERIES_INSTANCE_UID)?;
+        crate::utils::file_helpers::safe_uid(&series)?;
+
+        let dir = state.storage_dir.join(&study);
+        let file_path = dir.join(format!("{}.dcm", sop_instance));
+
+        // Part 10 files need the 128-byte preamble, which uploads may omit
+        let mut bytes = Vec::with_capacity(part.body.len() + 128);
+        if !(part.body.len() >= 132 && &part.body[128..132] == b"DICM") {
+            bytes.resize(128, 0);
+        }
+        bytes.extend_from_slice(&part.body);
+
+        let target = file_path.clone();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            std::fs::create_dir_all(&dir)?;
+            crate::utils::file_helpers::write_atomic(&target, |temp| Ok(std::fs::write(temp, &bytes)?))
+        })
+        .await??;
+
+        crate::database::index::index_file(&state.pool, &file_path).await?;
+
+        put(
+            &mut item,
+            tags::RETRIEVE_URL,
+            "UR",
+            &format!("{}/studies/{}/series/{}/instances/{}", state.base_url, study, series, sop_instance),
+        );
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(item),
+        Err(e) => {
+            item.insert(
+                tag_key(tags::FAILURE_REASON),
+                json!({ "vr": "US", "Value": [PROCESSING_FAILURE] }),
+            );
+            Err((item, e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> DbPool {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("DOE^*", "DOE^JOHN"));
+        assert!(wildcard_match("C?", "CT"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("MR", "CT"));
+        assert!(!wildcard_match("DOE?", "DOE"));
+    }
+
+    #[test]
+    fn test_matches_attribute() {
+        let mut obj = Map::new();
+        put(&mut obj, tags::PATIENT_NAME, "PN", "Doe^John");
+        put(&mut obj, tags::STUDY_DATE, "DA", "20240115");
+        put(&mut obj, tags::STUDY_INSTANCE_UID, "UI", "1.2.3");
+
+        assert!(matches_attribute(&obj, tags::PATIENT_NAME, "doe*", false));
+        assert!(matches_attribute(&obj, tags::STUDY_DATE, "20240101-20240131", false));
+        assert!(!matches_attribute(&obj, tags::STUDY_DATE, "20240201-", false));
+        assert!(matches_attribute(&obj, tags::STUDY_INSTANCE_UID, "1.2.4,1.2.3", false));
+        assert!(!matches_attribute(&obj, tags::ACCESSION_NUMBER, "A1", false));
+    }
+
+    #[test]
+    fn test_local_origin() {
+        assert!(is_local_origin("http://localhost:3000"));
+        assert!(is_local_origin("http://127.0.0.1:8080"));
+        assert!(!is_local_origin("https://example.com"));
+    }
+
+    #[test]
+    fn test_local_host() {
+        assert!(is_local_host("127.0.0.1:8088", 8088));
+        assert!(is_local_host("localhost:8088", 8088));
+        assert!(is_local_host("[::1]:8088", 8088));
+        assert!(is_local_host("localhost", 80));
+        assert!(!is_local_host("localhost:8089", 8088));
+        assert!(!is_local_host("localhost", 8088));
+        assert!(!is_local_host("[::1]", 8088));
+        assert!(!is_local_host("rebind.example.com:8088", 8088));
+        assert!(!is_local_host("127.0.0.1.example.com:8088", 8088));
+    }
+
+    #[tokio::test]
+    async fn test_server_lifecycle() {
+        let server = DicomWebServer::new();
+        let config = ServerConfig {
+            port: 0,
+            ..Default::default()
+        };
+
+        let status = server.start(test_pool().await, config).await.unwrap();
+        let base_url = status.base_url.unwrap();
+        assert!(server.start(test_pool().await, ServerConfig::default()).await.is_err());
+
+        let client = reqwest::Client::new();
+        for path in ["studies", "qido-rs/studies?PatientName=DOE*&limit=10"] {
+            let response = client.get(format!("{}/{}", base_url, path)).send().await.unwrap();
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.text().await.unwrap(), "[]");
+        }
+
+        let response = client
+            .get(format!("{}/studies/1.2.3/metadata", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        // A page that rebound its domain to 127.0.0.1 still sends its own Host
+        let port = base_url.rsplit(':').next().unwrap();
+        let response = client
+            .get(format!("{}/studies", base_url))
+            .header("Host", format!("rebind.example.com:{}", port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        let response = client
+            .post(format!("{}/studies", base_url))
+            .header("Content-Type", "multipart/related; type=\"application/dicom\"; boundary=x")
+            .body("--x--\r\n")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        server.stop().await.unwrap();
+        assert!(!server.status().await.running);
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_unsafe_uids() {
+        use dicom_core::{DataElement, PrimitiveValue, VR};
+        use dicom_object::InMemDicomObject;
+
+        let storage_dir = std::env::temp_dir().join(format!("stow-uid-test-{}", std::process::id()));
+        let state = AppState {
+            pool: test_pool().await,
+            allow_stow: true,
+            storage_dir: storage_dir.clone(),
+            base_url: "http://localhost".to_string(),
+            port: 80,
+        };
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from("1.2.840.10008.5.1.4.1.1.7")),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from("../../evil")),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3")),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3.4")),
+        ]);
+        let meta = crate::dicom::build_file_meta(&obj, "1.2.840.10008.1.2.1").unwrap();
+        let mut body = Vec::new();
+        obj.with_exact_meta(meta).write_all(&mut body).unwrap();
+        let part = MultipartPart {
+            headers: HashMap::from([("content-type".to_string(), "application/dicom".to_string())]),
+            body,
+        };
+
+        let (item, _) = store_part(&state, &part, None).await.unwrap_err();
+        assert!(item.contains_key(&tag_key(tags::FAILURE_REASON)));
+        assert!(!storage_dir.exists());
+    }
+}
@@ -1,52 +1,69 @@
 // STOW-RS (Store Over the Web)
 
 use super::client::DicomWebClient;
-use super::DicomWebRequest;
+use super::{multipart, DicomWebRequest};
 use anyhow::Result;
 use std::collections::HashMap;
 
-/// Store DICOM instances
-pub async fn store_instances(
-    client: &DicomWebClient,
-    study_uid: Option<&str>,
-    instances: Vec<Vec<u8>>,
-) -> Result<StowResponse> {
+/// Build a STOW-RS request storing Part 10 byte streams as one multipart/related body
+pub fn store_request(study_uid: Option<&str>, instances: Vec<Vec<u8>>) -> DicomWebRequest {
     let endpoint = if let Some(uid) = study_uid {
         format!("stow-rs/studies/{}", uid)
     } else {
         "stow-rs/studies".to_string()
     };
 
-    // TODO: Implement multipart/related encoding for DICOM instances
-    // This is a placeholder - need to properly encode multiple DICOM files
+    let parts: Vec<multipart::MultipartPart> = instances
+        .into_iter()
+        .map(|body| {
+            let mut headers = HashMap::new();
+            headers.insert("Content-Type".to_string(), "application/dicom".to_string());
+            multipart::MultipartPart { headers, body }
+        })
+        .collect();
+    let boundary = multipart::generate_boundary();
 
-    let request = DicomWebRequest {
+    DicomWebRequest {
         method: "POST".to_string(),
         endpoint,
         headers: {
             let mut headers = HashMap::new();
             headers.insert(
                 "Content-Type".to_string(),
-                "multipart/related; type=\"application/dicom\"".to_string(),
+                format!("multipart/related; type=\"application/dicom\"; boundary={}", boundary),
             );
             headers.insert("Accept".to_string(), "application/dicom+json".to_string());
             headers
         },
-        body: None, // TODO: Build multipart body
-    };
+        body: Some(multipart::encode(&boundary, &parts)),
+    }
+}
 
+/// Store DICOM instances (Part 10 byte streams)
+pub async fn store_instances(
+    client: &DicomWebClient,
+    study_uid: Option<&str>,
+    instances: Vec<Vec<u8>>,
+) -> Result<StowResponse> {
+    let request = store_request(study_uid, instances);
     let response = client.execute(request).await?;
 
-    if response.status != 200 {
-        return Err(anyhow::anyhow!(
+    // 202 and 409 still carry a store response listing the failed instances
+    match response.status {
+        200 | 202 | 409 if !response.body.trim().is_empty() => {
+            let value: serde_json::Value = serde_json::from_str(&response.body)?;
+            Ok(StowResponse::from_json(&value))
+        }
+        200 => Ok(StowResponse {
+            success: Vec::new(),
+            failed: Vec::new(),
+        }),
+        _ => Err(anyhow::anyhow!(
             "STOW-RS failed with status {}: {}",
             response.status,
             response.body
-        ));
+        )),
     }
-
-    let stow_response: StowResponse = serde_json::from_str(&response.body)?;
-    Ok(stow_response)
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -55,6 +72,49 @@ pub struct StowResponse {
     pub failed: Vec<FailedInstance>,
 }
 
+impl StowResponse {
+    /// Read the Referenced and Failed SOP Sequences of a store response (PS3.18 10.5.3)
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        let items = |tag: &str| -> Vec<serde_json::Value> {
+            value
+                .get(tag)
+                .and_then(|sq| sq.get("Value"))
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default()
+        };
+        let instance_uid = |item: &serde_json::Value| -> String {
+            item.get("00081155")
+                .and_then(|e| e.get("Value"))
+                .and_then(|v| v.get(0))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+
+        let success = items("00081199").iter().map(instance_uid).collect();
+        let failed = items("00081198")
+            .iter()
+            .map(|item| {
+                let reason = item
+                    .get("00081197")
+                    .and_then(|e| e.get("Value"))
+                    .and_then(|v| v.get(0))
+                    .and_then(|v| v.as_u64());
+                FailedInstance {
+                    instance_uid: instance_uid(item),
+                    reason: match reason {
+                        Some(code) => format!("Failure reason 0x{:04X}", code),
+                        None => "Unknown failure".to_string(),
+                    },
+                }
+            })
+            .collect();
+
+        Self { success, failed }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FailedInstance {
     pub instance_uid: String,
@@ -65,5 +125,21 @@ pub struct FailedInstance {
 mod tests {
     use super::*;
 
-    // Add tests
+    #[test]
+    fn test_store_response_from_json() {
+        let value = serde_json::json!({
+            "00081199": { "vr": "SQ", "Value": [
+                { "00081155": { "vr": "UI", "Value": ["1.2.3"] } }
+            ]},
+            "00081198": { "vr": "SQ", "Value": [
+                { "00081155": { "vr": "UI", "Value": ["1.2.4"] },
+                  "00081197": { "vr": "US", "Value": [49152] } }
+            ]}
+        });
+
+        let response = StowResponse::from_json(&value);
+        assert_eq!(response.success, vec!["1.2.3".to_string()]);
+        assert_eq!(response.failed[0].instance_uid, "1.2.4");
+        assert_eq!(response.failed[0].reason, "Failure reason 0xC000");
+    }
 }
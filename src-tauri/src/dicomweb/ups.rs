@@ -0,0 +1,854 @@
+// UPS-RS (Unified Procedure Step) worklist client (PS3.18 section 11)
+
+use super::client::DicomWebClient;
+use super::qido::{encode, QidoQuery, QidoResponse};
+use super::{DicomWebRequest, DicomWebResponse};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Well-known UID addressing all workitems in subscription requests
+pub const GLOBAL_SUBSCRIPTION_UID: &str = "1.2.840.10008.5.1.4.34.5";
+
+/// Well-known UID addressing workitems that match a filter in subscription requests
+pub const FILTERED_SUBSCRIPTION_UID: &str = "1.2.840.10008.5.1.4.34.5.1";
+
+const SOP_INSTANCE_UID: &str = "00080018";
+const TRANSACTION_UID: &str = "00081195";
+const PROCEDURE_STEP_STATE: &str = "00741000";
+const REASON_FOR_CANCELLATION: &str = "00741238";
+const AFFECTED_SOP_INSTANCE_UID: &str = "00001000";
+const EVENT_TYPE_ID: &str = "00001002";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProcedureStepState {
+    Scheduled,
+    InProgress,
+    Canceled,
+    Completed,
+}
+
+impl ProcedureStepState {
+    /// Value of the Procedure Step State attribute
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcedureStepState::Scheduled => "SCHEDULED",
+            ProcedureStepState::InProgress => "IN PROGRESS",
+            ProcedureStepState::Canceled => "CANCELED",
+            ProcedureStepState::Completed => "COMPLETED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "SCHEDULED" => Some(ProcedureStepState::Scheduled),
+            "IN PROGRESS" => Some(ProcedureStepState::InProgress),
+            "CANCELED" => Some(ProcedureStepState::Canceled),
+            "COMPLETED" => Some(ProcedureStepState::Completed),
+            _ => None,
+        }
+    }
+}
+
+fn workitem_path(uid: &str) -> String {
+    format!("ups-rs/workitems/{}", encode(uid))
+}
+
+fn json_request(method: &str, endpoint: String, body: Option<Value>) -> DicomWebRequest {
+    let mut headers = HashMap::new();
+    headers.insert("Accept".to_string(), "application/dicom+json".to_string());
+    if body.is_some() {
+        headers.insert("Content-Type".to_string(), "application/dicom+json".to_string());
+    }
+
+    DicomWebRequest {
+        method: method.to_string(),
+        endpoint,
+        headers,
+        body: body.map(|b| b.to_string().into_bytes()),
+    }
+}
+
+fn header<'a>(response: &'a DicomWebResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Error for an unexpected status, including the server's Warning header which
+/// UPS-RS uses to explain rejected state changes
+fn failure(action: &str, response: &DicomWebResponse) -> anyhow::Error {
+    let warning = header(response, "warning")
+        .map(|w| format!(" ({})", w))
+        .unwrap_or_default();
+    anyhow::anyhow!(
+        "{} failed with status {}{}: {}",
+        action,
+        response.status,
+        warning,
+        response.body
+    )
+}
+
+fn string_value(dataset: &Value, key: &str) -> Option<String> {
+    dataset
+        .get(key)?
+        .get("Value")?
+        .get(0)?
+        .as_str()
+        .map(|s| s.trim().to_string())
+}
+
+fn state_of(dataset: &Value) -> Option<ProcedureStepState> {
+    string_value(dataset, PROCEDURE_STEP_STATE).and_then(|s| ProcedureStepState::parse(&s))
+}
+
+/// Generate a Transaction UID for claiming a workitem
+pub fn generate_transaction_uid() -> String {
+    format!("2.25.{}", uuid::Uuid::new_v4().as_u128())
+}
+
+/// Search for workitems. Only the matching keys, includefield, fuzzymatching and
+/// paging of `query` are used; its level and study/series scope do not apply.
+pub async fn search(client: &DicomWebClient, query: &QidoQuery) -> Result<QidoResponse> {
+    let mut endpoint = "ups-rs/workitems".to_string();
+    let query_string = query.query_string();
+    if !query_string.is_empty() {
+        endpoint.push('?');
+        endpoint.push_str(&query_string);
+    }
+
+    let response = client.execute(json_request("GET", endpoint, None)).await?;
+    let warnings: Vec<String> = response
+        .headers
+        .iter()
+        .filter(|(key, _)| key.eq_ignore_ascii_case("warning"))
+        .map(|(_, value)| value.clone())
+        .collect();
+
+    match response.status {
+        204 => Ok(QidoResponse {
+            results: Vec::new(),
+            warnings,
+            partial: false,
+        }),
+        200 | 206 => Ok(QidoResponse {
+            results: serde_json::from_str(&response.body)?,
+            warnings,
+            partial: response.status == 206,
+        }),
+        _ => Err(failure("UPS-RS search", &response)),
+    }
+}
+
+/// Retrieve a single workitem as a DICOM JSON object
+pub async fn retrieve(client: &DicomWebClient, uid: &str) -> Result<Value> {
+    let response = client.execute(json_request("GET", workitem_path(uid), None)).await?;
+    if response.status != 200 {
+        return Err(failure("UPS-RS retrieve", &response));
+    }
+
+    let value: Value = serde_json::from_str(&response.body)?;
+    match value {
+        Value::Array(mut items) if !items.is_empty() => Ok(items.swap_remove(0)),
+        Value::Object(_) => Ok(value),
+        _ => Err(anyhow::anyhow!("UPS-RS retrieve returned no workitem")),
+    }
+}
+
+/// Create a workitem, returning its UID. The state defaults to SCHEDULED; without
+/// `uid` or a SOP Instance UID in the data set the server assigns one.
+pub async fn create(client: &DicomWebClient, mut workitem: Value, uid: Option<&str>) -> Result<String> {
+    let uid = uid
+        .map(|u| u.to_string())
+        .or_else(|| string_value(&workitem, SOP_INSTANCE_UID));
+
+    workitem
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("Workitem must be a DICOM JSON object"))?
+        .entry(PROCEDURE_STEP_STATE)
+        .or_insert_with(|| json!({ "vr": "CS", "Value": [ProcedureStepState::Scheduled.as_str()] }));
+
+    let endpoint = match &uid {
+        Some(uid) => format!("ups-rs/workitems?{}", encode(uid)),
+        None => "ups-rs/workitems".to_string(),
+    };
+    let response = client
+        .execute(json_request("POST", endpoint, Some(Value::Array(vec![workitem]))))
+        .await?;
+    if response.status != 201 {
+        return Err(failure("UPS-RS create", &response));
+    }
+
+    // The Location header points at the new workitem
+    let location_uid = header(&response, "location")
+        .and_then(|location| location.trim_end_matches('/').rsplit('/').next())
+        .map(|s| s.to_string());
+
+    uid.or(location_uid)
+        .ok_or_else(|| anyhow::anyhow!("Server did not return the UID of the created workitem"))
+}
+
+/// Update attributes of a workitem. Workitems in progress require the Transaction UID
+/// returned by `claim`.
+pub async fn update(
+    client: &DicomWebClient,
+    uid: &str,
+    transaction_uid: Option<&str>,
+    changes: Value,
+) -> Result<()> {
+    let mut endpoint = workitem_path(uid);
+    if let Some(transaction_uid) = transaction_uid {
+        endpoint.push('?');
+        endpoint.push_str(&encode(transaction_uid));
+    }
+
+    let response = client
+        .execute(json_request("POST", endpoint, Some(Value::Array(vec![changes]))))
+        .await?;
+    match response.status {
+        200 | 204 => Ok(()),
+        _ => Err(failure("UPS-RS update", &response)),
+    }
+}
+
+/// Change the state of a workitem
+pub async fn change_state(
+    client: &DicomWebClient,
+    uid: &str,
+    state: ProcedureStepState,
+    transaction_uid: &str,
+) -> Result<()> {
+    let body = json!([{
+        PROCEDURE_STEP_STATE: { "vr": "CS", "Value": [state.as_str()] },
+        TRANSACTION_UID: { "vr": "UI", "Value": [transaction_uid] },
+    }]);
+
+    let endpoint = format!("{}/state", workitem_path(uid));
+    let response = client.execute(json_request("PUT", endpoint, Some(body))).await?;
+    match response.status {
+        200 | 204 => Ok(()),
+        _ => Err(failure(&format!("UPS-RS change state to {}", state.as_str()), &response)),
+    }
+}
+
+/// Claim a scheduled workitem, returning the Transaction UID needed to update,
+/// complete or cancel it
+pub async fn claim(client: &DicomWebClient, uid: &str) -> Result<String> {
+    let transaction_uid = generate_transaction_uid();
+    change_state(client, uid, ProcedureStepState::InProgress, &transaction_uid).await?;
+    Ok(transaction_uid)
+}
+
+pub async fn complete(client: &DicomWebClient, uid: &str, transaction_uid: &str) -> Result<()> {
+    change_state(client, uid, ProcedureStepState::Completed, transaction_uid).await
+}
+
+pub async fn cancel(client: &DicomWebClient, uid: &str, transaction_uid: &str) -> Result<()> {
+    change_state(client, uid, ProcedureStepState::Canceled, transaction_uid).await
+}
+
+/// Ask the performer of a workitem to cancel it
+pub async fn request_cancellation(client: &DicomWebClient, uid: &str, reason: Option<&str>) -> Result<()> {
+    let mut dataset = serde_json::Map::new();
+    if let Some(reason) = reason {
+        dataset.insert(
+            REASON_FOR_CANCELLATION.to_string(),
+            json!({ "vr": "LT", "Value": [reason] }),
+        );
+    }
+
+    let endpoint = format!("{}/cancelrequest", workitem_path(uid));
+    let response = client
+        .execute(json_request("POST", endpoint, Some(json!([dataset]))))
+        .await?;
+    match response.status {
+        200 | 202 => Ok(()),
+        _ => Err(failure("UPS-RS cancellation request", &response)),
+    }
+}
+
+/// What a subscription covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SubscriptionTarget {
+    Workitem { uid: String },
+    Global,
+    /// Workitems matching the query's keys
+    Filtered { query: QidoQuery },
+}
+
+impl SubscriptionTarget {
+    fn path(&self, aet: &str) -> String {
+        let uid = match self {
+            SubscriptionTarget::Workitem { uid } => uid.as_str(),
+            SubscriptionTarget::Global => GLOBAL_SUBSCRIPTION_UID,
+            SubscriptionTarget::Filtered { .. } => FILTERED_SUBSCRIPTION_UID,
+        };
+        format!("{}/subscribers/{}", workitem_path(uid), encode(aet))
+    }
+}
+
+/// Subscribe `aet` to workitem events. Returns the event channel URL from the
+/// Content-Location header, when the server sends one.
+pub async fn subscribe(
+    client: &DicomWebClient,
+    target: &SubscriptionTarget,
+    aet: &str,
+    deletion_lock: bool,
+) -> Result<Option<String>> {
+    let mut params = vec![format!("deletionlock={}", deletion_lock)];
+    if let SubscriptionTarget::Filtered { query } = target {
+        let query_string = query.query_string();
+        if !query_string.is_empty() {
+            params.push(query_string);
+        }
+    }
+
+    let endpoint = format!("{}?{}", target.path(aet), params.join("&"));
+    let response = client.execute(json_request("POST", endpoint, None)).await?;
+    match response.status {
+        200 | 201 => Ok(header(&response, "content-location").map(|s| s.to_string())),
+        _ => Err(failure("UPS-RS subscribe", &response)),
+    }
+}
+
+pub async fn unsubscribe(client: &DicomWebClient, target: &SubscriptionTarget, aet: &str) -> Result<()> {
+    let response = client
+        .execute(json_request("DELETE", target.path(aet), None))
+        .await?;
+    match response.status {
+        200 | 204 => Ok(()),
+        _ => Err(failure("UPS-RS unsubscribe", &response)),
+    }
+}
+
+/// Stop new global or filtered subscriptions for `aet` while keeping existing ones
+pub async fn suspend_subscription(client: &DicomWebClient, target: &SubscriptionTarget, aet: &str) -> Result<()> {
+    let endpoint = format!("{}/suspend", target.path(aet));
+    let response = client.execute(json_request("POST", endpoint, None)).await?;
+    match response.status {
+        200 | 204 => Ok(()),
+        _ => Err(failure("UPS-RS suspend subscription", &response)),
+    }
+}
+
+/// Event Type ID of a UPS event report (PS3.4 Table CC.2.4-1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpsEventType {
+    StateReport,
+    CancelRequested,
+    ProgressReport,
+    ScpStatusChange,
+    Assigned,
+    Other(u16),
+}
+
+impl From<u16> for UpsEventType {
+    fn from(id: u16) -> Self {
+        match id {
+            1 => UpsEventType::StateReport,
+            2 => UpsEventType::CancelRequested,
+            3 => UpsEventType::ProgressReport,
+            4 => UpsEventType::ScpStatusChange,
+            5 => UpsEventType::Assigned,
+            other => UpsEventType::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsEvent {
+    pub workitem_uid: String,
+    pub event_type: UpsEventType,
+    pub state: Option<ProcedureStepState>,
+    /// Event information, or in polling mode the workitem as last seen
+    pub dataset: Value,
+}
+
+impl UpsEvent {
+    /// Parse an event report received on the event channel
+    pub fn from_event_report(value: &Value) -> Result<Self> {
+        let workitem_uid = string_value(value, AFFECTED_SOP_INSTANCE_UID)
+            .or_else(|| string_value(value, SOP_INSTANCE_UID))
+            .ok_or_else(|| anyhow::anyhow!("Event report has no workitem UID"))?;
+
+        let event_type = value
+            .get(EVENT_TYPE_ID)
+            .and_then(|e| e.get("Value"))
+            .and_then(|v| v.get(0))
+            .and_then(|v| v.as_u64())
+            .map(|id| UpsEventType::from(id as u16))
+            .unwrap_or(UpsEventType::StateReport);
+
+        Ok(Self {
+            workitem_uid,
+            event_type,
+            state: state_of(value),
+            dataset: value.clone(),
+        })
+    }
+}
+
+/// How to receive workitem events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum EventMode {
+    /// Repeat a search and report workitems that appear or change state. Works with
+    /// servers that do not offer an event channel.
+    Polling { query: QidoQuery, interval_ms: u64 },
+    /// Subscribe as `aet` and receive event reports over the server's WebSocket channel
+    #[serde(rename = "websocket")]
+    WebSocket { aet: String, target: SubscriptionTarget },
+}
+
+/// Stream of workitem events; the background task stops when the stream is dropped
+pub struct UpsEventStream {
+    receiver: mpsc::Receiver<UpsEvent>,
+    task: JoinHandle<()>,
+}
+
+impl UpsEventStream {
+    /// Wait for the next event; `None` once the event source has closed
+    pub async fn next(&mut self) -> Option<UpsEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for UpsEventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Start receiving workitem events
+pub async fn watch_events(client: DicomWebClient, mode: EventMode) -> Result<UpsEventStream> {
+    let (sender, receiver) = mpsc::channel(64);
+
+    let task = match mode {
+        EventMode::Polling { query, interval_ms } => {
+            let interval = Duration::from_millis(interval_ms.max(100));
+            tokio::spawn(poll_events(client, query, interval, sender))
+        }
+        EventMode::WebSocket { aet, target } => {
+            let url = subscribe(&client, &target, &aet, false)
+                .await?
+                .unwrap_or_else(|| {
+                    format!(
+                        "{}/ups-rs/subscribers/{}",
+                        client.endpoint().base_url.trim_end_matches('/'),
+                        encode(&aet)
+                    )
+                });
+            let socket = connect_channel(&client, &websocket_url(&url)).await?;
+            tokio::spawn(forward_channel(socket, sender))
+        }
+    };
+
+    Ok(UpsEventStream { receiver, task })
+}
+
+async fn poll_events(
+    client: DicomWebClient,
+    query: QidoQuery,
+    interval: Duration,
+    sender: mpsc::Sender<UpsEvent>,
+) {
+    let mut known: Option<HashMap<String, Option<ProcedureStepState>>> = None;
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        if sender.is_closed() {
+            return;
+        }
+
+        let response = match search(&client, &query).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("UPS-RS polling failed: {}", e);
+                continue;
+            }
+        };
+
+        let mut events = Vec::new();
+        let mut seen = HashMap::new();
+        for item in response.results {
+            let Some(uid) = string_value(&item, SOP_INSTANCE_UID) else {
+                continue;
+            };
+            let state = state_of(&item);
+
+            // The first poll only establishes the baseline
+            if let Some(known) = &known {
+                if known.get(&uid) != Some(&state) {
+                    events.push(UpsEvent {
+                        workitem_uid: uid.clone(),
+                        event_type: UpsEventType::StateReport,
+                        state,
+                        dataset: item,
+                    });
+                }
+            }
+            seen.insert(uid, state);
+        }
+
+        // Workitems leaving the results (e.g. a state filter no longer matches) are
+        // looked up once so their final state is still reported
+        if let Some(known) = &known {
+            for (uid, previous) in known {
+                if seen.contains_key(uid) {
+                    continue;
+                }
+                if let Ok(item) = retrieve(&client, uid).await {
+                    let state = state_of(&item);
+                    if state != *previous {
+                        events.push(UpsEvent {
+                            workitem_uid: uid.clone(),
+                            event_type: UpsEventType::StateReport,
+                            state,
+                            dataset: item,
+                        });
+                    }
+                }
+            }
+        }
+
+        for event in events {
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+        known = Some(seen);
+    }
+}
+
+type EventChannel =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Event channel URLs may be given with an http(s) scheme
+fn websocket_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+async fn connect_channel(client: &DicomWebClient, url: &str) -> Result<EventChannel> {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::header::{HeaderName, HeaderValue, AUTHORIZATION};
+
+    let mut request = url.into_client_request()?;
+    let headers = request.headers_mut();
+    for (key, value) in &client.endpoint().headers {
+        headers.insert(HeaderName::from_bytes(key.as_bytes())?, HeaderValue::from_str(value)?);
+    }
+    if let Some(authorization) = client.authorization().await? {
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+    }
+
+    tracing::info!("Opening UPS-RS event channel {}", url);
+    let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+    Ok(socket)
+}
+
+async fn forward_channel(mut socket: EventChannel, sender: mpsc::Sender<UpsEvent>) {
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    while let Some(message) = socket.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Binary(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!("UPS-RS event channel error: {}", e);
+                break;
+            }
+        };
+
+        match parse_event_message(&text) {
+            Ok(events) => {
+                for event in events {
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Ignoring malformed UPS-RS event: {}", e),
+        }
+    }
+
+    tracing::info!("UPS-RS event channel closed");
+}
+
+/// An event message holds one event report or an array of them
+fn parse_event_message(text: &str) -> Result<Vec<UpsEvent>> {
+    match serde_json::from_str::<Value>(text)? {
+        Value::Array(items) => items.iter().map(UpsEvent::from_event_report).collect(),
+        other => Ok(vec![UpsEvent::from_event_report(&other)?]),
+    }
+}
+
+struct Watch {
+    task: JoinHandle<()>,
+    client: DicomWebClient,
+    /// Server-side subscription to remove when the watch stops
+    subscription: Option<(SubscriptionTarget, String)>,
+}
+
+/// Event watches running in the background, by watch id
+#[derive(Default)]
+pub struct UpsWatchers {
+    watches: Arc<Mutex<HashMap<String, Watch>>>,
+}
+
+impl UpsWatchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching for events under the caller's watch id, calling `on_event` for each.
+    /// A watch whose event stream ends by itself is removed along with its subscription.
+    pub async fn start<F>(&self, id: String, client: DicomWebClient, mode: EventMode, on_event: F) -> Result<()>
+    where
+        F: Fn(UpsEvent) + Send + 'static,
+    {
+        let subscription = match &mode {
+            EventMode::WebSocket { aet, target } => Some((target.clone(), aet.clone())),
+            EventMode::Polling { .. } => None,
+        };
+
+        let mut stream = watch_events(client.clone(), mode).await?;
+
+        // The map stays locked until the watch is inserted, so a stream that ends
+        // at once can't try to remove its watch before it is there
+        let mut watches = self.watches.lock().await;
+        let all = Arc::clone(&self.watches);
+        let task_id = id.clone();
+        let task = tokio::spawn(async move {
+            while let Some(event) = stream.next().await {
+                on_event(event);
+            }
+
+            let Some(watch) = all.lock().await.remove(&task_id) else {
+                return;
+            };
+            tracing::info!("UPS event watch {} ended", task_id);
+            if let Some((target, aet)) = &watch.subscription {
+                if let Err(e) = unsubscribe(&watch.client, target, aet).await {
+                    tracing::warn!("Failed to remove subscription of ended watch {}: {}", task_id, e);
+                }
+            }
+        });
+
+        watches.insert(
+            id,
+            Watch {
+                task,
+                client,
+                subscription,
+            },
+        );
+        Ok(())
+    }
+
+    /// Stop a watch and remove its subscription. Returns false for unknown ids.
+    pub async fn stop(&self, id: &str) -> Result<bool> {
+        let Some(watch) = self.watches.lock().await.remove(id) else {
+            return Ok(false);
+        };
+
+        watch.task.abort();
+        if let Some((target, aet)) = &watch.subscription {
+            unsubscribe(&watch.client, target, aet).await?;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicomweb::qido::{AttributeKey, QueryLevel};
+    use crate::dicomweb::DicomWebEndpoint;
+    use wiremock::matchers::{body_string_contains, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> DicomWebClient {
+        DicomWebClient::new(DicomWebEndpoint::for_test(server.uri())).unwrap()
+    }
+
+    fn workitem(uid: &str, state: &str) -> Value {
+        json!({
+            SOP_INSTANCE_UID: { "vr": "UI", "Value": [uid] },
+            PROCEDURE_STEP_STATE: { "vr": "CS", "Value": [state] },
+        })
+    }
+
+    fn scheduled_query() -> QidoQuery {
+        let mut query = QidoQuery::new(QueryLevel::Studies);
+        query.params.insert(
+            AttributeKey::parse("ProcedureStepState").unwrap(),
+            "SCHEDULED".to_string(),
+        );
+        query
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ups-rs/workitems"))
+            .and(query_param("ProcedureStepState", "SCHEDULED"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([workitem("1.2.3", "SCHEDULED")])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = search(&client(&server), &scheduled_query()).await.unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(state_of(&response.results[0]), Some(ProcedureStepState::Scheduled));
+    }
+
+    #[tokio::test]
+    async fn test_create_uses_location() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/ups-rs/workitems"))
+            .and(body_string_contains("SCHEDULED"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("Location", format!("{}/ups-rs/workitems/1.2.9", server.uri()).as_str()),
+            )
+            .mount(&server)
+            .await;
+
+        let uid = create(&client(&server), json!({}), None).await.unwrap();
+        assert_eq!(uid, "1.2.9");
+    }
+
+    #[tokio::test]
+    async fn test_claim_then_complete() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/ups-rs/workitems/1.2.3/state"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let transaction_uid = claim(&client, "1.2.3").await.unwrap();
+        assert!(transaction_uid.starts_with("2.25."));
+        complete(&client, "1.2.3", &transaction_uid).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let claim_body = String::from_utf8_lossy(&requests[0].body).to_string();
+        let complete_body = String::from_utf8_lossy(&requests[1].body).to_string();
+        assert!(claim_body.contains("IN PROGRESS"));
+        assert!(complete_body.contains("COMPLETED"));
+        assert!(complete_body.contains(&transaction_uid));
+    }
+
+    #[tokio::test]
+    async fn test_state_change_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/ups-rs/workitems/1.2.3/state"))
+            .respond_with(
+                ResponseTemplate::new(409)
+                    .insert_header("Warning", "299 ups: The workitem is already in progress"),
+            )
+            .mount(&server)
+            .await;
+
+        let err = claim(&client(&server), "1.2.3").await.unwrap_err();
+        assert!(err.to_string().contains("already in progress"));
+    }
+
+    #[tokio::test]
+    async fn test_polling_reports_state_change() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ups-rs/workitems"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([workitem("1.2.3", "SCHEDULED")])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ups-rs/workitems"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([workitem("1.2.3", "IN PROGRESS")])))
+            .mount(&server)
+            .await;
+
+        let mode = EventMode::Polling {
+            query: QidoQuery::new(QueryLevel::Studies),
+            interval_ms: 100,
+        };
+        let mut stream = watch_events(client(&server), mode).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(event.workitem_uid, "1.2.3");
+        assert_eq!(event.event_type, UpsEventType::StateReport);
+        assert_eq!(event.state, Some(ProcedureStepState::InProgress));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_events() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let report = json!({
+                AFFECTED_SOP_INSTANCE_UID: { "vr": "UI", "Value": ["1.2.3"] },
+                EVENT_TYPE_ID: { "vr": "US", "Value": [2] },
+            });
+            socket.send(Message::Text(report.to_string())).await.unwrap();
+        });
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(format!("/ups-rs/workitems/{}/subscribers/DICOMFLOW", GLOBAL_SUBSCRIPTION_UID)))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("Content-Location", format!("ws://{}/subscribers/DICOMFLOW", ws_addr).as_str()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mode = EventMode::WebSocket {
+            aet: "DICOMFLOW".to_string(),
+            target: SubscriptionTarget::Global,
+        };
+        let mut stream = watch_events(client(&server), mode).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(event.workitem_uid, "1.2.3");
+        assert_eq!(event.event_type, UpsEventType::CancelRequested);
+    }
+}
@@ -1,9 +1,23 @@
 // WADO-RS (Web Access to DICOM Objects)
 
 use super::client::DicomWebClient;
-use super::DicomWebRequest;
+use super::{multipart, DicomWebRequest};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Build the Accept header for a Part 10 retrieval, optionally requesting a transfer syntax.
+/// Use `"*"` to ask for the instances as stored by the origin server.
+pub fn dicom_accept_header(transfer_syntax: Option<&str>) -> String {
+    match transfer_syntax {
+        Some(ts) => format!(
+            "multipart/related; type=\"application/dicom\"; transfer-syntax={}",
+            ts
+        ),
+        None => "multipart/related; type=\"application/dicom\"".to_string(),
+    }
+}
 
 /// Retrieve DICOM instance
 pub async fn retrieve_instance(
@@ -17,28 +31,397 @@ pub async fn retrieve_instance(
         study_uid, series_uid, instance_uid
     );
 
+    let mut instances = retrieve_part10(client, endpoint, None).await?;
+    if instances.is_empty() {
+        return Err(anyhow::anyhow!("WADO-RS retrieve returned no instances"));
+    }
+
+    Ok(instances.swap_remove(0))
+}
+
+/// Retrieve all instances of a study as Part 10 byte streams
+pub async fn retrieve_study(
+    client: &DicomWebClient,
+    study_uid: &str,
+    transfer_syntax: Option<&str>,
+) -> Result<Vec<Vec<u8>>> {
+    let endpoint = format!("wado-rs/studies/{}", study_uid);
+    retrieve_part10(client, endpoint, transfer_syntax).await
+}
+
+/// Retrieve all instances of a series as Part 10 byte streams
+pub async fn retrieve_series(
+    client: &DicomWebClient,
+    study_uid: &str,
+    series_uid: &str,
+    transfer_syntax: Option<&str>,
+) -> Result<Vec<Vec<u8>>> {
+    let endpoint = format!("wado-rs/studies/{}/series/{}", study_uid, series_uid);
+    retrieve_part10(client, endpoint, transfer_syntax).await
+}
+
+/// GET a resource that returns application/dicom parts and split the response
+async fn retrieve_part10(
+    client: &DicomWebClient,
+    endpoint: String,
+    transfer_syntax: Option<&str>,
+) -> Result<Vec<Vec<u8>>> {
     let request = DicomWebRequest {
         method: "GET".to_string(),
         endpoint,
         headers: {
             let mut headers = HashMap::new();
-            headers.insert("Accept".to_string(), "application/dicom".to_string());
+            headers.insert("Accept".to_string(), dicom_accept_header(transfer_syntax));
             headers
         },
         body: None,
     };
 
-    let response = client.execute(request).await?;
+    let response = client.execute_raw(request).await?;
 
-    if response.status != 200 {
+    if response.status != 200 && response.status != 206 {
         return Err(anyhow::anyhow!(
             "WADO-RS retrieve failed with status {}: {}",
             response.status,
-            response.body
+            String::from_utf8_lossy(&response.body)
         ));
     }
 
-    Ok(response.body.into_bytes())
+    let content_type = response.content_type().unwrap_or("").to_string();
+
+    // Some servers answer single-instance requests with a bare application/dicom body
+    if !multipart::is_multipart_related(&content_type) {
+        return Ok(vec![response.body]);
+    }
+
+    let parts = multipart::parse(&content_type, &response.body)?;
+    let instances: Vec<Vec<u8>> = parts
+        .into_iter()
+        .filter(|part| {
+            part.content_type()
+                .map(|ct| ct.starts_with("application/dicom"))
+                .unwrap_or(true)
+        })
+        .map(|part| part.body)
+        .collect();
+
+    tracing::info!("WADO-RS retrieve returned {} instances", instances.len());
+
+    Ok(instances)
+}
+
+/// An instance that could not be written by [`save_instances`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveFailure {
+    /// SOP Instance UID, or the position in the response when it could not be read
+    pub instance: String,
+    pub reason: String,
+}
+
+/// Outcome of [`save_instances`]
+#[derive(Debug, Default)]
+pub struct SavedInstances {
+    pub paths: Vec<PathBuf>,
+    pub failures: Vec<SaveFailure>,
+}
+
+/// Write retrieved Part 10 instances into `output_dir`, named by SOP Instance UID.
+/// An instance that cannot be read or written is reported and the rest are still saved.
+pub fn save_instances<P: AsRef<Path>>(instances: &[Vec<u8>], output_dir: P) -> Result<SavedInstances> {
+    use dicom_dictionary_std::tags;
+
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)?;
+
+    let mut saved = SavedInstances::default();
+    for (index, data) in instances.iter().enumerate() {
+        let sop_uid = crate::dicom::read_dicom_bytes(data).and_then(|obj| {
+            Ok(obj.element(tags::SOP_INSTANCE_UID)?.to_str()?.to_string())
+        });
+        let sop_uid = match sop_uid {
+            Ok(uid) => uid,
+            Err(e) => {
+                saved.failures.push(SaveFailure {
+                    instance: format!("#{}", index + 1),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        // The UID comes from the server and becomes a file name
+        let result = crate::utils::file_helpers::safe_uid(&sop_uid).and_then(|uid| {
+            let path = output_dir.join(format!("{}.dcm", uid));
+            std::fs::write(&path, data)?;
+            Ok(path)
+        });
+        match result {
+            Ok(path) => saved.paths.push(path),
+            Err(e) => saved.failures.push(SaveFailure {
+                instance: sop_uid.trim_end_matches('\0').to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    if !saved.failures.is_empty() {
+        tracing::warn!("{} retrieved instances could not be saved", saved.failures.len());
+    }
+    Ok(saved)
+}
+
+/// Path of a study, series or instance resource below `wado-rs/`
+pub fn resource_path(
+    study_uid: &str,
+    series_uid: Option<&str>,
+    instance_uid: Option<&str>,
+) -> Result<String> {
+    match (series_uid, instance_uid) {
+        (Some(series), Some(instance)) => Ok(format!(
+            "wado-rs/studies/{}/series/{}/instances/{}",
+            study_uid, series, instance
+        )),
+        (Some(series), None) => Ok(format!("wado-rs/studies/{}/series/{}", study_uid, series)),
+        (None, None) => Ok(format!("wado-rs/studies/{}", study_uid)),
+        (None, Some(_)) => Err(anyhow::anyhow!(
+            "Series Instance UID is required to address an instance"
+        )),
+    }
+}
+
+/// Pixel data of a single frame as returned by the `/frames` resource
+#[derive(Debug, Clone)]
+pub struct FrameData {
+    pub frame_number: u32,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Build the Accept header for a frames request.
+/// `media_type` defaults to uncompressed `application/octet-stream`; compressed
+/// types such as `image/jpeg` or `image/jls` return the frames in that encoding.
+pub fn frames_accept_header(media_type: Option<&str>, transfer_syntax: Option<&str>) -> String {
+    let mut accept = format!(
+        "multipart/related; type=\"{}\"",
+        media_type.unwrap_or("application/octet-stream")
+    );
+    if let Some(ts) = transfer_syntax {
+        accept.push_str(&format!("; transfer-syntax={}", ts));
+    }
+    accept
+}
+
+/// Retrieve pixel data for a list of frames (1-based frame numbers)
+pub async fn retrieve_frames(
+    client: &DicomWebClient,
+    study_uid: &str,
+    series_uid: &str,
+    instance_uid: &str,
+    frames: &[u32],
+    media_type: Option<&str>,
+    transfer_syntax: Option<&str>,
+) -> Result<Vec<FrameData>> {
+    if frames.is_empty() {
+        return Err(anyhow::anyhow!("At least one frame number is required"));
+    }
+    if frames.contains(&0) {
+        return Err(anyhow::anyhow!("Frame numbers start at 1"));
+    }
+
+    let frame_list: Vec<String> = frames.iter().map(|f| f.to_string()).collect();
+    let endpoint = format!(
+        "{}/frames/{}",
+        resource_path(study_uid, Some(series_uid), Some(instance_uid))?,
+        frame_list.join(",")
+    );
+
+    let request = DicomWebRequest {
+        method: "GET".to_string(),
+        endpoint,
+        headers: {
+            let mut headers = HashMap::new();
+            headers.insert(
+                "Accept".to_string(),
+                frames_accept_header(media_type, transfer_syntax),
+            );
+            headers
+        },
+        body: None,
+    };
+
+    let response = client.execute_raw(request).await?;
+
+    if response.status != 200 && response.status != 206 {
+        return Err(anyhow::anyhow!(
+            "WADO-RS frames failed with status {}: {}",
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        ));
+    }
+
+    let content_type = response.content_type().unwrap_or("").to_string();
+
+    if !multipart::is_multipart_related(&content_type) {
+        // A single body can only be attributed to a single requested frame
+        if frames.len() > 1 {
+            return Err(anyhow::anyhow!(
+                "WADO-RS frames returned a single {} body for {} requested frames",
+                if content_type.is_empty() { "untyped" } else { content_type.as_str() },
+                frames.len()
+            ));
+        }
+        return Ok(vec![FrameData {
+            frame_number: frames[0],
+            content_type,
+            data: response.body,
+        }]);
+    }
+
+    // Parts are returned in the order the frames were requested
+    let parts = multipart::parse(&content_type, &response.body)?;
+    Ok(parts
+        .into_iter()
+        .zip(frames.iter())
+        .map(|(part, &frame_number)| FrameData {
+            frame_number,
+            content_type: part.content_type().unwrap_or("application/octet-stream").to_string(),
+            data: part.body,
+        })
+        .collect())
+}
+
+/// Windowing for the `/rendered` resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderWindow {
+    pub center: f64,
+    pub width: f64,
+    /// "linear", "linear-exact" or "sigmoid"
+    pub function: Option<String>,
+}
+
+/// Output size for `/rendered` and `/thumbnail`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Query parameters for rendered and thumbnail resources
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderedParams {
+    /// Requested media type, e.g. "image/jpeg" (default) or "image/png"
+    pub media_type: Option<String>,
+    pub window: Option<RenderWindow>,
+    pub viewport: Option<Viewport>,
+    /// Compression quality 1-100 for lossy media types
+    pub quality: Option<u8>,
+}
+
+impl RenderedParams {
+    fn accept(&self) -> String {
+        self.media_type.clone().unwrap_or_else(|| "image/jpeg".to_string())
+    }
+
+    fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(window) = &self.window {
+            params.push(format!(
+                "window={},{},{}",
+                window.center,
+                window.width,
+                window.function.as_deref().unwrap_or("linear")
+            ));
+        }
+        if let Some(viewport) = &self.viewport {
+            params.push(format!("viewport={},{}", viewport.width, viewport.height));
+        }
+        if let Some(quality) = self.quality {
+            params.push(format!("quality={}", quality.clamp(1, 100)));
+        }
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+/// A rendered image or thumbnail returned by the server
+#[derive(Debug, Clone)]
+pub struct RenderedImage {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Retrieve a server-side rendered image of a study, series, instance or single frame
+pub async fn retrieve_rendered(
+    client: &DicomWebClient,
+    study_uid: &str,
+    series_uid: Option<&str>,
+    instance_uid: Option<&str>,
+    frame: Option<u32>,
+    params: &RenderedParams,
+) -> Result<RenderedImage> {
+    let mut path = resource_path(study_uid, series_uid, instance_uid)?;
+    if let Some(frame) = frame {
+        if instance_uid.is_none() {
+            return Err(anyhow::anyhow!("A frame can only be rendered for an instance"));
+        }
+        path.push_str(&format!("/frames/{}", frame));
+    }
+
+    let endpoint = format!("{}/rendered{}", path, params.to_query_string());
+    retrieve_image(client, endpoint, params.accept(), "rendered").await
+}
+
+/// Retrieve a thumbnail of a study, series or instance
+pub async fn retrieve_thumbnail(
+    client: &DicomWebClient,
+    study_uid: &str,
+    series_uid: Option<&str>,
+    instance_uid: Option<&str>,
+    params: &RenderedParams,
+) -> Result<RenderedImage> {
+    let path = resource_path(study_uid, series_uid, instance_uid)?;
+    let endpoint = format!("{}/thumbnail{}", path, params.to_query_string());
+    retrieve_image(client, endpoint, params.accept(), "thumbnail").await
+}
+
+async fn retrieve_image(
+    client: &DicomWebClient,
+    endpoint: String,
+    accept: String,
+    resource: &str,
+) -> Result<RenderedImage> {
+    let request = DicomWebRequest {
+        method: "GET".to_string(),
+        endpoint,
+        headers: {
+            let mut headers = HashMap::new();
+            headers.insert("Accept".to_string(), accept.clone());
+            headers
+        },
+        body: None,
+    };
+
+    let response = client.execute_raw(request).await?;
+
+    if response.status != 200 {
+        return Err(anyhow::anyhow!(
+            "WADO-RS {} failed with status {}: {}",
+            resource,
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        ));
+    }
+
+    let content_type = response.content_type().map(|s| s.to_string()).unwrap_or(accept);
+
+    Ok(RenderedImage {
+        content_type,
+        data: response.body,
+    })
 }
 
 /// Retrieve metadata
@@ -90,5 +473,42 @@ pub async fn retrieve_metadata(
 mod tests {
     use super::*;
 
-    // Add tests
+    #[test]
+    fn test_resource_path() {
+        assert_eq!(resource_path("1.2", None, None).unwrap(), "wado-rs/studies/1.2");
+        assert_eq!(
+            resource_path("1.2", Some("1.3"), Some("1.4")).unwrap(),
+            "wado-rs/studies/1.2/series/1.3/instances/1.4"
+        );
+        assert!(resource_path("1.2", None, Some("1.4")).is_err());
+    }
+
+    #[test]
+    fn test_rendered_query_string() {
+        assert_eq!(RenderedParams::default().to_query_string(), "");
+
+        let params = RenderedParams {
+            media_type: Some("image/png".to_string()),
+            window: Some(RenderWindow { center: 40.0, width: 400.0, function: None }),
+            viewport: Some(Viewport { width: 256, height: 256 }),
+            quality: Some(90),
+        };
+        assert_eq!(
+            params.to_query_string(),
+            "?window=40,400,linear&viewport=256,256&quality=90"
+        );
+        assert_eq!(params.accept(), "image/png");
+    }
+
+    #[test]
+    fn test_dicom_accept_header() {
+        assert_eq!(
+            dicom_accept_header(None),
+            "multipart/related; type=\"application/dicom\""
+        );
+        assert_eq!(
+            dicom_accept_header(Some("1.2.840.10008.1.2.1")),
+            "multipart/related; type=\"application/dicom\"; transfer-syntax=1.2.840.10008.1.2.1"
+        );
+    }
 }
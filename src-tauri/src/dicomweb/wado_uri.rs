@@ -0,0 +1,293 @@
+// WADO-URI (PS3.18 chapter 9), the legacy single-object retrieve
+
+use super::client::DicomWebClient;
+use super::qido::encode;
+use super::DicomWebRequest;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Path of the WADO-URI service when the endpoint doesn't configure one
+const DEFAULT_PATH: &str = "wado";
+
+/// Image region to render, as fractions (0.0-1.0) of the image width and height
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Region {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+/// Parameters of a WADO-URI request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WadoUriParams {
+    pub study_uid: String,
+    pub series_uid: String,
+    pub object_uid: String,
+    /// "application/dicom" for the Part 10 object, or an image type such as
+    /// "image/jpeg". Servers return a rendered JPEG when it is omitted.
+    pub content_type: Option<String>,
+    /// Transfer syntax of the returned object; only valid with application/dicom
+    pub transfer_syntax: Option<String>,
+    pub window_center: Option<f64>,
+    pub window_width: Option<f64>,
+    pub region: Option<Region>,
+    /// 1-based frame of a multi-frame image
+    pub frame_number: Option<u32>,
+    pub rows: Option<u32>,
+    pub columns: Option<u32>,
+    /// Compression quality 1-100 for lossy image types
+    pub image_quality: Option<u8>,
+}
+
+impl WadoUriParams {
+    fn is_dicom(&self) -> bool {
+        self.content_type.as_deref() == Some("application/dicom")
+    }
+
+    /// Reject parameter combinations the standard doesn't allow
+    pub fn validate(&self) -> Result<()> {
+        if self.study_uid.is_empty() || self.series_uid.is_empty() || self.object_uid.is_empty() {
+            return Err(anyhow::anyhow!("Study, series and object UIDs are all required"));
+        }
+
+        if self.is_dicom() {
+            let rendering = self.window_center.is_some()
+                || self.window_width.is_some()
+                || self.region.is_some()
+                || self.frame_number.is_some()
+                || self.rows.is_some()
+                || self.columns.is_some()
+                || self.image_quality.is_some();
+            if rendering {
+                return Err(anyhow::anyhow!(
+                    "Windowing, region, frame, size and quality only apply to rendered images"
+                ));
+            }
+        } else if self.transfer_syntax.is_some() {
+            return Err(anyhow::anyhow!("transferSyntax requires contentType application/dicom"));
+        }
+
+        if self.window_center.is_some() != self.window_width.is_some() {
+            return Err(anyhow::anyhow!("windowCenter and windowWidth must be given together"));
+        }
+        if matches!(self.window_width, Some(width) if width < 1.0) {
+            return Err(anyhow::anyhow!("windowWidth must be at least 1"));
+        }
+
+        if let Some(region) = &self.region {
+            let values = [region.left, region.top, region.right, region.bottom];
+            if values.iter().any(|v| !(0.0..=1.0).contains(v))
+                || region.left >= region.right
+                || region.top >= region.bottom
+            {
+                return Err(anyhow::anyhow!(
+                    "Region must be left < right and top < bottom, within 0.0-1.0"
+                ));
+            }
+        }
+
+        if self.frame_number == Some(0) {
+            return Err(anyhow::anyhow!("Frame numbers start at 1"));
+        }
+
+        Ok(())
+    }
+
+    /// Query string, starting with `requestType=WADO`
+    pub fn to_query_string(&self) -> String {
+        let mut params = vec![
+            "requestType=WADO".to_string(),
+            format!("studyUID={}", encode(&self.study_uid)),
+            format!("seriesUID={}", encode(&self.series_uid)),
+            format!("objectUID={}", encode(&self.object_uid)),
+        ];
+
+        if let Some(content_type) = &self.content_type {
+            params.push(format!("contentType={}", encode(content_type)));
+        }
+        if let Some(ts) = &self.transfer_syntax {
+            params.push(format!("transferSyntax={}", encode(ts)));
+        }
+        if let (Some(center), Some(width)) = (self.window_center, self.window_width) {
+            params.push(format!("windowCenter={}", center));
+            params.push(format!("windowWidth={}", width));
+        }
+        if let Some(region) = &self.region {
+            params.push(format!(
+                "region={},{},{},{}",
+                region.left, region.top, region.right, region.bottom
+            ));
+        }
+        if let Some(frame) = self.frame_number {
+            params.push(format!("frameNumber={}", frame));
+        }
+        if let Some(rows) = self.rows {
+            params.push(format!("rows={}", rows));
+        }
+        if let Some(columns) = self.columns {
+            params.push(format!("columns={}", columns));
+        }
+        if let Some(quality) = self.image_quality {
+            params.push(format!("imageQuality={}", quality.clamp(1, 100)));
+        }
+
+        params.join("&")
+    }
+}
+
+/// Object returned by a WADO-URI request
+#[derive(Debug, Clone)]
+pub struct WadoUriObject {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Request path of a WADO-URI retrieve for this client's endpoint
+pub fn request_endpoint(client: &DicomWebClient, params: &WadoUriParams) -> String {
+    let path = client
+        .endpoint()
+        .wado_uri
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .unwrap_or(DEFAULT_PATH);
+
+    // Configured URLs may already carry query parameters of their own
+    let separator = if path.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", path.trim_start_matches('/'), separator, params.to_query_string())
+}
+
+/// Retrieve a Part 10 object or a rendered image through WADO-URI
+pub async fn retrieve(client: &DicomWebClient, params: &WadoUriParams) -> Result<WadoUriObject> {
+    params.validate()?;
+
+    let accept = match &params.content_type {
+        Some(content_type) => content_type.clone(),
+        None => "image/jpeg, */*;q=0.5".to_string(),
+    };
+    let mut headers = HashMap::new();
+    headers.insert("Accept".to_string(), accept);
+
+    let request = DicomWebRequest {
+        method: "GET".to_string(),
+        endpoint: request_endpoint(client, params),
+        headers,
+        body: None,
+    };
+
+    let response = client.execute_raw(request).await?;
+
+    if response.status != 200 {
+        return Err(anyhow::anyhow!(
+            "WADO-URI retrieve failed with status {}: {}",
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        ));
+    }
+
+    let content_type = response
+        .content_type()
+        .or(params.content_type.as_deref())
+        .unwrap_or("image/jpeg")
+        .to_string();
+
+    tracing::info!(
+        "WADO-URI retrieved {} ({} bytes, {})",
+        params.object_uid,
+        response.body.len(),
+        content_type
+    );
+
+    Ok(WadoUriObject {
+        content_type,
+        data: response.body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicomweb::{AuthType, DicomWebEndpoint};
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn params() -> WadoUriParams {
+        WadoUriParams {
+            study_uid: "1.2.3".to_string(),
+            series_uid: "1.2.3.4".to_string(),
+            object_uid: "1.2.3.4.5".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_query_string() {
+        let mut rendered = params();
+        rendered.content_type = Some("image/jpeg".to_string());
+        rendered.window_center = Some(40.0);
+        rendered.window_width = Some(400.0);
+        rendered.region = Some(Region { left: 0.0, top: 0.25, right: 0.5, bottom: 1.0 });
+        rendered.frame_number = Some(2);
+        rendered.validate().unwrap();
+
+        assert_eq!(
+            rendered.to_query_string(),
+            "requestType=WADO&studyUID=1.2.3&seriesUID=1.2.3.4&objectUID=1.2.3.4.5\
+             &contentType=image%2Fjpeg&windowCenter=40&windowWidth=400\
+             &region=0,0.25,0.5,1&frameNumber=2"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut dicom = params();
+        dicom.content_type = Some("application/dicom".to_string());
+        dicom.transfer_syntax = Some("1.2.840.10008.1.2.1".to_string());
+        assert!(dicom.validate().is_ok());
+
+        dicom.frame_number = Some(1);
+        assert!(dicom.validate().is_err());
+
+        let mut rendered = params();
+        rendered.transfer_syntax = Some("1.2.840.10008.1.2.1".to_string());
+        assert!(rendered.validate().is_err());
+
+        let mut window = params();
+        window.window_center = Some(40.0);
+        assert!(window.validate().is_err());
+
+        let mut region = params();
+        region.region = Some(Region { left: 0.5, top: 0.0, right: 0.25, bottom: 1.0 });
+        assert!(region.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_uses_configured_path_and_auth() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/legacy/wado"))
+            .and(query_param("requestType", "WADO"))
+            .and(query_param("objectUID", "1.2.3.4.5"))
+            .and(query_param("contentType", "application/dicom"))
+            .and(header("Authorization", "Bearer tok"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(b"DICM".to_vec(), "application/dicom"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = DicomWebClient::new(DicomWebEndpoint {
+            auth_type: AuthType::Bearer { token: "tok".to_string() },
+            wado_uri: Some(format!("{}/legacy/wado", server.uri())),
+            ..DicomWebEndpoint::for_test(format!("{}/rs", server.uri()))
+        })
+        .unwrap();
+
+        let mut request = params();
+        request.content_type = Some("application/dicom".to_string());
+        let object = retrieve(&client, &request).await.unwrap();
+
+        assert_eq!(object.content_type, "application/dicom");
+        assert_eq!(object.data, b"DICM");
+    }
+}
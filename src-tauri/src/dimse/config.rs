@@ -62,14 +62,12 @@ pub async fn save_pacs_endpoint(pool: &DbPool, endpoint: &PacsEndpoint) -> Resul
     sqlx::query(
         "INSERT INTO connections (name, connection_type, config_json, created_at, updated_at)
          VALUES (?, 'dimse', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
-         ON CONFLICT(id) DO UPDATE SET
+         ON CONFLICT(connection_type, name) DO UPDATE SET
              config_json = excluded.config_json,
-             updated_at = CURRENT_TIMESTAMP
-         WHERE name = ? AND connection_type = 'dimse'"
+             updated_at = CURRENT_TIMESTAMP"
     )
     .bind(&endpoint.name)
     .bind(&json_str)
-    .bind(&endpoint.name)
     .execute(pool)
     .await?;
 
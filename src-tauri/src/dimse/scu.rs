@@ -3,6 +3,7 @@
 use super::PacsEndpoint;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryParams {
@@ -11,6 +12,8 @@ pub struct QueryParams {
     pub study_date: Option<String>,
     pub modality: Option<String>,
     pub accession_number: Option<String>,
+    #[serde(default)]
+    pub study_instance_uid: Option<String>,
 }
 
 /// Perform C-ECHO to test connectivity
@@ -132,13 +135,14 @@ pub async fn c_find(endpoint: &PacsEndpoint, params: QueryParams) -> Result<Vec<
         ));
     }
 
-    // Add empty tags for return keys
+    // Study Instance UID - matching key when given, otherwise a return key
     query_obj.put_element(InMemElement::new(
         tags::STUDY_INSTANCE_UID,
         VR::UI,
-        Value::Primitive(PrimitiveValue::Str(String::new())),
+        Value::Primitive(PrimitiveValue::Str(params.study_instance_uid.unwrap_or_default())),
     ));
 
+    // Add empty tags for return keys
     query_obj.put_element(InMemElement::new(
         tags::STUDY_DESCRIPTION,
         VR::LO,
@@ -320,6 +324,435 @@ pub async fn c_get(endpoint: &PacsEndpoint, study_uid: &str) -> Result<Vec<Strin
     Ok(file_paths)
 }
 
+/// Outcome of storing one instance with C-STORE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreOutcome {
+    pub sop_instance_uid: String,
+    /// DIMSE status of the C-STORE-RSP; 0x0000 is success, 0xBxxx a warning
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl StoreOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, Some(status) if status == 0x0000 || status & 0xF000 == 0xB000)
+    }
+
+    fn failed(sop_instance_uid: &str, error: String) -> Self {
+        Self {
+            sop_instance_uid: sop_instance_uid.to_string(),
+            status: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Send Part 10 files to a PACS with C-STORE, over a single association. Files are
+/// loaded one at a time as they are sent.
+pub async fn c_store(endpoint: &PacsEndpoint, files: Vec<PathBuf>) -> Result<Vec<StoreOutcome>> {
+    let endpoint = endpoint.clone();
+    // The association is blocking; keep it off the async runtime
+    tokio::task::spawn_blocking(move || c_store_blocking(&endpoint, &files)).await?
+}
+
+/// SOP class, SOP instance and transfer syntax of a file waiting to be sent
+struct PendingStore<'a> {
+    path: &'a Path,
+    sop_class: String,
+    sop_instance: String,
+    transfer_syntax: String,
+}
+
+fn c_store_blocking(endpoint: &PacsEndpoint, files: &[PathBuf]) -> Result<Vec<StoreOutcome>> {
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+    use dicom_object::{InMemDicomObject, OpenFileOptions};
+    use dicom_transfer_syntax_registry::{entries, TransferSyntaxRegistry};
+    use dicom_ul::association::client::ClientAssociationOptions;
+    use dicom_ul::pdu::{PDataValue, PDataValueType, Pdu, PresentationContextResultReason};
+
+    tracing::info!("Performing C-STORE of {} instances to {}", files.len(), endpoint.name);
+
+    // Only the headers are needed to negotiate the association
+    let mut outcomes = Vec::new();
+    let mut pending = Vec::new();
+    for path in files {
+        match OpenFileOptions::new().read_until(tags::PIXEL_DATA).open_file(path) {
+            Ok(obj) => pending.push(PendingStore {
+                path,
+                sop_class: obj.meta().media_storage_sop_class_uid().trim_end_matches('\0').to_string(),
+                sop_instance: obj.meta().media_storage_sop_instance_uid().trim_end_matches('\0').to_string(),
+                transfer_syntax: obj.meta().transfer_syntax().trim_end_matches('\0').to_string(),
+            }),
+            Err(e) => outcomes.push(StoreOutcome::failed(
+                "",
+                format!("{} is not a DICOM file: {}", path.display(), e),
+            )),
+        }
+    }
+    if pending.is_empty() {
+        return Ok(outcomes);
+    }
+
+    // One presentation context per SOP class and transfer syntax; uncompressed data
+    // can also be sent in the other little endian syntaxes
+    let mut contexts: Vec<(String, String)> = Vec::new();
+    for file in &pending {
+        let key = (file.sop_class.clone(), file.transfer_syntax.clone());
+        if !contexts.contains(&key) {
+            contexts.push(key);
+        }
+    }
+    if contexts.len() > 128 {
+        return Err(anyhow::anyhow!(
+            "Too many SOP class and transfer syntax combinations for one association ({})",
+            contexts.len()
+        ));
+    }
+
+    let address = format!("{}:{}", endpoint.host, endpoint.port);
+    let mut options = ClientAssociationOptions::new()
+        .calling_ae_title(&endpoint.our_ae_title)
+        .called_ae_title(&endpoint.ae_title)
+        .max_pdu_length(16384);
+    for (sop_class, ts) in &contexts {
+        let mut syntaxes = vec![ts.clone()];
+        let codec_free = TransferSyntaxRegistry.get(ts).map(|t| t.is_codec_free()).unwrap_or(false);
+        if codec_free {
+            for fallback in [uids::EXPLICIT_VR_LITTLE_ENDIAN, uids::IMPLICIT_VR_LITTLE_ENDIAN] {
+                if ts != fallback {
+                    syntaxes.push(fallback.to_string());
+                }
+            }
+        }
+        options = options.with_presentation_context(sop_class.clone(), syntaxes);
+    }
+
+    let mut association = options.establish(&address)?;
+    tracing::info!("Association established for C-STORE with {}", endpoint.name);
+
+    // Presentation context IDs are odd numbers assigned in proposal order
+    let accepted: Vec<Option<(u8, String)>> = (0..contexts.len())
+        .map(|i| {
+            let id = (i * 2 + 1) as u8;
+            association
+                .presentation_contexts()
+                .iter()
+                .find(|pc| pc.id == id && pc.reason == PresentationContextResultReason::Acceptance)
+                .map(|pc| (id, pc.transfer_syntax.trim_end_matches('\0').to_string()))
+        })
+        .collect();
+
+    let command_ts = entries::IMPLICIT_VR_LITTLE_ENDIAN.erased();
+    let max_pdv = (association.acceptor_max_pdu_length() as usize)
+        .checked_sub(16)
+        .filter(|n| *n > 0)
+        .unwrap_or(16_000);
+
+    let mut reader = PdvReader::default();
+    let mut message_id: u16 = 1;
+    let mut aborted: Option<String> = None;
+
+    for file in &pending {
+        let PendingStore { sop_class, sop_instance, .. } = file;
+
+        if let Some(reason) = &aborted {
+            outcomes.push(StoreOutcome::failed(sop_instance, reason.clone()));
+            continue;
+        }
+
+        let index = contexts
+            .iter()
+            .position(|(class, ts)| class == sop_class && *ts == file.transfer_syntax)
+            .unwrap_or_default();
+        let Some((pc_id, ts_uid)) = accepted[index].clone() else {
+            outcomes.push(StoreOutcome::failed(
+                sop_instance,
+                format!("{} rejected SOP class {}", endpoint.ae_title, sop_class),
+            ));
+            continue;
+        };
+        let Some(ts) = TransferSyntaxRegistry.get(&ts_uid) else {
+            outcomes.push(StoreOutcome::failed(
+                sop_instance,
+                format!("Unsupported transfer syntax {}", ts_uid),
+            ));
+            continue;
+        };
+
+        let mut object_data = Vec::new();
+        let encoded = dicom_object::open_file(file.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|obj| Ok(obj.write_dataset_with_ts(&mut object_data, ts)?));
+        if let Err(e) = encoded {
+            outcomes.push(StoreOutcome::failed(sop_instance, format!("Failed to encode instance: {}", e)));
+            continue;
+        }
+
+        let command = InMemDicomObject::command_from_element_iter([
+            DataElement::new(tags::AFFECTED_SOP_CLASS_UID, VR::UI, PrimitiveValue::from(sop_class.as_str())),
+            DataElement::new(tags::COMMAND_FIELD, VR::US, PrimitiveValue::from(0x0001_u16)),
+            DataElement::new(tags::MESSAGE_ID, VR::US, PrimitiveValue::from(message_id)),
+            DataElement::new(tags::PRIORITY, VR::US, PrimitiveValue::from(0x0000_u16)),
+            DataElement::new(tags::COMMAND_DATA_SET_TYPE, VR::US, PrimitiveValue::from(0x0000_u16)),
+            DataElement::new(tags::AFFECTED_SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(sop_instance.as_str())),
+        ]);
+        message_id = message_id.wrapping_add(1);
+
+        let result = (|| -> Result<u16> {
+            let mut command_data = Vec::new();
+            command.write_dataset_with_ts(&mut command_data, &command_ts)?;
+            association.send(&Pdu::PData {
+                data: vec![PDataValue {
+                    presentation_context_id: pc_id,
+                    value_type: PDataValueType::Command,
+                    is_last: true,
+                    data: command_data,
+                }],
+            })?;
+
+            let chunks: Vec<&[u8]> = object_data.chunks(max_pdv).collect();
+            for (i, chunk) in chunks.iter().enumerate() {
+                association.send(&Pdu::PData {
+                    data: vec![PDataValue {
+                        presentation_context_id: pc_id,
+                        value_type: PDataValueType::Data,
+                        is_last: i + 1 == chunks.len(),
+                        data: chunk.to_vec(),
+                    }],
+                })?;
+            }
+
+            let data = reader.read(|| Ok(association.receive()?), PDataValueType::Command)?;
+            let response = InMemDicomObject::read_dataset_with_ts(&data[..], &command_ts)?;
+            Ok(response.element(tags::STATUS)?.to_int::<u16>()?)
+        })();
+
+        match result {
+            Ok(status) => {
+                let outcome = StoreOutcome {
+                    sop_instance_uid: sop_instance.clone(),
+                    status: Some(status),
+                    error: None,
+                };
+                if !outcome.is_success() {
+                    tracing::warn!("C-STORE of {} failed with status {:04X}H", sop_instance, status);
+                }
+                outcomes.push(outcome);
+            }
+            Err(e) => {
+                // The association can't be trusted after a transport error
+                tracing::warn!("C-STORE association with {} failed: {}", endpoint.name, e);
+                let reason = format!("Association failed: {}", e);
+                outcomes.push(StoreOutcome::failed(sop_instance, reason.clone()));
+                aborted = Some(reason);
+            }
+        }
+    }
+
+    if aborted.is_some() {
+        let _ = association.abort();
+    } else {
+        association.release()?;
+    }
+
+    let stored = outcomes.iter().filter(|o| o.is_success()).count();
+    tracing::info!("C-STORE completed, {} of {} instances stored", stored, files.len());
+
+    Ok(outcomes)
+}
+
+/// Instance found by an IMAGE level C-FIND
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceResult {
+    pub study_instance_uid: String,
+    pub series_instance_uid: String,
+    pub sop_instance_uid: String,
+    pub sop_class_uid: Option<String>,
+}
+
+/// List the instances of a study with a Study Root C-FIND at IMAGE level
+pub async fn c_find_instances(endpoint: &PacsEndpoint, study_uid: &str) -> Result<Vec<InstanceResult>> {
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_object::InMemDicomObject;
+
+    tracing::info!("Performing IMAGE level C-FIND to {} for study {}", endpoint.name, study_uid);
+
+    let identifier = InMemDicomObject::from_element_iter([
+        DataElement::new(tags::QUERY_RETRIEVE_LEVEL, VR::CS, PrimitiveValue::from("IMAGE")),
+        DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from(study_uid)),
+        DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from("")),
+        DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from("")),
+        DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from("")),
+    ]);
+
+    let endpoint = endpoint.clone();
+    let matches = tokio::task::spawn_blocking(move || {
+        find_blocking(&endpoint, uids::STUDY_ROOT_QUERY_RETRIEVE_INFORMATION_MODEL_FIND, &identifier)
+    })
+    .await??;
+
+    let text = |obj: &InMemDicomObject, tag| {
+        obj.element(tag)
+            .ok()
+            .and_then(|e| e.to_str().ok())
+            .map(|s| s.trim_end_matches('\0').trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let results: Vec<InstanceResult> = matches
+        .iter()
+        .filter_map(|obj| {
+            Some(InstanceResult {
+                study_instance_uid: text(obj, tags::STUDY_INSTANCE_UID)?,
+                series_instance_uid: text(obj, tags::SERIES_INSTANCE_UID)?,
+                sop_instance_uid: text(obj, tags::SOP_INSTANCE_UID)?,
+                sop_class_uid: text(obj, tags::SOP_CLASS_UID),
+            })
+        })
+        .collect();
+
+    tracing::info!("C-FIND completed, found {} instances", results.len());
+    Ok(results)
+}
+
+/// Run a C-FIND on a new association and collect the identifiers of all pending responses
+fn find_blocking(
+    endpoint: &PacsEndpoint,
+    sop_class: &str,
+    identifier: &dicom_object::InMemDicomObject,
+) -> Result<Vec<dicom_object::InMemDicomObject>> {
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+    use dicom_transfer_syntax_registry::{entries, TransferSyntaxRegistry};
+    use dicom_ul::association::client::ClientAssociationOptions;
+    use dicom_ul::pdu::{PDataValue, PDataValueType, Pdu, PresentationContextResultReason};
+    use dicom_object::InMemDicomObject;
+
+    let address = format!("{}:{}", endpoint.host, endpoint.port);
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(&endpoint.our_ae_title)
+        .called_ae_title(&endpoint.ae_title)
+        .max_pdu_length(16384)
+        .with_presentation_context(
+            sop_class,
+            vec![uids::EXPLICIT_VR_LITTLE_ENDIAN, uids::IMPLICIT_VR_LITTLE_ENDIAN],
+        )
+        .establish(&address)?;
+
+    let (pc_id, ts_uid) = association
+        .presentation_contexts()
+        .iter()
+        .find(|pc| pc.reason == PresentationContextResultReason::Acceptance)
+        .map(|pc| (pc.id, pc.transfer_syntax.trim_end_matches('\0').to_string()))
+        .ok_or_else(|| anyhow::anyhow!("{} rejected the query model {}", endpoint.ae_title, sop_class))?;
+    let ts = TransferSyntaxRegistry
+        .get(&ts_uid)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported transfer syntax {}", ts_uid))?;
+    let command_ts = entries::IMPLICIT_VR_LITTLE_ENDIAN.erased();
+
+    let command = InMemDicomObject::command_from_element_iter([
+        DataElement::new(tags::AFFECTED_SOP_CLASS_UID, VR::UI, PrimitiveValue::from(sop_class)),
+        DataElement::new(tags::COMMAND_FIELD, VR::US, PrimitiveValue::from(0x0020_u16)),
+        DataElement::new(tags::MESSAGE_ID, VR::US, PrimitiveValue::from(1_u16)),
+        DataElement::new(tags::PRIORITY, VR::US, PrimitiveValue::from(0x0000_u16)),
+        DataElement::new(tags::COMMAND_DATA_SET_TYPE, VR::US, PrimitiveValue::from(0x0001_u16)),
+    ]);
+    let mut command_data = Vec::new();
+    command.write_dataset_with_ts(&mut command_data, &command_ts)?;
+    let mut identifier_data = Vec::new();
+    identifier.write_dataset_with_ts(&mut identifier_data, ts)?;
+
+    association.send(&Pdu::PData {
+        data: vec![
+            PDataValue {
+                presentation_context_id: pc_id,
+                value_type: PDataValueType::Command,
+                is_last: true,
+                data: command_data,
+            },
+            PDataValue {
+                presentation_context_id: pc_id,
+                value_type: PDataValueType::Data,
+                is_last: true,
+                data: identifier_data,
+            },
+        ],
+    })?;
+
+    let mut reader = PdvReader::default();
+    let mut matches = Vec::new();
+    loop {
+        let data = reader.read(|| Ok(association.receive()?), PDataValueType::Command)?;
+        let response = InMemDicomObject::read_dataset_with_ts(&data[..], &command_ts)?;
+        let status = response.element(tags::STATUS)?.to_int::<u16>()?;
+        let has_dataset = response
+            .element(tags::COMMAND_DATA_SET_TYPE)
+            .ok()
+            .and_then(|e| e.to_int::<u16>().ok())
+            .map_or(false, |t| t != 0x0101);
+
+        if has_dataset {
+            let data = reader.read(|| Ok(association.receive()?), PDataValueType::Data)?;
+            if matches!(status, 0xFF00 | 0xFF01) {
+                matches.push(InMemDicomObject::read_dataset_with_ts(&data[..], ts)?);
+            }
+        }
+
+        match status {
+            0xFF00 | 0xFF01 => continue,
+            0x0000 => break,
+            status => {
+                let _ = association.abort();
+                return Err(anyhow::anyhow!("C-FIND failed with status {:04X}H", status));
+            }
+        }
+    }
+
+    association.release()?;
+    Ok(matches)
+}
+
+/// Reassembles DIMSE messages from P-DATA value fragments, which may be split
+/// over several PDUs or share one PDU with the next message
+#[derive(Default)]
+struct PdvReader {
+    pending: std::collections::VecDeque<dicom_ul::pdu::PDataValue>,
+}
+
+impl PdvReader {
+    fn read(
+        &mut self,
+        mut receive: impl FnMut() -> Result<dicom_ul::pdu::Pdu>,
+        value_type: dicom_ul::pdu::PDataValueType,
+    ) -> Result<Vec<u8>> {
+        use dicom_ul::pdu::Pdu;
+
+        let mut buffer = Vec::new();
+        loop {
+            while let Some(pdv) = self.pending.pop_front() {
+                if pdv.value_type != value_type {
+                    return Err(anyhow::anyhow!("Unexpected {:?} fragment", pdv.value_type));
+                }
+                buffer.extend_from_slice(&pdv.data);
+                if pdv.is_last {
+                    return Ok(buffer);
+                }
+            }
+
+            match receive()? {
+                Pdu::PData { data } => self.pending.extend(data),
+                other => return Err(anyhow::anyhow!("Unexpected PDU: {:?}", other)),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudyResult {
     pub study_instance_uid: String,
@@ -342,6 +775,170 @@ pub struct MoveProgress {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dicom_core::value::PrimitiveValue;
+    use dicom_core::{DataElement, VR};
+    use dicom_dictionary_std::{tags, uids};
+    use dicom_object::InMemDicomObject;
+    use dicom_ul::pdu::{PDataValue, PDataValueType, Pdu};
+
+    /// Store SCP for one association that answers each C-STORE with the next of
+    /// `statuses`; returns the SOP Instance UIDs it received
+    fn store_scp(statuses: Vec<u16>) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
+        use dicom_transfer_syntax_registry::{entries, TransferSyntaxRegistry};
+        use dicom_ul::association::server::ServerAssociationOptions;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut association = ServerAssociationOptions::new()
+                .ae_title("STORE-SCP")
+                .with_abstract_syntax(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+                .establish(stream)
+                .unwrap();
+            let command_ts = entries::IMPLICIT_VR_LITTLE_ENDIAN.erased();
+
+            let mut received = Vec::new();
+            let mut command = None;
+            let mut data = Vec::new();
+            loop {
+                let pdvs = match association.receive().unwrap() {
+                    Pdu::PData { data } => data,
+                    Pdu::ReleaseRQ => {
+                        let _ = association.send(&Pdu::ReleaseRP);
+                        break;
+                    }
+                    _ => break,
+                };
+                for pdv in pdvs {
+                    if pdv.value_type == PDataValueType::Command {
+                        command = Some((
+                            pdv.presentation_context_id,
+                            InMemDicomObject::read_dataset_with_ts(&pdv.data[..], &command_ts).unwrap(),
+                        ));
+                        continue;
+                    }
+                    data.extend_from_slice(&pdv.data);
+                    if !pdv.is_last {
+                        continue;
+                    }
+
+                    let (pc_id, request) = command.take().unwrap();
+                    let ts_uid = association
+                        .presentation_contexts()
+                        .iter()
+                        .find(|pc| pc.id == pc_id)
+                        .map(|pc| pc.transfer_syntax.trim_end_matches('\0').to_string())
+                        .unwrap();
+                    let ts = TransferSyntaxRegistry.get(&ts_uid).unwrap();
+                    let obj = InMemDicomObject::read_dataset_with_ts(&data[..], ts).unwrap();
+                    data.clear();
+                    let sop_instance = obj.element(tags::SOP_INSTANCE_UID).unwrap().to_str().unwrap();
+                    received.push(sop_instance.trim_end_matches('\0').to_string());
+
+                    let text = |tag| request.element(tag).unwrap().to_str().unwrap().to_string();
+                    let message_id = request.element(tags::MESSAGE_ID).unwrap().to_int::<u16>().unwrap();
+                    let response = InMemDicomObject::command_from_element_iter([
+                        DataElement::new(tags::AFFECTED_SOP_CLASS_UID, VR::UI, PrimitiveValue::from(text(tags::AFFECTED_SOP_CLASS_UID))),
+                        DataElement::new(tags::COMMAND_FIELD, VR::US, PrimitiveValue::from(0x8001_u16)),
+                        DataElement::new(tags::MESSAGE_ID_BEING_RESPONDED_TO, VR::US, PrimitiveValue::from(message_id)),
+                        DataElement::new(tags::COMMAND_DATA_SET_TYPE, VR::US, PrimitiveValue::from(0x0101_u16)),
+                        DataElement::new(tags::STATUS, VR::US, PrimitiveValue::from(statuses[received.len() - 1])),
+                        DataElement::new(tags::AFFECTED_SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(text(tags::AFFECTED_SOP_INSTANCE_UID))),
+                    ]);
+                    let mut response_data = Vec::new();
+                    response.write_dataset_with_ts(&mut response_data, &command_ts).unwrap();
+                    association
+                        .send(&Pdu::PData {
+                            data: vec![PDataValue {
+                                presentation_context_id: pc_id,
+                                value_type: PDataValueType::Command,
+                                is_last: true,
+                                data: response_data,
+                            }],
+                        })
+                        .unwrap();
+                }
+            }
+            received
+        });
+        (port, handle)
+    }
 
-    // Add tests with mock PACS server
+    fn write_instance(dir: &Path, sop_instance: &str) -> PathBuf {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, PrimitiveValue::from(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, PrimitiveValue::from(sop_instance)),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3")),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, PrimitiveValue::from("1.2.3.4")),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^Jane")),
+        ]);
+        let meta = crate::dicom::build_file_meta(&obj, uids::EXPLICIT_VR_LITTLE_ENDIAN).unwrap();
+        let path = dir.join(format!("{}.dcm", sop_instance));
+        obj.with_exact_meta(meta).write_to_file(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_store_outcome_status() {
+        let outcome = |status| StoreOutcome {
+            sop_instance_uid: "1.2".to_string(),
+            status: Some(status),
+            error: None,
+        };
+        assert!(outcome(0x0000).is_success());
+        assert!(outcome(0xB007).is_success());
+        assert!(!outcome(0xA700).is_success());
+        assert!(!StoreOutcome::failed("1.2", "refused".to_string()).is_success());
+    }
+
+    #[tokio::test]
+    async fn test_c_store_reports_each_instance() {
+        let dir = std::env::temp_dir().join(format!("c-store-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stored = write_instance(&dir, "1.2.3.4.1");
+        let refused = write_instance(&dir, "1.2.3.4.2");
+        let garbage = dir.join("garbage.dcm");
+        std::fs::write(&garbage, b"not dicom").unwrap();
+
+        let (port, scp) = store_scp(vec![0x0000, 0xA700]);
+        let endpoint = PacsEndpoint {
+            name: "test".to_string(),
+            ae_title: "STORE-SCP".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            our_ae_title: "TEST-SCU".to_string(),
+        };
+        let outcomes = c_store(&endpoint, vec![stored, garbage, refused]).await.unwrap();
+        let received = scp.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(received, vec!["1.2.3.4.1", "1.2.3.4.2"]);
+        assert_eq!(outcomes.len(), 3);
+        let outcome = |uid: &str| outcomes.iter().find(|o| o.sop_instance_uid == uid).unwrap();
+        assert!(outcome("1.2.3.4.1").is_success());
+        assert_eq!(outcome("1.2.3.4.2").status, Some(0xA700));
+        assert!(!outcome("1.2.3.4.2").is_success());
+        assert!(outcome("").error.as_deref().unwrap().contains("not a DICOM file"));
+    }
+
+    #[tokio::test]
+    async fn test_c_store_connection_refused() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let endpoint = PacsEndpoint {
+            name: "test".to_string(),
+            ae_title: "NOBODY".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            our_ae_title: "TEST-SCU".to_string(),
+        };
+        let dir = std::env::temp_dir().join(format!("c-store-refused-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = write_instance(&dir, "1.2.3.4.9");
+
+        let result = c_store(&endpoint, vec![file]).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
 }
@@ -6,6 +6,8 @@ mod database;
 mod dicom;
 mod dicomweb;
 mod dimse;
+mod reconcile;
+mod transfer;
 mod utils;
 
 use tauri::Manager;
@@ -21,11 +23,33 @@ async fn main() {
         .await
         .expect("Failed to initialize database");
 
+    // Unlock keyfile-protected connection secrets; failures leave the vault locked
+    let vault = utils::secrets::SecretVault::init(&db).await;
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .manage(db)
+        .manage(vault)
+        .manage(dicomweb::server::DicomWebServer::new())
+        .manage(dicomweb::ups::UpsWatchers::new())
+        .setup(|app| {
+            // Persist refresh tokens that identity providers rotate during renewal
+            let handle = app.handle().clone();
+            dicomweb::auth::on_refresh_token_rotated(move |rotated| {
+                let handle = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    let db = handle.state::<database::DbPool>();
+                    let vault = handle.state::<utils::secrets::SecretVault>();
+                    match dicomweb::config::store_rotated_refresh_token(&db, &vault, &rotated).await {
+                        Ok(count) => tracing::info!("Stored rotated refresh token for {} connections", count),
+                        Err(e) => tracing::warn!("Failed to store rotated refresh token: {}", e),
+                    }
+                });
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // File operations
             commands::file::open_dicom_file,
@@ -36,13 +60,34 @@ async fn main() {
             commands::viewer::get_image_data,
             commands::viewer::get_metadata,
             commands::viewer::apply_windowing,
+            commands::viewer::get_remote_rendered,
+            commands::viewer::get_remote_thumbnail,
+            commands::viewer::get_remote_frame,
 
             // Tag operations
             commands::tags::get_all_tags,
+            commands::tags::get_tag,
+            commands::tags::get_vendor_info,
             commands::tags::update_tag,
             commands::tags::delete_tag,
+            commands::tags::undo_tag_edit,
+            commands::tags::redo_tag_edit,
+            commands::tags::get_edit_journal,
+            commands::tags::get_backup_mode,
+            commands::tags::set_backup_mode,
+            commands::tags::list_file_versions,
+            commands::tags::restore_file_version,
+            commands::tags::batch_edit_tags,
             commands::tags::anonymize_study,
             commands::tags::get_anonymization_templates,
+            commands::tags::get_private_dictionary,
+            commands::tags::save_private_dictionary,
+
+            // Validation
+            commands::validation::validate_file,
+            commands::validation::validate_study,
+            commands::validation::export_validation_report,
+            commands::validation::check_study_consistency,
 
             // DIMSE operations
             commands::dimse::start_scp,
@@ -53,13 +98,62 @@ async fn main() {
 
             // DICOMweb operations
             commands::dicomweb::qido_rs,
+            commands::dicomweb::qido_rs_all,
+            commands::dicomweb::qido_rs_records,
             commands::dicomweb::wado_rs,
+            commands::dicomweb::wado_rs_retrieve,
+            commands::dicomweb::wado_uri_retrieve,
             commands::dicomweb::stow_rs,
+            commands::dicomweb::inspect_endpoint,
+            commands::dicomweb::list_request_history,
+            commands::dicomweb::get_request_history_entry,
+            commands::dicomweb::clear_request_history,
+            commands::dicomweb::replay_request,
+            commands::dicomweb::export_request,
+            commands::dicomweb::start_dicomweb_server,
+            commands::dicomweb::stop_dicomweb_server,
+            commands::dicomweb::get_dicomweb_server_status,
+
+            // UPS-RS worklist
+            commands::ups::ups_search,
+            commands::ups::ups_retrieve,
+            commands::ups::ups_create,
+            commands::ups::ups_update,
+            commands::ups::ups_claim,
+            commands::ups::ups_complete,
+            commands::ups::ups_cancel,
+            commands::ups::ups_request_cancellation,
+            commands::ups::ups_subscribe,
+            commands::ups::ups_unsubscribe,
+            commands::ups::watch_ups_events,
+            commands::ups::unwatch_ups_events,
+
+            // Study transfer and reconciliation
+            commands::transfer::transfer_study,
+            commands::transfer::reconcile_archive,
+            commands::transfer::send_reconcile_differences,
+            commands::transfer::retrieve_reconcile_differences,
 
             // Export operations
             commands::export::export_tags_json,
+            commands::export::export_dicom_json,
             commands::export::export_tags_xml,
             commands::export::export_image_png,
+
+            // Connection management
+            commands::connections::list_connections,
+            commands::connections::create_connection,
+            commands::connections::update_connection,
+            commands::connections::rename_connection,
+            commands::connections::delete_connection,
+            commands::connections::duplicate_connection,
+            commands::connections::test_connection,
+            commands::connections::get_secrets_status,
+            commands::connections::unlock_secrets,
+            commands::connections::set_secrets_passphrase,
+            commands::connections::use_secrets_keyfile,
+            commands::connections::export_connection_profiles,
+            commands::connections::import_connection_profiles,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,426 @@
+// Reconciliation of local instances against a remote archive
+
+use crate::database::DbPool;
+use crate::transfer::{self, FailedTransfer, TransferEndpoint, TransferSelector};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Local instances to compare
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LocalSource {
+    /// Instances in the local database index
+    Index,
+    /// DICOM files found by scanning a directory
+    Directory { path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconcileRequest {
+    pub local: LocalSource,
+    /// DIMSE or DICOMweb archive to compare against
+    pub remote: TransferEndpoint,
+    /// Studies to compare. Defaults to every local study; studies that only exist
+    /// remotely are found only when listed here.
+    #[serde(default)]
+    pub study_uids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InstanceRef {
+    pub study_uid: String,
+    pub series_uid: String,
+    pub sop_instance_uid: String,
+    /// File of a local instance
+    pub path: Option<String>,
+}
+
+/// A study held on both sides with different series or instance counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountMismatch {
+    pub study_uid: String,
+    pub local_series: usize,
+    pub remote_series: usize,
+    pub local_instances: usize,
+    pub remote_instances: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudyError {
+    pub study_uid: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    pub studies_compared: usize,
+    pub only_local: Vec<InstanceRef>,
+    pub only_remote: Vec<InstanceRef>,
+    pub count_mismatches: Vec<CountMismatch>,
+    /// Studies that could not be queried on the remote archive
+    pub errors: Vec<StudyError>,
+}
+
+/// Result of sending or retrieving differences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub transferred: Vec<String>,
+    pub failed: Vec<FailedTransfer>,
+}
+
+fn ensure_remote(remote: &TransferEndpoint) -> Result<()> {
+    match remote {
+        TransferEndpoint::Local { .. } => Err(anyhow::anyhow!(
+            "Reconciliation needs a DIMSE or DICOMweb archive as the remote side"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Compare local instances with the remote archive, study by study
+pub async fn reconcile(pool: &DbPool, request: &ReconcileRequest) -> Result<ReconcileReport> {
+    ensure_remote(&request.remote)?;
+
+    let local = load_local(pool, &request.local).await?;
+    let mut local_by_study: BTreeMap<String, Vec<InstanceRef>> = BTreeMap::new();
+    for instance in local {
+        local_by_study.entry(instance.study_uid.clone()).or_default().push(instance);
+    }
+
+    let studies: Vec<String> = match &request.study_uids {
+        Some(uids) => uids.clone(),
+        None => local_by_study.keys().cloned().collect(),
+    };
+
+    tracing::info!("Reconciling {} studies against the remote archive", studies.len());
+
+    let mut report = ReconcileReport {
+        studies_compared: 0,
+        only_local: Vec::new(),
+        only_remote: Vec::new(),
+        count_mismatches: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for study_uid in studies {
+        let remote = match list_remote(pool, &request.remote, &study_uid).await {
+            Ok(remote) => remote,
+            Err(e) => {
+                report.errors.push(StudyError {
+                    study_uid,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let local = local_by_study.remove(&study_uid).unwrap_or_default();
+        report.studies_compared += 1;
+        compare_study(&study_uid, local, remote, &mut report);
+    }
+
+    tracing::info!(
+        "Reconciliation found {} local-only and {} remote-only instances",
+        report.only_local.len(),
+        report.only_remote.len()
+    );
+
+    Ok(report)
+}
+
+fn compare_study(
+    study_uid: &str,
+    local: Vec<InstanceRef>,
+    remote: Vec<InstanceRef>,
+    report: &mut ReconcileReport,
+) {
+    let local_uids: BTreeSet<&str> = local.iter().map(|i| i.sop_instance_uid.as_str()).collect();
+    let remote_uids: BTreeSet<&str> = remote.iter().map(|i| i.sop_instance_uid.as_str()).collect();
+
+    if !local.is_empty() && !remote.is_empty() {
+        let local_series: BTreeSet<&str> = local.iter().map(|i| i.series_uid.as_str()).collect();
+        let remote_series: BTreeSet<&str> = remote.iter().map(|i| i.series_uid.as_str()).collect();
+        if local_series.len() != remote_series.len() || local_uids.len() != remote_uids.len() {
+            report.count_mismatches.push(CountMismatch {
+                study_uid: study_uid.to_string(),
+                local_series: local_series.len(),
+                remote_series: remote_series.len(),
+                local_instances: local_uids.len(),
+                remote_instances: remote_uids.len(),
+            });
+        }
+    }
+
+    report.only_local.extend(
+        local
+            .iter()
+            .filter(|i| !remote_uids.contains(i.sop_instance_uid.as_str()))
+            .cloned(),
+    );
+    report.only_remote.extend(
+        remote
+            .iter()
+            .filter(|i| !local_uids.contains(i.sop_instance_uid.as_str()))
+            .cloned(),
+    );
+}
+
+async fn load_local(pool: &DbPool, source: &LocalSource) -> Result<Vec<InstanceRef>> {
+    match source {
+        LocalSource::Index => {
+            let rows = sqlx::query_as::<_, (String, String, String, String)>(
+                "SELECT s.study_instance_uid, i.series_instance_uid, i.sop_instance_uid, i.file_path
+                 FROM instances i JOIN studies s ON s.id = i.study_id"
+            )
+            .fetch_all(pool)
+            .await?;
+
+            Ok(rows
+                .into_iter()
+                .map(|(study_uid, series_uid, sop_instance_uid, path)| InstanceRef {
+                    study_uid,
+                    series_uid,
+                    sop_instance_uid,
+                    path: Some(path),
+                })
+                .collect())
+        }
+        LocalSource::Directory { path } => {
+            let files = crate::dicom::parser::scan_directory_fast(path)?;
+            Ok(files
+                .into_iter()
+                .map(|f| InstanceRef {
+                    study_uid: f.study_instance_uid.trim_end_matches('\0').to_string(),
+                    series_uid: f.series_instance_uid.trim_end_matches('\0').to_string(),
+                    sop_instance_uid: f.sop_instance_uid.trim_end_matches('\0').to_string(),
+                    path: Some(f.path),
+                })
+                .collect())
+        }
+    }
+}
+
+/// Instance-level listing of a study on the remote archive
+async fn list_remote(
+    pool: &DbPool,
+    remote: &TransferEndpoint,
+    study_uid: &str,
+) -> Result<Vec<InstanceRef>> {
+    match remote {
+        TransferEndpoint::Dimse { endpoint } => {
+            let results = crate::dimse::scu::c_find_instances(endpoint, study_uid).await?;
+            Ok(results
+                .into_iter()
+                .map(|r| InstanceRef {
+                    study_uid: r.study_instance_uid,
+                    series_uid: r.series_instance_uid,
+                    sop_instance_uid: r.sop_instance_uid,
+                    path: None,
+                })
+                .collect())
+        }
+        TransferEndpoint::Dicomweb { endpoint } => {
+            use crate::dicomweb::client::DicomWebClient;
+            use crate::dicomweb::qido::{AttributeKey, QidoPager, QidoQuery, QueryLevel};
+            use dicom_dictionary_std::tags;
+
+            let client = DicomWebClient::new(endpoint.clone())?.with_history(pool.clone());
+            let mut query = QidoQuery::new(QueryLevel::Instances);
+            query.study_uid = Some(study_uid.to_string());
+            query.include_fields.push(AttributeKey::from(tags::SERIES_INSTANCE_UID));
+
+            let response = QidoPager::new(&client, query).collect_all(None).await?;
+            let value = |result: &serde_json::Value, key: &str| -> Option<String> {
+                result.get(key)?.get("Value")?.get(0)?.as_str().map(String::from)
+            };
+            Ok(response
+                .results
+                .iter()
+                .filter_map(|r| {
+                    Some(InstanceRef {
+                        study_uid: study_uid.to_string(),
+                        series_uid: value(r, "0020000E")?,
+                        sop_instance_uid: value(r, "00080018")?,
+                        path: None,
+                    })
+                })
+                .collect())
+        }
+        TransferEndpoint::Local { .. } => ensure_remote(remote).map(|_| Vec::new()),
+    }
+}
+
+/// Send local-only instances to the remote archive
+pub async fn send_differences(
+    pool: &DbPool,
+    remote: &TransferEndpoint,
+    instances: &[InstanceRef],
+) -> Result<SyncResult> {
+    ensure_remote(remote)?;
+
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for instance in instances {
+        match &instance.path {
+            Some(path) => files.push((instance.sop_instance_uid.clone(), PathBuf::from(path))),
+            None => failed.push(FailedTransfer {
+                sop_instance_uid: instance.sop_instance_uid.clone(),
+                reason: "No local file for this instance".to_string(),
+            }),
+        }
+    }
+
+    let mut transferred = Vec::new();
+    if !files.is_empty() {
+        let (stored, store_failed) = transfer::send_files(pool, remote, files).await?;
+        transferred = stored;
+        failed.extend(store_failed);
+    }
+
+    tracing::info!("Sent {} instances, {} failed", transferred.len(), failed.len());
+    Ok(SyncResult { transferred, failed })
+}
+
+/// Retrieve remote-only instances into `output_dir` and add them to the local index.
+/// Each instance is written as soon as it arrives and each study is indexed once it
+/// is complete, so only one instance is held in memory at a time.
+pub async fn retrieve_differences(
+    pool: &DbPool,
+    remote: &TransferEndpoint,
+    instances: &[InstanceRef],
+    output_dir: &str,
+) -> Result<SyncResult> {
+    ensure_remote(remote)?;
+
+    let mut by_study: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for instance in instances {
+        by_study
+            .entry(instance.study_uid.as_str())
+            .or_default()
+            .insert(instance.sop_instance_uid.as_str());
+    }
+
+    let mut transferred = Vec::new();
+    let mut failed = Vec::new();
+    for (study_uid, wanted) in by_study {
+        let mut study_paths = Vec::new();
+        // C-GET retrieves whole studies; DICOMweb can fetch just the missing instances
+        let selectors: Vec<TransferSelector> = match remote {
+            TransferEndpoint::Dicomweb { .. } => instances
+                .iter()
+                .filter(|i| i.study_uid == study_uid)
+                .map(|i| TransferSelector {
+                    study_uid: i.study_uid.clone(),
+                    series_uid: Some(i.series_uid.clone()),
+                    instance_uid: Some(i.sop_instance_uid.clone()),
+                })
+                .collect(),
+            _ => vec![TransferSelector {
+                study_uid: study_uid.to_string(),
+                series_uid: None,
+                instance_uid: None,
+            }],
+        };
+
+        for selector in selectors {
+            let selection = match transfer::fetch(pool, remote, &selector).await {
+                Ok(selection) => selection,
+                Err(e) => {
+                    failed.push(FailedTransfer {
+                        sop_instance_uid: selector.instance_uid.unwrap_or_else(|| study_uid.to_string()),
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            // Whole-study retrievals also list instances that are already held locally
+            for instance in selection.instances.iter().filter(|i| wanted.contains(i.sop_instance_uid())) {
+                let data = match selection.load(instance).await {
+                    Ok(data) => data,
+                    Err(e) => {
+                        failed.push(FailedTransfer {
+                            sop_instance_uid: instance.sop_instance_uid().to_string(),
+                            reason: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                let dir = output_dir.to_string();
+                let saved = tokio::task::spawn_blocking(move || {
+                    crate::dicomweb::wado::save_instances(std::slice::from_ref(&data), dir)
+                })
+                .await??;
+                study_paths.extend(saved.paths);
+                failed.extend(saved.failures.into_iter().map(|f| FailedTransfer {
+                    sop_instance_uid: instance.sop_instance_uid().to_string(),
+                    reason: f.reason,
+                }));
+            }
+        }
+
+        crate::database::index::index_files(pool, &study_paths).await;
+        transferred.extend(
+            study_paths
+                .iter()
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string())),
+        );
+    }
+
+    tracing::info!("Retrieved {} instances into {}", transferred.len(), output_dir);
+
+    Ok(SyncResult { transferred, failed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(study: &str, series: &str, sop: &str) -> InstanceRef {
+        InstanceRef {
+            study_uid: study.to_string(),
+            series_uid: series.to_string(),
+            sop_instance_uid: sop.to_string(),
+            path: None,
+        }
+    }
+
+    fn empty_report() -> ReconcileReport {
+        ReconcileReport {
+            studies_compared: 0,
+            only_local: Vec::new(),
+            only_remote: Vec::new(),
+            count_mismatches: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_study() {
+        let local = vec![instance("1", "1.1", "a"), instance("1", "1.1", "b"), instance("1", "1.2", "c")];
+        let remote = vec![instance("1", "1.1", "a"), instance("1", "1.1", "d")];
+
+        let mut report = empty_report();
+        compare_study("1", local, remote, &mut report);
+
+        let only_local: Vec<&str> = report.only_local.iter().map(|i| i.sop_instance_uid.as_str()).collect();
+        let only_remote: Vec<&str> = report.only_remote.iter().map(|i| i.sop_instance_uid.as_str()).collect();
+        assert_eq!(only_local, vec!["b", "c"]);
+        assert_eq!(only_remote, vec!["d"]);
+
+        let mismatch = &report.count_mismatches[0];
+        assert_eq!((mismatch.local_series, mismatch.remote_series), (2, 1));
+        assert_eq!((mismatch.local_instances, mismatch.remote_instances), (3, 2));
+    }
+
+    #[test]
+    fn test_study_missing_remotely_is_not_a_count_mismatch() {
+        let mut report = empty_report();
+        compare_study("1", vec![instance("1", "1.1", "a")], Vec::new(), &mut report);
+
+        assert_eq!(report.only_local.len(), 1);
+        assert!(report.count_mismatches.is_empty());
+    }
+}
@@ -0,0 +1,716 @@
+// Study transfer between local folders, DIMSE and DICOMweb endpoints
+
+use crate::database::DbPool;
+use crate::dicom::anonymizer::{self, AnonymizationTemplate};
+use crate::dicomweb::client::DicomWebClient;
+use crate::dicomweb::DicomWebEndpoint;
+use crate::dimse::{scu, PacsEndpoint};
+use crate::utils::file_helpers::{safe_uid, write_atomic};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Where instances are read from or written to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TransferEndpoint {
+    /// A folder of DICOM files, searched recursively as a source
+    Local { path: String },
+    Dimse { endpoint: PacsEndpoint },
+    Dicomweb { endpoint: DicomWebEndpoint },
+}
+
+impl TransferEndpoint {
+    fn describe(&self) -> String {
+        match self {
+            TransferEndpoint::Local { path } => format!("folder {}", path),
+            TransferEndpoint::Dimse { endpoint } => format!("PACS {}", endpoint.name),
+            TransferEndpoint::Dicomweb { endpoint } => format!("DICOMweb {}", endpoint.name),
+        }
+    }
+}
+
+/// Study, series or instance to transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferSelector {
+    pub study_uid: String,
+    pub series_uid: Option<String>,
+    /// Requires `series_uid`
+    pub instance_uid: Option<String>,
+}
+
+impl TransferSelector {
+    fn validate(&self) -> Result<()> {
+        if self.study_uid.trim().is_empty() {
+            return Err(anyhow::anyhow!("Study Instance UID is required"));
+        }
+        if self.instance_uid.is_some() && self.series_uid.is_none() {
+            return Err(anyhow::anyhow!("Series Instance UID is required to select an instance"));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, uids: &InstanceUids) -> bool {
+        uids.study == self.study_uid
+            && self.series_uid.as_ref().map_or(true, |s| *s == uids.series)
+            && self.instance_uid.as_ref().map_or(true, |i| *i == uids.instance)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub source: TransferEndpoint,
+    pub destination: TransferEndpoint,
+    pub selector: TransferSelector,
+    /// Name of an anonymization template applied to every instance on the way
+    #[serde(default)]
+    pub anonymization_template: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedTransfer {
+    pub sop_instance_uid: String,
+    pub reason: String,
+}
+
+/// Instance counts found at the destination after the transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferVerification {
+    pub expected: usize,
+    /// `None` when the destination could not be queried
+    pub found: Option<usize>,
+    pub verified: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferReport {
+    pub retrieved: usize,
+    pub stored: usize,
+    pub failed: Vec<FailedTransfer>,
+    /// Study Instance UID at the destination; differs from the selector when anonymized
+    pub destination_study_uid: String,
+    pub verification: TransferVerification,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct InstanceUids {
+    study: String,
+    series: String,
+    instance: String,
+}
+
+impl InstanceUids {
+    fn of(data: &[u8]) -> Result<Self> {
+        Self::from_object(&crate::dicom::read_dicom_bytes(data)?)
+    }
+
+    /// Read the UIDs of a Part 10 file, stopping before the pixel data
+    fn of_file(path: &Path) -> Result<Self> {
+        use dicom_dictionary_std::tags;
+        use dicom_object::OpenFileOptions;
+
+        Self::from_object(&OpenFileOptions::new().read_until(tags::PIXEL_DATA).open_file(path)?)
+    }
+
+    fn from_object(obj: &dicom_object::InMemDicomObject) -> Result<Self> {
+        use dicom_dictionary_std::tags;
+
+        let get = |tag| -> Result<String> {
+            Ok(obj.element(tag)?.to_str()?.trim_end_matches('\0').to_string())
+        };
+        Ok(Self {
+            study: get(tags::STUDY_INSTANCE_UID)?,
+            series: get(tags::SERIES_INSTANCE_UID)?,
+            instance: get(tags::SOP_INSTANCE_UID)?,
+        })
+    }
+}
+
+/// An instance selected at the source; its data is only read when it is sent
+#[derive(Debug, Clone)]
+pub(crate) enum SourceInstance {
+    /// A file in a local folder, or one retrieved with C-GET
+    File { path: PathBuf, uids: InstanceUids },
+    /// An instance retrieved with WADO-RS when it is loaded
+    Dicomweb { uids: InstanceUids },
+}
+
+impl SourceInstance {
+    pub(crate) fn sop_instance_uid(&self) -> &str {
+        match self {
+            SourceInstance::File { uids, .. } | SourceInstance::Dicomweb { uids } => &uids.instance,
+        }
+    }
+}
+
+/// The instances selected at a source
+pub(crate) struct Selection {
+    client: Option<DicomWebClient>,
+    pub(crate) instances: Vec<SourceInstance>,
+}
+
+impl Selection {
+    /// Read one selected instance as a Part 10 byte stream
+    pub(crate) async fn load(&self, instance: &SourceInstance) -> Result<Vec<u8>> {
+        match instance {
+            SourceInstance::File { path, .. } => Ok(std::fs::read(path)?),
+            SourceInstance::Dicomweb { uids } => {
+                let client = self
+                    .client
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No DICOMweb client for instance {}", uids.instance))?;
+                crate::dicomweb::wado::retrieve_instance(client, &uids.study, &uids.series, &uids.instance).await
+            }
+        }
+    }
+}
+
+/// Copy the selected instances from the source to the destination one at a time,
+/// then check that the destination holds all of them
+pub async fn transfer(pool: &DbPool, request: &TransferRequest) -> Result<TransferReport> {
+    request.selector.validate()?;
+
+    let template = match &request.anonymization_template {
+        Some(name) => Some(
+            anonymizer::get_builtin_templates()
+                .into_iter()
+                .find(|t| t.name == *name)
+                .ok_or_else(|| anyhow::anyhow!("Template not found: {}", name))?,
+        ),
+        None => None,
+    };
+
+    tracing::info!(
+        "Transferring study {} from {} to {}",
+        request.selector.study_uid,
+        request.source.describe(),
+        request.destination.describe()
+    );
+
+    let selection = fetch(pool, &request.source, &request.selector).await?;
+    if selection.instances.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} returned no instances for the selection",
+            request.source.describe()
+        ));
+    }
+
+    // One UID map for the whole transfer keeps anonymized studies consistent
+    let mut uid_map = HashMap::new();
+    let mut sink = Sink::new(pool, &request.destination)?;
+    let mut retrieved = 0;
+    let mut outgoing = Vec::new();
+    let mut failed = Vec::new();
+    for instance in &selection.instances {
+        if let (None, SourceInstance::File { path, uids }) = (&template, instance) {
+            retrieved += 1;
+            sink.push_file(path, uids).await?;
+            outgoing.push(uids.clone());
+            continue;
+        }
+
+        match prepare(&selection, instance, template.as_ref(), &mut uid_map).await {
+            Ok((data, uids)) => {
+                retrieved += 1;
+                sink.push(data, &uids).await?;
+                outgoing.push(uids);
+            }
+            Err(e) => failed.push(FailedTransfer {
+                sop_instance_uid: instance.sop_instance_uid().to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+    let (stored_uids, store_failed) = sink.finish().await?;
+    failed.extend(store_failed);
+
+    // What the destination should now hold, under the UIDs it was sent with
+    let selector = &request.selector;
+    let destination_selector = TransferSelector {
+        study_uid: outgoing.first().map_or_else(|| selector.study_uid.clone(), |u| u.study.clone()),
+        series_uid: single(outgoing.iter().map(|u| &u.series)).filter(|_| selector.series_uid.is_some()),
+        instance_uid: single(outgoing.iter().map(|u| &u.instance)).filter(|_| selector.instance_uid.is_some()),
+    };
+    let verification = verify(pool, &request.destination, &destination_selector, &stored_uids).await;
+
+    tracing::info!(
+        "Transfer of study {} finished: {} stored, {} failed, verified: {}",
+        request.selector.study_uid,
+        stored_uids.len(),
+        failed.len(),
+        verification.verified
+    );
+
+    Ok(TransferReport {
+        retrieved,
+        stored: stored_uids.len(),
+        failed,
+        destination_study_uid: destination_selector.study_uid,
+        verification,
+    })
+}
+
+/// Load one instance and anonymize it if a template is given
+async fn prepare(
+    selection: &Selection,
+    instance: &SourceInstance,
+    template: Option<&AnonymizationTemplate>,
+    uid_map: &mut HashMap<String, String>,
+) -> Result<(Vec<u8>, InstanceUids)> {
+    let data = selection.load(instance).await?;
+    let data = match template {
+        Some(template) => anonymize(&data, template, uid_map)?,
+        None => data,
+    };
+    let uids = InstanceUids::of(&data)?;
+    Ok((data, uids))
+}
+
+/// The value shared by every item, if they all agree
+fn single<'a>(mut values: impl Iterator<Item = &'a String>) -> Option<String> {
+    let first = values.next()?;
+    values.all(|v| v == first).then(|| first.clone())
+}
+
+fn dicomweb_client(pool: &DbPool, endpoint: &DicomWebEndpoint) -> Result<DicomWebClient> {
+    Ok(DicomWebClient::new(endpoint.clone())?.with_history(pool.clone()))
+}
+
+/// List the selected instances at the source without reading their data
+pub(crate) async fn fetch(
+    pool: &DbPool,
+    source: &TransferEndpoint,
+    selector: &TransferSelector,
+) -> Result<Selection> {
+    let selection = match source {
+        TransferEndpoint::Local { path } => {
+            let instances = crate::dicom::parser::scan_directory_fast(path)?
+                .into_iter()
+                .filter_map(|file| {
+                    let uids = InstanceUids {
+                        study: file.study_instance_uid.trim_end_matches('\0').to_string(),
+                        series: file.series_instance_uid.trim_end_matches('\0').to_string(),
+                        instance: file.sop_instance_uid.trim_end_matches('\0').to_string(),
+                    };
+                    selector.matches(&uids).then(|| SourceInstance::File {
+                        path: PathBuf::from(file.path),
+                        uids,
+                    })
+                })
+                .collect();
+            Selection { client: None, instances }
+        }
+        TransferEndpoint::Dimse { endpoint } => {
+            // C-GET works at study level; narrow down to the selection afterwards
+            let mut instances = Vec::new();
+            for path in scu::c_get(endpoint, &selector.study_uid).await? {
+                let path = PathBuf::from(path);
+                let uids = InstanceUids::of_file(&path)?;
+                if selector.matches(&uids) {
+                    instances.push(SourceInstance::File { path, uids });
+                }
+            }
+            Selection { client: None, instances }
+        }
+        TransferEndpoint::Dicomweb { endpoint } => {
+            let client = dicomweb_client(pool, endpoint)?;
+            let instances = match (&selector.series_uid, &selector.instance_uid) {
+                (Some(series), Some(instance)) => vec![InstanceUids {
+                    study: selector.study_uid.clone(),
+                    series: series.clone(),
+                    instance: instance.clone(),
+                }],
+                _ => search_instances(&client, selector).await?,
+            };
+            Selection {
+                instances: instances.into_iter().map(|uids| SourceInstance::Dicomweb { uids }).collect(),
+                client: Some(client),
+            }
+        }
+    };
+
+    tracing::info!("Selected {} instances at {}", selection.instances.len(), source.describe());
+    Ok(selection)
+}
+
+/// List the instances matching a selector with QIDO-RS
+async fn search_instances(client: &DicomWebClient, selector: &TransferSelector) -> Result<Vec<InstanceUids>> {
+    use crate::dicomweb::qido::{AttributeKey, QidoPager, QidoQuery, QueryLevel};
+    use dicom_dictionary_std::tags;
+
+    let mut query = QidoQuery::new(QueryLevel::Instances);
+    query.study_uid = Some(selector.study_uid.clone());
+    query.series_uid = selector.series_uid.clone();
+    if let Some(instance) = &selector.instance_uid {
+        query.params.insert(AttributeKey::from(tags::SOP_INSTANCE_UID), instance.clone());
+    }
+
+    let response = QidoPager::new(client, query).collect_all(None).await?;
+    let text = |result: &serde_json::Value, key: &str| {
+        result.get(key)?.get("Value")?.get(0)?.as_str().map(String::from)
+    };
+    Ok(response
+        .results
+        .iter()
+        .filter_map(|r| {
+            Some(InstanceUids {
+                // Study-scoped searches may leave out the study UID
+                study: text(r, "0020000D").unwrap_or_else(|| selector.study_uid.clone()),
+                series: text(r, "0020000E")?,
+                instance: text(r, "00080018")?,
+            })
+        })
+        .collect())
+}
+
+/// Anonymize one instance, sharing `uid_map` with the rest of the transfer
+fn anonymize(data: &[u8], template: &AnonymizationTemplate, uid_map: &mut HashMap<String, String>) -> Result<Vec<u8>> {
+    let obj = crate::dicom::read_dicom_bytes(data)?;
+    let transfer_syntax = obj.meta().transfer_syntax().to_string();
+
+    let mut dataset = obj.into_inner();
+    anonymizer::anonymize_with_uid_map(&mut dataset, template, uid_map)?;
+
+    // Rebuild the file meta so it carries the new SOP Instance UID
+    let meta = crate::dicom::build_file_meta(&dataset, &transfer_syntax)?;
+    let file_obj = dataset.with_exact_meta(meta);
+    let mut bytes = Vec::new();
+    file_obj.write_all(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Send local Part 10 files, given with their SOP Instance UIDs, to a destination;
+/// returns the SOP Instance UIDs that were stored and the failures
+pub(crate) async fn send_files(
+    pool: &DbPool,
+    destination: &TransferEndpoint,
+    files: Vec<(String, PathBuf)>,
+) -> Result<(Vec<String>, Vec<FailedTransfer>)> {
+    let mut sink = Sink::new(pool, destination)?;
+    let mut failed = Vec::new();
+    for (sop_instance_uid, path) in files {
+        match InstanceUids::of_file(&path) {
+            Ok(uids) => sink.push_file(&path, &uids).await?,
+            Err(e) => failed.push(FailedTransfer {
+                sop_instance_uid,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    let (stored, store_failed) = sink.finish().await?;
+    failed.extend(store_failed);
+    Ok((stored, failed))
+}
+
+/// Instances sent in one STOW-RS request
+const STOW_BATCH_SIZE: usize = 16;
+
+/// Temporary directory of DIMSE instances, removed when dropped so an aborted
+/// transfer doesn't leave staged copies behind
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    fn create() -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("transfer-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            tracing::warn!("Failed to remove staging directory {:?}: {}", self.0, e);
+        }
+    }
+}
+
+/// Sends instances to a destination as they are pushed. Folders are written
+/// directly, DICOMweb instances go out in small per-study STOW-RS batches, and
+/// DIMSE instances are staged on disk for one C-STORE association.
+struct Sink<'a> {
+    destination: &'a TransferEndpoint,
+    client: Option<DicomWebClient>,
+    /// Study UID and instances of the pending STOW-RS request
+    batch: Option<(String, Vec<Vec<u8>>)>,
+    staging: Option<StagingDir>,
+    staged: Vec<PathBuf>,
+    stored: Vec<String>,
+    failed: Vec<FailedTransfer>,
+}
+
+impl<'a> Sink<'a> {
+    fn new(pool: &DbPool, destination: &'a TransferEndpoint) -> Result<Self> {
+        let client = match destination {
+            TransferEndpoint::Dicomweb { endpoint } => Some(dicomweb_client(pool, endpoint)?),
+            _ => None,
+        };
+        Ok(Self {
+            destination,
+            client,
+            batch: None,
+            staging: None,
+            staged: Vec::new(),
+            stored: Vec::new(),
+            failed: Vec::new(),
+        })
+    }
+
+    fn fail(&mut self, uids: &InstanceUids, e: anyhow::Error) {
+        self.failed.push(FailedTransfer {
+            sop_instance_uid: uids.instance.clone(),
+            reason: e.to_string(),
+        });
+    }
+
+    /// Send an unmodified file, without reading it into memory where possible
+    async fn push_file(&mut self, path: &Path, uids: &InstanceUids) -> Result<()> {
+        match self.destination {
+            TransferEndpoint::Local { path: root } => {
+                let result = local_target(root, uids)
+                    .and_then(|target| {
+                        write_atomic(target, |temp| {
+                            std::fs::copy(path, temp)?;
+                            Ok(())
+                        })
+                    });
+                match result {
+                    Ok(()) => self.stored.push(uids.instance.clone()),
+                    Err(e) => self.fail(uids, e),
+                }
+            }
+            TransferEndpoint::Dimse { .. } => self.staged.push(path.to_path_buf()),
+            TransferEndpoint::Dicomweb { .. } => match std::fs::read(path) {
+                Ok(data) => self.push(data, uids).await?,
+                Err(e) => self.fail(uids, e.into()),
+            },
+        }
+        Ok(())
+    }
+
+    async fn push(&mut self, data: Vec<u8>, uids: &InstanceUids) -> Result<()> {
+        match self.destination {
+            TransferEndpoint::Local { path: root } => {
+                let result = local_target(root, uids)
+                    .and_then(|target| write_atomic(target, |temp| Ok(std::fs::write(temp, &data)?)));
+                match result {
+                    Ok(()) => self.stored.push(uids.instance.clone()),
+                    Err(e) => self.fail(uids, e),
+                }
+            }
+            TransferEndpoint::Dimse { .. } => {
+                let staging = match self.staging.take() {
+                    Some(dir) => dir,
+                    None => StagingDir::create()?,
+                };
+                let file = staging.0.join(format!("{}.dcm", self.staged.len()));
+                self.staging = Some(staging);
+                match std::fs::write(&file, &data) {
+                    Ok(()) => self.staged.push(file),
+                    Err(e) => self.fail(uids, e.into()),
+                }
+            }
+            TransferEndpoint::Dicomweb { .. } => {
+                // STOW-RS requests are sent per study
+                if self.batch.as_ref().is_some_and(|(study, _)| *study != uids.study) {
+                    self.flush().await?;
+                }
+                let (_, batch) = self.batch.get_or_insert_with(|| (uids.study.clone(), Vec::new()));
+                batch.push(data);
+                if batch.len() >= STOW_BATCH_SIZE {
+                    self.flush().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the pending STOW-RS batch
+    async fn flush(&mut self) -> Result<()> {
+        let (Some(client), Some((study, batch))) = (&self.client, self.batch.take()) else {
+            return Ok(());
+        };
+        let response = crate::dicomweb::stow::store_instances(client, Some(study.as_str()), batch).await?;
+        self.stored.extend(response.success);
+        self.failed.extend(response.failed.into_iter().map(|f| FailedTransfer {
+            sop_instance_uid: f.instance_uid,
+            reason: f.reason,
+        }));
+        Ok(())
+    }
+
+    /// Send whatever is still pending; returns the stored SOP Instance UIDs and the failures
+    async fn finish(mut self) -> Result<(Vec<String>, Vec<FailedTransfer>)> {
+        self.flush().await?;
+
+        if let TransferEndpoint::Dimse { endpoint } = self.destination {
+            let outcomes = if self.staged.is_empty() {
+                Ok(Vec::new())
+            } else {
+                scu::c_store(endpoint, std::mem::take(&mut self.staged)).await
+            };
+            drop(self.staging.take());
+            for outcome in outcomes? {
+                if outcome.is_success() {
+                    self.stored.push(outcome.sop_instance_uid);
+                } else {
+                    let reason = outcome.error.clone().unwrap_or_else(|| {
+                        format!("C-STORE status {:04X}H", outcome.status.unwrap_or_default())
+                    });
+                    self.failed.push(FailedTransfer {
+                        sop_instance_uid: outcome.sop_instance_uid,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        Ok((self.stored, self.failed))
+    }
+}
+
+/// Where an instance is written in a destination folder: `study/series/instance.dcm`
+fn local_target(root: &str, uids: &InstanceUids) -> Result<PathBuf> {
+    // UIDs become directory and file names, so anything but a valid UID is refused
+    let dir = PathBuf::from(root).join(safe_uid(&uids.study)?).join(safe_uid(&uids.series)?);
+    let file_name = format!("{}.dcm", safe_uid(&uids.instance)?);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(file_name))
+}
+
+/// Check the transferred instances at the destination
+async fn verify(
+    pool: &DbPool,
+    destination: &TransferEndpoint,
+    selector: &TransferSelector,
+    stored: &[String],
+) -> TransferVerification {
+    let expected = stored.len();
+    let found = match count_at_destination(pool, destination, selector, stored).await {
+        Ok(found) => found,
+        Err(e) => {
+            tracing::warn!("Could not verify transfer at {}: {}", destination.describe(), e);
+            return TransferVerification {
+                expected,
+                found: None,
+                verified: false,
+                detail: Some(e.to_string()),
+            };
+        }
+    };
+
+    let verified = found == expected;
+    TransferVerification {
+        expected,
+        found: Some(found),
+        verified,
+        detail: (!verified).then(|| format!("Destination holds {} of {} stored instances", found, expected)),
+    }
+}
+
+/// How many of the stored SOP Instance UIDs the destination lists for the selection
+async fn count_at_destination(
+    pool: &DbPool,
+    destination: &TransferEndpoint,
+    selector: &TransferSelector,
+    stored: &[String],
+) -> Result<usize> {
+    let present: BTreeSet<String> = match destination {
+        TransferEndpoint::Local { path } => {
+            crate::dicom::parser::scan_directory_fast(PathBuf::from(path).join(safe_uid(&selector.study_uid)?))?
+                .into_iter()
+                .map(|f| f.sop_instance_uid.trim_end_matches('\0').to_string())
+                .collect()
+        }
+        TransferEndpoint::Dicomweb { endpoint } => {
+            let client = dicomweb_client(pool, endpoint)?;
+            search_instances(&client, selector)
+                .await?
+                .into_iter()
+                .map(|uids| uids.instance)
+                .collect()
+        }
+        TransferEndpoint::Dimse { endpoint } => scu::c_find_instances(endpoint, &selector.study_uid)
+            .await?
+            .into_iter()
+            .map(|i| InstanceUids {
+                study: i.study_instance_uid,
+                series: i.series_instance_uid,
+                instance: i.sop_instance_uid,
+            })
+            .filter(|uids| selector.matches(uids))
+            .map(|uids| uids.instance)
+            .collect(),
+    };
+    Ok(stored.iter().filter(|uid| present.contains(*uid)).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uids(study: &str, series: &str, instance: &str) -> InstanceUids {
+        InstanceUids {
+            study: study.to_string(),
+            series: series.to_string(),
+            instance: instance.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_selector_matching() {
+        let study = TransferSelector {
+            study_uid: "1.2".to_string(),
+            series_uid: None,
+            instance_uid: None,
+        };
+        assert!(study.matches(&uids("1.2", "1.2.3", "1.2.3.4")));
+        assert!(!study.matches(&uids("1.3", "1.2.3", "1.2.3.4")));
+
+        let series = TransferSelector {
+            series_uid: Some("1.2.3".to_string()),
+            ..study.clone()
+        };
+        assert!(series.matches(&uids("1.2", "1.2.3", "1.2.3.4")));
+        assert!(!series.matches(&uids("1.2", "1.2.4", "1.2.4.1")));
+
+        let orphan = TransferSelector {
+            instance_uid: Some("1.2.3.4".to_string()),
+            ..study
+        };
+        assert!(orphan.validate().is_err());
+    }
+
+    #[test]
+    fn test_local_target_rejects_unsafe_uids() {
+        let root = std::env::temp_dir().join(format!("transfer-target-{}", std::process::id()));
+        let root_str = root.to_string_lossy().to_string();
+
+        assert!(local_target(&root_str, &uids("1.2", "1.2.3", "../../evil")).is_err());
+        assert!(local_target(&root_str, &uids("1.2", "/etc", "1.2.3.4")).is_err());
+        assert!(!root.exists());
+
+        let target = local_target(&root_str, &uids("1.2", "1.2.3", "1.2.3.4")).unwrap();
+        assert_eq!(target, root.join("1.2").join("1.2.3").join("1.2.3.4.dcm"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_single_value() {
+        let same = ["a".to_string(), "a".to_string()];
+        let mixed = ["a".to_string(), "b".to_string()];
+        assert_eq!(single(same.iter()), Some("a".to_string()));
+        assert_eq!(single(mixed.iter()), None);
+    }
+
+    #[test]
+    fn test_staging_dir_removed_on_drop() {
+        let staging = StagingDir::create().unwrap();
+        let dir = staging.0.clone();
+        std::fs::write(dir.join("0.dcm"), b"staged").unwrap();
+        drop(staging);
+        assert!(!dir.exists());
+    }
+}
@@ -31,3 +31,243 @@ pub fn get_file_size<P: AsRef<Path>>(path: P) -> std::io::Result<u64> {
     let metadata = std::fs::metadata(path)?;
     Ok(metadata.len())
 }
+
+/// Check that a UID read from a dataset or sent by a peer is safe to use as a
+/// file or directory name: digits separated by single dots, at most 64 characters
+pub fn safe_uid(uid: &str) -> anyhow::Result<&str> {
+    let uid = uid.trim_end_matches('\0').trim();
+    let valid = !uid.is_empty()
+        && uid.len() <= 64
+        && uid
+            .split('.')
+            .all(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_digit()));
+    if valid {
+        Ok(uid)
+    } else {
+        Err(anyhow::anyhow!("Invalid UID {:?}", uid))
+    }
+}
+
+/// Write a file atomically: `write` fills a temporary file next to `path`,
+/// which is synced and then renamed over it, so readers never see a partial file
+pub fn write_atomic<P, F>(path: P, write: F) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&Path) -> anyhow::Result<()>,
+{
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path: {:?}", path))?
+        .to_string_lossy();
+    // Unique per call, so concurrent writes of the same file don't share a temp file
+    let temp = path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()));
+
+    let result = write(&temp).and_then(|_| {
+        std::fs::File::open(&temp)?.sync_all()?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// What to keep of a file before it is overwritten by an edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum BackupMode {
+    #[default]
+    None,
+    /// A single `<file>.bak` next to the file, replaced on every edit
+    Bak,
+    /// Timestamped copies in the app data directory, the newest `keep` retained
+    Versions { keep: usize },
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileVersion {
+    pub path: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+/// Directory holding the versions of `path`, keyed by a hash of its path
+fn versions_dir(path: &Path) -> PathBuf {
+    use sha2::{Digest, Sha256};
+
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let hash = format!("{:x}", Sha256::digest(absolute.to_string_lossy().as_bytes()));
+    super::get_app_data_dir().join("versions").join(&hash[0..16])
+}
+
+/// Back up `path` according to `mode` before it is overwritten; returns the
+/// backup's location. A file that doesn't exist yet has nothing to back up.
+pub fn backup_file<P: AsRef<Path>>(path: P, mode: BackupMode) -> anyhow::Result<Option<PathBuf>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let backup = match mode {
+        BackupMode::None => return Ok(None),
+        BackupMode::Bak => {
+            let mut name = path.as_os_str().to_owned();
+            name.push(".bak");
+            PathBuf::from(name)
+        }
+        BackupMode::Versions { keep } => {
+            let dir = versions_dir(path);
+            super::ensure_dir(&dir)?;
+            let backup = copy_to_new_version(path, &dir)?;
+            prune_versions(&dir, keep.max(1))?;
+            tracing::info!("Saved version of {:?} to {:?}", path, backup);
+            return Ok(Some(backup));
+        }
+    };
+
+    std::fs::copy(path, &backup)?;
+    Ok(Some(backup))
+}
+
+/// Copy `path` into a new timestamped file in `dir`. Names are claimed with
+/// `create_new`, so saves within the same millisecond get a counter suffix
+/// (`_001`, ...) that still sorts after the unsuffixed name.
+fn copy_to_new_version(path: &Path, dir: &Path) -> anyhow::Result<PathBuf> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f").to_string();
+    for n in 0..1000 {
+        let name = match n {
+            0 => format!("{}.dcm", stamp),
+            n => format!("{}_{:03}.dcm", stamp, n),
+        };
+        let backup = dir.join(name);
+        let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(&backup) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let copied = std::io::copy(&mut std::fs::File::open(path)?, &mut file).and_then(|_| file.sync_all());
+        if let Err(e) = copied {
+            let _ = std::fs::remove_file(&backup);
+            return Err(e.into());
+        }
+        return Ok(backup);
+    }
+    Err(anyhow::anyhow!("Too many versions of {:?} saved at {}", path, stamp))
+}
+
+/// Delete all but the newest `keep` versions in `dir`
+fn prune_versions(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut versions: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().map_or(false, |ext| ext == "dcm"))
+        .collect();
+    // Timestamped names sort chronologically
+    versions.sort();
+    let excess = versions.len().saturating_sub(keep);
+    for old in &versions[..excess] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Saved versions of `path`, newest first
+pub fn list_versions<P: AsRef<Path>>(path: P) -> Vec<FileVersion> {
+    let dir = versions_dir(path.as_ref());
+    let mut versions: Vec<FileVersion> = std::fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.extension().map_or(false, |ext| ext == "dcm"))
+                .map(|p| FileVersion {
+                    created_at: p.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                    size: get_file_size(&p).unwrap_or(0),
+                    path: p.to_string_lossy().to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    versions
+}
+
+/// Put a saved version back in place of `path`, backing up the current file
+/// according to `mode` first
+pub fn restore_version<P: AsRef<Path>>(path: P, version: &str, mode: BackupMode) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let version = Path::new(version);
+    if version.parent() != Some(versions_dir(path).as_path()) {
+        return Err(anyhow::anyhow!("{:?} is not a saved version of {:?}", version, path));
+    }
+    // Read it first: the backup may prune the oldest versions
+    let data = std::fs::read(version)?;
+    backup_file(path, mode)?;
+    write_atomic(path, |temp| {
+        std::fs::write(temp, &data)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_uid() {
+        assert_eq!(safe_uid("1.2.840.10008\0").unwrap(), "1.2.840.10008");
+        assert!(safe_uid("").is_err());
+        assert!(safe_uid("../../etc/passwd").is_err());
+        assert!(safe_uid("1.2/3").is_err());
+        assert!(safe_uid("1..2").is_err());
+        assert!(safe_uid(&"1".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("atomic-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.dcm");
+        std::fs::write(&path, b"old").unwrap();
+
+        write_atomic(&path, |temp| Ok(std::fs::write(temp, b"new")?)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+
+        // A failed write leaves the original and no temp file behind
+        assert!(write_atomic(&path, |temp| {
+            std::fs::write(temp, b"partial")?;
+            Err(anyhow::anyhow!("encoder failed"))
+        })
+        .is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        assert_eq!(backup_file(&path, BackupMode::Bak).unwrap(), Some(dir.join("file.dcm.bak")));
+        assert_eq!(std::fs::read(dir.join("file.dcm.bak")).unwrap(), b"new");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_versions_in_same_millisecond_are_kept() {
+        let dir = std::env::temp_dir().join(format!("versions-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.dcm");
+
+        let mut versions = Vec::new();
+        for content in ["a", "b", "c"] {
+            std::fs::write(&path, content).unwrap();
+            versions.push(copy_to_new_version(&path, &dir).unwrap());
+        }
+
+        let mut sorted = versions.clone();
+        sorted.sort();
+        assert_eq!(sorted, versions);
+        let contents: Vec<_> = versions.iter().map(|v| std::fs::read_to_string(v).unwrap()).collect();
+        assert_eq!(contents, ["a", "b", "c"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
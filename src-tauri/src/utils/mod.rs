@@ -2,6 +2,7 @@
 
 pub mod logging;
 pub mod file_helpers;
+pub mod secrets;
 
 use std::path::PathBuf;
 
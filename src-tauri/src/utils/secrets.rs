@@ -0,0 +1,518 @@
+// Encrypted storage for connection secrets (passwords, tokens, client secrets)
+//
+// Secrets live in the `secrets` table encrypted with ChaCha20-Poly1305. The key
+// comes either from a keyfile (default, portable between machines) or is derived
+// from a user passphrase with Argon2. Connection configs only hold references.
+
+use crate::database::DbPool;
+use anyhow::Result;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Prefix of the references stored in connection configs in place of secrets
+pub const SECRET_REF_PREFIX: &str = "secret://";
+
+const NONCE_LEN: usize = 12;
+const KEY_CHECK_PLAINTEXT: &str = "dicomflow-secrets-v1";
+
+const SETTING_MODE: &str = "secrets_mode";
+const SETTING_SALT: &str = "secrets_kdf_salt";
+const SETTING_CHECK: &str = "secrets_key_check";
+const SETTING_KEYFILE: &str = "secrets_keyfile_path";
+
+/// How the encryption key is obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum KeySource {
+    Keyfile,
+    Passphrase,
+}
+
+/// Status reported to the frontend
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VaultStatus {
+    pub key_source: KeySource,
+    pub unlocked: bool,
+    pub keyfile_path: Option<String>,
+    /// Why the vault could not be unlocked at startup
+    pub error: Option<String>,
+}
+
+/// Holds the active encryption key; managed as Tauri state
+pub struct SecretVault {
+    key: RwLock<Option<[u8; 32]>>,
+    init_error: RwLock<Option<String>>,
+}
+
+impl SecretVault {
+    /// A vault without a key, for tests that never touch secrets
+    #[cfg(test)]
+    pub(crate) fn locked() -> Self {
+        SecretVault {
+            key: RwLock::new(None),
+            init_error: RwLock::new(None),
+        }
+    }
+
+    /// Load the vault configuration. Keyfile vaults are unlocked immediately,
+    /// passphrase vaults stay locked until `unlock` is called. A keyfile that
+    /// can't be used leaves the vault locked and is reported by `status`.
+    pub async fn init(pool: &DbPool) -> Self {
+        let vault = Self {
+            key: RwLock::new(None),
+            init_error: RwLock::new(None),
+        };
+
+        if let Err(e) = vault.unlock_with_keyfile(pool).await {
+            tracing::error!("Secret storage stays locked: {}", e);
+            *vault.init_error.write().unwrap() = Some(e.to_string());
+        }
+
+        vault
+    }
+
+    async fn unlock_with_keyfile(&self, pool: &DbPool) -> Result<()> {
+        if key_source(pool).await? != KeySource::Keyfile {
+            return Ok(());
+        }
+
+        let path = keyfile_path(pool).await?;
+        let key = match read_keyfile(&path)? {
+            Some(key) => key,
+            None => {
+                // A new key would leave every stored secret undecryptable for good
+                let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM secrets")
+                    .fetch_one(pool)
+                    .await?;
+                if stored > 0 {
+                    return Err(anyhow::anyhow!(
+                        "Keyfile {:?} is missing but {} stored secrets were encrypted with it; restore the keyfile",
+                        path,
+                        stored
+                    ));
+                }
+                // Nothing is encrypted yet, so the check value of a lost key can go
+                sqlx::query("DELETE FROM settings WHERE key = ?")
+                    .bind(SETTING_CHECK)
+                    .execute(pool)
+                    .await?;
+                create_keyfile(&path)?
+            }
+        };
+        self.activate(pool, key).await
+    }
+
+    pub async fn status(&self, pool: &DbPool) -> Result<VaultStatus> {
+        let key_source = key_source(pool).await?;
+        let keyfile_path = match key_source {
+            KeySource::Keyfile => Some(keyfile_path(pool).await?.to_string_lossy().to_string()),
+            KeySource::Passphrase => None,
+        };
+
+        Ok(VaultStatus {
+            key_source,
+            unlocked: self.current_key().is_some(),
+            keyfile_path,
+            error: self.init_error.read().unwrap().clone(),
+        })
+    }
+
+    /// Unlock a passphrase-protected vault
+    pub async fn unlock(&self, pool: &DbPool, passphrase: &str) -> Result<()> {
+        let salt = get_setting(pool, SETTING_SALT)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No passphrase has been configured"))?;
+        let key = derive_key(passphrase, salt.as_bytes())?;
+        self.activate(pool, key).await
+    }
+
+    /// Switch to a passphrase-derived key, re-encrypting every stored secret
+    pub async fn use_passphrase(&self, pool: &DbPool, passphrase: &str) -> Result<()> {
+        if passphrase.len() < 8 {
+            return Err(anyhow::anyhow!("Passphrase must be at least 8 characters"));
+        }
+
+        let salt = random_token(16);
+        let key = derive_key(passphrase, salt.as_bytes())?;
+        self.rekey(pool, key).await?;
+
+        set_setting(pool, SETTING_SALT, &salt).await?;
+        set_setting(pool, SETTING_MODE, "passphrase").await?;
+        Ok(())
+    }
+
+    /// Switch to a keyfile, creating it if needed, and re-encrypt every stored secret
+    pub async fn use_keyfile(&self, pool: &DbPool, path: Option<String>) -> Result<()> {
+        let path = path.map(PathBuf::from).unwrap_or_else(default_keyfile_path);
+        let key = load_or_create_keyfile(&path)?;
+        self.rekey(pool, key).await?;
+
+        set_setting(pool, SETTING_KEYFILE, &path.to_string_lossy()).await?;
+        set_setting(pool, SETTING_MODE, "keyfile").await?;
+        Ok(())
+    }
+
+    /// Encrypt and store a secret, returning the reference to put in the config
+    pub async fn store(&self, pool: &DbPool, plaintext: &str) -> Result<String> {
+        let key = self.require_key()?;
+        let id = uuid::Uuid::new_v4().to_string();
+
+        sqlx::query("INSERT INTO secrets (id, ciphertext) VALUES (?, ?)")
+            .bind(&id)
+            .bind(encrypt(&key, plaintext)?)
+            .execute(pool)
+            .await?;
+
+        Ok(format!("{}{}", SECRET_REF_PREFIX, id))
+    }
+
+    /// Decrypt the secret behind a reference
+    pub async fn resolve(&self, pool: &DbPool, reference: &str) -> Result<String> {
+        let key = self.require_key()?;
+        let id = reference_id(reference)
+            .ok_or_else(|| anyhow::anyhow!("Not a secret reference: {}", reference))?;
+
+        let ciphertext = sqlx::query_scalar::<_, String>("SELECT ciphertext FROM secrets WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Secret {} not found", id))?;
+
+        decrypt(&key, &ciphertext)
+    }
+
+    /// Remove the secret behind a reference
+    pub async fn delete(&self, pool: &DbPool, reference: &str) -> Result<()> {
+        if let Some(id) = reference_id(reference) {
+            sqlx::query("DELETE FROM secrets WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn current_key(&self) -> Option<[u8; 32]> {
+        *self.key.read().unwrap()
+    }
+
+    fn require_key(&self) -> Result<[u8; 32]> {
+        self.current_key()
+            .ok_or_else(|| anyhow::anyhow!("Secret storage is locked; unlock it with your passphrase"))
+    }
+
+    /// Verify `key` against the stored check value (or record one) and make it active
+    async fn activate(&self, pool: &DbPool, key: [u8; 32]) -> Result<()> {
+        match get_setting(pool, SETTING_CHECK).await? {
+            Some(check) => {
+                let ok = decrypt(&key, &check)
+                    .map(|plain| plain == KEY_CHECK_PLAINTEXT)
+                    .unwrap_or(false);
+                if !ok {
+                    return Err(anyhow::anyhow!("Wrong passphrase or keyfile"));
+                }
+            }
+            None => {
+                set_setting(pool, SETTING_CHECK, &encrypt(&key, KEY_CHECK_PLAINTEXT)?).await?;
+            }
+        }
+
+        *self.key.write().unwrap() = Some(key);
+        *self.init_error.write().unwrap() = None;
+
+        // Configs saved before secrets were encrypted still hold them in plain text
+        match crate::dicomweb::config::seal_stored_endpoints(pool, self).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Moved plaintext secrets of {} connections into secret storage", count),
+            Err(e) => tracing::warn!("Failed to seal plaintext connection secrets: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt all secrets with `new_key` and make it the active key
+    async fn rekey(&self, pool: &DbPool, new_key: [u8; 32]) -> Result<()> {
+        // Read inside the transaction so secrets stored meanwhile are re-encrypted too
+        let mut tx = pool.begin().await?;
+        let rows = sqlx::query_as::<_, (String, String)>("SELECT id, ciphertext FROM secrets")
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let old_key = if rows.is_empty() { None } else { Some(self.require_key()?) };
+        if let Some(old_key) = old_key {
+            for (id, ciphertext) in rows {
+                let plaintext = decrypt(&old_key, &ciphertext)?;
+                sqlx::query("UPDATE secrets SET ciphertext = ? WHERE id = ?")
+                    .bind(encrypt(&new_key, &plaintext)?)
+                    .bind(&id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO settings (key, value, updated_at)
+             VALUES (?, ?, CURRENT_TIMESTAMP)"
+        )
+        .bind(SETTING_CHECK)
+        .bind(encrypt(&new_key, KEY_CHECK_PLAINTEXT)?)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        *self.key.write().unwrap() = Some(new_key);
+        Ok(())
+    }
+}
+
+/// Whether a config value is a reference into the secret store
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with(SECRET_REF_PREFIX)
+}
+
+fn reference_id(reference: &str) -> Option<&str> {
+    reference.strip_prefix(SECRET_REF_PREFIX)
+}
+
+/// Derive a 256-bit key from a passphrase with Argon2id
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypt to base64(nonce || ciphertext)
+pub fn encrypt(key: &[u8; 32], plaintext: &str) -> Result<String> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(general_purpose::STANDARD.encode(out))
+}
+
+/// Decrypt a value produced by `encrypt`
+pub fn decrypt(key: &[u8; 32], encoded: &str) -> Result<String> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let data = general_purpose::STANDARD.decode(encoded)?;
+    if data.len() < NONCE_LEN {
+        return Err(anyhow::anyhow!("Encrypted value is too short"));
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Decryption failed: wrong key or corrupted data"))?;
+
+    Ok(String::from_utf8(plaintext)?)
+}
+
+/// Random base64 string of `len` bytes, used for salts
+pub fn random_token(len: usize) -> String {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::STANDARD_NO_PAD.encode(bytes)
+}
+
+fn default_keyfile_path() -> PathBuf {
+    super::get_app_data_dir().join("secrets.key")
+}
+
+async fn keyfile_path(pool: &DbPool) -> Result<PathBuf> {
+    Ok(get_setting(pool, SETTING_KEYFILE)
+        .await?
+        .map(PathBuf::from)
+        .unwrap_or_else(default_keyfile_path))
+}
+
+async fn key_source(pool: &DbPool) -> Result<KeySource> {
+    Ok(match get_setting(pool, SETTING_MODE).await?.as_deref() {
+        Some("passphrase") => KeySource::Passphrase,
+        _ => KeySource::Keyfile,
+    })
+}
+
+fn load_or_create_keyfile(path: &PathBuf) -> Result<[u8; 32]> {
+    match read_keyfile(path)? {
+        Some(key) => Ok(key),
+        None => create_keyfile(path),
+    }
+}
+
+/// Keyfiles hold the raw key as base64 text so they can be copied between systems;
+/// `None` when there is no keyfile at `path`
+fn read_keyfile(path: &PathBuf) -> Result<Option<[u8; 32]>> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let bytes = general_purpose::STANDARD.decode(text.trim())?;
+    bytes
+        .try_into()
+        .map(Some)
+        .map_err(|_| anyhow::anyhow!("Keyfile {:?} does not contain a 256-bit key", path))
+}
+
+/// Write a new random key to `path`, which must not exist yet. On Unix the file
+/// is created readable by its owner only, never briefly with default permissions.
+fn create_keyfile(path: &PathBuf) -> Result<[u8; 32]> {
+    use base64::{Engine as _, engine::general_purpose};
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        super::ensure_dir(&parent.to_path_buf())?;
+    }
+
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(general_purpose::STANDARD.encode(key).as_bytes())?;
+    file.sync_all()?;
+
+    tracing::info!("Created secrets keyfile at {:?}", path);
+    Ok(key)
+}
+
+async fn get_setting(pool: &DbPool, key: &str) -> Result<Option<String>> {
+    Ok(sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?)
+}
+
+async fn set_setting(pool: &DbPool, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO settings (key, value, updated_at)
+         VALUES (?, ?, CURRENT_TIMESTAMP)"
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let key = derive_key("correct horse battery", b"0123456789abcdef").unwrap();
+        let encrypted = encrypt(&key, "s3cret").unwrap();
+        assert_ne!(encrypted, "s3cret");
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), "s3cret");
+
+        let other = derive_key("wrong passphrase", b"0123456789abcdef").unwrap();
+        assert!(decrypt(&other, &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_reference_prefix() {
+        assert!(is_reference("secret://1234"));
+        assert!(!is_reference("hunter2"));
+        assert_eq!(reference_id("secret://abc"), Some("abc"));
+    }
+
+    #[test]
+    fn test_keyfile_created_once_and_reloaded() {
+        let path = std::env::temp_dir().join(format!("secrets-{}.key", uuid::Uuid::new_v4()));
+        let key = load_or_create_keyfile(&path).unwrap();
+        assert_eq!(load_or_create_keyfile(&path).unwrap(), key);
+        assert!(create_keyfile(&path).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_keyfile_with_stored_secrets_fails() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("secrets-{}.key", uuid::Uuid::new_v4()));
+        set_setting(&pool, SETTING_KEYFILE, &path.to_string_lossy()).await.unwrap();
+        sqlx::query("INSERT INTO secrets (id, ciphertext) VALUES ('lost', 'AAAA')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let vault = SecretVault {
+            key: RwLock::new(None),
+            init_error: RwLock::new(None),
+        };
+        assert!(vault.unlock_with_keyfile(&pool).await.is_err());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_unlock_seals_plaintext_connections() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let plaintext = serde_json::json!({
+            "name": "legacy",
+            "base_url": "http://localhost",
+            "auth_type": { "Basic": { "username": "user", "password": "hunter2" } },
+            "headers": {}
+        });
+        sqlx::query(
+            "INSERT INTO connections (name, connection_type, config_json, created_at, updated_at)
+             VALUES ('legacy', 'dicomweb', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"
+        )
+        .bind(plaintext.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let vault = SecretVault {
+            key: RwLock::new(None),
+            init_error: RwLock::new(None),
+        };
+        vault.activate(&pool, derive_key("correct horse battery", b"0123456789abcdef").unwrap()).await.unwrap();
+
+        let stored: String = sqlx::query_scalar("SELECT config_json FROM connections WHERE name = 'legacy'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!stored.contains("hunter2"));
+        let endpoint: crate::dicomweb::DicomWebEndpoint = serde_json::from_str(&stored).unwrap();
+        let endpoint = crate::dicomweb::config::unseal_endpoint(&pool, &vault, endpoint).await.unwrap();
+        assert!(matches!(endpoint.auth_type, crate::dicomweb::AuthType::Basic { password, .. } if password == "hunter2"));
+    }
+}
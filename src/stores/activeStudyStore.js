@@ -14,4 +14,7 @@ export const activeStudyStore = writable({
   tags: [],
   windowCenter: 128,
   windowWidth: 256,
+  // Instance on a DICOMweb server shown frame by frame:
+  // { endpoint, studyUid, seriesUid, instanceUid, frame, numberOfFrames }
+  remote: null,
 });